@@ -1,5 +1,10 @@
 use clap::{App, Arg, SubCommand};
 use reqwest;
+use ed25519_dalek::SigningKey;
+use traceguard::provenance::dsse::EnvelopeSigner;
+use traceguard::provenance::generator::LocalBuild;
+use traceguard::provenance::trusted_keys::parse_ed25519_public_key;
+use traceguard::models::model_signing::{self, ModelManifest, ModelSignatureBundle, MODEL_SIGNATURE_FILE};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -49,6 +54,90 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("The provenance record ID")
                 .required(true)
                 .index(1)))
+        .subcommand(SubCommand::with_name("build")
+            .about("Run a build step and submit signed SLSA provenance for its outputs")
+            .arg(Arg::with_name("builder-id")
+                .long("builder-id")
+                .help("The builder ID recorded in the provenance")
+                .takes_value(true)
+                .default_value("https://traceguard.dev/local-runner"))
+            .arg(Arg::with_name("signing-key")
+                .long("signing-key")
+                .help("File containing the hex-encoded Ed25519 signing key seed")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("working-dir")
+                .long("working-dir")
+                .help("Directory the build runs in")
+                .takes_value(true)
+                .default_value("."))
+            .arg(Arg::with_name("env")
+                .long("env")
+                .help("Environment variable to record (repeatable)")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+            .arg(Arg::with_name("input")
+                .long("input")
+                .help("Input file or directory to digest (repeatable)")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+            .arg(Arg::with_name("output")
+                .long("output")
+                .help("Output file or directory produced by the build (repeatable)")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required(true))
+            .arg(Arg::with_name("command")
+                .help("The build command, after --")
+                .multiple(true)
+                .last(true)
+                .required(true)))
+        .subcommand(SubCommand::with_name("ingest")
+            .about("Bulk-upload attestations from a JSON Lines file or a tar archive")
+            .arg(Arg::with_name("file")
+                .help("A .jsonl file of DSSE envelopes, or a .tar/.tar.gz/.tgz archive of attestation files")
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("sign-model")
+            .about("Hash a model directory and have TraceGuard sign it for a registered model version")
+            .arg(Arg::with_name("dir")
+                .help("The model directory")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("model")
+                .long("model")
+                .help("The registered model name")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("version")
+                .long("version")
+                .help("The registered model version")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("ignore")
+                .long("ignore")
+                .help("Path relative to the model directory to leave out (repeatable)")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)))
+        .subcommand(SubCommand::with_name("verify-model")
+            .about("Verify a model directory against its signature, offline")
+            .arg(Arg::with_name("dir")
+                .help("The model directory")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("public-key")
+                .long("public-key")
+                .help("File containing the hex-encoded Ed25519 model signing public key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("signature")
+                .long("signature")
+                .help("The signature bundle, defaulting to model.sig in the model directory")
+                .takes_value(true)))
         .get_matches();
 
     let client = reqwest::Client::new();
@@ -109,10 +198,101 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         ("verify-provenance", Some(sub_m)) => {
             let id = sub_m.value_of("id").unwrap();
-            let response = client.get(&format!("http://localhost:8080/api/provenance/{}/verify", id))
+            let response = client.post(&format!("http://localhost:8080/api/provenance/{}/verify", id))
+                .send()
+                .await?;
+            let report = response.json::<serde_json::Value>().await?;
+            println!("Provenance verification passed: {}", report["passed"]);
+            for check in report["checks"].as_array().into_iter().flatten() {
+                println!("  [{}] {}: {}", check["status"], check["kind"], check["message"]);
+            }
+        },
+        ("build", Some(sub_m)) => {
+            let values = |name: &str| -> Vec<String> {
+                sub_m.values_of(name).map(|values| values.map(String::from).collect()).unwrap_or_default()
+            };
+            let seed = hex::decode(std::fs::read_to_string(sub_m.value_of("signing-key").unwrap())?.trim())?;
+            let seed: [u8; 32] = seed.try_into().map_err(|_| "signing key seed must be 32 bytes")?;
+            let signer = EnvelopeSigner::from_signing_key(SigningKey::from_bytes(&seed));
+
+            let build = LocalBuild {
+                builder_id: sub_m.value_of("builder-id").unwrap().to_string(),
+                command: values("command"),
+                working_dir: std::path::PathBuf::from(sub_m.value_of("working-dir").unwrap()),
+                env_allowlist: values("env"),
+                inputs: values("input").into_iter().map(std::path::PathBuf::from).collect(),
+                outputs: values("output").into_iter().map(std::path::PathBuf::from).collect(),
+            };
+            let envelope = build.run_and_sign(&signer)?;
+
+            let response = client.post("http://localhost:8080/api/provenance")
+                .json(&envelope)
                 .send()
                 .await?;
-            println!("Provenance verification result: {:?}", response.json::<bool>().await?);
+
+            println!("Build provenance submitted with key {}. Response: {:?}", signer.key_id(), response);
+        },
+        ("ingest", Some(sub_m)) => {
+            let file_path = sub_m.value_of("file").unwrap();
+            let content_type = if file_path.ends_with(".tar") {
+                "application/x-tar"
+            } else if file_path.ends_with(".tar.gz") || file_path.ends_with(".tgz") {
+                "application/gzip"
+            } else {
+                "application/x-ndjson"
+            };
+
+            let response = client.post("http://localhost:8080/api/ingest/provenance")
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(std::fs::read(file_path)?)
+                .send()
+                .await?;
+            let report = response.json::<serde_json::Value>().await?;
+            println!("Ingested {} of {} attestations", report["ingested"], report["total"]);
+            for item in report["items"].as_array().into_iter().flatten().filter(|item| item["status"] == "failed") {
+                println!("  {}: {}", item["source"], item["error"]);
+            }
+        },
+        ("sign-model", Some(sub_m)) => {
+            let dir = std::path::Path::new(sub_m.value_of("dir").unwrap());
+            let model = sub_m.value_of("model").unwrap();
+            let version = sub_m.value_of("version").unwrap();
+            let ignore: Vec<String> = sub_m.values_of("ignore").map(|values| values.map(String::from).collect()).unwrap_or_default();
+            let manifest = ModelManifest::from_directory(dir, model, &ignore)?;
+
+            let response = client.post(&format!("http://localhost:8080/api/models/{}/versions/{}/signature", model, version))
+                .json(&serde_json::json!({
+                    "ignore_paths": manifest.ignore_paths,
+                    "resources": manifest.resources,
+                }))
+                .send()
+                .await?
+                .error_for_status()?;
+            let signature = response.json::<serde_json::Value>().await?;
+            std::fs::write(dir.join(MODEL_SIGNATURE_FILE), serde_json::to_vec_pretty(&signature["bundle"])?)?;
+            println!("Signed {} files with root digest {}", manifest.resources.len(), signature["root_digest"]);
+        },
+        ("verify-model", Some(sub_m)) => {
+            let dir = std::path::Path::new(sub_m.value_of("dir").unwrap());
+            let key = parse_ed25519_public_key(std::fs::read_to_string(sub_m.value_of("public-key").unwrap())?.trim())?;
+            let bundle_path = sub_m.value_of("signature").map(std::path::PathBuf::from)
+                .unwrap_or_else(|| dir.join(MODEL_SIGNATURE_FILE));
+            let bundle: ModelSignatureBundle = serde_json::from_slice(&std::fs::read(bundle_path)?)?;
+
+            let verification = model_signing::verify_directory(&bundle, &key, dir)?;
+            println!("Model verification passed: {}", verification.verified);
+            for name in &verification.diff.changed {
+                println!("  changed: {}", name);
+            }
+            for name in &verification.diff.added {
+                println!("  added: {}", name);
+            }
+            for name in &verification.diff.removed {
+                println!("  removed: {}", name);
+            }
+            if !verification.verified {
+                std::process::exit(1);
+            }
         },
         _ => println!("Invalid command. Use --help for usage information."),
     }
@@ -0,0 +1,10 @@
+CREATE TABLE provenance_verifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    provenance_id UUID NOT NULL REFERENCES provenance_records(id) ON DELETE CASCADE,
+    verified_by UUID NOT NULL,
+    verified_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    passed BOOLEAN NOT NULL,
+    checks JSONB NOT NULL
+);
+
+CREATE INDEX idx_provenance_verifications_provenance_id ON provenance_verifications (provenance_id, verified_at DESC);
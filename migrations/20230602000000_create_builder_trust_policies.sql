@@ -0,0 +1,17 @@
+CREATE TABLE builder_trust_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    subject_pattern TEXT NOT NULL,
+    allowed_builder_ids TEXT[] NOT NULL DEFAULT '{}',
+    allowed_build_types TEXT[] NOT NULL DEFAULT '{}',
+    allowed_source_uris TEXT[] NOT NULL DEFAULT '{}',
+    allowed_source_refs TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, name)
+);
+
+CREATE INDEX idx_builder_trust_policies_tenant_id ON builder_trust_policies (tenant_id);
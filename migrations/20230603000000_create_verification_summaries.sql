@@ -0,0 +1,14 @@
+CREATE TABLE verification_summaries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    provenance_id UUID NOT NULL REFERENCES provenance_records(id) ON DELETE CASCADE,
+    verification_report_id UUID NOT NULL REFERENCES provenance_verifications(id) ON DELETE CASCADE,
+    subject_digests TEXT[] NOT NULL,
+    verification_result VARCHAR(16) NOT NULL,
+    verified_level INTEGER NOT NULL,
+    envelope JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_verification_summaries_subject_digests ON verification_summaries USING GIN (subject_digests);
+CREATE INDEX idx_verification_summaries_tenant_id ON verification_summaries (tenant_id, created_at DESC);
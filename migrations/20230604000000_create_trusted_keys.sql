@@ -0,0 +1,10 @@
+CREATE TABLE trusted_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    key_id VARCHAR(64) NOT NULL,
+    algorithm VARCHAR(32) NOT NULL,
+    public_key TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, key_id)
+);
@@ -0,0 +1,15 @@
+ALTER TABLE builder_trust_policies ADD COLUMN require_reproducible BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE reproducibility_checks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    subject_name TEXT NOT NULL,
+    subject_digest VARCHAR(64),
+    provenance_ids UUID[] NOT NULL,
+    independent_builders TEXT[] NOT NULL,
+    reproduced BOOLEAN NOT NULL,
+    report JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_reproducibility_checks_subject_digest ON reproducibility_checks (tenant_id, subject_digest);
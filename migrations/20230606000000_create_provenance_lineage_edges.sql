@@ -0,0 +1,14 @@
+CREATE TABLE provenance_lineage_edges (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    provenance_id UUID NOT NULL,
+    input_digest TEXT NOT NULL,
+    input_uri TEXT NOT NULL,
+    output_digest TEXT NOT NULL,
+    output_name TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provenance_id, input_digest, output_digest)
+);
+
+CREATE INDEX idx_provenance_lineage_edges_input ON provenance_lineage_edges (tenant_id, input_digest);
+CREATE INDEX idx_provenance_lineage_edges_output ON provenance_lineage_edges (tenant_id, output_digest);
@@ -0,0 +1,46 @@
+CREATE TABLE provenance_revisions (
+    id UUID PRIMARY KEY,
+    provenance_id UUID NOT NULL,
+    revision INTEGER NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    content JSONB,
+    content_hash VARCHAR(64) NOT NULL,
+    previous_hash VARCHAR(64),
+    chain_hash VARCHAR(64) NOT NULL,
+    reason TEXT,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provenance_id, revision)
+);
+
+-- Revisions are append-only; reject any attempt to rewrite history.
+CREATE FUNCTION reject_provenance_revision_change() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'provenance revisions are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER provenance_revisions_append_only
+    BEFORE UPDATE OR DELETE ON provenance_revisions
+    FOR EACH ROW EXECUTE FUNCTION reject_provenance_revision_change();
+
+-- Give records stored before revisions existed their initial revision. The
+-- stored content is the record's JSON as the API serialized it, so hashing
+-- it reproduces `content_hash`; `chain_hash` length-prefixes each field with
+-- its 8-byte big-endian length like `revisions::chain_hash`.
+INSERT INTO provenance_revisions
+    (id, provenance_id, revision, kind, content, content_hash, previous_hash, chain_hash, reason, created_by, created_at)
+SELECT gen_random_uuid(), p.id, 1, 'created', p.content::jsonb, h.content_hash, NULL,
+       encode(sha256(
+           int8send(octet_length(p.id::text)) || convert_to(p.id::text, 'UTF8') ||
+           int8send(1) || convert_to('1', 'UTF8') ||
+           int8send(7) || convert_to('created', 'UTF8') ||
+           int8send(64) || convert_to(h.content_hash, 'UTF8') ||
+           int8send(0) ||
+           int8send(0)
+       ), 'hex'),
+       NULL, (p.content::jsonb ->> 'created_by')::uuid, p.created_at
+FROM provenance_records p
+CROSS JOIN LATERAL (SELECT encode(sha256(convert_to(p.content, 'UTF8')), 'hex') AS content_hash) h
+WHERE p.content::jsonb ? 'slsa_provenance'
+  AND p.content::jsonb ? 'created_by';
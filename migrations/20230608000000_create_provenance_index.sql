@@ -0,0 +1,64 @@
+-- Normalized, queryable view of the latest revision of each provenance record.
+CREATE TABLE provenance_index (
+    provenance_id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    subject_digests TEXT[] NOT NULL,
+    builder_id TEXT NOT NULL,
+    build_type TEXT NOT NULL,
+    source_uri TEXT NOT NULL,
+    source_ref TEXT,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    content JSONB NOT NULL
+);
+
+CREATE INDEX idx_provenance_index_page ON provenance_index (tenant_id, created_at DESC, provenance_id DESC) WHERE NOT deleted;
+CREATE INDEX idx_provenance_index_subject_digests ON provenance_index USING GIN (subject_digests);
+CREATE INDEX idx_provenance_index_builder ON provenance_index (tenant_id, builder_id, created_at DESC);
+CREATE INDEX idx_provenance_index_build_type ON provenance_index (tenant_id, build_type, created_at DESC);
+CREATE INDEX idx_provenance_index_source ON provenance_index (tenant_id, source_uri, source_ref, created_at DESC);
+CREATE INDEX idx_provenance_index_created_by ON provenance_index (tenant_id, created_by, created_at DESC);
+
+-- Index the backfilled initial revisions, normalizing the config source the
+-- way `SLSAProvenance::normalize` does. Records from before tenancy carry no
+-- tenant; they take the tenant they were last verified under, or the nil
+-- tenant until an operator reassigns them.
+INSERT INTO provenance_index
+    (provenance_id, tenant_id, subject_digests, builder_id, build_type, source_uri, source_ref,
+     created_by, created_at, content)
+SELECT r.provenance_id,
+       COALESCE(
+           (SELECT s.tenant_id FROM verification_summaries s
+            WHERE s.provenance_id = r.provenance_id
+            ORDER BY s.created_at DESC LIMIT 1),
+           '00000000-0000-0000-0000-000000000000'::uuid
+       ),
+       ARRAY(SELECT lower(regexp_replace(subject -> 'digest' ->> 'sha256', '^sha256:', ''))
+             FROM jsonb_array_elements(COALESCE(r.content -> 'slsa_provenance' -> 'subject', '[]'::jsonb)) subject),
+       r.content -> 'slsa_provenance' -> 'builder' ->> 'id',
+       r.content -> 'slsa_provenance' ->> 'build_type',
+       regexp_replace(
+           regexp_replace(CASE WHEN src.has_ref THEN src.repo ELSE src.uri END, '^(https?://)+', ''),
+           '(\.git)+$', ''
+       ),
+       CASE WHEN src.has_ref THEN src.git_ref END,
+       r.created_by,
+       (r.content ->> 'created_at')::timestamptz,
+       r.content
+FROM provenance_revisions r
+CROSS JOIN LATERAL (
+    SELECT regexp_replace(r.content -> 'slsa_provenance' -> 'invocation' -> 'config_source' ->> 'uri', '^git\+', '') AS uri
+) cfg
+CROSS JOIN LATERAL (
+    SELECT cfg.uri,
+           substring(cfg.uri FROM '^(.*)@[^@]*$') AS repo,
+           substring(cfg.uri FROM '@([^@]*)$') AS git_ref
+) parts
+CROSS JOIN LATERAL (
+    SELECT parts.uri, parts.repo, parts.git_ref,
+           parts.git_ref IS NOT NULL AND (position('/' IN parts.git_ref) = 0 OR parts.git_ref LIKE 'refs/%') AS has_ref
+) src
+WHERE r.revision = 1
+  AND r.kind = 'created'
+ON CONFLICT (provenance_id) DO NOTHING;
@@ -0,0 +1,17 @@
+CREATE TABLE attestations (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    predicate_type TEXT NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    subject_names TEXT[] NOT NULL,
+    subject_digests TEXT[] NOT NULL,
+    predicate JSONB NOT NULL,
+    passed BOOLEAN,
+    verified_key_id TEXT,
+    envelope JSONB NOT NULL,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_attestations_subject_digests ON attestations USING GIN (subject_digests);
+CREATE INDEX idx_attestations_kind ON attestations (tenant_id, kind, created_at DESC);
@@ -0,0 +1,20 @@
+CREATE TABLE source_trust_policies (
+    tenant_id UUID PRIMARY KEY,
+    allowed_signers JSONB NOT NULL,
+    protected_refs TEXT[] NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE source_verifications (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    provenance_id UUID NOT NULL,
+    source_commit VARCHAR(64),
+    source_ref TEXT,
+    passed BOOLEAN NOT NULL,
+    checks JSONB NOT NULL,
+    verified_by UUID NOT NULL,
+    verified_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_source_verifications_provenance_id ON source_verifications (provenance_id, verified_at DESC);
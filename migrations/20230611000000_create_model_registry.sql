@@ -0,0 +1,28 @@
+CREATE TABLE registered_models (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, name)
+);
+
+CREATE TABLE model_versions (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    model_id UUID NOT NULL REFERENCES registered_models(id),
+    version TEXT NOT NULL,
+    weights JSONB,
+    weights_sha256 VARCHAR(64),
+    weights_uri TEXT,
+    card JSONB NOT NULL,
+    training_provenance_id UUID,
+    datasets JSONB NOT NULL,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (model_id, version)
+);
+
+CREATE INDEX idx_model_versions_weights_sha256 ON model_versions (tenant_id, weights_sha256);
+CREATE INDEX idx_model_versions_training_provenance_id ON model_versions (training_provenance_id);
@@ -0,0 +1,31 @@
+CREATE TABLE dataset_versions (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    uri TEXT,
+    digest VARCHAR(64) NOT NULL,
+    files JSONB NOT NULL,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, name, version)
+);
+
+CREATE INDEX idx_dataset_versions_digest ON dataset_versions (tenant_id, digest);
+
+CREATE TABLE data_transformations (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    job_name TEXT NOT NULL,
+    run_id TEXT,
+    code JSONB,
+    inputs JSONB NOT NULL,
+    outputs JSONB NOT NULL,
+    facets JSONB NOT NULL,
+    started_at TIMESTAMP WITH TIME ZONE,
+    ended_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_data_transformations_job ON data_transformations (tenant_id, job_name, created_at DESC);
@@ -0,0 +1,15 @@
+CREATE TABLE openlineage_events (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    run_id UUID NOT NULL,
+    event_type VARCHAR(16) NOT NULL,
+    event_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    job_name TEXT NOT NULL,
+    event JSONB NOT NULL,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_openlineage_events_run_id ON openlineage_events (tenant_id, run_id, event_time);
+
+-- A run is recorded as at most one transformation, so redelivered COMPLETE events are ignored
+CREATE UNIQUE INDEX idx_data_transformations_run ON data_transformations (tenant_id, job_name, run_id) WHERE run_id IS NOT NULL;
@@ -0,0 +1,12 @@
+CREATE TABLE model_signatures (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    model_version_id UUID NOT NULL REFERENCES model_versions(id),
+    key_id TEXT NOT NULL,
+    root_digest VARCHAR(64) NOT NULL,
+    bundle JSONB NOT NULL,
+    signed_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_model_signatures_model_version_id ON model_signatures (model_version_id, created_at DESC);
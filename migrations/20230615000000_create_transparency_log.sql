@@ -0,0 +1,25 @@
+CREATE TABLE transparency_log_entries (
+    tenant_id UUID NOT NULL,
+    leaf_index BIGINT NOT NULL,
+    leaf_hash VARCHAR(64) NOT NULL,
+    leaf_data TEXT NOT NULL,
+    body JSONB NOT NULL,
+    kind TEXT NOT NULL,
+    resource_id UUID NOT NULL,
+    digest VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tenant_id, leaf_index)
+);
+
+CREATE INDEX idx_transparency_log_entries_digest ON transparency_log_entries (tenant_id, digest);
+CREATE INDEX idx_transparency_log_entries_resource ON transparency_log_entries (tenant_id, resource_id);
+
+CREATE TABLE transparency_tree_heads (
+    tenant_id UUID NOT NULL,
+    tree_size BIGINT NOT NULL,
+    root_hash VARCHAR(64) NOT NULL,
+    log_id TEXT NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    envelope JSONB NOT NULL,
+    PRIMARY KEY (tenant_id, tree_size)
+);
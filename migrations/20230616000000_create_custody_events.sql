@@ -0,0 +1,31 @@
+CREATE TABLE custody_events (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    artifact_digest VARCHAR(64) NOT NULL,
+    sequence INTEGER NOT NULL,
+    event_type TEXT NOT NULL,
+    category TEXT,
+    environment TEXT,
+    actor UUID NOT NULL,
+    evidence JSONB NOT NULL DEFAULT '[]',
+    details JSONB,
+    previous_hash VARCHAR(64),
+    event_hash VARCHAR(64) NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    UNIQUE (tenant_id, artifact_digest, sequence)
+);
+
+CREATE TABLE custody_policies (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    gated_event TEXT NOT NULL,
+    environment_pattern TEXT,
+    required_events JSONB NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, name)
+);
+
+CREATE INDEX idx_custody_policies_tenant_id ON custody_policies (tenant_id);
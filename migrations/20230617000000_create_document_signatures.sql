@@ -0,0 +1,10 @@
+CREATE TABLE document_signatures (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    digest VARCHAR(64) NOT NULL,
+    bundle JSONB NOT NULL,
+    signed_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_document_signatures_digest ON document_signatures (tenant_id, digest);
@@ -0,0 +1,11 @@
+CREATE TABLE record_timestamps (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    resource_id UUID NOT NULL,
+    digest VARCHAR(64) NOT NULL,
+    timestamp_token JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_record_timestamps_resource ON record_timestamps (tenant_id, kind, resource_id);
@@ -0,0 +1,12 @@
+CREATE TABLE cosign_imports (
+    tenant_id UUID NOT NULL,
+    layer_digest VARCHAR(71) NOT NULL,
+    repository TEXT NOT NULL,
+    image_digest VARCHAR(71) NOT NULL,
+    record_kind VARCHAR(32) NOT NULL,
+    record_id UUID NOT NULL,
+    imported_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tenant_id, layer_digest)
+);
+
+CREATE INDEX idx_cosign_imports_image ON cosign_imports (tenant_id, image_digest);
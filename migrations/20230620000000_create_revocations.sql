@@ -0,0 +1,14 @@
+CREATE TABLE revocations (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    target_type VARCHAR(32) NOT NULL,
+    target TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    effective_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_revocations_tenant ON revocations (tenant_id, created_at DESC);
+CREATE INDEX idx_revocations_target ON revocations (tenant_id, target_type, target);
+CREATE INDEX idx_attestations_verified_key ON attestations (tenant_id, verified_key_id, created_at);
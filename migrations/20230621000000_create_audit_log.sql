@@ -0,0 +1,43 @@
+CREATE TABLE audit_entries (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    sequence BIGINT NOT NULL,
+    actor UUID,
+    channel VARCHAR(16) NOT NULL,
+    action TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    before_hash VARCHAR(64),
+    after_hash VARCHAR(64),
+    request_id TEXT NOT NULL,
+    source_ip TEXT,
+    previous_hash VARCHAR(64),
+    entry_hash VARCHAR(64) NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    UNIQUE (tenant_id, sequence)
+);
+
+CREATE INDEX idx_audit_entries_actor ON audit_entries (tenant_id, actor, sequence);
+CREATE INDEX idx_audit_entries_action ON audit_entries (tenant_id, action, sequence);
+CREATE INDEX idx_audit_entries_timestamp ON audit_entries (tenant_id, timestamp);
+
+-- The audit log is append-only; reject any attempt to rewrite history.
+CREATE FUNCTION reject_audit_entry_change() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit entries are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_entries_append_only
+    BEFORE UPDATE OR DELETE ON audit_entries
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_entry_change();
+
+CREATE TABLE audit_checkpoints (
+    tenant_id UUID NOT NULL,
+    sequence BIGINT NOT NULL,
+    head_hash VARCHAR(64) NOT NULL,
+    log_id TEXT NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    envelope JSONB NOT NULL,
+    PRIMARY KEY (tenant_id, sequence)
+);
@@ -0,0 +1,55 @@
+CREATE TABLE witness_peers (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    log_id TEXT NOT NULL,
+    log_public_key TEXT NOT NULL,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, name)
+);
+
+-- Heads this instance verified and co-signed, kept as received.
+CREATE TABLE witnessed_tree_heads (
+    peer_id UUID NOT NULL REFERENCES witness_peers (id),
+    tree_size BIGINT NOT NULL,
+    root_hash VARCHAR(64) NOT NULL,
+    head JSONB NOT NULL,
+    witnessed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (peer_id, tree_size)
+);
+
+CREATE TABLE trusted_witnesses (
+    tenant_id UUID NOT NULL,
+    key_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tenant_id, key_id)
+);
+
+-- Witness signatures over this instance's own signed tree heads.
+CREATE TABLE tree_head_cosignatures (
+    tenant_id UUID NOT NULL,
+    tree_size BIGINT NOT NULL,
+    witness_key_id TEXT NOT NULL,
+    witness_name TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (tenant_id, tree_size, witness_key_id),
+    FOREIGN KEY (tenant_id, tree_size) REFERENCES transparency_tree_heads (tenant_id, tree_size)
+);
+
+CREATE TABLE split_view_alerts (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    peer_id UUID REFERENCES witness_peers (id),
+    kind VARCHAR(32) NOT NULL,
+    log_id TEXT NOT NULL,
+    expected JSONB NOT NULL,
+    observed JSONB NOT NULL,
+    detected_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX idx_split_view_alerts_tenant ON split_view_alerts (tenant_id, detected_at DESC);
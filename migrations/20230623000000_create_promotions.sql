@@ -0,0 +1,30 @@
+CREATE TABLE promotion_policies (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    environment_pattern TEXT,
+    rules JSONB NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (tenant_id, name)
+);
+CREATE INDEX idx_promotion_policies_tenant_id ON promotion_policies (tenant_id);
+
+-- Every decision is kept, including refusals; `envelope` holds the signed rationale.
+CREATE TABLE promotion_decisions (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    artifact_digest VARCHAR(64) NOT NULL,
+    from_environment TEXT NOT NULL,
+    to_environment TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    checks JSONB NOT NULL,
+    policy_ids UUID[] NOT NULL,
+    override_reason TEXT,
+    requested_by UUID NOT NULL,
+    request_id TEXT,
+    envelope JSONB NOT NULL,
+    decided_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+CREATE INDEX idx_promotion_decisions_artifact ON promotion_decisions (tenant_id, artifact_digest, decided_at);
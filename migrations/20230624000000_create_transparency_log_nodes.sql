@@ -0,0 +1,34 @@
+-- Hashes of complete subtrees of each tenant's log, so roots and proofs read
+-- O(log n) rows instead of every leaf. Level 0 holds the leaf hashes.
+CREATE TABLE transparency_log_nodes (
+    tenant_id UUID NOT NULL,
+    level INTEGER NOT NULL,
+    node_index BIGINT NOT NULL,
+    hash VARCHAR(64) NOT NULL,
+    PRIMARY KEY (tenant_id, level, node_index)
+);
+
+INSERT INTO transparency_log_nodes (tenant_id, level, node_index, hash)
+SELECT tenant_id, 0, leaf_index, leaf_hash
+FROM transparency_log_entries;
+
+-- Pair up complete subtrees level by level, hashing interior nodes as
+-- SHA-256(0x01 || left || right) like RFC 6962
+DO $$
+DECLARE
+    current_level INTEGER := 0;
+    added BIGINT;
+BEGIN
+    LOOP
+        INSERT INTO transparency_log_nodes (tenant_id, level, node_index, hash)
+        SELECT l.tenant_id, current_level + 1, l.node_index / 2,
+               encode(sha256('\x01'::bytea || decode(l.hash, 'hex') || decode(r.hash, 'hex')), 'hex')
+        FROM transparency_log_nodes l
+        JOIN transparency_log_nodes r
+          ON r.tenant_id = l.tenant_id AND r.level = l.level AND r.node_index = l.node_index + 1
+        WHERE l.level = current_level AND l.node_index % 2 = 0;
+        GET DIAGNOSTICS added = ROW_COUNT;
+        EXIT WHEN added = 0;
+        current_level := current_level + 1;
+    END LOOP;
+END $$;
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Deserialize;
+use tracing::{error, info, instrument};
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::attestations::{Attestation, AttestationKind};
+use crate::provenance::dsse::Envelope;
+use crate::auth::AuthenticatedUser;
+
+#[instrument(skip(db, user, envelope))]
+pub async fn create_attestation(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(envelope): Json<Envelope>,
+) -> Result<(StatusCode, Json<Attestation>)> {
+    let tracer = global::tracer("attestations_api");
+    let mut span = tracer.start("create_attestation");
+
+    let mut attestation = Attestation::from_envelope(user.tenant_id, user.id, envelope)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    span.set_attribute(KeyValue::new("attestation.kind", attestation.kind.as_str()));
+
+    let trusted_keys = db.list_trusted_keys(&user.tenant_id).await?;
+    attestation.verify_signature(&trusted_keys);
+
+    if let Err(e) = db.create_attestation(&attestation).await {
+        error!("Failed to save attestation: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Stored {} attestation {} for {} subjects", attestation.kind.as_str(), attestation.id, attestation.subject_digests.len());
+    span.end();
+    Ok((StatusCode::CREATED, Json(attestation)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttestationFilter {
+    pub kind: Option<AttestationKind>,
+}
+
+pub async fn list_attestations_by_digest(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+    Query(filter): Query<AttestationFilter>,
+) -> Result<Json<Vec<Attestation>>> {
+    let tracer = global::tracer("attestations_api");
+    let span = tracer.start("list_attestations_by_digest");
+    let _guard = span.enter();
+
+    let digest = parse_digest(&digest).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let attestations = db.list_attestations_by_digest(&user.tenant_id, &digest, filter.kind).await?;
+    Ok(Json(attestations))
+}
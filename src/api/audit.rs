@@ -0,0 +1,310 @@
+use axum::{
+    body::{self, Body, Full},
+    extract::{ConnectInfo, MatchedPath, Query, State},
+    http::{header::CONTENT_TYPE, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use crate::auth::AuthenticatedUser;
+use crate::chain_of_custody::audit_log::{
+    client_ip, paginate_audit, to_jsonl, verify_audit_chain, AuditChannel, AuditCheckpoint, AuditEntry, AuditPage, AuditQuery,
+    AuditRecord, AuditVerification, AUDIT_LOG_KEY_ID, MAX_AUDIT_PAGE_SIZE,
+};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::dsse::{DsseError, EnvelopeSigner};
+use crate::security::secret_management::{SecretError, SecretManager};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Hashes of the resource a handler changed. Handlers that know them return
+/// this as a response extension; otherwise the after hash is the hash of the
+/// response body.
+#[derive(Debug, Clone)]
+pub struct AuditHashes {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Records every POST, PUT, PATCH and DELETE in the caller's audit log, after
+/// the handler has run so the entry carries the response status. The state
+/// holds the proxies trusted to report the client address.
+pub async fn record_mutations(
+    State((db, trusted_proxies)): State<(Database, Arc<[IpAddr]>)>,
+    user: Option<AuthenticatedUser>,
+    mut request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if !matches!(*request.method(), Method::POST | Method::PUT | Method::PATCH | Method::DELETE) {
+        return next.run(request).await;
+    }
+
+    let request_id = request.headers().get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        request.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    let route = request.extensions().get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let action = format!("{} {}", request.method(), route);
+    let resource = request.uri().path().to_string();
+    let source_ip = source_ip(&request, &trusted_proxies);
+
+    let response = next.run(request).await;
+    let (mut parts, response_body) = response.into_parts();
+    let (hashes, response_body) = match parts.extensions.remove::<AuditHashes>() {
+        Some(hashes) => (hashes, response_body),
+        None => match hyper::body::to_bytes(response_body).await {
+            Ok(bytes) => {
+                let after = Some(hex::encode(Sha256::digest(&bytes)));
+                (AuditHashes { before: None, after }, body::boxed(Full::from(bytes)))
+            }
+            Err(e) => {
+                error!("Failed to read response body for audit entry {}: {}", request_id, e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        },
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        parts.headers.insert(REQUEST_ID_HEADER, value);
+    }
+
+    let (tenant_id, actor) = match user {
+        Some(AuthenticatedUser(user)) => (user.tenant_id, Some(user.id)),
+        None => (Uuid::nil(), None),
+    };
+    let record = AuditRecord {
+        tenant_id,
+        actor,
+        channel: AuditChannel::Rest,
+        action,
+        resource,
+        status: parts.status.as_u16() as i32,
+        before_hash: hashes.before,
+        after_hash: hashes.after,
+        request_id,
+        source_ip,
+    };
+    // The mutation has already happened, so a failed append is logged rather
+    // than turned into an error response
+    if let Err(e) = db.append_audit_entry(record.clone()).await {
+        error!("Failed to record audit entry for {} (request {}): {}", record.action, record.request_id, e);
+    }
+    Response::from_parts(parts, response_body)
+}
+
+/// The client address: the peer, or the address a trusted proxy forwarded.
+fn source_ip(request: &Request<Body>, trusted_proxies: &[IpAddr]) -> Option<String> {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+    let forwarded_for = request.headers().get("x-forwarded-for").and_then(|value| value.to_str().ok());
+    client_ip(peer, forwarded_for, trusted_proxies)
+}
+
+pub async fn list_audit_entries(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditPage>> {
+    let tracer = global::tracer("audit_api");
+    let span = tracer.start("list_audit_entries");
+    let _guard = span.enter();
+
+    let page_size = query.page_size();
+    let entries = db.list_audit_entries(&user.tenant_id, &query, page_size + 1).await?;
+    Ok(Json(paginate_audit(entries, page_size)))
+}
+
+/// Every entry matching the query as JSON Lines, ignoring `limit`.
+pub async fn export_audit_log(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(mut query): Query<AuditQuery>,
+) -> Result<Response> {
+    let tracer = global::tracer("audit_api");
+    let mut span = tracer.start("export_audit_log");
+
+    let mut output = String::new();
+    let mut exported = 0;
+    loop {
+        let entries = db.list_audit_entries(&user.tenant_id, &query, MAX_AUDIT_PAGE_SIZE).await?;
+        output.push_str(&to_jsonl(&entries)?);
+        exported += entries.len();
+        match entries.last() {
+            Some(last) if entries.len() as i64 == MAX_AUDIT_PAGE_SIZE => query.after_sequence = Some(last.sequence),
+            _ => break,
+        }
+    }
+
+    span.set_attribute(KeyValue::new("audit.exported", exported as i64));
+    span.end();
+    Ok(([(CONTENT_TYPE, "application/x-ndjson")], output).into_response())
+}
+
+/// Signs a checkpoint at the head of the tenant's audit log, or returns the
+/// latest one if the log has not grown since.
+pub async fn checkpoint_tenant<M: SecretManager>(db: &Database, secret_manager: &M, tenant_id: Uuid) -> Result<Option<AuditCheckpoint>> {
+    let Some(head) = db.get_audit_head(&tenant_id).await? else {
+        return Ok(None);
+    };
+    if let Some(latest) = db.list_audit_checkpoints(&tenant_id, 1).await?.pop() {
+        if latest.checkpoint.sequence == head.sequence {
+            return Ok(Some(latest));
+        }
+    }
+
+    let signer = EnvelopeSigner::load_or_create(secret_manager, AUDIT_LOG_KEY_ID, tenant_id).await.map_err(|e| {
+        error!("Failed to load audit log key: {}", e);
+        AppError::InternalServerError
+    })?;
+    let checkpoint = AuditCheckpoint::sign(&signer, &head).map_err(|e| {
+        error!("Failed to sign audit checkpoint: {}", e);
+        AppError::InternalServerError
+    })?;
+    db.create_audit_checkpoint(&checkpoint).await?;
+    info!("Checkpointed audit log of tenant {} at entry {}", tenant_id, head.sequence);
+    Ok(Some(checkpoint))
+}
+
+pub async fn create_audit_checkpoint<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<(StatusCode, Json<AuditCheckpoint>)> {
+    let tracer = global::tracer("audit_api");
+    let span = tracer.start("create_audit_checkpoint");
+    let _guard = span.enter();
+
+    let checkpoint = checkpoint_tenant(&db, &secret_manager, user.tenant_id).await?
+        .ok_or_else(|| AppError::NotFound("The audit log is empty".to_string()))?;
+    Ok((StatusCode::CREATED, Json(checkpoint)))
+}
+
+pub async fn list_audit_checkpoints(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<AuditCheckpoint>>> {
+    let tracer = global::tracer("audit_api");
+    let span = tracer.start("list_audit_checkpoints");
+    let _guard = span.enter();
+
+    let checkpoints = db.list_audit_checkpoints(&user.tenant_id, MAX_AUDIT_PAGE_SIZE).await?;
+    Ok(Json(checkpoints))
+}
+
+/// Re-hashes the whole chain and checks the latest checkpoint's signature
+/// against the entry it was taken at.
+pub async fn verify_audit_log<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<AuditVerification>> {
+    let tracer = global::tracer("audit_api");
+    let mut span = tracer.start("verify_audit_log");
+
+    let checkpoint = db.list_audit_checkpoints(&user.tenant_id, 1).await?.pop();
+    let mut query = AuditQuery::default();
+    let mut previous: Option<AuditEntry> = None;
+    let mut checkpoint_entry: Option<AuditEntry> = None;
+    let mut length = 0;
+    let mut error = None;
+    loop {
+        let page = db.list_audit_entries(&user.tenant_id, &query, MAX_AUDIT_PAGE_SIZE).await?;
+        let Some(last) = page.last().cloned() else {
+            break;
+        };
+        // Carry the previous page's last entry over so the pages link up
+        let chunk: Vec<AuditEntry> = previous.take().into_iter().chain(page.iter().cloned()).collect();
+        if let Err(e) = verify_audit_chain(&chunk) {
+            error = Some(e.to_string());
+            break;
+        }
+        if let Some(checkpoint) = &checkpoint {
+            if let Some(entry) = page.iter().find(|entry| entry.sequence == checkpoint.checkpoint.sequence) {
+                checkpoint_entry = Some(entry.clone());
+            }
+        }
+        length += page.len();
+        query.after_sequence = Some(last.sequence);
+        let full = page.len() as i64 == MAX_AUDIT_PAGE_SIZE;
+        previous = Some(last);
+        if !full {
+            break;
+        }
+    }
+    // A gap before the first entry would otherwise go unnoticed
+    if error.is_none() && previous.as_ref().is_some_and(|head| head.sequence != length as i64) {
+        error = Some("Audit log has missing entries".to_string());
+    }
+
+    let checkpoint_sequence = checkpoint.as_ref().map(|checkpoint| checkpoint.checkpoint.sequence);
+    if let (None, Some(checkpoint)) = (&error, &checkpoint) {
+        // Verification must not mint a key; one that signed a checkpoint should exist
+        let signer = EnvelopeSigner::load(&secret_manager, AUDIT_LOG_KEY_ID, user.tenant_id).await.map_err(|e| match e {
+            DsseError::SecretError(SecretError::SecretNotFound) => {
+                AppError::NotFound("Tenant has no audit log key".to_string())
+            }
+            e => {
+                error!("Failed to load audit log key: {}", e);
+                AppError::InternalServerError
+            }
+        })?;
+        let result = match &checkpoint_entry {
+            Some(entry) => checkpoint.verify(&signer.verifying_key(), entry).map_err(|e| e.to_string()),
+            None => Err(format!("Checkpointed entry {} is missing", checkpoint.checkpoint.sequence)),
+        };
+        error = result.err();
+    }
+
+    if let Some(e) = &error {
+        warn!("Audit log of tenant {} failed verification: {}", user.tenant_id, e);
+    }
+    span.set_attribute(KeyValue::new("audit.length", length as i64));
+    span.end();
+    Ok(Json(AuditVerification {
+        valid: error.is_none(),
+        length,
+        head_hash: previous.map(|head| head.entry_hash),
+        checkpoint_sequence,
+        error,
+    }))
+}
+
+/// Periodically checkpoints every tenant whose audit log has grown since its
+/// last checkpoint.
+pub fn spawn_audit_checkpoints<M: SecretManager + Send + Sync + 'static>(
+    db: Database,
+    secret_manager: M,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let tenants = match db.list_audit_tenants_pending_checkpoint().await {
+                Ok(tenants) => tenants,
+                Err(e) => {
+                    error!("Failed to list tenants pending an audit checkpoint: {}", e);
+                    continue;
+                }
+            };
+            for tenant_id in tenants {
+                if let Err(e) = checkpoint_tenant(&db, &secret_manager, tenant_id).await {
+                    error!("Failed to checkpoint audit log of tenant {}: {:?}", tenant_id, e);
+                }
+            }
+        }
+    })
+}
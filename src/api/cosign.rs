@@ -0,0 +1,125 @@
+use axum::{
+    extract::State,
+    Json,
+};
+use chrono::Utc;
+use opentelemetry::{global, KeyValue};
+use serde::Deserialize;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::auth::{AuthenticatedUser, User};
+use crate::chain_of_custody::timestamp::TimestampService;
+use crate::chain_of_custody::transparency_log::{LogEntryBody, LogEntryKind};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::models::ProvenanceRecord;
+use crate::provenance::attestations::Attestation;
+use crate::provenance::cosign::{CosignAttestation, CosignError, CosignImport, CosignLookup, CosignService, ImportedRecordKind};
+use crate::provenance::revisions::ProvenanceRevision;
+use crate::storage::blob_storage::BlobStorage;
+use super::provenance::store_provenance_content;
+use super::timestamps::attach_timestamp;
+
+#[derive(Debug, Deserialize)]
+pub struct CosignLookupRequest {
+    /// Repository in the configured registry, e.g. `team/app`.
+    pub repository: String,
+    pub digest: String,
+}
+
+/// Pulls the cosign signatures and attestations attached to an image and
+/// verifies them. Verified attestations are imported into the provenance
+/// store: SLSA provenance as a provenance record, anything else as an
+/// attestation. Imports are logged and timestamped like direct uploads.
+#[instrument(skip(db, storage, cosign, timestamps, user))]
+pub async fn lookup_cosign_artifacts<S: BlobStorage>(
+    State(db): State<Database>,
+    State(storage): State<S>,
+    State(cosign): State<CosignService>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<CosignLookupRequest>,
+) -> Result<Json<CosignLookup>> {
+    let tracer = global::tracer("cosign_api");
+    let mut span = tracer.start("lookup_cosign_artifacts");
+    span.set_attribute(KeyValue::new("oci.repository", request.repository.clone()));
+
+    let trusted_keys = db.list_trusted_keys(&user.tenant_id).await?;
+    let mut lookup = cosign.lookup(&trusted_keys, &request.repository, &request.digest).await.map_err(|e| match e {
+        CosignError::InvalidDigest(_) => AppError::BadRequest(e.to_string()),
+        CosignError::Disabled => AppError::NotFound("No OCI registry is configured".to_string()),
+        e => {
+            error!("Failed to look up cosign artifacts for {}@{}: {}", request.repository, request.digest, e);
+            AppError::InternalServerError
+        }
+    })?;
+
+    for attestation in lookup.attestations.iter_mut().filter(|attestation| attestation.verified()) {
+        match import_attestation(&db, &storage, &timestamps, &user, &lookup.repository, &lookup.digest, attestation).await {
+            Ok(import) => attestation.import = Some(import),
+            Err(e) => attestation.error = Some(format!("Import failed: {}", e)),
+        }
+    }
+
+    info!(
+        "Found {} signatures and {} attestations for {}@{}",
+        lookup.signatures.len(), lookup.attestations.len(), lookup.repository, lookup.digest
+    );
+    span.end();
+    Ok(Json(lookup))
+}
+
+/// Imports a verified attestation once; later lookups return the record it
+/// was first imported as.
+async fn import_attestation<S: BlobStorage>(
+    db: &Database,
+    storage: &S,
+    timestamps: &TimestampService,
+    user: &User,
+    repository: &str,
+    image_digest: &str,
+    attestation: &CosignAttestation,
+) -> Result<CosignImport> {
+    if let Some(import) = db.get_cosign_import(&user.tenant_id, &attestation.layer_digest).await? {
+        return Ok(import);
+    }
+    let envelope = attestation.envelope.clone()
+        .ok_or_else(|| AppError::BadRequest("Attestation has no envelope".to_string()))?;
+
+    // Provenance is stored the way bulk ingest stores it, which logs it too
+    let (record_kind, record_id) = if attestation.is_provenance() {
+        let mut record = ProvenanceRecord::from_envelope(envelope).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        record.id = Uuid::new_v4();
+        record.created_by = user.id;
+        let revision = ProvenanceRevision::initial(record).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        if let Some(record) = &revision.content {
+            store_provenance_content(db, storage, record).await?;
+        }
+        db.ingest_provenance_batch(&user.tenant_id, std::slice::from_ref(&revision)).await?;
+        attach_timestamp(db, timestamps, user.tenant_id, LogEntryKind::Provenance, revision.provenance_id, &revision.content_hash).await;
+        (ImportedRecordKind::Provenance, revision.provenance_id)
+    } else {
+        let payload = envelope.payload_bytes().map_err(|e| AppError::BadRequest(e.to_string()))?;
+        let mut stored = Attestation::from_envelope(user.tenant_id, user.id, envelope)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        stored.verified_key_id = attestation.verified_key_id.clone();
+        db.create_attestation(&stored).await?;
+        let log_entry = LogEntryBody::for_content(LogEntryKind::Attestation, stored.id, &payload);
+        db.append_log_entries(&user.tenant_id, std::slice::from_ref(&log_entry)).await?;
+        attach_timestamp(db, timestamps, user.tenant_id, LogEntryKind::Attestation, stored.id, &log_entry.digest).await;
+        (ImportedRecordKind::Attestation, stored.id)
+    };
+
+    let import = CosignImport {
+        tenant_id: user.tenant_id,
+        repository: repository.to_string(),
+        image_digest: image_digest.to_string(),
+        layer_digest: attestation.layer_digest.clone(),
+        record_kind,
+        record_id,
+        imported_at: Utc::now(),
+    };
+    db.create_cosign_import(&import).await?;
+    info!("Imported cosign attestation {} as {} {}", import.layer_digest, record_kind.as_str(), record_id);
+    Ok(import)
+}
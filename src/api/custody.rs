@@ -0,0 +1,160 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::{
+    evaluate_custody_policies, normalize_digest, parse_digest, AppendCustodyEventRequest, CustodyChainVerification,
+    CustodyEvent, CustodyPolicy, CustodyPolicyRequest,
+};
+use crate::chain_of_custody::promotion::check_manual_event;
+use crate::chain_of_custody::timestamp::TimestampService;
+use crate::chain_of_custody::transparency_log::LogEntryKind;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::auth::AuthenticatedUser;
+use crate::auth::authorization::Authorization;
+use super::timestamps::attach_timestamp;
+
+/// Appends an event to the artifact's custody chain. Events gated by a
+/// custody policy are rejected until the chain holds the required events.
+/// Events that would move the artifact between environments are refused;
+/// only a promotion may do that. Each event type needs its own action on
+/// `custody_events`, e.g. `approved`, so a build bot cannot sign off.
+#[instrument(skip(db, auth, timestamps, user, request))]
+pub async fn append_custody_event<A: Authorization>(
+    State(db): State<Database>,
+    State(auth): State<A>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+    Json(request): Json<AppendCustodyEventRequest>,
+) -> Result<(StatusCode, Json<CustodyEvent>)> {
+    let tracer = global::tracer("custody_api");
+    let mut span = tracer.start("append_custody_event");
+    span.set_attribute(KeyValue::new("custody.event_type", request.event_type.as_str()));
+
+    if !auth.is_allowed(&user.id.to_string(), "custody_events", request.event_type.as_str(), user.tenant_id).await {
+        span.end();
+        return Err(AppError::Forbidden(format!(
+            "You don't have permission to record {} events", request.event_type.as_str()
+        )));
+    }
+
+    let digest = parse_digest(&digest).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let events = db.list_custody_events(&user.tenant_id, &digest).await?;
+    if let Err(e) = check_manual_event(&request, &events) {
+        span.end();
+        return Err(AppError::BadRequest(e.to_string()));
+    }
+    let policies = db.list_custody_policies(&user.tenant_id).await?;
+    let violations = evaluate_custody_policies(&policies, &events, request.event_type, request.environment.as_deref());
+    if !violations.is_empty() {
+        let missing: Vec<String> = violations.iter()
+            .map(|violation| {
+                let mut required = violation.missing.event_type.as_str().to_string();
+                if let Some(category) = &violation.missing.category {
+                    required = format!("{} ({})", required, category);
+                }
+                format!("{} requires a {} event", violation.policy_name, required)
+            })
+            .collect();
+        span.end();
+        return Err(AppError::BadRequest(format!("Custody policy not satisfied: {}", missing.join("; "))));
+    }
+
+    let event = CustodyEvent::append(user.tenant_id, &digest, user.id, events.last(), request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if let Err(e) = db.create_custody_event(&event).await {
+        error!("Failed to append custody event for {}: {}", digest, e);
+        span.end();
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+    attach_timestamp(&db, &timestamps, user.tenant_id, LogEntryKind::CustodyEvent, event.id, &event.event_hash).await;
+
+    info!("Appended {} event {} to custody chain of {}", event.event_type.as_str(), event.sequence, digest);
+    span.end();
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+pub async fn list_custody_events(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+) -> Result<Json<Vec<CustodyEvent>>> {
+    let tracer = global::tracer("custody_api");
+    let span = tracer.start("list_custody_events");
+    let _guard = span.enter();
+
+    let events = db.list_custody_events(&user.tenant_id, &normalize_digest(&digest)).await?;
+    Ok(Json(events))
+}
+
+pub async fn verify_custody_chain(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+) -> Result<Json<CustodyChainVerification>> {
+    let tracer = global::tracer("custody_api");
+    let span = tracer.start("verify_custody_chain");
+    let _guard = span.enter();
+
+    let events = db.list_custody_events(&user.tenant_id, &normalize_digest(&digest)).await?;
+    if events.is_empty() {
+        return Err(AppError::NotFound(format!("No custody events for {}", digest)));
+    }
+    Ok(Json(CustodyChainVerification::new(&digest, &events)))
+}
+
+#[instrument(skip(db, user))]
+pub async fn create_custody_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<CustodyPolicyRequest>,
+) -> Result<(StatusCode, Json<CustodyPolicy>)> {
+    let tracer = global::tracer("custody_api");
+    let span = tracer.start("create_custody_policy");
+    let _guard = span.enter();
+
+    let policy = CustodyPolicy::from_request(user.tenant_id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    if let Err(e) = db.create_custody_policy(&policy).await {
+        error!("Failed to save custody policy: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Created custody policy {} for tenant {}", policy.id, policy.tenant_id);
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+pub async fn list_custody_policies(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<CustodyPolicy>>> {
+    let tracer = global::tracer("custody_api");
+    let span = tracer.start("list_custody_policies");
+    let _guard = span.enter();
+
+    let policies = db.list_custody_policies(&user.tenant_id).await?;
+    Ok(Json(policies))
+}
+
+#[instrument(skip(db, user))]
+pub async fn delete_custody_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let tracer = global::tracer("custody_api");
+    let span = tracer.start("delete_custody_policy");
+    let _guard = span.enter();
+
+    if !db.delete_custody_policy(&user.tenant_id, &id).await? {
+        return Err(AppError::NotFound(format!("Custody policy {} not found", id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
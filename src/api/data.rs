@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::data::data_provenance::{
+    DataLineage, DatasetVersion, RecordTransformationRequest, RegisterDatasetVersionRequest, Transformation,
+};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::lineage::{traverse, LineageDirection, DEFAULT_LINEAGE_DEPTH};
+use crate::auth::AuthenticatedUser;
+
+#[instrument(skip(db, user, request))]
+pub async fn register_dataset_version(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<RegisterDatasetVersionRequest>,
+) -> Result<(StatusCode, Json<DatasetVersion>)> {
+    let tracer = global::tracer("data_api");
+    let mut span = tracer.start("register_dataset_version");
+
+    let dataset = DatasetVersion::new(user.tenant_id, user.id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    span.set_attribute(KeyValue::new("dataset.files", dataset.files.len() as i64));
+    let existing = db.list_dataset_versions(&user.tenant_id, Some(&dataset.name), Some(&dataset.version), None, None).await?;
+    if !existing.is_empty() {
+        return Err(AppError::BadRequest(format!("Dataset {} version {} already exists", dataset.name, dataset.version)));
+    }
+
+    match db.create_dataset_version(&dataset).await {
+        Ok(true) => {}
+        Ok(false) => return Err(AppError::BadRequest(format!("Dataset {} version {} already exists", dataset.name, dataset.version))),
+        Err(e) => {
+            error!("Failed to register dataset version: {}", e);
+            return Err(AppError::DatabaseError(e.to_string()));
+        }
+    }
+
+    info!("Registered dataset {} version {} with digest {}", dataset.name, dataset.version, dataset.digest);
+    span.end();
+    Ok((StatusCode::CREATED, Json(dataset)))
+}
+
+pub async fn list_dataset_versions(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<DatasetVersion>>> {
+    let tracer = global::tracer("data_api");
+    let span = tracer.start("list_dataset_versions");
+    let _guard = span.enter();
+
+    Ok(Json(db.list_dataset_versions(&user.tenant_id, Some(&name), None, None, None).await?))
+}
+
+pub async fn get_dataset_version(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+) -> Result<Json<DatasetVersion>> {
+    let tracer = global::tracer("data_api");
+    let span = tracer.start("get_dataset_version");
+    let _guard = span.enter();
+
+    Ok(Json(find_dataset_version(&db, &user.tenant_id, &name, &version).await?))
+}
+
+#[instrument(skip(db, user, request))]
+pub async fn record_transformation(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<RecordTransformationRequest>,
+) -> Result<(StatusCode, Json<Transformation>)> {
+    let tracer = global::tracer("data_api");
+    let mut span = tracer.start("record_transformation");
+    span.set_attribute(KeyValue::new("transformation.job", request.job_name.clone()));
+
+    let inputs = resolve_datasets(&db, &user.tenant_id, &request.inputs).await?;
+    let outputs = resolve_datasets(&db, &user.tenant_id, &request.outputs).await?;
+    let transformation = Transformation::new(user.tenant_id, user.id, request, &inputs, &outputs)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    match db.create_transformation(&transformation).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let run_id = transformation.run_id.as_deref().unwrap_or_default();
+            return Err(AppError::BadRequest(format!("Run {} of job {} is already recorded", run_id, transformation.job_name)));
+        }
+        Err(e) => {
+            error!("Failed to record transformation: {}", e);
+            return Err(AppError::DatabaseError(e.to_string()));
+        }
+    }
+
+    info!("Recorded transformation {} with {} inputs and {} outputs", transformation.id, inputs.len(), outputs.len());
+    span.end();
+    Ok((StatusCode::CREATED, Json(transformation)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineageFormat {
+    Json,
+    JsonLd,
+    Turtle,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DataLineageParams {
+    pub depth: Option<u32>,
+    pub format: Option<LineageFormat>,
+}
+
+/// Answers "which datasets and code produced this dataset version", as
+/// TraceGuard JSON or W3C PROV-O (`?format=jsonld` or `?format=turtle`).
+#[instrument(skip(db, user))]
+pub async fn get_dataset_lineage(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+    Query(params): Query<DataLineageParams>,
+) -> Result<Response> {
+    let tracer = global::tracer("data_api");
+    let mut span = tracer.start("get_dataset_lineage");
+
+    let root = find_dataset_version(&db, &user.tenant_id, &name, &version).await?;
+    let depth = params.depth.unwrap_or(DEFAULT_LINEAGE_DEPTH);
+    let graph = traverse(&db, &user.tenant_id, &root.digest, LineageDirection::Upstream, depth).await?;
+
+    let digests: Vec<String> = graph.nodes.iter().map(|node| node.digest.clone()).collect();
+    let datasets = db.list_dataset_versions(&user.tenant_id, None, None, None, Some(&digests)).await?;
+    let ids: Vec<Uuid> = graph.edges.iter().map(|edge| edge.provenance_id).collect::<BTreeSet<_>>().into_iter().collect();
+    let transformations = db.list_transformations(&user.tenant_id, &ids).await?;
+    let lineage = DataLineage::new(root, &graph, datasets, transformations);
+
+    span.set_attribute(KeyValue::new("lineage.transformations", lineage.transformations.len() as i64));
+    span.end();
+    Ok(match params.format.unwrap_or(LineageFormat::Json) {
+        LineageFormat::Json => Json(lineage).into_response(),
+        LineageFormat::JsonLd => (
+            [(CONTENT_TYPE, "application/ld+json")],
+            Json(lineage.to_prov().to_json_ld()),
+        ).into_response(),
+        LineageFormat::Turtle => (
+            [(CONTENT_TYPE, "text/turtle; charset=utf-8")],
+            lineage.to_prov().to_turtle(),
+        ).into_response(),
+    })
+}
+
+async fn find_dataset_version(db: &Database, tenant_id: &Uuid, name: &str, version: &str) -> Result<DatasetVersion> {
+    db.list_dataset_versions(tenant_id, Some(name), Some(version), None, None).await?
+        .pop()
+        .ok_or_else(|| AppError::NotFound(format!("Dataset {} version {} not found", name, version)))
+}
+
+/// Looks up dataset versions by ID, keeping request order and rejecting unknown IDs.
+async fn resolve_datasets(db: &Database, tenant_id: &Uuid, ids: &[Uuid]) -> Result<Vec<DatasetVersion>> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let found = db.list_dataset_versions(tenant_id, None, None, Some(ids), None).await?;
+    ids.iter()
+        .map(|id| {
+            found.iter().find(|dataset| dataset.id == *id)
+                .cloned()
+                .ok_or_else(|| AppError::NotFound(format!("Dataset version {} not found", id)))
+        })
+        .collect()
+}
@@ -0,0 +1,45 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::global;
+use tracing::{error, info, instrument};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::trusted_keys::{TrustedKey, TrustedKeyRequest};
+use crate::auth::AuthenticatedUser;
+
+#[instrument(skip(db, user))]
+pub async fn register_trusted_key(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<TrustedKeyRequest>,
+) -> Result<(StatusCode, Json<TrustedKey>)> {
+    let tracer = global::tracer("keys_api");
+    let span = tracer.start("register_trusted_key");
+    let _guard = span.enter();
+
+    let key = TrustedKey::from_request(user.tenant_id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    if let Err(e) = db.create_trusted_key(&key).await {
+        error!("Failed to save trusted key: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Registered trusted key {} for tenant {}", key.key_id, key.tenant_id);
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+pub async fn list_trusted_keys(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<TrustedKey>>> {
+    let tracer = global::tracer("keys_api");
+    let span = tracer.start("list_trusted_keys");
+    let _guard = span.enter();
+
+    let keys = db.list_trusted_keys(&user.tenant_id).await?;
+    Ok(Json(keys))
+}
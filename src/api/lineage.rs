@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Deserialize;
+use tracing::{info, instrument};
+use crate::chain_of_custody::custody_events::normalize_digest;
+use crate::database::Database;
+use crate::error::Result;
+use crate::provenance::lineage::{traverse, LineageDirection, LineageGraph, DEFAULT_LINEAGE_DEPTH};
+use crate::auth::AuthenticatedUser;
+
+#[derive(Debug, Deserialize)]
+pub struct LineageParams {
+    pub depth: Option<u32>,
+}
+
+#[instrument(skip(db, user))]
+pub async fn get_upstream_lineage(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+    Query(params): Query<LineageParams>,
+) -> Result<Json<LineageGraph>> {
+    lineage(db, user.tenant_id, digest, LineageDirection::Upstream, params).await
+}
+
+#[instrument(skip(db, user))]
+pub async fn get_downstream_lineage(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+    Query(params): Query<LineageParams>,
+) -> Result<Json<LineageGraph>> {
+    lineage(db, user.tenant_id, digest, LineageDirection::Downstream, params).await
+}
+
+async fn lineage(
+    db: Database,
+    tenant_id: uuid::Uuid,
+    digest: String,
+    direction: LineageDirection,
+    params: LineageParams,
+) -> Result<Json<LineageGraph>> {
+    let tracer = global::tracer("lineage_api");
+    let mut span = tracer.start("traverse_lineage");
+    span.set_attribute(KeyValue::new("lineage.direction", format!("{:?}", direction)));
+
+    let digest = normalize_digest(&digest);
+    let depth = params.depth.unwrap_or(DEFAULT_LINEAGE_DEPTH);
+    info!("Traversing {:?} lineage of {} to depth {}", direction, digest, depth);
+
+    let graph = traverse(&db, &tenant_id, &digest, direction, depth).await?;
+
+    span.set_attribute(KeyValue::new("lineage.nodes", graph.nodes.len() as i64));
+    span.end();
+    Ok(Json(graph))
+}
@@ -1,15 +1,41 @@
 mod sbom;
-mod provenance;
+pub(crate) mod provenance;
+mod audit;
 mod compliance;
+mod cosign;
+mod custody;
 mod lifecycle;
 mod auth;
+mod attestations;
+mod data;
+mod keys;
+mod lineage;
+mod models;
+mod openlineage;
+mod policy;
+mod promotion;
+mod reproducibility;
+mod revocations;
+mod signing;
+mod source;
+mod timestamps;
+mod transparency;
+mod vsa;
+mod witness;
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
-    extract::Multipart,
+    extract::DefaultBodyLimit,
+    middleware,
 };
+use std::net::IpAddr;
+use std::sync::Arc;
+use crate::chain_of_custody::signing::SigningService;
+use crate::chain_of_custody::timestamp::TimestampService;
 use crate::database::Database;
+use crate::provenance::cosign::CosignService;
+use crate::websocket::{live_updates_handler, LiveUpdates};
 use crate::auth::AuthUser;
 use crate::storage::blob_storage::BlobStorage;
 use crate::auth::authorization::Authorization;
@@ -25,24 +51,112 @@ pub fn create_router<S: BlobStorage + Clone + Send + Sync + 'static>(
     secret_manager: impl SecretManager + Clone + Send + Sync + 'static,
     key_rotation_manager: KeyRotationManager<impl SecretManager>,
     lifecycle_manager: LifecycleManager<S>,
+    signing_service: SigningService,
+    timestamp_service: TimestampService,
+    cosign_service: CosignService,
+    live_updates: LiveUpdates,
+    trusted_proxies: Vec<IpAddr>,
 ) -> Router {
     Router::new()
-        .route("/api/sboms", 
-            get(sbom::list_sboms)
-            .post(|state: State<(Database, S)>, multipart: Multipart| 
-                sbom::create_sbom(state.0, state.1, multipart))
-        )
-        .route("/api/provenance", get(provenance::list_provenance_records).post(provenance::create_provenance_record))
+        .route("/api/sboms", get(sbom::list_sboms).post(sbom::create_sbom::<S>))
+        .route("/api/provenance", get(provenance::list_provenance).post(provenance::create_provenance_record))
+        .route("/api/ingest/provenance",
+            post(provenance::bulk_ingest_provenance::<S>).layer(DefaultBodyLimit::max(64 * 1024 * 1024)))
+        .route("/api/provenance/:id",
+            get(provenance::get_provenance)
+            .put(provenance::update_provenance::<S>)
+            .delete(provenance::delete_provenance))
+        .route("/api/provenance/:id/revisions", get(provenance::list_provenance_revisions))
+        .route("/api/provenance/:id/revisions/:revision", get(provenance::get_provenance_revision))
+        .route("/api/provenance/:id/verify", post(provenance::verify_slsa_provenance))
+        .route("/api/provenance/:id/verify/signed", post(provenance::sign_verification_report))
+        .route("/api/provenance/:id/verifications", get(provenance::list_verification_history))
+        .route("/api/provenance/:id/source-verifications",
+            get(source::list_source_verifications)
+            .post(source::verify_provenance_source)
+            .layer(DefaultBodyLimit::max(256 * 1024 * 1024)))
+        .route("/api/source-policy", get(source::get_source_policy).put(source::put_source_policy))
+        .route("/api/provenance/:id/vsa", post(vsa::issue_vsa))
+        .route("/api/attestations", post(attestations::create_attestation))
+        .route("/api/attestations/digest/:digest", get(attestations::list_attestations_by_digest))
+        .route("/api/cosign/lookup", post(cosign::lookup_cosign_artifacts::<S>))
+        .route("/api/revocations", get(revocations::list_revocations).post(revocations::create_revocation))
+        .route("/api/revocations/:id", get(revocations::get_revocation_impact))
+        .route("/api/revocations/artifacts/:digest", get(revocations::get_artifact_revocation_status))
+        .route("/api/updates", get(live_updates_handler))
+        .route("/api/audit", get(audit::list_audit_entries))
+        .route("/api/audit/export", get(audit::export_audit_log))
+        .route("/api/audit/checkpoints", get(audit::list_audit_checkpoints).post(audit::create_audit_checkpoint))
+        .route("/api/audit/verify", get(audit::verify_audit_log))
+        .route("/api/vsa/public-key", get(vsa::get_vsa_public_key))
+        .route("/api/vsa/digest/:digest", get(vsa::list_vsas_by_digest))
+        .route("/api/lineage/:digest/upstream", get(lineage::get_upstream_lineage))
+        .route("/api/lineage/:digest/downstream", get(lineage::get_downstream_lineage))
+        .route("/api/datasets", post(data::register_dataset_version))
+        .route("/api/datasets/:name/versions", get(data::list_dataset_versions))
+        .route("/api/datasets/:name/versions/:version", get(data::get_dataset_version))
+        .route("/api/datasets/:name/versions/:version/lineage", get(data::get_dataset_lineage))
+        .route("/api/transformations", post(data::record_transformation))
+        .route("/api/v1/lineage", post(openlineage::ingest_openlineage_event))
+        .route("/api/models", get(models::list_models).post(models::register_model))
+        .route("/api/models/:name/versions", get(models::list_model_versions).post(models::register_model_version))
+        .route("/api/models/:name/versions/:version", get(models::get_model_version))
+        .route("/api/models/:name/versions/:version/mlbom", get(models::get_model_mlbom))
+        .route("/api/models/:name/versions/:version/weights",
+            put(models::upload_model_weights).layer(DefaultBodyLimit::disable()))
+        .route("/api/models/:name/versions/:version/signature",
+            get(models::get_model_signature).post(models::sign_model_version))
+        .route("/api/models/:name/versions/:version/verify", post(models::verify_model_version))
+        .route("/api/model-signing/public-key", get(models::get_model_signing_public_key))
+        .route("/api/transparency/tree-head", get(transparency::get_tree_head))
+        .route("/api/transparency/public-key", get(transparency::get_log_public_key))
+        .route("/api/transparency/entries", get(transparency::list_log_entries))
+        .route("/api/transparency/entries/:index", get(transparency::get_log_entry))
+        .route("/api/transparency/entries/:index/proof", get(transparency::get_inclusion_proof))
+        .route("/api/transparency/consistency", get(transparency::get_consistency_proof))
+        .route("/api/transparency/witnesses",
+            get(transparency::list_trusted_witnesses).post(transparency::register_trusted_witness))
+        .route("/api/transparency/cosignatures", post(transparency::submit_cosigned_head))
+        .route("/api/witness/public-key", get(witness::get_witness_public_key))
+        .route("/api/witness/peers", get(witness::list_witness_peers).post(witness::create_witness_peer))
+        .route("/api/witness/peers/:id/witness", post(witness::witness_peer_now))
+        .route("/api/witness/peers/:id/heads", get(witness::list_witnessed_heads))
+        .route("/api/witness/alerts", get(witness::list_split_view_alerts))
+        .route("/api/custody/:digest/events", get(custody::list_custody_events).post(custody::append_custody_event))
+        .route("/api/custody/:digest/verify", get(custody::verify_custody_chain))
+        .route("/api/custody-policies", get(custody::list_custody_policies).post(custody::create_custody_policy))
+        .route("/api/custody-policies/:id", delete(custody::delete_custody_policy))
+        .route("/api/promotions/public-key", get(promotion::get_promotion_public_key))
+        .route("/api/promotions/digest/:digest",
+            get(promotion::list_promotion_decisions).post(promotion::promote_artifact))
+        .route("/api/promotion-policies", get(promotion::list_promotion_policies).post(promotion::create_promotion_policy))
+        .route("/api/promotion-policies/:id", delete(promotion::delete_promotion_policy))
+        .route("/api/signing/sign", post(signing::sign_document))
+        .route("/api/signing/verify", post(signing::verify_document))
+        .route("/api/signing/public-key", get(signing::get_document_signing_public_key))
+        .route("/api/signing/trust-bundle", get(signing::get_trust_bundle))
+        .route("/api/signing/signatures/:id", get(signing::get_document_signature))
+        .route("/api/timestamps", get(timestamps::list_record_timestamps))
+        .route("/api/timestamps/certificates", get(timestamps::get_tsa_certificates))
+        .route("/api/timestamps/:id/verify", get(timestamps::verify_record_timestamp))
+        .route("/api/reproducibility", post(reproducibility::compare_builds))
+        .route("/api/reproducibility/digest/:digest", get(reproducibility::list_reproducibility_reports))
+        .route("/api/keys", get(keys::list_trusted_keys).post(keys::register_trusted_key))
+        .route("/api/policies", get(policy::list_builder_policies).post(policy::create_builder_policy))
+        .route("/api/policies/:id", get(policy::get_builder_policy).put(policy::update_builder_policy).delete(policy::delete_builder_policy))
         .route("/api/compliance/report", get(compliance::generate_compliance_report))
         .route("/api/lifecycle/:bucket/:object_key/expiration", post(lifecycle::set_expiration))
         .route("/api/lifecycle/:bucket/:object_key", get(lifecycle::get_lifecycle_policy))
         .route("/api/auth/login", post(auth::login))
         .route("/api/auth/register", post(auth::register))
         .route("/api/auth/refresh", post(auth::refresh_token))
-        .with_state((db, storage, auth, secret_manager, key_rotation_manager, lifecycle_manager))
+        .route_layer(middleware::from_fn_with_state((db.clone(), Arc::<[IpAddr]>::from(trusted_proxies)), audit::record_mutations))
+        .with_state((db, storage, auth, secret_manager, key_rotation_manager, lifecycle_manager, signing_service, timestamp_service, cosign_service, live_updates))
 }
 
 // Re-export types that might be used in other modules
 pub use sbom::SBOM;
 pub use provenance::ProvenanceRecord;
-pub use auth::AuthUser;
\ No newline at end of file
+pub use auth::AuthUser;
+pub use audit::spawn_audit_checkpoints;
+pub use witness::spawn_witness;
\ No newline at end of file
@@ -0,0 +1,302 @@
+use axum::{
+    extract::{BodyStream, Path, State},
+    http::StatusCode,
+    Json,
+};
+use futures_util::StreamExt;
+use opentelemetry::{global, KeyValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, instrument};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::models::model_registry::{
+    ChunkedHasher, ModelVersion, RegisterModelRequest, RegisterModelVersionRequest, RegisteredModel,
+};
+use crate::models::model_signing::{
+    self, ManifestResource, ModelManifest, ModelSignature, ModelVerification, MODEL_SIGNING_KEY_ID,
+};
+use crate::provenance::dsse::{DsseError, EnvelopeSigner};
+use crate::security::secret_management::{SecretError, SecretManager};
+use crate::auth::AuthenticatedUser;
+use super::provenance::current_record;
+
+#[instrument(skip(db, user))]
+pub async fn register_model(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<RegisterModelRequest>,
+) -> Result<(StatusCode, Json<RegisteredModel>)> {
+    let tracer = global::tracer("models_api");
+    let span = tracer.start("register_model");
+    let _guard = span.enter();
+
+    let model = RegisteredModel::new(user.tenant_id, user.id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if db.get_registered_model(&user.tenant_id, &model.name).await?.is_some() {
+        return Err(AppError::BadRequest(format!("Model {} is already registered", model.name)));
+    }
+    if let Err(e) = db.create_registered_model(&model).await {
+        error!("Failed to register model: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+    Ok((StatusCode::CREATED, Json(model)))
+}
+
+pub async fn list_models(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<RegisteredModel>>> {
+    let tracer = global::tracer("models_api");
+    let span = tracer.start("list_models");
+    let _guard = span.enter();
+
+    Ok(Json(db.list_registered_models(&user.tenant_id).await?))
+}
+
+#[instrument(skip(db, user, request))]
+pub async fn register_model_version(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+    Json(request): Json<RegisterModelVersionRequest>,
+) -> Result<(StatusCode, Json<ModelVersion>)> {
+    let tracer = global::tracer("models_api");
+    let mut span = tracer.start("register_model_version");
+    span.set_attribute(KeyValue::new("model.name", name.clone()));
+
+    let model = db.get_registered_model(&user.tenant_id, &name).await?
+        .ok_or_else(|| AppError::NotFound(format!("Model {} not found", name)))?;
+    let version = ModelVersion::new(&model, user.id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if !db.list_model_versions(&user.tenant_id, &name, Some(&version.version)).await?.is_empty() {
+        return Err(AppError::BadRequest(format!("Version {} of model {} already exists", version.version, name)));
+    }
+    if let Some(provenance_id) = version.training_provenance_id {
+        current_record(&db, &user.tenant_id, &provenance_id).await?;
+    }
+
+    if let Err(e) = db.create_model_version(&version).await {
+        error!("Failed to register model version: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Registered version {} of model {}", version.version, name);
+    span.end();
+    Ok((StatusCode::CREATED, Json(version)))
+}
+
+pub async fn list_model_versions(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<ModelVersion>>> {
+    let tracer = global::tracer("models_api");
+    let span = tracer.start("list_model_versions");
+    let _guard = span.enter();
+
+    Ok(Json(db.list_model_versions(&user.tenant_id, &name, None).await?))
+}
+
+pub async fn get_model_version(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+) -> Result<Json<ModelVersion>> {
+    let tracer = global::tracer("models_api");
+    let span = tracer.start("get_model_version");
+    let _guard = span.enter();
+
+    Ok(Json(find_version(&db, &user.tenant_id, &name, &version).await?))
+}
+
+/// Returns the version as a CycloneDX ML-BOM.
+pub async fn get_model_mlbom(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+) -> Result<Json<Value>> {
+    let tracer = global::tracer("models_api");
+    let span = tracer.start("get_model_mlbom");
+    let _guard = span.enter();
+
+    Ok(Json(find_version(&db, &user.tenant_id, &name, &version).await?.to_cyclonedx()))
+}
+
+/// Hashes uploaded weights as they stream in; the body is never buffered.
+/// When the version links training provenance, the weights must be one of
+/// its subjects.
+#[instrument(skip(db, user, body))]
+pub async fn upload_model_weights(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+    mut body: BodyStream,
+) -> Result<Json<ModelVersion>> {
+    let tracer = global::tracer("models_api");
+    let mut span = tracer.start("upload_model_weights");
+
+    let mut model_version = find_version(&db, &user.tenant_id, &name, &version).await?;
+    let mut hasher = ChunkedHasher::new();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| AppError::BadRequest(format!("Failed to read weights: {}", e)))?;
+        hasher.update(&chunk);
+    }
+    let weights = hasher.finalize();
+    span.set_attribute(KeyValue::new("weights.size_bytes", weights.size_bytes as i64));
+
+    if let Some(provenance_id) = model_version.training_provenance_id {
+        let record = current_record(&db, &user.tenant_id, &provenance_id).await?;
+        model_version.check_training_provenance(&weights, &record)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    }
+
+    if let Err(e) = db.set_model_weights(&model_version.id, &weights).await {
+        error!("Failed to store model weights digest: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Hashed {} bytes of weights for {}@{}: {}", weights.size_bytes, name, version, weights.sha256);
+    model_version.weights = Some(weights);
+    span.end();
+    Ok(Json(model_version))
+}
+
+/// Per-file digests of a model directory, as produced by
+/// `traceguard-cli model-manifest`.
+#[derive(Debug, Deserialize)]
+pub struct ModelManifestRequest {
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+    pub resources: Vec<ManifestResource>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelSigningPublicKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+/// Signs a model directory manifest for a registered version. When weights
+/// were uploaded, one of the signed files must have the same digest, so the
+/// signature covers the weights the registry actually holds.
+#[instrument(skip(db, secret_manager, user, request))]
+pub async fn sign_model_version<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+    Json(request): Json<ModelManifestRequest>,
+) -> Result<(StatusCode, Json<ModelSignature>)> {
+    let tracer = global::tracer("models_api");
+    let mut span = tracer.start("sign_model_version");
+    span.set_attribute(KeyValue::new("model.name", name.clone()));
+    span.set_attribute(KeyValue::new("model.files", request.resources.len() as i64));
+
+    let model_version = find_version(&db, &user.tenant_id, &name, &version).await?;
+    let manifest = ModelManifest::new(name.clone(), request.ignore_paths, request.resources)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if let Some(weights) = &model_version.weights {
+        if !manifest.resources.iter().any(|resource| resource.digest == weights.sha256) {
+            return Err(AppError::BadRequest(format!(
+                "Manifest does not contain the registered weights (sha256:{})", weights.sha256
+            )));
+        }
+    }
+
+    let signer = EnvelopeSigner::load_or_create(&secret_manager, MODEL_SIGNING_KEY_ID, user.tenant_id).await.map_err(|e| {
+        error!("Failed to load model signing key: {}", e);
+        AppError::InternalServerError
+    })?;
+    let bundle = model_signing::sign_manifest(&signer, &manifest).map_err(|e| {
+        error!("Failed to sign model manifest: {}", e);
+        AppError::InternalServerError
+    })?;
+    let signature = ModelSignature::new(user.tenant_id, model_version.id, &manifest, bundle, user.id);
+
+    if let Err(e) = db.create_model_signature(&signature).await {
+        error!("Failed to store model signature: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Signed model {} version {} with root digest {}", name, version, signature.root_digest);
+    span.end();
+    Ok((StatusCode::CREATED, Json(signature)))
+}
+
+pub async fn get_model_signature(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+) -> Result<Json<ModelSignature>> {
+    let tracer = global::tracer("models_api");
+    let span = tracer.start("get_model_signature");
+    let _guard = span.enter();
+
+    let model_version = find_version(&db, &user.tenant_id, &name, &version).await?;
+    db.get_latest_model_signature(&user.tenant_id, &model_version.id).await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Version {} of model {} has not been signed", version, name)))
+}
+
+/// Deploy-time check: compares the manifest of the model about to be loaded
+/// against the latest signature for the version.
+#[instrument(skip(db, secret_manager, user, request))]
+pub async fn verify_model_version<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((name, version)): Path<(String, String)>,
+    Json(request): Json<ModelManifestRequest>,
+) -> Result<Json<ModelVerification>> {
+    let tracer = global::tracer("models_api");
+    let mut span = tracer.start("verify_model_version");
+    span.set_attribute(KeyValue::new("model.name", name.clone()));
+
+    let model_version = find_version(&db, &user.tenant_id, &name, &version).await?;
+    let signature = db.get_latest_model_signature(&user.tenant_id, &model_version.id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Version {} of model {} has not been signed", version, name)))?;
+    let current = ModelManifest::new(name, request.ignore_paths, request.resources)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    // Verification must not mint a key: a tenant that never signed a model
+    // has nothing to verify against.
+    let signer = EnvelopeSigner::load(&secret_manager, MODEL_SIGNING_KEY_ID, user.tenant_id).await.map_err(|e| match e {
+        DsseError::SecretError(SecretError::SecretNotFound) => {
+            AppError::ValidationError("Tenant has no model signing key".to_string())
+        }
+        e => {
+            error!("Failed to load model signing key: {}", e);
+            AppError::InternalServerError
+        }
+    })?;
+    let verification = model_signing::verify_manifest(&signature.bundle, &signer.verifying_key(), &current)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    span.set_attribute(KeyValue::new("model.verified", verification.verified));
+    span.end();
+    Ok(Json(verification))
+}
+
+pub async fn get_model_signing_public_key<M: SecretManager>(
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<ModelSigningPublicKeyResponse>> {
+    let signer = EnvelopeSigner::load_or_create(&secret_manager, MODEL_SIGNING_KEY_ID, user.tenant_id).await.map_err(|e| {
+        error!("Failed to load model signing key: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(Json(ModelSigningPublicKeyResponse {
+        key_id: signer.key_id().to_string(),
+        algorithm: "ed25519".to_string(),
+        public_key: hex::encode(signer.verifying_key().as_bytes()),
+    }))
+}
+
+async fn find_version(db: &Database, tenant_id: &uuid::Uuid, name: &str, version: &str) -> Result<ModelVersion> {
+    db.list_model_versions(tenant_id, name, Some(version)).await?
+        .pop()
+        .ok_or_else(|| AppError::NotFound(format!("Version {} of model {} not found", version, name)))
+}
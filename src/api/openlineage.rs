@@ -0,0 +1,125 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::data::data_provenance::{DatasetVersion, RecordTransformationRequest, Transformation};
+use crate::data::openlineage::{EventType, LineageEventResult, OpenLineageDataset, RunEvent, UNVERSIONED};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::auth::AuthenticatedUser;
+
+/// Accepts an OpenLineage `RunEvent`, as sent by the OpenLineage HTTP
+/// transport. Every event is stored; a `COMPLETE` event is also recorded as a
+/// transformation between dataset versions.
+#[instrument(skip(db, user, event))]
+pub async fn ingest_openlineage_event(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(event): Json<RunEvent>,
+) -> Result<(StatusCode, Json<LineageEventResult>)> {
+    let tracer = global::tracer("openlineage_api");
+    let mut span = tracer.start("ingest_openlineage_event");
+    span.set_attribute(KeyValue::new("openlineage.event_type", event.event_type().as_str()));
+    span.set_attribute(KeyValue::new("openlineage.job", event.job_name()));
+
+    let event_id = Uuid::new_v4();
+    if let Err(e) = db.create_openlineage_event(&user.tenant_id, &event_id, &event).await {
+        error!("Failed to store OpenLineage event: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    let transformation_id = if event.event_type() == EventType::Complete {
+        record_completed_run(&db, &user.tenant_id, &user.id, &event).await?
+    } else {
+        None
+    };
+
+    info!("Accepted OpenLineage {} event for run {} of {}", event.event_type().as_str(), event.run.run_id, event.job_name());
+    span.end();
+    Ok((StatusCode::CREATED, Json(LineageEventResult { event_id, event_type: event.event_type(), transformation_id })))
+}
+
+/// Inputs without a version facet resolve to the latest registered version,
+/// so a dataset written by an earlier run links to that run. Outputs without
+/// one are versioned by the run ID.
+async fn record_completed_run(db: &Database, tenant_id: &Uuid, user_id: &Uuid, event: &RunEvent) -> Result<Option<Uuid>> {
+    let run_id = event.run.run_id.to_string();
+    if let Some(existing) = db.find_transformation_for_run(tenant_id, &event.job_name(), &run_id).await? {
+        return Ok(Some(existing));
+    }
+    if event.outputs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut inputs = Vec::new();
+    for dataset in &event.inputs {
+        let version = match dataset.version_facet() {
+            Some(version) => version,
+            None => db.list_dataset_versions(tenant_id, Some(&dataset.qualified_name()), None, None, None).await?
+                .first()
+                .map(|latest| latest.version.clone())
+                .unwrap_or_else(|| UNVERSIONED.to_string()),
+        };
+        inputs.push(ensure_dataset_version(db, tenant_id, user_id, dataset, version).await?);
+    }
+    let mut outputs = Vec::new();
+    for dataset in &event.outputs {
+        let version = dataset.version_facet().unwrap_or_else(|| run_id.clone());
+        outputs.push(ensure_dataset_version(db, tenant_id, user_id, dataset, version).await?);
+    }
+
+    let started_at = db.openlineage_event_time(tenant_id, &event.run.run_id, EventType::Start).await?;
+    let request = RecordTransformationRequest {
+        job_name: event.job_name(),
+        run_id: Some(run_id),
+        code: event.code_version(),
+        inputs: inputs.iter().map(|dataset| dataset.id).collect(),
+        outputs: outputs.iter().map(|dataset| dataset.id).collect(),
+        facets: event.transformation_facets(),
+        started_at,
+        ended_at: Some(event.event_time),
+    };
+    let transformation = Transformation::new(*tenant_id, *user_id, request, &inputs, &outputs)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    match db.create_transformation(&transformation).await {
+        Ok(true) => Ok(Some(transformation.id)),
+        // A concurrent redelivery of the same COMPLETE event recorded the run first
+        Ok(false) => Ok(db.find_transformation_for_run(tenant_id, &transformation.job_name, &event.run.run_id.to_string()).await?),
+        Err(e) => {
+            error!("Failed to record OpenLineage run as transformation: {}", e);
+            Err(AppError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+async fn ensure_dataset_version(
+    db: &Database,
+    tenant_id: &Uuid,
+    user_id: &Uuid,
+    dataset: &OpenLineageDataset,
+    version: String,
+) -> Result<DatasetVersion> {
+    let existing = db.list_dataset_versions(tenant_id, Some(&dataset.qualified_name()), Some(&version), None, None).await?;
+    if let Some(existing) = existing.into_iter().next() {
+        return Ok(existing);
+    }
+
+    let registered = DatasetVersion::new(*tenant_id, *user_id, dataset.register_request(version.clone()))
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    match db.create_dataset_version(&registered).await {
+        Ok(true) => Ok(registered),
+        // Registered by a concurrent event since the lookup above
+        Ok(false) => db.list_dataset_versions(tenant_id, Some(&dataset.qualified_name()), Some(&version), None, None).await?
+            .into_iter()
+            .next()
+            .ok_or(AppError::InternalServerError),
+        Err(e) => {
+            error!("Failed to register OpenLineage dataset version: {}", e);
+            Err(AppError::DatabaseError(e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::global;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::policy::{BuilderTrustPolicy, BuilderTrustPolicyRequest};
+use crate::auth::AuthenticatedUser;
+
+#[instrument(skip(db, user))]
+pub async fn create_builder_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<BuilderTrustPolicyRequest>,
+) -> Result<(StatusCode, Json<BuilderTrustPolicy>)> {
+    let tracer = global::tracer("policy_api");
+    let span = tracer.start("create_builder_policy");
+    let _guard = span.enter();
+
+    let policy = BuilderTrustPolicy::from_request(user.tenant_id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    if let Err(e) = db.create_builder_policy(&policy).await {
+        error!("Failed to save builder trust policy: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Created builder trust policy {} for tenant {}", policy.id, policy.tenant_id);
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+pub async fn list_builder_policies(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<BuilderTrustPolicy>>> {
+    let tracer = global::tracer("policy_api");
+    let span = tracer.start("list_builder_policies");
+    let _guard = span.enter();
+
+    let policies = db.list_builder_policies(&user.tenant_id).await?;
+    Ok(Json(policies))
+}
+
+pub async fn get_builder_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BuilderTrustPolicy>> {
+    let tracer = global::tracer("policy_api");
+    let span = tracer.start("get_builder_policy");
+    let _guard = span.enter();
+
+    let policy = db.get_builder_policy(&user.tenant_id, &id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Builder trust policy {} not found", id)))?;
+    Ok(Json(policy))
+}
+
+#[instrument(skip(db, user))]
+pub async fn update_builder_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Json(request): Json<BuilderTrustPolicyRequest>,
+) -> Result<Json<BuilderTrustPolicy>> {
+    let tracer = global::tracer("policy_api");
+    let span = tracer.start("update_builder_policy");
+    let _guard = span.enter();
+
+    let mut policy = db.get_builder_policy(&user.tenant_id, &id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Builder trust policy {} not found", id)))?;
+    policy.apply_update(request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    db.update_builder_policy(&policy).await?;
+    Ok(Json(policy))
+}
+
+pub async fn delete_builder_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let tracer = global::tracer("policy_api");
+    let span = tracer.start("delete_builder_policy");
+    let _guard = span.enter();
+
+    if !db.delete_builder_policy(&user.tenant_id, &id).await? {
+        return Err(AppError::NotFound(format!("Builder trust policy {} not found", id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
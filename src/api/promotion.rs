@@ -0,0 +1,292 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Serialize;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+use crate::auth::authorization::Authorization;
+use crate::auth::{AuthenticatedUser, User};
+use crate::chain_of_custody::audit_log::{AuditChannel, AuditRecord};
+use crate::chain_of_custody::custody_events::{
+    normalize_digest, parse_digest, AppendCustodyEventRequest, CustodyEvent, CustodyEventType, EvidenceRef,
+};
+use crate::chain_of_custody::promotion::{
+    evaluate_promotion, PromotionDecision, PromotionEvidence, PromotionOutcome, PromotionPolicy, PromotionPolicyRequest,
+    PromotionRequest, PROMOTION_SIGNING_KEY_ID,
+};
+use crate::chain_of_custody::timestamp::TimestampService;
+use crate::chain_of_custody::transparency_log::LogEntryKind;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::models::VerificationReport;
+use crate::provenance::dsse::{DsseError, EnvelopeSigner};
+use crate::provenance::query::ProvenanceQuery;
+use crate::security::secret_management::{SecretError, SecretManager};
+use super::audit::REQUEST_ID_HEADER;
+use super::provenance::run_verification;
+use super::revocations::revocations_reaching;
+use super::timestamps::attach_timestamp;
+
+/// Provenance records verified per promotion, newest first.
+const PROMOTION_PROVENANCE_LIMIT: i64 = 20;
+
+#[derive(Debug, Serialize)]
+pub struct PromotionPublicKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+/// Promotes an artifact between environments once every check against its
+/// evidence passes. The signed decision is stored and returned either way; a
+/// refusal answers 422, and a promotion is stored together with its custody
+/// event. With `override_reason`, a user allowed to override
+/// promotes despite failed checks, and the override is written to the audit
+/// log before the artifact moves.
+#[instrument(skip(db, secret_manager, auth, timestamps, user, headers, request))]
+#[allow(clippy::too_many_arguments)]
+pub async fn promote_artifact<M: SecretManager, A: Authorization>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(auth): State<A>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<PromotionRequest>,
+) -> Result<(StatusCode, Json<PromotionDecision>)> {
+    let tracer = global::tracer("promotion_api");
+    let mut span = tracer.start("promote_artifact");
+    span.set_attribute(KeyValue::new("promotion.to", request.to.clone()));
+
+    request.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let digest = parse_digest(&digest).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let policies = db.list_promotion_policies(&user.tenant_id).await?;
+    let revocations = db.list_revocations(&user.tenant_id).await?;
+    let revoked = revocations_reaching(&db, &user.tenant_id, &revocations, std::slice::from_ref(&digest)).await?
+        .into_iter()
+        .filter(|artifact| artifact.digest == digest)
+        .collect();
+    let evidence = PromotionEvidence {
+        reports: verify_provenance(&db, &timestamps, &user, &digest).await?,
+        attestations: db.list_attestations_by_digest(&user.tenant_id, &digest, None).await?,
+        custody_events: db.list_custody_events(&user.tenant_id, &digest).await?,
+        custody_policies: db.list_custody_policies(&user.tenant_id).await?,
+        revocations,
+        revoked,
+    };
+    let checks = evaluate_promotion(&policies, &evidence, &request.from, &request.to);
+
+    let failed = checks.iter().any(|check| !check.passed);
+    if failed && request.override_reason.is_some()
+        && !auth.is_allowed(&user.id.to_string(), "promotions", "override", user.tenant_id).await
+    {
+        span.end();
+        return Err(AppError::Forbidden("You don't have permission to override promotion policies".to_string()));
+    }
+
+    let request_id = headers.get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let signer = load_promotion_signer(&secret_manager, user.tenant_id).await?;
+    let decision = PromotionDecision::sign(&signer, user.tenant_id, &digest, &request, &policies, checks, user.id, request_id)
+        .map_err(|e| {
+            error!("Failed to sign promotion decision: {}", e);
+            AppError::InternalServerError
+        })?;
+    let decision_hash = decision.payload_hash().map_err(|e| {
+        error!("Failed to hash promotion decision: {}", e);
+        AppError::InternalServerError
+    })?;
+    let outcome = decision.decision.outcome;
+    span.set_attribute(KeyValue::new("promotion.outcome", outcome.as_str()));
+
+    // An override that cannot be audited does not happen
+    if outcome == PromotionOutcome::Overridden {
+        let record = AuditRecord {
+            tenant_id: user.tenant_id,
+            actor: Some(user.id),
+            channel: AuditChannel::Rest,
+            action: "OVERRIDE promotion".to_string(),
+            resource: format!("/api/promotions/digest/{}", digest),
+            status: StatusCode::CREATED.as_u16() as i32,
+            before_hash: evidence.custody_events.last().map(|event| event.event_hash.clone()),
+            after_hash: Some(decision_hash.clone()),
+            request_id: decision.decision.request_id.clone().unwrap_or_default(),
+            source_ip: None,
+        };
+        db.append_audit_entry(record).await.map_err(|e| {
+            error!("Failed to audit promotion override {}: {}", decision.decision.id, e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+        warn!("User {} overrode promotion policies for {} into {}", user.id, digest, request.to);
+    }
+
+    if !outcome.promotes() {
+        db.record_promotion(&decision, None).await.map_err(|e| {
+            error!("Failed to store promotion decision: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+        info!("Refused promotion of {} from {} to {}", digest, request.from, request.to);
+        span.end();
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(decision)));
+    }
+
+    let event_request = AppendCustodyEventRequest {
+        event_type: CustodyEventType::Promoted,
+        category: None,
+        environment: Some(request.to.clone()),
+        evidence: vec![EvidenceRef {
+            kind: "promotion_decision".to_string(),
+            uri: format!("traceguard:promotions/{}", decision.decision.id),
+            digest: Some(decision_hash),
+        }],
+        details: Some(serde_json::json!({
+            "from": request.from,
+            "outcome": outcome.as_str(),
+            "override_reason": decision.decision.override_reason,
+        })),
+    };
+    let event = CustodyEvent::append(user.tenant_id, &digest, user.id, evidence.custody_events.last(), event_request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    // The decision and the event it causes are stored together, and only if
+    // the chain is still the one the checks were evaluated against
+    let recorded = db.record_promotion(&decision, Some(&event)).await.map_err(|e| {
+        error!("Failed to record promotion of {}: {}", digest, e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+    if !recorded {
+        span.end();
+        return Err(AppError::BadRequest(format!(
+            "The custody chain of {} changed while the promotion was evaluated; retry the promotion", digest
+        )));
+    }
+    attach_timestamp(&db, &timestamps, user.tenant_id, LogEntryKind::CustodyEvent, event.id, &event.event_hash).await;
+
+    info!("Promoted {} from {} to {} ({})", digest, request.from, request.to, outcome.as_str());
+    span.end();
+    Ok((StatusCode::CREATED, Json(decision)))
+}
+
+/// Verifies every provenance record naming the artifact, persisting the
+/// reports as a manual verification would.
+async fn verify_provenance(db: &Database, timestamps: &TimestampService, user: &User, digest: &str) -> Result<Vec<VerificationReport>> {
+    let query = ProvenanceQuery { subject_digest: Some(digest.to_string()), ..Default::default() };
+    let records = db.query_provenance(&user.tenant_id, &query, None, PROMOTION_PROVENANCE_LIMIT).await?;
+    let mut reports = Vec::with_capacity(records.len());
+    for record in records {
+        let (_record, report, _policies) = run_verification(db, timestamps, user, &record.id, None).await?;
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+pub async fn list_promotion_decisions(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+) -> Result<Json<Vec<PromotionDecision>>> {
+    let tracer = global::tracer("promotion_api");
+    let span = tracer.start("list_promotion_decisions");
+    let _guard = span.enter();
+
+    let decisions = db.list_promotion_decisions(&user.tenant_id, &normalize_digest(&digest)).await?;
+    Ok(Json(decisions))
+}
+
+pub async fn get_promotion_public_key<M: SecretManager>(
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<PromotionPublicKeyResponse>> {
+    // Reading the key must not mint one; it is created when the first decision is signed
+    let signer = EnvelopeSigner::load(&secret_manager, PROMOTION_SIGNING_KEY_ID, user.tenant_id).await.map_err(|e| match e {
+        DsseError::SecretError(SecretError::SecretNotFound) => {
+            AppError::NotFound("Tenant has no promotion signing key".to_string())
+        }
+        e => {
+            error!("Failed to load promotion signing key: {}", e);
+            AppError::InternalServerError
+        }
+    })?;
+    Ok(Json(PromotionPublicKeyResponse {
+        key_id: signer.key_id().to_string(),
+        algorithm: "ed25519".to_string(),
+        public_key: hex::encode(signer.verifying_key().as_bytes()),
+    }))
+}
+
+#[instrument(skip(db, auth, user))]
+pub async fn create_promotion_policy<A: Authorization>(
+    State(db): State<Database>,
+    State(auth): State<A>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<PromotionPolicyRequest>,
+) -> Result<(StatusCode, Json<PromotionPolicy>)> {
+    let tracer = global::tracer("promotion_api");
+    let span = tracer.start("create_promotion_policy");
+    let _guard = span.enter();
+
+    require_policy_admin(&auth, &user).await?;
+
+    let policy = PromotionPolicy::from_request(user.tenant_id, request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    if let Err(e) = db.create_promotion_policy(&policy).await {
+        error!("Failed to save promotion policy: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Created promotion policy {} for tenant {}", policy.id, policy.tenant_id);
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+pub async fn list_promotion_policies(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<PromotionPolicy>>> {
+    let tracer = global::tracer("promotion_api");
+    let span = tracer.start("list_promotion_policies");
+    let _guard = span.enter();
+
+    let policies = db.list_promotion_policies(&user.tenant_id).await?;
+    Ok(Json(policies))
+}
+
+#[instrument(skip(db, auth, user))]
+pub async fn delete_promotion_policy<A: Authorization>(
+    State(db): State<Database>,
+    State(auth): State<A>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let tracer = global::tracer("promotion_api");
+    let span = tracer.start("delete_promotion_policy");
+    let _guard = span.enter();
+
+    require_policy_admin(&auth, &user).await?;
+
+    if !db.delete_promotion_policy(&user.tenant_id, &id).await? {
+        return Err(AppError::NotFound(format!("Promotion policy {} not found", id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Promotion policies decide what reaches production, so changing them
+/// needs the `write` action on `promotion_policies`.
+async fn require_policy_admin<A: Authorization>(auth: &A, user: &User) -> Result<()> {
+    if !auth.is_allowed(&user.id.to_string(), "promotion_policies", "write", user.tenant_id).await {
+        return Err(AppError::Forbidden("You don't have permission to change promotion policies".to_string()));
+    }
+    Ok(())
+}
+
+async fn load_promotion_signer<M: SecretManager>(secret_manager: &M, tenant_id: Uuid) -> Result<EnvelopeSigner> {
+    EnvelopeSigner::load_or_create(secret_manager, PROMOTION_SIGNING_KEY_ID, tenant_id).await.map_err(|e| {
+        error!("Failed to load promotion signing key: {}", e);
+        AppError::InternalServerError
+    })
+}
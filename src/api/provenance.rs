@@ -1,22 +1,50 @@
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
-    Json,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    Extension, Json,
 };
 use opentelemetry::{global, KeyValue};
+use serde::{Deserialize, Serialize};
 use tracing::{error, info, instrument};
+use uuid::Uuid;
 use crate::database::Database;
 use crate::storage::blob_storage::BlobStorage;
 use crate::error::{AppError, Result};
-use crate::models::{ProvenanceRecord, SLSAProvenance};
-use crate::auth::AuthenticatedUser;
+use crate::models::{ProvenanceRecord, SLSAProvenance, VerificationReport};
+use crate::models::provenance::{VerificationOptions, VerifyParams};
+use crate::auth::{AuthenticatedUser, User};
+use crate::chain_of_custody::signing::{SignatureBundle, SigningAlgorithm, SigningService, VERIFICATION_REPORT_PAYLOAD_TYPE};
+use crate::chain_of_custody::timestamp::{TimestampService, VerifiedTimestamp};
+use crate::chain_of_custody::transparency_log::LogEntryKind;
+use crate::provenance::dsse::Envelope;
+use crate::provenance::ingest::{
+    parse_upload, BulkIngestReport, IngestFormat, IngestItemResult, IngestStatus, INGEST_BATCH_SIZE,
+};
+use crate::provenance::revisions::{verify_chain, ProvenanceRevision};
+use crate::provenance::policy::BuilderTrustPolicy;
+use crate::provenance::query::{paginate, ProvenancePage, ProvenanceQuery};
+use crate::security::secret_management::SecretManager;
+use super::audit::AuditHashes;
+use super::timestamps::attach_timestamp;
+use super::revocations::revocations_reaching;
+
+/// Provenance may be posted either as a bare SLSA predicate or as a signed DSSE
+/// envelope carrying an SLSA v1.0 statement.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ProvenanceSubmission {
+    Envelope(Envelope),
+    Provenance(SLSAProvenance),
+}
 
-#[instrument(skip(db, storage, user))]
+#[instrument(skip(db, storage, timestamps, user))]
 pub async fn create_provenance<S: BlobStorage>(
     State(db): State<Database>,
     State(storage): State<S>,
+    State(timestamps): State<TimestampService>,
     AuthenticatedUser(user): AuthenticatedUser,
-    Json(slsa_provenance): Json<SLSAProvenance>,
+    Json(submission): Json<ProvenanceSubmission>,
 ) -> Result<Json<ProvenanceRecord>> {
     let tracer = global::tracer("provenance_api");
     let mut span = tracer.start("create_provenance");
@@ -28,25 +56,27 @@ pub async fn create_provenance<S: BlobStorage>(
     info!("Creating new provenance record for user {}", user.id);
 
     // Convert SLSA Provenance to ProvenanceRecord
-    let mut record = ProvenanceRecord::from_slsa(slsa_provenance);
+    let mut record = match submission {
+        ProvenanceSubmission::Provenance(slsa_provenance) => ProvenanceRecord::from_slsa(slsa_provenance),
+        ProvenanceSubmission::Envelope(envelope) => ProvenanceRecord::from_envelope(envelope)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?,
+    };
     record.id = uuid::Uuid::new_v4();
     record.created_by = user.id;
 
-    // Store provenance content in blob storage
     let storage_span = tracer.start_with_context("store_provenance_blob", &span.context());
-    if let Err(e) = storage.put_object("provenance", &record.id.to_string(), &serde_json::to_string(&record)?).await {
-        error!("Failed to store provenance in blob storage: {}", e);
-        return Err(AppError::StorageError(e.to_string()));
-    }
+    store_provenance_content(&db, &storage, &record).await?;
     storage_span.end();
 
-    // Save provenance metadata to the database
+    // The revision, lineage, index row and log entry become visible together
     let db_span = tracer.start_with_context("save_provenance_metadata", &span.context());
-    if let Err(e) = db.create_provenance(&record).await {
-        error!("Failed to save provenance metadata to database: {}", e);
+    let revision = initial_revision(&record)?;
+    if let Err(e) = db.ingest_provenance_batch(&user.tenant_id, std::slice::from_ref(&revision)).await {
+        error!("Failed to save provenance record {}: {}", record.id, e);
         return Err(AppError::DatabaseError(e.to_string()));
     }
     db_span.end();
+    attach_timestamp(&db, &timestamps, user.tenant_id, LogEntryKind::Provenance, record.id, &revision.content_hash).await;
 
     create_counter.add(1, &[KeyValue::new("user.id", user.id.to_string())]);
 
@@ -57,24 +87,25 @@ pub async fn create_provenance<S: BlobStorage>(
 
 pub async fn get_provenance(
     State(db): State<Database>,
-    AuthenticatedUser(_user): AuthenticatedUser,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ProvenanceRecord>> {
     let tracer = global::tracer("provenance_api");
     let span = tracer.start("get_provenance");
     let _guard = span.enter();
 
-    let record = db.get_provenance(&id).await?;
-    Ok(Json(record))
+    Ok(Json(current_record(&db, &user.tenant_id, &id).await?))
 }
 
+/// Records an updated version as a new revision; earlier revisions are kept.
 pub async fn update_provenance<S: BlobStorage>(
     State(db): State<Database>,
     State(storage): State<S>,
+    State(timestamps): State<TimestampService>,
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<Uuid>,
     Json(mut record): Json<ProvenanceRecord>,
-) -> Result<Json<ProvenanceRecord>> {
+) -> Result<(Extension<AuditHashes>, Json<ProvenanceRevision>)> {
     let tracer = global::tracer("provenance_api");
     let span = tracer.start("update_provenance");
     let _guard = span.enter();
@@ -85,64 +116,272 @@ pub async fn update_provenance<S: BlobStorage>(
     }
 
     // Check if the user has permission to update this record
-    let existing_record = db.get_provenance(&id).await?;
+    let latest = latest_revision(&db, &user.tenant_id, &id).await?;
+    let existing_record = latest.content.as_ref()
+        .ok_or_else(|| AppError::NotFound(format!("Provenance record {} was deleted", id)))?;
     if existing_record.created_by != user.id {
         return Err(AppError::Forbidden("You don't have permission to update this record".to_string()));
     }
+    record.created_by = existing_record.created_by;
+    record.created_at = existing_record.created_at;
+
+    let revision = ProvenanceRevision::update(&latest, record, user.id)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
 
-    // Update the provenance content in blob storage
-    storage.put_object("provenance", &record.id.to_string(), &serde_json::to_string(&record)?).await?;
+    // Each revision gets its own blob so earlier content is never overwritten
+    let blob_key = format!("{}/{}", id, revision.revision);
+    storage.put_object("provenance", &blob_key, &serde_json::to_string(&revision)?).await?;
 
-    // Update provenance metadata in the database
-    let updated_record = db.update_provenance(&id, record).await?;
-    Ok(Json(updated_record))
+    db.record_provenance_update(&user.tenant_id, &revision).await?;
+    attach_timestamp(&db, &timestamps, user.tenant_id, LogEntryKind::Provenance, id, &revision.content_hash).await;
+    info!("Recorded revision {} of provenance record {}", revision.revision, id);
+    let hashes = AuditHashes { before: Some(latest.content_hash), after: Some(revision.content_hash.clone()) };
+    Ok((Extension(hashes), Json(revision)))
 }
 
-pub async fn delete_provenance<S: BlobStorage>(
+#[derive(Debug, Deserialize)]
+pub struct TombstoneRequest {
+    pub reason: String,
+}
+
+/// Marks the record deleted by appending a tombstone revision carrying the reason.
+pub async fn delete_provenance(
     State(db): State<Database>,
-    State(storage): State<S>,
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode> {
+    Json(request): Json<TombstoneRequest>,
+) -> Result<(Extension<AuditHashes>, Json<ProvenanceRevision>)> {
     let tracer = global::tracer("provenance_api");
     let span = tracer.start("delete_provenance");
     let _guard = span.enter();
 
     // Check if the user has permission to delete this record
-    let existing_record = db.get_provenance(&id).await?;
+    let latest = latest_revision(&db, &user.tenant_id, &id).await?;
+    let existing_record = latest.content.as_ref()
+        .ok_or_else(|| AppError::NotFound(format!("Provenance record {} was already deleted", id)))?;
     if existing_record.created_by != user.id {
         return Err(AppError::Forbidden("You don't have permission to delete this record".to_string()));
     }
 
-    // Delete provenance content from blob storage
-    storage.delete_object("provenance", &id.to_string()).await?;
+    let tombstone = ProvenanceRevision::tombstone(&latest, request.reason, user.id)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    // Logged too, so the log shows the record was withdrawn and when
+    db.record_provenance_tombstone(&user.tenant_id, &tombstone).await?;
+
+    info!("Tombstoned provenance record {} at revision {}", id, tombstone.revision);
+    let hashes = AuditHashes { before: Some(latest.content_hash), after: None };
+    Ok((Extension(hashes), Json(tombstone)))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvenanceHistory {
+    pub provenance_id: Uuid,
+    /// False when stored revisions no longer form an unbroken hash chain.
+    pub chain_valid: bool,
+    pub revisions: Vec<ProvenanceRevision>,
+}
+
+pub async fn list_provenance_revisions(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ProvenanceHistory>> {
+    let tracer = global::tracer("provenance_api");
+    let span = tracer.start("list_provenance_revisions");
+    let _guard = span.enter();
+
+    let revisions = db.list_provenance_revisions(&user.tenant_id, &id).await?;
+    if revisions.is_empty() {
+        return Err(AppError::NotFound(format!("Provenance record {} not found", id)));
+    }
+
+    let chain_valid = match verify_chain(&revisions) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Revision chain for provenance record {} is invalid: {}", id, e);
+            false
+        }
+    };
+    Ok(Json(ProvenanceHistory { provenance_id: id, chain_valid, revisions }))
+}
+
+pub async fn get_provenance_revision(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((id, revision)): Path<(Uuid, i32)>,
+) -> Result<Json<ProvenanceRevision>> {
+    let tracer = global::tracer("provenance_api");
+    let span = tracer.start("get_provenance_revision");
+    let _guard = span.enter();
+
+    db.list_provenance_revisions(&user.tenant_id, &id).await?
+        .into_iter()
+        .find(|candidate| candidate.revision == revision)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Revision {} of provenance record {} not found", revision, id)))
+}
+
+async fn latest_revision(db: &Database, tenant_id: &Uuid, id: &Uuid) -> Result<ProvenanceRevision> {
+    db.list_provenance_revisions(tenant_id, id).await?
+        .pop()
+        .ok_or_else(|| AppError::NotFound(format!("Provenance record {} not found", id)))
+}
+
+/// The content of the record's latest revision, if the record belongs to the
+/// tenant and has not been deleted.
+pub(crate) async fn current_record(db: &Database, tenant_id: &Uuid, id: &Uuid) -> Result<ProvenanceRecord> {
+    current_revision(db, tenant_id, id).await.map(|(record, _content_hash)| record)
+}
+
+/// Like `current_record`, along with the revision's content hash.
+async fn current_revision(db: &Database, tenant_id: &Uuid, id: &Uuid) -> Result<(ProvenanceRecord, String)> {
+    let latest = latest_revision(db, tenant_id, id).await?;
+    match latest.content {
+        Some(record) if !latest.is_tombstone() => Ok((record, latest.content_hash)),
+        _ => Err(AppError::NotFound(format!(
+            "Provenance record {} was deleted: {}", id, latest.reason.unwrap_or_default()
+        ))),
+    }
+}
+
+fn initial_revision(record: &ProvenanceRecord) -> Result<ProvenanceRevision> {
+    // Revisions own their content, so snapshot the record through its JSON form
+    let snapshot: ProvenanceRecord = serde_json::from_value(serde_json::to_value(record)?)?;
+    ProvenanceRevision::initial(snapshot).map_err(|e| AppError::BadRequest(e.to_string()))
+}
 
-    // Delete provenance metadata from the database
-    db.delete_provenance(&id).await?;
-    Ok(StatusCode::NO_CONTENT)
+/// Stores a new record's content the way `create_provenance` does: the blob
+/// and the `provenance_records` row that verification reports reference.
+/// Revisions, lineage and the index are written separately.
+pub(crate) async fn store_provenance_content<S: BlobStorage>(db: &Database, storage: &S, record: &ProvenanceRecord) -> Result<()> {
+    if let Err(e) = storage.put_object("provenance", &record.id.to_string(), &serde_json::to_string(record)?).await {
+        error!("Failed to store provenance in blob storage: {}", e);
+        return Err(AppError::StorageError(e.to_string()));
+    }
+    if let Err(e) = db.create_provenance(record).await {
+        error!("Failed to save provenance metadata to database: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+    Ok(())
 }
 
+/// Ingests many DSSE-wrapped attestations in one request, either as JSON Lines
+/// or as a tar archive. Valid items are stored in transactions of up to
+/// `INGEST_BATCH_SIZE`; every item gets its own result.
+#[instrument(skip(db, storage, user, headers, body))]
+pub async fn bulk_ingest_provenance<S: BlobStorage>(
+    State(db): State<Database>,
+    State(storage): State<S>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<BulkIngestReport>)> {
+    let tracer = global::tracer("provenance_api");
+    let mut span = tracer.start("bulk_ingest_provenance");
+    span.set_attribute(KeyValue::new("user.id", user.id.to_string()));
+
+    let meter = global::meter("provenance_metrics");
+    let create_counter = meter.u64_counter("provenance_created").init();
+
+    let format = headers.get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(IngestFormat::from_content_type)
+        .ok_or_else(|| AppError::BadRequest(
+            "Content-Type must be application/x-ndjson, application/x-tar or application/gzip".to_string()
+        ))?;
+    let parsed = parse_upload(format, &body).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    info!("Bulk ingesting {} attestations for user {}", parsed.len(), user.id);
+
+    let mut results = Vec::with_capacity(parsed.len());
+    let mut pending_indices = Vec::new();
+    let mut pending = Vec::new();
+    for (index, item) in parsed.into_iter().enumerate() {
+        let mut revision = item.record.and_then(|mut record| {
+            record.id = Uuid::new_v4();
+            record.created_by = user.id;
+            ProvenanceRevision::initial(record).map_err(|e| e.to_string())
+        });
+        if let Ok(Some(record)) = revision.as_ref().map(|revision| revision.content.as_ref()) {
+            if let Err(e) = store_provenance_content(&db, &storage, record).await {
+                revision = Err(e.to_string());
+            }
+        }
+        match revision {
+            Ok(revision) => {
+                results.push(IngestItemResult {
+                    index,
+                    source: item.source,
+                    status: IngestStatus::Ingested,
+                    provenance_id: Some(revision.provenance_id),
+                    error: None,
+                });
+                pending_indices.push(index);
+                pending.push(revision);
+            }
+            Err(error) => results.push(IngestItemResult {
+                index,
+                source: item.source,
+                status: IngestStatus::Failed,
+                provenance_id: None,
+                error: Some(error),
+            }),
+        }
+    }
+
+    for (indices, batch) in pending_indices.chunks(INGEST_BATCH_SIZE).zip(pending.chunks(INGEST_BATCH_SIZE)) {
+        match db.ingest_provenance_batch(&user.tenant_id, batch).await {
+            Ok(()) => {
+                create_counter.add(batch.len() as u64, &[KeyValue::new("user.id", user.id.to_string())]);
+                for revision in batch {
+                    attach_timestamp(&db, &timestamps, user.tenant_id, LogEntryKind::Provenance, revision.provenance_id, &revision.content_hash).await;
+                }
+            }
+            Err(e) => {
+                error!("Failed to store provenance ingest batch: {}", e);
+                for index in indices {
+                    let result = &mut results[*index];
+                    result.status = IngestStatus::Failed;
+                    result.provenance_id = None;
+                    result.error = Some(format!("Batch rolled back: {}", e));
+                }
+            }
+        }
+    }
+
+    let report = BulkIngestReport::new(results);
+    span.set_attribute(KeyValue::new("ingest.failed", report.failed as i64));
+    span.end();
+
+    let status = if report.failed == 0 { StatusCode::OK } else { StatusCode::MULTI_STATUS };
+    Ok((status, Json(report)))
+}
+
+/// Lists provenance records matching the query filters, newest first. Pass
+/// the returned `next_cursor` as `cursor` to fetch the following page.
 pub async fn list_provenance(
     State(db): State<Database>,
-    AuthenticatedUser(_user): AuthenticatedUser,
-    Query(params): Query<ListProvenanceParams>,
-) -> Result<Json<ListProvenanceResponse>> {
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(mut query): Query<ProvenanceQuery>,
+) -> Result<Json<ProvenancePage>> {
     let tracer = global::tracer("provenance_api");
     let span = tracer.start("list_provenance");
     let _guard = span.enter();
 
-    let page = params.page.unwrap_or(1);
-    let page_size = params.page_size.unwrap_or(10);
-    let (records, total) = db.list_provenance(page, page_size).await?;
-    Ok(Json(ListProvenanceResponse { records, total }))
+    let cursor = query.prepare().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let page_size = query.page_size();
+    let records = db.query_provenance(&user.tenant_id, &query, cursor, page_size + 1).await?;
+    Ok(Json(paginate(records, page_size)))
 }
 
-#[instrument(skip(db))]
+#[instrument(skip(db, timestamps))]
 pub async fn verify_slsa_provenance(
     State(db): State<Database>,
-    AuthenticatedUser(_user): AuthenticatedUser,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<uuid::Uuid>,
-) -> Result<Json<bool>> {
+    Query(params): Query<VerifyParams>,
+) -> Result<Json<VerificationReport>> {
     let tracer = global::tracer("provenance_api");
     let mut span = tracer.start("verify_slsa_provenance");
     span.set_attribute(KeyValue::new("provenance.id", id.to_string()));
@@ -152,17 +391,133 @@ pub async fn verify_slsa_provenance(
 
     info!("Verifying SLSA provenance for record ID: {}", id);
 
-    let record = db.get_provenance(&id).await.map_err(|e| {
-        error!("Failed to retrieve provenance record: {}", e);
+    let (_record, report, _policies) = run_verification(&db, &timestamps, &user, &id, params.max_age()).await?;
+
+    verify_counter.add(1, &[KeyValue::new("result", report.passed.to_string())]);
+    span.set_attribute(KeyValue::new("verification_result", report.passed.to_string()));
+
+    info!("SLSA provenance verification result for ID {}: {}", id, report.passed);
+    span.end();
+    Ok(Json(report))
+}
+
+/// Verifies the record like `verify_slsa_provenance` and returns the report
+/// signed by the tenant's Ed25519 document key, the one
+/// GET /api/signing/public-key serves.
+#[instrument(skip(db, secret_manager, signing, timestamps, user))]
+pub async fn sign_verification_report<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(signing): State<SigningService>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<VerifyParams>,
+) -> Result<(StatusCode, Json<SignatureBundle>)> {
+    let tracer = global::tracer("provenance_api");
+    let mut span = tracer.start("sign_verification_report");
+    span.set_attribute(KeyValue::new("provenance.id", id.to_string()));
+
+    let (_record, report, _policies) = run_verification(&db, &timestamps, &user, &id, params.max_age()).await?;
+    let payload = serde_json::to_vec(&report).map_err(|e| {
+        error!("Failed to serialize verification report {}: {}", report.id, e);
+        AppError::InternalServerError
+    })?;
+    let secret_id = SigningAlgorithm::Ed25519.secret_id();
+    let signer = signing.issuer_signer(&secret_manager, user.tenant_id, &secret_id).await.map_err(|e| {
+        error!("Failed to load document signing key: {}", e);
+        AppError::InternalServerError
+    })?;
+    let bundle = signer.sign_envelope(VERIFICATION_REPORT_PAYLOAD_TYPE, &payload);
+
+    info!("Signed verification report {} for provenance record {}", report.id, id);
+    span.end();
+    Ok((StatusCode::CREATED, Json(bundle)))
+}
+
+/// Verifies the latest revision of a stored record against the caller's
+/// tenant policies and persists the report. Records older than `max_age`
+/// fail the freshness check.
+pub(crate) async fn run_verification(
+    db: &Database,
+    timestamps: &TimestampService,
+    user: &User,
+    id: &uuid::Uuid,
+    max_age: Option<chrono::Duration>,
+) -> Result<(ProvenanceRecord, VerificationReport, Vec<BuilderTrustPolicy>)> {
+    let (record, content_hash) = current_revision(db, &user.tenant_id, id).await?;
+
+    let policies = db.list_builder_policies(&user.tenant_id).await.map_err(|e| {
+        error!("Failed to load builder trust policies: {}", e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+    let trusted_keys = db.list_trusted_keys(&user.tenant_id).await.map_err(|e| {
+        error!("Failed to load trusted keys: {}", e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+    let reproduced_digests = db.list_reproduced_digests(&user.tenant_id, &record.slsa_provenance.normalize().subject_digests).await.map_err(|e| {
+        error!("Failed to load reproducibility results: {}", e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+    let revocations = db.list_revocations(&user.tenant_id).await.map_err(|e| {
+        error!("Failed to load revocations: {}", e);
         AppError::DatabaseError(e.to_string())
     })?;
+    let revoked_artifacts = revocations_reaching(db, &user.tenant_id, &revocations, &record.involved_digests()).await?;
+    let timestamps = verify_revision_timestamps(db, timestamps, &user.tenant_id, id, &content_hash).await?;
+    let options = VerificationOptions {
+        max_age,
+        policies,
+        trusted_keys,
+        reproduced_digests,
+        revocations,
+        revoked_artifacts,
+        timestamps,
+        ..Default::default()
+    };
 
-    let is_valid = record.verify_slsa();
+    let mut report = record.verify_slsa_with(&options);
+    report.verified_by = user.id;
 
-    verify_counter.add(1, &[KeyValue::new("result", is_valid.to_string())]);
-    span.set_attribute(KeyValue::new("verification_result", is_valid.to_string()));
+    if let Err(e) = db.create_verification_report(&report).await {
+        error!("Failed to persist verification report: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
 
-    info!("SLSA provenance verification result for ID {}: {}", id, is_valid);
-    span.end();
-    Ok(Json(is_valid))
-}
\ No newline at end of file
+    Ok((record, report, options.policies))
+}
+
+/// Checks the tokens stored for the record's current revision. Without a
+/// configured TSA there is nothing to check them against, so none are.
+async fn verify_revision_timestamps(
+    db: &Database,
+    timestamps: &TimestampService,
+    tenant_id: &Uuid,
+    id: &Uuid,
+    content_hash: &str,
+) -> Result<Vec<std::result::Result<VerifiedTimestamp, String>>> {
+    if !timestamps.enabled() {
+        return Ok(Vec::new());
+    }
+    let stored = db.list_record_timestamps(tenant_id, LogEntryKind::Provenance, id).await.map_err(|e| {
+        error!("Failed to load timestamps of provenance record {}: {}", id, e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+    Ok(stored.iter()
+        .filter(|timestamp| timestamp.digest == content_hash)
+        .map(|timestamp| timestamps.verify(&timestamp.timestamp, content_hash).map_err(|e| e.to_string()))
+        .collect())
+}
+
+pub async fn list_verification_history(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<Vec<VerificationReport>>> {
+    let tracer = global::tracer("provenance_api");
+    let span = tracer.start("list_verification_history");
+    let _guard = span.enter();
+
+    let reports = db.list_verification_reports(&user.tenant_id, &id).await?;
+    Ok(Json(reports))
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Deserialize;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::reproducibility::{compare_attestations, ReproducibilityReport};
+use crate::auth::AuthenticatedUser;
+use super::provenance::current_record;
+
+#[derive(Debug, Deserialize)]
+pub struct CompareAttestationsRequest {
+    pub subject_name: String,
+    pub provenance_ids: Vec<Uuid>,
+}
+
+#[instrument(skip(db, user))]
+pub async fn compare_builds(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<CompareAttestationsRequest>,
+) -> Result<(StatusCode, Json<ReproducibilityReport>)> {
+    let tracer = global::tracer("reproducibility_api");
+    let mut span = tracer.start("compare_builds");
+    span.set_attribute(KeyValue::new("subject.name", request.subject_name.clone()));
+
+    info!("Comparing {} attestations for subject {}", request.provenance_ids.len(), request.subject_name);
+
+    let mut records = Vec::with_capacity(request.provenance_ids.len());
+    for id in &request.provenance_ids {
+        records.push(current_record(&db, &user.tenant_id, id).await?);
+    }
+    let trusted_keys = db.list_trusted_keys(&user.tenant_id).await.map_err(|e| {
+        error!("Failed to load trusted keys: {}", e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+
+    let report = compare_attestations(user.tenant_id, &request.subject_name, &records, &trusted_keys)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    if let Err(e) = db.create_reproducibility_report(&report).await {
+        error!("Failed to persist reproducibility report: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    span.set_attribute(KeyValue::new("reproduced", report.reproduced.to_string()));
+    span.end();
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+pub async fn list_reproducibility_reports(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+) -> Result<Json<Vec<ReproducibilityReport>>> {
+    let tracer = global::tracer("reproducibility_api");
+    let span = tracer.start("list_reproducibility_reports");
+    let _guard = span.enter();
+
+    let digest = parse_digest(&digest).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let reports = db.list_reproducibility_reports(&user.tenant_id, &digest).await?;
+    Ok(Json(reports))
+}
@@ -0,0 +1,176 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use opentelemetry::{global, KeyValue};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::auth::AuthenticatedUser;
+use crate::chain_of_custody::custody_events::normalize_digest;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::revocation::{
+    candidate_filter, direct_revocations, propagate, propagate_within, upstream_closure, Revocation, RevocationImpact,
+    RevocationRequest, RevokedArtifact,
+};
+use crate::websocket::{LiveUpdate, LiveUpdateKind, LiveUpdates};
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactRevocationStatus {
+    pub digest: String,
+    pub revoked: bool,
+    /// One entry per revocation that reaches the artifact.
+    pub revocations: Vec<RevokedArtifact>,
+}
+
+/// Evaluates revocations against the tenant's stored provenance and follows
+/// them downstream through the lineage graph.
+async fn revoked_artifacts(db: &Database, tenant_id: &Uuid, revocations: &[Revocation]) -> Result<Vec<RevokedArtifact>> {
+    if revocations.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (since, ids) = candidate_filter(revocations);
+    let records = db.list_revocation_candidates(tenant_id, since, &ids, None).await?;
+    let trusted_keys = db.list_trusted_keys(tenant_id).await?;
+    let direct = direct_revocations(revocations, &records, &trusted_keys, Utc::now());
+    Ok(propagate(db, tenant_id, direct).await?)
+}
+
+/// The revocations reaching `digests`. Only their upstream lineage is walked
+/// and only the records producing it are checked, so this stays cheap on
+/// every verification and promotion however large the tenant's history.
+pub(crate) async fn revocations_reaching(
+    db: &Database,
+    tenant_id: &Uuid,
+    revocations: &[Revocation],
+    digests: &[String],
+) -> Result<Vec<RevokedArtifact>> {
+    if revocations.is_empty() {
+        return Ok(Vec::new());
+    }
+    let upstream = upstream_closure(db, tenant_id, digests).await?;
+    let subjects: Vec<String> = upstream.iter().cloned().collect();
+    let (since, ids) = candidate_filter(revocations);
+    let records = db.list_revocation_candidates(tenant_id, since, &ids, Some(&subjects)).await?;
+    let trusted_keys = db.list_trusted_keys(tenant_id).await?;
+    let direct = direct_revocations(revocations, &records, &trusted_keys, Utc::now())
+        .into_iter()
+        .filter(|artifact| upstream.contains(&artifact.digest))
+        .collect();
+    Ok(propagate_within(db, tenant_id, direct, Some(&upstream)).await?)
+}
+
+async fn revocation_impact(db: &Database, revocation: Revocation) -> Result<RevocationImpact> {
+    let revocations = std::slice::from_ref(&revocation);
+    let artifacts = revoked_artifacts(db, &revocation.tenant_id, revocations).await?;
+    let provenance_ids: BTreeSet<Uuid> = artifacts.iter().filter_map(|artifact| artifact.provenance_id).collect();
+
+    let (since, ids) = candidate_filter(revocations);
+    let now = Utc::now();
+    let attestation_ids = db.list_revocable_attestations(&revocation.tenant_id, since, &ids).await?
+        .into_iter()
+        .filter(|attestation| revocation.covers_attestation(attestation, now))
+        .map(|attestation| attestation.id)
+        .collect();
+
+    Ok(RevocationImpact {
+        revocation,
+        provenance_ids: provenance_ids.into_iter().collect(),
+        attestation_ids,
+        artifacts,
+    })
+}
+
+/// Revokes a key, builder, attestation or artifact, and pushes what it flags
+/// to the tenant's live update stream.
+#[instrument(skip(db, updates, user))]
+pub async fn create_revocation(
+    State(db): State<Database>,
+    State(updates): State<LiveUpdates>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<RevocationRequest>,
+) -> Result<(StatusCode, Json<RevocationImpact>)> {
+    let tracer = global::tracer("revocations_api");
+    let mut span = tracer.start("create_revocation");
+    span.set_attribute(KeyValue::new("revocation.target_type", request.target_type.as_str()));
+
+    let revocation = Revocation::new(user.tenant_id, user.id, request)
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    db.create_revocation(&revocation).await.map_err(|e| {
+        error!("Failed to store revocation: {}", e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+
+    let impact = revocation_impact(&db, revocation).await?;
+    updates.publish(LiveUpdate::new(
+        user.tenant_id,
+        LiveUpdateKind::Revocation,
+        impact.revocation.id,
+        serde_json::to_value(&impact)?,
+    ));
+
+    info!(
+        "Revoked {} {}: {} records, {} attestations and {} artifacts flagged",
+        impact.revocation.target_type.as_str(),
+        impact.revocation.target,
+        impact.provenance_ids.len(),
+        impact.attestation_ids.len(),
+        impact.artifacts.len()
+    );
+    span.end();
+    Ok((StatusCode::CREATED, Json(impact)))
+}
+
+pub async fn list_revocations(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<Revocation>>> {
+    let tracer = global::tracer("revocations_api");
+    let span = tracer.start("list_revocations");
+    let _guard = span.enter();
+
+    let revocations = db.list_revocations(&user.tenant_id).await?;
+    Ok(Json(revocations))
+}
+
+/// Re-evaluates what a revocation flags against the records stored now.
+pub async fn get_revocation_impact(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RevocationImpact>> {
+    let tracer = global::tracer("revocations_api");
+    let mut span = tracer.start("get_revocation_impact");
+    span.set_attribute(KeyValue::new("revocation.id", id.to_string()));
+
+    let revocation = db.list_revocations(&user.tenant_id).await?
+        .into_iter()
+        .find(|revocation| revocation.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Revocation {} not found", id)))?;
+    let impact = revocation_impact(&db, revocation).await;
+    span.end();
+    Ok(Json(impact?))
+}
+
+pub async fn get_artifact_revocation_status(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+) -> Result<Json<ArtifactRevocationStatus>> {
+    let tracer = global::tracer("revocations_api");
+    let mut span = tracer.start("get_artifact_revocation_status");
+    span.set_attribute(KeyValue::new("artifact.digest", digest.clone()));
+
+    let digest = normalize_digest(&digest);
+    let revocations = db.list_revocations(&user.tenant_id).await?;
+    let revoked: Vec<RevokedArtifact> = revocations_reaching(&db, &user.tenant_id, &revocations, std::slice::from_ref(&digest)).await?
+        .into_iter()
+        .filter(|artifact| artifact.digest == digest)
+        .collect();
+    span.end();
+    Ok(Json(ArtifactRevocationStatus { digest, revoked: !revoked.is_empty(), revocations: revoked }))
+}
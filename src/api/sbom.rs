@@ -10,6 +10,10 @@ use crate::storage::blob_storage::BlobStorage;
 use crate::error::{AppError, Result};
 use crate::models::SBOM;
 use crate::sbom::parser::parse_sbom;
+use crate::auth::AuthenticatedUser;
+use crate::chain_of_custody::timestamp::TimestampService;
+use crate::chain_of_custody::transparency_log::{LogEntryBody, LogEntryKind};
+use super::timestamps::attach_timestamp;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SBOM {
@@ -35,6 +39,8 @@ pub struct ListSBOMsResponse {
 pub async fn create_sbom<S: BlobStorage>(
     State(db): State<Database>,
     State(storage): State<S>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
     mut multipart: Multipart,
 ) -> Result<Json<SBOM>> {
     let mut sbom = SBOM {
@@ -67,6 +73,9 @@ pub async fn create_sbom<S: BlobStorage>(
 
     // Save SBOM metadata to the database
     db.create_sbom(&sbom).await?;
+    let log_entry = LogEntryBody::for_content(LogEntryKind::Sbom, sbom.id, sbom.content.as_bytes());
+    db.append_log_entries(&user.tenant_id, std::slice::from_ref(&log_entry)).await?;
+    attach_timestamp(&db, &timestamps, user.tenant_id, LogEntryKind::Sbom, sbom.id, &log_entry.digest).await;
 
     Ok(Json(sbom))
 }
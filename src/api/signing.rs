@@ -0,0 +1,228 @@
+use axum::{
+    extract::{Path, Query, State, TypedHeader},
+    headers::{authorization::Bearer, Authorization as BearerAuthorization},
+    http::StatusCode,
+    Json,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use opentelemetry::{global, KeyValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::chain_of_custody::fulcio::{CertificateChain, TrustBundle};
+use crate::chain_of_custody::signing::{
+    DocumentKey, DocumentPublicKey, SignatureBundle, SigningAlgorithm, SigningError, SigningService,
+    VerificationMaterial, VerifiedSignature, SIGNABLE_PAYLOAD_TYPES,
+};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::security::secret_management::{SecretError, SecretManager};
+use crate::auth::authorization::Authorization;
+use crate::auth::AuthenticatedUser;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureFormat {
+    #[default]
+    Dsse,
+    Detached,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignDocumentRequest {
+    /// A JSON document, signed as its serialized bytes.
+    pub document: Option<Value>,
+    /// Base64 bytes, for documents that are not JSON.
+    pub content: Option<String>,
+    #[serde(default)]
+    pub format: SignatureFormat,
+    /// DSSE payload type; defaults to `application/json` and must be one of
+    /// `SIGNABLE_PAYLOAD_TYPES`.
+    pub payload_type: Option<String>,
+    #[serde(default)]
+    pub algorithm: SigningAlgorithm,
+    /// Sign with a short-lived certificate for the caller's identity instead
+    /// of the tenant key.
+    #[serde(default)]
+    pub keyless: bool,
+    /// Record the signature in the tenant's transparency log.
+    #[serde(default)]
+    pub publish: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyDocumentRequest {
+    pub bundle: SignatureBundle,
+    pub document: Option<Value>,
+    pub content: Option<String>,
+    /// PEM public key; defaults to the tenant's key for keyed bundles.
+    pub public_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicKeyParams {
+    #[serde(default)]
+    pub algorithm: SigningAlgorithm,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentSigningPublicKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+    pub public_key_pem: String,
+}
+
+/// Signs a caller-supplied document. Anything signed here carries the
+/// tenant's key, so it needs the `sign` action on `documents`, and envelopes
+/// are limited to payload types TraceGuard never issues itself.
+#[instrument(skip(db, secret_manager, auth, signing, user, bearer, request))]
+pub async fn sign_document<M: SecretManager, A: Authorization>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(auth): State<A>,
+    State(signing): State<SigningService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    bearer: Option<TypedHeader<BearerAuthorization<Bearer>>>,
+    Json(request): Json<SignDocumentRequest>,
+) -> Result<(StatusCode, Json<SignatureBundle>)> {
+    let tracer = global::tracer("signing_api");
+    let mut span = tracer.start("sign_document");
+    span.set_attribute(KeyValue::new("signing.keyless", request.keyless));
+
+    if !auth.is_allowed(&user.id.to_string(), "documents", "sign", user.tenant_id).await {
+        span.end();
+        return Err(AppError::Forbidden("You don't have permission to sign documents".to_string()));
+    }
+    let payload_type = request.payload_type.as_deref().unwrap_or("application/json");
+    if !SIGNABLE_PAYLOAD_TYPES.contains(&payload_type) {
+        span.end();
+        return Err(AppError::ValidationError(format!("Payload type {} cannot be signed on request", payload_type)));
+    }
+
+    let document = document_bytes(request.document.as_ref(), request.content.as_deref())?
+        .ok_or_else(|| AppError::ValidationError("Either document or content is required".to_string()))?;
+    let signer = if request.keyless {
+        let TypedHeader(BearerAuthorization(bearer)) = bearer
+            .ok_or_else(|| AppError::ValidationError("Keyless signing needs the caller's bearer token".to_string()))?;
+        signing.keyless_signer(bearer.token()).await
+    } else {
+        signing.keyed_signer(&secret_manager, user.tenant_id, request.algorithm).await
+    }
+    .map_err(|e| match e {
+        SigningError::KeylessDisabled | SigningError::Fulcio(_) => AppError::ValidationError(e.to_string()),
+        e => {
+            error!("Failed to prepare document signer: {}", e);
+            AppError::InternalServerError
+        }
+    })?;
+
+    let mut bundle = match request.format {
+        SignatureFormat::Dsse => signer.sign_envelope(payload_type, &document),
+        SignatureFormat::Detached => signer.sign_detached(&document),
+    };
+    if request.publish {
+        bundle = db.publish_document_signature(&user.tenant_id, &user.id, &bundle).await.map_err(|e| {
+            error!("Failed to publish document signature: {}", e);
+            AppError::DatabaseError(e.to_string())
+        })?;
+    }
+
+    info!("Signed document {} as bundle {}", bundle.digest, bundle.id);
+    span.end();
+    Ok((StatusCode::CREATED, Json(bundle)))
+}
+
+#[instrument(skip(secret_manager, signing, user, request))]
+pub async fn verify_document<M: SecretManager>(
+    State(secret_manager): State<M>,
+    State(signing): State<SigningService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<VerifyDocumentRequest>,
+) -> Result<Json<VerifiedSignature>> {
+    let tracer = global::tracer("signing_api");
+    let span = tracer.start("verify_document");
+    let _guard = span.enter();
+
+    let content = document_bytes(request.document.as_ref(), request.content.as_deref())?;
+    let public_key = match (&request.public_key, &request.bundle.verification_material) {
+        (Some(pem), _) => Some(DocumentPublicKey::from_pem(pem).map_err(|e| AppError::ValidationError(e.to_string()))?),
+        (None, VerificationMaterial::PublicKey { algorithm, .. }) => {
+            let key = DocumentKey::load(&secret_manager, user.tenant_id, *algorithm).await.map_err(|e| match e {
+                SigningError::SecretError(SecretError::SecretNotFound) => {
+                    AppError::ValidationError(format!("Tenant has no {} document signing key", algorithm.as_str()))
+                }
+                e => {
+                    error!("Failed to load document signing key: {}", e);
+                    AppError::InternalServerError
+                }
+            })?;
+            Some(key.public_key())
+        }
+        (None, VerificationMaterial::CertificateChain { .. }) => None,
+    };
+
+    let verified = signing.verify(&request.bundle, public_key.as_ref(), content.as_deref())
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    Ok(Json(verified))
+}
+
+pub async fn get_document_signing_public_key<M: SecretManager>(
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<PublicKeyParams>,
+) -> Result<Json<DocumentSigningPublicKeyResponse>> {
+    let key = DocumentKey::load_or_create(&secret_manager, user.tenant_id, params.algorithm).await.map_err(|e| {
+        error!("Failed to load document signing key: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    let public_key = key.public_key();
+    Ok(Json(DocumentSigningPublicKeyResponse {
+        key_id: public_key.key_id(),
+        algorithm: params.algorithm.as_str().to_string(),
+        public_key: hex::encode(public_key.to_bytes()),
+        public_key_pem: public_key.to_pem(),
+    }))
+}
+
+pub async fn get_document_signature(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SignatureBundle>> {
+    let tracer = global::tracer("signing_api");
+    let span = tracer.start("get_document_signature");
+    let _guard = span.enter();
+
+    let bundle = db.get_document_signature(&user.tenant_id, &id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Document signature {} not found", id)))?;
+    Ok(Json(bundle))
+}
+
+/// The CA roots keyless signatures chain to, for offline verifiers.
+pub async fn get_trust_bundle(
+    State(signing): State<SigningService>,
+    AuthenticatedUser(_user): AuthenticatedUser,
+) -> Result<Json<TrustBundle>> {
+    if !signing.keyless_enabled() {
+        return Err(AppError::NotFound("Keyless signing is not configured".to_string()));
+    }
+    Ok(Json(TrustBundle {
+        chains: vec![CertificateChain { certificates: signing.trust_roots().to_vec() }],
+    }))
+}
+
+fn document_bytes(document: Option<&Value>, content: Option<&str>) -> Result<Option<Vec<u8>>> {
+    match (document, content) {
+        (Some(_), Some(_)) => Err(AppError::ValidationError("Send either document or content, not both".to_string())),
+        (Some(document), None) => serde_json::to_vec(document)
+            .map(Some)
+            .map_err(|e| AppError::ValidationError(e.to_string())),
+        (None, Some(content)) => BASE64.decode(content)
+            .map(Some)
+            .map_err(|e| AppError::ValidationError(format!("content is not valid base64: {}", e))),
+        (None, None) => Ok(None),
+    }
+}
@@ -0,0 +1,146 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Deserialize;
+use std::path::{Component, PathBuf};
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::source::{
+    verify_source, GitRepository, SourceTrustPolicy, SourceTrustPolicyRequest, SourceVerification,
+};
+use crate::auth::AuthenticatedUser;
+use super::provenance::current_record;
+
+/// Server-side repositories are resolved relative to this directory.
+const GIT_ROOT_ENV: &str = "TRACEGUARD_GIT_ROOT";
+const GIT_BUNDLE_CONTENT_TYPE: &str = "application/x-git-bundle";
+
+pub async fn get_source_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<SourceTrustPolicy>> {
+    let tracer = global::tracer("source_api");
+    let span = tracer.start("get_source_policy");
+    let _guard = span.enter();
+
+    let policy = db.get_source_trust_policy(&user.tenant_id).await?
+        .ok_or_else(|| AppError::NotFound("No source trust policy is configured".to_string()))?;
+    Ok(Json(policy))
+}
+
+#[instrument(skip(db, user))]
+pub async fn put_source_policy(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<SourceTrustPolicyRequest>,
+) -> Result<Json<SourceTrustPolicy>> {
+    let tracer = global::tracer("source_api");
+    let span = tracer.start("put_source_policy");
+    let _guard = span.enter();
+
+    let policy = SourceTrustPolicy::from_request(user.tenant_id, request);
+    if let Err(e) = db.upsert_source_trust_policy(&policy).await {
+        error!("Failed to save source trust policy: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+    Ok(Json(policy))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourceRepositoryRequest {
+    /// Path of a repository below the configured git root.
+    pub repository: String,
+}
+
+/// Verifies the source commit of a provenance record against either a
+/// repository on the server (JSON body) or an uploaded `git bundle`.
+#[instrument(skip(db, user, headers, body))]
+pub async fn verify_provenance_source(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<SourceVerification>)> {
+    let tracer = global::tracer("source_api");
+    let mut span = tracer.start("verify_provenance_source");
+    span.set_attribute(KeyValue::new("provenance.id", id.to_string()));
+
+    let is_bundle = headers.get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.starts_with(GIT_BUNDLE_CONTENT_TYPE));
+    let repository_path = if is_bundle {
+        None
+    } else {
+        let request: SourceRepositoryRequest = serde_json::from_slice(&body)
+            .map_err(|e| AppError::BadRequest(format!("Expected a repository path or a git bundle: {}", e)))?;
+        Some(resolve_repository(&request.repository)?)
+    };
+
+    let record = current_record(&db, &user.tenant_id, &id).await?;
+    let policy = db.get_source_trust_policy(&user.tenant_id).await?
+        .unwrap_or_else(|| SourceTrustPolicy::from_request(user.tenant_id, SourceTrustPolicyRequest {
+            allowed_signers: vec![],
+            protected_refs: vec![],
+        }));
+
+    // git and gpg are run as subprocesses, so keep them off the async runtime
+    let (record, checks) = tokio::task::spawn_blocking(move || {
+        let repository = match repository_path {
+            Some(path) => GitRepository::open(&path),
+            None => GitRepository::from_bundle(&body),
+        };
+        repository.map(|repository| {
+            let checks = verify_source(&repository, &record, &policy);
+            (record, checks)
+        })
+    })
+    .await
+    .map_err(|e| {
+        error!("Source verification task failed: {}", e);
+        AppError::InternalServerError
+    })?
+    .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let verification = SourceVerification::new(user.tenant_id, &record, user.id, checks);
+    if let Err(e) = db.create_source_verification(&verification).await {
+        error!("Failed to save source verification: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Source verification for provenance record {} passed: {}", id, verification.passed);
+    span.set_attribute(KeyValue::new("verification_result", verification.passed.to_string()));
+    span.end();
+    Ok((StatusCode::CREATED, Json(verification)))
+}
+
+pub async fn list_source_verifications(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<SourceVerification>>> {
+    let tracer = global::tracer("source_api");
+    let span = tracer.start("list_source_verifications");
+    let _guard = span.enter();
+
+    let verifications = db.list_source_verifications(&user.tenant_id, &id).await?;
+    Ok(Json(verifications))
+}
+
+/// Resolves a client-supplied repository name below the git root, rejecting
+/// absolute paths and `..` components.
+fn resolve_repository(repository: &str) -> Result<PathBuf> {
+    let root = std::env::var(GIT_ROOT_ENV)
+        .map_err(|_| AppError::BadRequest(format!("{} is not set; upload a git bundle instead", GIT_ROOT_ENV)))?;
+    let relative = PathBuf::from(repository);
+    if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+        return Err(AppError::BadRequest("Repository must be a relative path without '..'".to_string()));
+    }
+    Ok(PathBuf::from(root).join(relative))
+}
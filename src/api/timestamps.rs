@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+use crate::chain_of_custody::timestamp::{RecordTimestamp, TimestampService, VerifiedTimestamp};
+use crate::chain_of_custody::transparency_log::LogEntryKind;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::auth::AuthenticatedUser;
+
+#[derive(Debug, Deserialize)]
+pub struct RecordTimestampParams {
+    pub kind: LogEntryKind,
+    pub resource_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TsaCertificatesResponse {
+    pub certificates: Vec<String>,
+}
+
+/// Timestamps a newly stored record over the same digest its transparency
+/// log entry commits to. Timestamping is best effort: the record is already
+/// stored and logged, so a TSA outage is logged rather than failing the
+/// request.
+pub(crate) async fn attach_timestamp(
+    db: &Database,
+    timestamps: &TimestampService,
+    tenant_id: Uuid,
+    kind: LogEntryKind,
+    resource_id: Uuid,
+    digest: &str,
+) {
+    if !timestamps.enabled() {
+        return;
+    }
+    let token = match timestamps.timestamp(digest).await {
+        Ok(token) => token,
+        Err(e) => {
+            error!("Failed to timestamp {} {}: {}", kind.as_str(), resource_id, e);
+            return;
+        }
+    };
+    let timestamp = RecordTimestamp::new(tenant_id, kind, resource_id, digest, token);
+    if let Err(e) = db.create_record_timestamp(&timestamp).await {
+        error!("Failed to store timestamp token for {} {}: {}", kind.as_str(), resource_id, e);
+        return;
+    }
+    info!("Timestamped {} {} at {}", kind.as_str(), resource_id, timestamp.timestamp.gen_time);
+}
+
+pub async fn list_record_timestamps(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<RecordTimestampParams>,
+) -> Result<Json<Vec<RecordTimestamp>>> {
+    let tracer = global::tracer("timestamps_api");
+    let span = tracer.start("list_record_timestamps");
+    let _guard = span.enter();
+
+    let timestamps = db.list_record_timestamps(&user.tenant_id, params.kind, &params.resource_id).await?;
+    Ok(Json(timestamps))
+}
+
+/// Checks a stored token against the configured TSA certificates.
+pub async fn verify_record_timestamp(
+    State(db): State<Database>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<VerifiedTimestamp>> {
+    let tracer = global::tracer("timestamps_api");
+    let mut span = tracer.start("verify_record_timestamp");
+    span.set_attribute(KeyValue::new("timestamp.id", id.to_string()));
+
+    let timestamp = db.get_record_timestamp(&user.tenant_id, &id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Timestamp {} not found", id)))?;
+    let verified = timestamps.verify(&timestamp.timestamp, &timestamp.digest)
+        .map_err(|e| AppError::ValidationError(e.to_string()));
+    span.end();
+    Ok(Json(verified?))
+}
+
+/// The TSA certificates tokens are verified against, for offline verifiers.
+pub async fn get_tsa_certificates(
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(_user): AuthenticatedUser,
+) -> Result<Json<TsaCertificatesResponse>> {
+    if !timestamps.enabled() {
+        return Err(AppError::NotFound("Timestamping is not configured".to_string()));
+    }
+    Ok(Json(TsaCertificatesResponse { certificates: timestamps.trusted_certificates().to_vec() }))
+}
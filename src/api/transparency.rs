@@ -0,0 +1,294 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::chain_of_custody::merkle;
+use crate::chain_of_custody::transparency_log::{
+    tree_root, ConsistencyProof, InclusionProof, LogEntry, SignedTreeHead, TRANSPARENCY_LOG_KEY_ID,
+};
+use crate::chain_of_custody::witness::{
+    add_cosignature, witness_signatures, SplitView, SplitViewAlert, SplitViewKind, TrustedWitness, TrustedWitnessRequest,
+};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::dsse::{DsseError, EnvelopeSigner};
+use crate::security::secret_management::{SecretError, SecretManager};
+use crate::auth::AuthenticatedUser;
+use crate::auth::authorization::Authorization;
+use crate::websocket::LiveUpdates;
+use super::witness::raise_split_view;
+
+#[derive(Debug, Serialize)]
+pub struct LogPublicKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogEntryParams {
+    pub digest: Option<String>,
+    pub resource_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InclusionProofParams {
+    /// Defaults to the current size of the log.
+    pub tree_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsistencyProofParams {
+    pub first: u64,
+    /// Defaults to the current size of the log.
+    pub second: Option<u64>,
+}
+
+/// Returns a signed head for the current log size, signing a new one if
+/// entries were appended since the last head.
+#[instrument(skip(db, secret_manager, user))]
+pub async fn get_tree_head<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<SignedTreeHead>> {
+    let tracer = global::tracer("transparency_api");
+    let mut span = tracer.start("get_tree_head");
+
+    let size = db.transparency_log_size(&user.tenant_id).await?;
+    span.set_attribute(KeyValue::new("log.tree_size", size as i64));
+    if let Some(head) = db.get_tree_head(&user.tenant_id, Some(size)).await? {
+        span.end();
+        return Ok(Json(with_cosignatures(&db, head).await?));
+    }
+
+    let signer = EnvelopeSigner::load_or_create(&secret_manager, TRANSPARENCY_LOG_KEY_ID, user.tenant_id).await.map_err(|e| {
+        error!("Failed to load transparency log key: {}", e);
+        AppError::InternalServerError
+    })?;
+    let nodes = db.transparency_nodes(&user.tenant_id, &merkle::range_nodes(0..size)).await?;
+    let root = tree_root(&nodes, size).map_err(|e| {
+        error!("Failed to compute transparency log root: {}", e);
+        AppError::InternalServerError
+    })?;
+    let head = SignedTreeHead::sign_root(&signer, user.tenant_id, size, &root).map_err(|e| {
+        error!("Failed to sign tree head: {}", e);
+        AppError::InternalServerError
+    })?;
+    if let Err(e) = db.create_tree_head(&head).await {
+        error!("Failed to store signed tree head: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    info!("Signed tree head of size {} with root {}", size, head.tree_head.root_hash);
+    span.end();
+    // Another request may have stored a head for this size first
+    let head = db.get_tree_head(&user.tenant_id, Some(size)).await?.unwrap_or(head);
+    Ok(Json(with_cosignatures(&db, head).await?))
+}
+
+/// Adds the witness co-signatures stored for the head to its envelope.
+async fn with_cosignatures(db: &Database, mut head: SignedTreeHead) -> Result<SignedTreeHead> {
+    for signature in db.list_tree_head_cosignatures(&head.tenant_id, head.tree_head.tree_size).await? {
+        add_cosignature(&mut head, signature);
+    }
+    Ok(head)
+}
+
+pub async fn get_log_public_key<M: SecretManager>(
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<LogPublicKeyResponse>> {
+    // Reading the key must not mint one; it is created when the first tree head is signed
+    let signer = EnvelopeSigner::load(&secret_manager, TRANSPARENCY_LOG_KEY_ID, user.tenant_id).await.map_err(|e| match e {
+        DsseError::SecretError(SecretError::SecretNotFound) => {
+            AppError::NotFound("Tenant has no transparency log key".to_string())
+        }
+        e => {
+            error!("Failed to load transparency log key: {}", e);
+            AppError::InternalServerError
+        }
+    })?;
+
+    Ok(Json(LogPublicKeyResponse {
+        key_id: signer.key_id().to_string(),
+        algorithm: "ed25519".to_string(),
+        public_key: hex::encode(signer.verifying_key().as_bytes()),
+    }))
+}
+
+pub async fn list_log_entries(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<LogEntryParams>,
+) -> Result<Json<Vec<LogEntry>>> {
+    let tracer = global::tracer("transparency_api");
+    let span = tracer.start("list_log_entries");
+    let _guard = span.enter();
+
+    if params.digest.is_none() && params.resource_id.is_none() {
+        return Err(AppError::BadRequest("Either digest or resource_id is required".to_string()));
+    }
+    let digest = params.digest.as_deref()
+        .map(parse_digest)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    Ok(Json(db.find_log_entries(&user.tenant_id, digest.as_deref(), params.resource_id.as_ref()).await?))
+}
+
+pub async fn get_log_entry(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(index): Path<u64>,
+) -> Result<Json<LogEntry>> {
+    let tracer = global::tracer("transparency_api");
+    let span = tracer.start("get_log_entry");
+    let _guard = span.enter();
+
+    db.get_log_entry(&user.tenant_id, index).await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Log entry {} not found", index)))
+}
+
+#[instrument(skip(db, user))]
+pub async fn get_inclusion_proof(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(index): Path<u64>,
+    Query(params): Query<InclusionProofParams>,
+) -> Result<Json<InclusionProof>> {
+    let tracer = global::tracer("transparency_api");
+    let span = tracer.start("get_inclusion_proof");
+    let _guard = span.enter();
+
+    let size = db.transparency_log_size(&user.tenant_id).await?;
+    let tree_size = params.tree_size.unwrap_or(size);
+    if tree_size > size || index >= tree_size {
+        return Err(AppError::BadRequest(format!("Leaf {} is not in a tree of size {} (log size {})", index, tree_size, size)));
+    }
+
+    let ids = InclusionProof::nodes(index, tree_size).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let nodes = db.transparency_nodes(&user.tenant_id, &ids).await?;
+    let proof = InclusionProof::from_nodes(&nodes, index, tree_size).map_err(|e| {
+        error!("Failed to build inclusion proof: {}", e);
+        AppError::InternalServerError
+    })?;
+    Ok(Json(proof))
+}
+
+#[instrument(skip(db, user))]
+pub async fn get_consistency_proof(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<ConsistencyProofParams>,
+) -> Result<Json<ConsistencyProof>> {
+    let tracer = global::tracer("transparency_api");
+    let span = tracer.start("get_consistency_proof");
+    let _guard = span.enter();
+
+    let size = db.transparency_log_size(&user.tenant_id).await?;
+    let second = params.second.unwrap_or(size);
+    if second > size || params.first > second {
+        return Err(AppError::BadRequest(format!(
+            "Cannot prove size {} against size {} (log size {})", params.first, second, size
+        )));
+    }
+
+    let ids = ConsistencyProof::nodes(params.first, second).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let nodes = db.transparency_nodes(&user.tenant_id, &ids).await?;
+    let proof = ConsistencyProof::from_nodes(&nodes, params.first, second).map_err(|e| {
+        error!("Failed to build consistency proof: {}", e);
+        AppError::InternalServerError
+    })?;
+    Ok(Json(proof))
+}
+
+pub async fn list_trusted_witnesses(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<TrustedWitness>>> {
+    let tracer = global::tracer("transparency_api");
+    let span = tracer.start("list_trusted_witnesses");
+    let _guard = span.enter();
+
+    Ok(Json(db.list_trusted_witnesses(&user.tenant_id).await?))
+}
+
+/// Trusts a witness's key to co-sign this tenant's tree heads. Trusted
+/// witnesses vouch for the log's history, so this needs the `write` action on
+/// `trusted_witnesses`.
+pub async fn register_trusted_witness<A: Authorization>(
+    State(db): State<Database>,
+    State(auth): State<A>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<TrustedWitnessRequest>,
+) -> Result<(StatusCode, Json<TrustedWitness>)> {
+    let tracer = global::tracer("transparency_api");
+    let span = tracer.start("register_trusted_witness");
+    let _guard = span.enter();
+
+    if !auth.is_allowed(&user.id.to_string(), "trusted_witnesses", "write", user.tenant_id).await {
+        return Err(AppError::Forbidden("You don't have permission to trust witnesses".to_string()));
+    }
+    let witness = TrustedWitness::new(user.tenant_id, request).map_err(|e| AppError::ValidationError(e.to_string()))?;
+    db.create_trusted_witness(&witness).await?;
+    info!("Trusted witness {} with key {}", witness.name, witness.key_id);
+    Ok((StatusCode::CREATED, Json(witness)))
+}
+
+/// Accepts a tree head co-signed by trusted witnesses and stores their
+/// signatures, so later heads of that size carry them. A head signed by this
+/// log that conflicts with the one it stored means someone was shown a
+/// different history, and raises a split view alert.
+#[instrument(skip(db, secret_manager, updates, user, head))]
+pub async fn submit_cosigned_head<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(updates): State<LiveUpdates>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(head): Json<SignedTreeHead>,
+) -> Result<Json<SignedTreeHead>> {
+    let tracer = global::tracer("transparency_api");
+    let mut span = tracer.start("submit_cosigned_head");
+    let size = head.tree_head.tree_size;
+    span.set_attribute(KeyValue::new("log.tree_size", size as i64));
+
+    let signer = EnvelopeSigner::load_or_create(&secret_manager, TRANSPARENCY_LOG_KEY_ID, user.tenant_id).await.map_err(|e| {
+        error!("Failed to load transparency log key: {}", e);
+        AppError::InternalServerError
+    })?;
+    head.verify(&signer.verifying_key())
+        .map_err(|e| AppError::BadRequest(format!("Tree head is not signed by this log: {}", e)))?;
+
+    let stored = db.get_tree_head(&user.tenant_id, Some(size)).await?
+        .ok_or_else(|| AppError::NotFound(format!("No tree head of size {} is stored", size)))?;
+    if stored.tree_head.root_hash != head.tree_head.root_hash {
+        let split_view = SplitView {
+            kind: SplitViewKind::ForkedRoot,
+            log_id: signer.key_id().to_string(),
+            expected: stored,
+            observed: head,
+        };
+        raise_split_view(&db, &updates, &SplitViewAlert::new(user.tenant_id, None, split_view)).await?;
+        return Err(AppError::BadRequest(format!("Tree head of size {} conflicts with the head this log stored", size)));
+    }
+
+    let witnesses = db.list_trusted_witnesses(&user.tenant_id).await?;
+    let signatures = witness_signatures(&head, &witnesses);
+    if signatures.is_empty() {
+        return Err(AppError::BadRequest("Tree head carries no signature from a trusted witness".to_string()));
+    }
+    for (witness_name, signature) in &signatures {
+        db.create_tree_head_cosignature(&user.tenant_id, size, witness_name, signature).await?;
+    }
+
+    info!("Stored {} witness co-signatures for tree head of size {}", signatures.len(), size);
+    span.end();
+    Ok(Json(with_cosignatures(&db, stored).await?))
+}
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Serialize;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+use crate::chain_of_custody::signing::SigningService;
+use crate::chain_of_custody::timestamp::TimestampService;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::models::provenance::VerifyParams;
+use crate::provenance::dsse::{DsseError, EnvelopeSigner};
+use crate::provenance::vsa::{self, VerificationSummary, VSA_SIGNING_KEY_ID};
+use crate::security::secret_management::{SecretError, SecretManager};
+use crate::auth::AuthenticatedUser;
+use super::provenance::run_verification;
+
+#[derive(Debug, Serialize)]
+pub struct VsaPublicKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+#[instrument(skip(db, secret_manager, signing, timestamps, user))]
+pub async fn issue_vsa<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(signing): State<SigningService>,
+    State(timestamps): State<TimestampService>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<VerifyParams>,
+) -> Result<(StatusCode, Json<VerificationSummary>)> {
+    let tracer = global::tracer("vsa_api");
+    let mut span = tracer.start("issue_vsa");
+    span.set_attribute(KeyValue::new("provenance.id", id.to_string()));
+
+    info!("Issuing verification summary for provenance record {}", id);
+
+    let (record, report, policies) = run_verification(&db, &timestamps, &user, &id, params.max_age()).await?;
+
+    let signer = signing.issuer_signer(&secret_manager, user.tenant_id, VSA_SIGNING_KEY_ID).await.map_err(|e| {
+        error!("Failed to load VSA signing key: {}", e);
+        AppError::InternalServerError
+    })?;
+    let summary = vsa::issue_vsa(&signer, user.tenant_id, &record, &report, &policies).map_err(|e| {
+        error!("Failed to sign verification summary: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    if let Err(e) = db.create_verification_summary(&summary).await {
+        error!("Failed to persist verification summary: {}", e);
+        return Err(AppError::DatabaseError(e.to_string()));
+    }
+
+    span.set_attribute(KeyValue::new("verified_level", summary.verified_level.to_string()));
+    span.end();
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+pub async fn list_vsas_by_digest(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(digest): Path<String>,
+) -> Result<Json<Vec<VerificationSummary>>> {
+    let tracer = global::tracer("vsa_api");
+    let span = tracer.start("list_vsas_by_digest");
+    let _guard = span.enter();
+
+    let digest = parse_digest(&digest).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let summaries = db.list_verification_summaries_by_digest(&user.tenant_id, &digest).await?;
+    Ok(Json(summaries))
+}
+
+pub async fn get_vsa_public_key<M: SecretManager>(
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<VsaPublicKeyResponse>> {
+    // Reading the key must not mint one; it is created when the first VSA is issued
+    let signer = EnvelopeSigner::load(&secret_manager, VSA_SIGNING_KEY_ID, user.tenant_id).await.map_err(|e| match e {
+        DsseError::SecretError(SecretError::SecretNotFound) => {
+            AppError::NotFound("Tenant has no VSA signing key".to_string())
+        }
+        e => {
+            error!("Failed to load VSA signing key: {}", e);
+            AppError::InternalServerError
+        }
+    })?;
+
+    Ok(Json(VsaPublicKeyResponse {
+        key_id: signer.key_id().to_string(),
+        algorithm: "ed25519".to_string(),
+        public_key: hex::encode(signer.verifying_key().as_bytes()),
+    }))
+}
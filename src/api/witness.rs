@@ -0,0 +1,262 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use opentelemetry::{global, KeyValue};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+use crate::auth::AuthenticatedUser;
+use crate::auth::authorization::Authorization;
+use crate::chain_of_custody::transparency_log::SignedTreeHead;
+use crate::chain_of_custody::witness::{
+    check_peer_addresses, check_tree_head, cosign_tree_head, peer_token_secret_id, SplitViewAlert, WitnessClient, WitnessError, WitnessPeer,
+    WitnessPeerRequest, WitnessRound, WITNESS_KEY_ID,
+};
+use crate::database::Database;
+use crate::error::{AppError, Result};
+use crate::provenance::dsse::EnvelopeSigner;
+use crate::security::secret_management::{SecretError, SecretManager};
+use crate::websocket::{LiveUpdate, LiveUpdateKind, LiveUpdates};
+
+const WITNESSED_HEADS_LIMIT: i64 = 100;
+
+#[derive(Debug, Serialize)]
+pub struct WitnessPublicKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+/// The key this tenant co-signs peers' tree heads with. Peers register it as
+/// a trusted witness.
+pub async fn get_witness_public_key<M: SecretManager>(
+    State(secret_manager): State<M>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<WitnessPublicKeyResponse>> {
+    let signer = load_witness_signer(&secret_manager, user.tenant_id).await?;
+    Ok(Json(WitnessPublicKeyResponse {
+        key_id: signer.key_id().to_string(),
+        algorithm: "ed25519".to_string(),
+        public_key: hex::encode(signer.verifying_key().as_bytes()),
+    }))
+}
+
+/// Adds a peer to witness. The server fetches from the peer's URL, so this
+/// needs the `write` action on `witness_peers`, and the URL must resolve to
+/// public addresses only.
+#[instrument(skip(db, secret_manager, auth, user, request))]
+pub async fn create_witness_peer<M: SecretManager, A: Authorization>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(auth): State<A>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(request): Json<WitnessPeerRequest>,
+) -> Result<(StatusCode, Json<WitnessPeer>)> {
+    let tracer = global::tracer("witness_api");
+    let mut span = tracer.start("create_witness_peer");
+    span.set_attribute(KeyValue::new("witness.peer", request.name.clone()));
+
+    if !auth.is_allowed(&user.id.to_string(), "witness_peers", "write", user.tenant_id).await {
+        span.end();
+        return Err(AppError::Forbidden("You don't have permission to add witness peers".to_string()));
+    }
+    let peer = WitnessPeer::new(user.tenant_id, user.id, &request)
+        .map_err(|e| AppError::ValidationError(e.to_string()))?;
+    check_peer_addresses(&peer.url).await.map_err(|e| AppError::ValidationError(e.to_string()))?;
+    if let Some(token) = request.token.as_deref().filter(|token| !token.is_empty()) {
+        secret_manager.set_secret(&peer_token_secret_id(&peer.id), token, user.tenant_id).await.map_err(|e| {
+            error!("Failed to store token for witness peer {}: {}", peer.name, e);
+            AppError::InternalServerError
+        })?;
+    }
+    db.create_witness_peer(&peer).await.map_err(|e| {
+        error!("Failed to store witness peer: {}", e);
+        AppError::DatabaseError(e.to_string())
+    })?;
+
+    info!("Witnessing log {} of peer {} at {}", peer.log_id, peer.name, peer.url);
+    span.end();
+    Ok((StatusCode::CREATED, Json(peer)))
+}
+
+pub async fn list_witness_peers(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<WitnessPeer>>> {
+    let tracer = global::tracer("witness_api");
+    let span = tracer.start("list_witness_peers");
+    let _guard = span.enter();
+
+    Ok(Json(db.list_witness_peers(Some(&user.tenant_id)).await?))
+}
+
+/// Runs a witnessing round of one peer right away.
+pub async fn witness_peer_now<M: SecretManager>(
+    State(db): State<Database>,
+    State(secret_manager): State<M>,
+    State(updates): State<LiveUpdates>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WitnessRound>> {
+    let tracer = global::tracer("witness_api");
+    let mut span = tracer.start("witness_peer_now");
+    span.set_attribute(KeyValue::new("witness.peer_id", id.to_string()));
+
+    let peer = find_peer(&db, &user.tenant_id, &id).await?;
+    let round = witness_peer(&db, &secret_manager, &updates, &peer).await;
+    span.end();
+    Ok(Json(round?))
+}
+
+pub async fn list_witnessed_heads(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<SignedTreeHead>>> {
+    let tracer = global::tracer("witness_api");
+    let span = tracer.start("list_witnessed_heads");
+    let _guard = span.enter();
+
+    let peer = find_peer(&db, &user.tenant_id, &id).await?;
+    Ok(Json(db.list_witnessed_heads(&peer.id, WITNESSED_HEADS_LIMIT).await?))
+}
+
+/// Split views found while witnessing peers, and conflicting heads handed to
+/// this tenant's own log.
+pub async fn list_split_view_alerts(
+    State(db): State<Database>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Json<Vec<SplitViewAlert>>> {
+    let tracer = global::tracer("witness_api");
+    let span = tracer.start("list_split_view_alerts");
+    let _guard = span.enter();
+
+    Ok(Json(db.list_split_view_alerts(&user.tenant_id).await?))
+}
+
+async fn find_peer(db: &Database, tenant_id: &Uuid, id: &Uuid) -> Result<WitnessPeer> {
+    db.list_witness_peers(Some(tenant_id)).await?
+        .into_iter()
+        .find(|peer| peer.id == *id)
+        .ok_or_else(|| AppError::NotFound(format!("Witness peer {} not found", id)))
+}
+
+async fn load_witness_signer<M: SecretManager>(secret_manager: &M, tenant_id: Uuid) -> Result<EnvelopeSigner> {
+    EnvelopeSigner::load_or_create(secret_manager, WITNESS_KEY_ID, tenant_id).await.map_err(|e| {
+        error!("Failed to load witness key: {}", e);
+        AppError::InternalServerError
+    })
+}
+
+/// Raises a split view alert: stores it and pushes it to the tenant's live
+/// update stream.
+pub(crate) async fn raise_split_view(db: &Database, updates: &LiveUpdates, alert: &SplitViewAlert) -> Result<()> {
+    db.create_split_view_alert(alert).await?;
+    updates.publish(LiveUpdate::new(alert.tenant_id, LiveUpdateKind::SplitView, alert.id, serde_json::to_value(alert)?));
+    Ok(())
+}
+
+/// Fetches the peer's signed tree head, checks it is consistent with the
+/// last head witnessed from the peer, then co-signs it and hands it back.
+/// A head that conflicts with what was witnessed before raises an alert and
+/// is not co-signed.
+pub(crate) async fn witness_peer<M: SecretManager>(
+    db: &Database,
+    secret_manager: &M,
+    updates: &LiveUpdates,
+    peer: &WitnessPeer,
+) -> Result<WitnessRound> {
+    let token = match secret_manager.get_secret(&peer_token_secret_id(&peer.id), peer.tenant_id).await {
+        Ok(token) => Some(token),
+        Err(SecretError::SecretNotFound) => None,
+        Err(e) => {
+            error!("Failed to load token for witness peer {}: {}", peer.name, e);
+            return Err(AppError::InternalServerError);
+        }
+    };
+    let log_key = peer.log_key().map_err(|e| AppError::ValidationError(e.to_string()))?;
+    check_peer_addresses(&peer.url).await.map_err(|e| AppError::ValidationError(e.to_string()))?;
+    let client = WitnessClient::new();
+    let peer_error = |e: WitnessError| {
+        error!("Failed to witness peer {}: {}", peer.name, e);
+        AppError::InternalServerError
+    };
+
+    let mut head = client.fetch_tree_head(peer, token.as_deref()).await.map_err(peer_error)?;
+    let previous = db.list_witnessed_heads(&peer.id, 1).await?.pop();
+    let proof = match &previous {
+        Some(previous) if previous.tree_head.tree_size < head.tree_head.tree_size => Some(
+            client.fetch_consistency_proof(peer, token.as_deref(), previous.tree_head.tree_size, head.tree_head.tree_size)
+                .await
+                .map_err(peer_error)?,
+        ),
+        _ => None,
+    };
+
+    let split_view = check_tree_head(&log_key, previous.as_ref(), &head, proof.as_ref())
+        .map_err(|e| AppError::BadRequest(format!("Peer {} returned an unusable tree head: {}", peer.name, e)))?;
+    if let Some(split_view) = split_view {
+        // A conflict already alerted on is reported again but not re-raised
+        let known = db.list_split_view_alerts(&peer.tenant_id).await?
+            .into_iter()
+            .find(|alert| alert.peer_id == Some(peer.id) && alert.split_view.same_conflict(&split_view));
+        let alert = match known {
+            Some(alert) => alert,
+            None => {
+                let alert = SplitViewAlert::new(peer.tenant_id, Some(peer.id), split_view);
+                raise_split_view(db, updates, &alert).await?;
+                alert
+            }
+        };
+        return Ok(WitnessRound { peer_id: peer.id, head, cosigned: false, submitted: false, submit_error: None, alert: Some(alert) });
+    }
+
+    let signer = load_witness_signer(secret_manager, peer.tenant_id).await?;
+    cosign_tree_head(&signer, &mut head).map_err(|e| {
+        error!("Failed to co-sign tree head of peer {}: {}", peer.name, e);
+        AppError::InternalServerError
+    })?;
+    db.create_witnessed_head(&peer.id, &head).await?;
+
+    // The head is witnessed whether or not the peer takes the co-signature
+    let (submitted, submit_error) = match client.submit_cosigned_head(peer, token.as_deref(), &head).await {
+        Ok(_) => (true, None),
+        Err(e) => {
+            warn!("Peer {} did not accept co-signed tree head of size {}: {}", peer.name, head.tree_head.tree_size, e);
+            (false, Some(e.to_string()))
+        }
+    };
+    info!("Witnessed tree head of size {} from peer {}", head.tree_head.tree_size, peer.name);
+    Ok(WitnessRound { peer_id: peer.id, head, cosigned: true, submitted, submit_error, alert: None })
+}
+
+/// Periodically witnesses every tenant's peers.
+pub fn spawn_witness<M: SecretManager + Send + Sync + 'static>(
+    db: Database,
+    secret_manager: M,
+    updates: LiveUpdates,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let peers = match db.list_witness_peers(None).await {
+                Ok(peers) => peers,
+                Err(e) => {
+                    error!("Failed to list witness peers: {}", e);
+                    continue;
+                }
+            };
+            for peer in peers {
+                if let Err(e) = witness_peer(&db, &secret_manager, &updates, &peer).await {
+                    error!("Witnessing round of peer {} failed: {:?}", peer.name, e);
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use ed25519_dalek::VerifyingKey;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::provenance::dsse::{verify_envelope, DsseError, Envelope, EnvelopeSigner};
+
+/// Secret under which each tenant's audit checkpoint key is stored.
+pub const AUDIT_LOG_KEY_ID: &str = "audit_log_key";
+pub const AUDIT_CHECKPOINT_PAYLOAD_TYPE: &str = "application/vnd.traceguard.audit-checkpoint+json";
+
+#[derive(Error, Debug)]
+pub enum AuditLogError {
+    #[error("Audit entry {0} does not link to the preceding entry")]
+    BrokenChain(i64),
+    #[error("Audit entry {0} does not match its hash")]
+    HashMismatch(i64),
+    #[error("Checkpoint at entry {0} does not match the log")]
+    CheckpointMismatch(i64),
+    #[error("Checkpoint does not match its payload")]
+    CheckpointPayloadMismatch,
+    #[error("Unknown audit channel: {0}")]
+    UnknownChannel(String),
+    #[error("Envelope error: {0}")]
+    Dsse(#[from] DsseError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditChannel {
+    Rest,
+    Grpc,
+}
+
+impl AuditChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditChannel::Rest => "rest",
+            AuditChannel::Grpc => "grpc",
+        }
+    }
+
+}
+
+impl FromStr for AuditChannel {
+    type Err = AuditLogError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "rest" => Ok(AuditChannel::Rest),
+            "grpc" => Ok(AuditChannel::Grpc),
+            _ => Err(AuditLogError::UnknownChannel(value.to_string())),
+        }
+    }
+}
+
+/// One mutating call, as seen by the layer that recorded it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditRecord {
+    /// Nil for calls made without a tenant, e.g. failed logins.
+    pub tenant_id: Uuid,
+    pub actor: Option<Uuid>,
+    pub channel: AuditChannel,
+    /// The route or method called, e.g. `PUT /api/provenance/:id` or
+    /// `/traceguard.v1.TraceGuardService/CreateSBOM`.
+    pub action: String,
+    /// The concrete resource the call acted on, e.g. `/api/provenance/<id>`.
+    pub resource: String,
+    /// HTTP status, or the gRPC status code.
+    pub status: i32,
+    /// SHA-256 of the resource before the call, where the handler knows it.
+    pub before_hash: Option<String>,
+    /// SHA-256 of the resource after the call, or of the response body.
+    pub after_hash: Option<String>,
+    pub request_id: String,
+    pub source_ip: Option<String>,
+}
+
+/// An audit record linked into its tenant's chain. `entry_hash` commits to
+/// every field and to the previous entry's hash, so editing, reordering or
+/// removing an entry breaks every later one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub sequence: i64,
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub previous_hash: Option<String>,
+    pub entry_hash: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    /// Builds the entry following `previous`, the current head of the tenant's chain.
+    pub fn append(previous: Option<&AuditEntry>, record: AuditRecord) -> Self {
+        let mut entry = Self {
+            id: Uuid::new_v4(),
+            sequence: previous.map_or(1, |previous| previous.sequence + 1),
+            record,
+            previous_hash: previous.map(|previous| previous.entry_hash.clone()),
+            entry_hash: String::new(),
+            // Stored with microsecond precision, so hash it that way
+            timestamp: Utc::now().trunc_subsecs(6),
+        };
+        entry.entry_hash = entry.compute_hash();
+        entry
+    }
+
+    pub fn compute_hash(&self) -> String {
+        let record = &self.record;
+        let mut hasher = Sha256::new();
+        for field in [
+            record.tenant_id.to_string().as_str(),
+            self.sequence.to_string().as_str(),
+            record.actor.map(|actor| actor.to_string()).unwrap_or_default().as_str(),
+            record.channel.as_str(),
+            record.action.as_str(),
+            record.resource.as_str(),
+            record.status.to_string().as_str(),
+            record.before_hash.as_deref().unwrap_or(""),
+            record.after_hash.as_deref().unwrap_or(""),
+            record.request_id.as_str(),
+            record.source_ip.as_deref().unwrap_or(""),
+            self.previous_hash.as_deref().unwrap_or(""),
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true).as_str(),
+        ] {
+            // Length-prefix each field so adjacent values cannot be shifted between fields
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Checks that `entries` (ordered by sequence) are consecutive, link to each
+/// other and still match their hashes. The slice may start mid-chain, so the
+/// first entry's link to its predecessor is taken as given.
+pub fn verify_audit_chain(entries: &[AuditEntry]) -> Result<(), AuditLogError> {
+    let mut previous: Option<&AuditEntry> = None;
+    for entry in entries {
+        if entry.compute_hash() != entry.entry_hash {
+            return Err(AuditLogError::HashMismatch(entry.sequence));
+        }
+        if let Some(previous) = previous {
+            if entry.previous_hash.as_deref() != Some(previous.entry_hash.as_str()) || entry.sequence != previous.sequence + 1 {
+                return Err(AuditLogError::BrokenChain(entry.sequence));
+            }
+        } else if entry.sequence == 1 && entry.previous_hash.is_some() {
+            return Err(AuditLogError::BrokenChain(entry.sequence));
+        }
+        previous = Some(entry);
+    }
+    Ok(())
+}
+
+/// The signed statement of a chain's length and head hash at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditCheckpointBody {
+    /// Key ID of the tenant's audit signing key.
+    pub log_id: String,
+    pub tenant_id: Uuid,
+    pub sequence: i64,
+    pub head_hash: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    #[serde(flatten)]
+    pub checkpoint: AuditCheckpointBody,
+    pub envelope: Envelope,
+}
+
+impl AuditCheckpoint {
+    pub fn sign(signer: &EnvelopeSigner, head: &AuditEntry) -> Result<Self, AuditLogError> {
+        let checkpoint = AuditCheckpointBody {
+            log_id: signer.key_id().to_string(),
+            tenant_id: head.record.tenant_id,
+            sequence: head.sequence,
+            head_hash: head.entry_hash.clone(),
+            timestamp: Utc::now().trunc_subsecs(6),
+        };
+        let envelope = signer.sign_json(AUDIT_CHECKPOINT_PAYLOAD_TYPE, &checkpoint)?;
+        Ok(Self { checkpoint, envelope })
+    }
+
+    /// Checks the signature, that the signed payload matches the unsigned
+    /// fields, and that `entry` is the entry the checkpoint was taken at.
+    pub fn verify(&self, log_key: &VerifyingKey, entry: &AuditEntry) -> Result<(), AuditLogError> {
+        verify_envelope(&self.envelope, log_key)?;
+        let signed: AuditCheckpointBody = self.envelope.decode_payload()?;
+        if signed != self.checkpoint {
+            return Err(AuditLogError::CheckpointPayloadMismatch);
+        }
+        if entry.sequence != self.checkpoint.sequence || entry.entry_hash != self.checkpoint.head_hash {
+            return Err(AuditLogError::CheckpointMismatch(self.checkpoint.sequence));
+        }
+        Ok(())
+    }
+}
+
+/// Filters over audit entries. Every filter is optional and filters combine
+/// with AND. Results are ordered by sequence.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<Uuid>,
+    /// Matches the action exactly, e.g. `POST /api/revocations`.
+    pub action: Option<String>,
+    /// Matches resources starting with this prefix.
+    pub resource: Option<String>,
+    pub request_id: Option<String>,
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+    /// Returns entries after this sequence number; pass the previous page's `next_sequence`.
+    pub after_sequence: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+pub const DEFAULT_AUDIT_PAGE_SIZE: i64 = 100;
+pub const MAX_AUDIT_PAGE_SIZE: i64 = 1000;
+
+impl AuditQuery {
+    pub fn page_size(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_AUDIT_PAGE_SIZE).clamp(1, MAX_AUDIT_PAGE_SIZE)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub next_sequence: Option<i64>,
+}
+
+/// Builds a page from up to `page_size + 1` entries; the extra entry only
+/// signals that another page exists.
+pub fn paginate_audit(mut entries: Vec<AuditEntry>, page_size: i64) -> AuditPage {
+    let has_more = entries.len() as i64 > page_size;
+    entries.truncate(page_size as usize);
+    let next_sequence = entries.last().filter(|_| has_more).map(|entry| entry.sequence);
+    AuditPage { entries, next_sequence }
+}
+
+/// Writes entries as JSON Lines, one entry per line.
+pub fn to_jsonl(entries: &[AuditEntry]) -> Result<String, serde_json::Error> {
+    let mut output = String::new();
+    for entry in entries {
+        output.push_str(&serde_json::to_string(entry)?);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub length: usize,
+    pub head_hash: Option<String>,
+    /// Sequence of the latest checkpoint that was checked.
+    pub checkpoint_sequence: Option<i64>,
+    pub error: Option<String>,
+}
+
+pub const DEFAULT_CHECKPOINT_INTERVAL_SECS: u64 = 300;
+
+/// How often tenants' audit logs are checkpointed in the background, and
+/// which reverse proxies may report the client address. Zero disables
+/// background checkpoints.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct AuditConfig {
+    pub checkpoint_interval_secs: u64,
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self { checkpoint_interval_secs: DEFAULT_CHECKPOINT_INTERVAL_SECS, trusted_proxies: Vec::new() }
+    }
+}
+
+/// The client address of a call. `X-Forwarded-For` is only believed when the
+/// peer is a trusted proxy; its hops are then walked from the nearest, and
+/// the first address that is not itself a trusted proxy is the client.
+pub fn client_ip(peer: Option<IpAddr>, forwarded_for: Option<&str>, trusted_proxies: &[IpAddr]) -> Option<String> {
+    let peer = peer?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer.to_string());
+    }
+    let mut client = peer;
+    for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+        match hop.trim().parse::<IpAddr>() {
+            Ok(hop) => {
+                client = hop;
+                if !trusted_proxies.contains(&hop) {
+                    break;
+                }
+            }
+            // A hop that is not an address cannot be attributed
+            Err(_) => break,
+        }
+    }
+    Some(client.to_string())
+}
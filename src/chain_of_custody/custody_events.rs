@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use glob::Pattern;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustodyEventType {
+    Built,
+    Scanned,
+    Approved,
+    Promoted,
+    Deployed,
+    Revoked,
+}
+
+impl CustodyEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CustodyEventType::Built => "built",
+            CustodyEventType::Scanned => "scanned",
+            CustodyEventType::Approved => "approved",
+            CustodyEventType::Promoted => "promoted",
+            CustodyEventType::Deployed => "deployed",
+            CustodyEventType::Revoked => "revoked",
+        }
+    }
+
+    /// Events that move the artifact into an environment.
+    pub fn requires_environment(&self) -> bool {
+        matches!(self, CustodyEventType::Promoted | CustodyEventType::Deployed)
+    }
+}
+
+impl FromStr for CustodyEventType {
+    type Err = CustodyError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "built" => Ok(CustodyEventType::Built),
+            "scanned" => Ok(CustodyEventType::Scanned),
+            "approved" => Ok(CustodyEventType::Approved),
+            "promoted" => Ok(CustodyEventType::Promoted),
+            "deployed" => Ok(CustodyEventType::Deployed),
+            "revoked" => Ok(CustodyEventType::Revoked),
+            _ => Err(CustodyError::UnknownEventType(value.to_string())),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CustodyError {
+    #[error("Artifact {0} has been revoked")]
+    Revoked(String),
+    #[error("{0} events must name an environment")]
+    MissingEnvironment(&'static str),
+    #[error("Event {0} does not link to the preceding event")]
+    BrokenChain(i32),
+    #[error("Event {0} does not match its hash")]
+    HashMismatch(i32),
+    #[error("Invalid environment pattern: {0}")]
+    InvalidPattern(String),
+    #[error("Custody policy requires at least one event")]
+    NoRequiredEvents,
+    #[error("Unknown custody event type: {0}")]
+    UnknownEventType(String),
+    #[error("Artifact digest must be a hex-encoded sha256 digest: {0}")]
+    InvalidDigest(String),
+}
+
+/// Points at the evidence behind an event, e.g. a provenance record, a scan
+/// report or a change ticket.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvidenceRef {
+    /// What the evidence is, e.g. `provenance`, `vsa` or `scan_report`.
+    pub kind: String,
+    pub uri: String,
+    pub digest: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppendCustodyEventRequest {
+    pub event_type: CustodyEventType,
+    /// Narrows the event, e.g. `security` for a security approval.
+    pub category: Option<String>,
+    pub environment: Option<String>,
+    #[serde(default)]
+    pub evidence: Vec<EvidenceRef>,
+    pub details: Option<Value>,
+}
+
+/// One link in an artifact's custody chain. `event_hash` commits to every
+/// other field and to the previous event's hash, so editing, reordering or
+/// removing an earlier event breaks every later one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustodyEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub artifact_digest: String,
+    pub sequence: i32,
+    pub event_type: CustodyEventType,
+    pub category: Option<String>,
+    pub environment: Option<String>,
+    pub actor: Uuid,
+    pub evidence: Vec<EvidenceRef>,
+    pub details: Option<Value>,
+    pub previous_hash: Option<String>,
+    pub event_hash: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CustodyEvent {
+    /// Builds the event following `previous`, the current head of the chain.
+    pub fn append(
+        tenant_id: Uuid,
+        artifact_digest: &str,
+        actor: Uuid,
+        previous: Option<&CustodyEvent>,
+        request: AppendCustodyEventRequest,
+    ) -> Result<Self, CustodyError> {
+        let artifact_digest = parse_digest(artifact_digest)?;
+        if let Some(previous) = previous {
+            if previous.event_type == CustodyEventType::Revoked {
+                return Err(CustodyError::Revoked(artifact_digest));
+            }
+        }
+        if request.event_type.requires_environment() && request.environment.as_deref().is_none_or(|env| env.trim().is_empty()) {
+            return Err(CustodyError::MissingEnvironment(request.event_type.as_str()));
+        }
+
+        let mut event = Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            artifact_digest,
+            sequence: previous.map_or(1, |previous| previous.sequence + 1),
+            event_type: request.event_type,
+            category: request.category,
+            environment: request.environment,
+            actor,
+            evidence: request.evidence,
+            details: request.details,
+            previous_hash: previous.map(|previous| previous.event_hash.clone()),
+            event_hash: String::new(),
+            // Stored with microsecond precision, so hash it that way
+            timestamp: Utc::now().trunc_subsecs(6),
+        };
+        event.event_hash = event.compute_hash();
+        Ok(event)
+    }
+
+    pub fn compute_hash(&self) -> String {
+        let evidence = serde_json::to_string(&self.evidence).unwrap_or_default();
+        let details = self.details.as_ref().map(Value::to_string).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        for field in [
+            self.artifact_digest.as_str(),
+            self.sequence.to_string().as_str(),
+            self.event_type.as_str(),
+            self.category.as_deref().unwrap_or(""),
+            self.environment.as_deref().unwrap_or(""),
+            self.actor.to_string().as_str(),
+            evidence.as_str(),
+            details.as_str(),
+            self.previous_hash.as_deref().unwrap_or(""),
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true).as_str(),
+        ] {
+            // Length-prefix each field so adjacent values cannot be shifted between fields
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+pub fn normalize_digest(digest: &str) -> String {
+    digest.strip_prefix("sha256:").unwrap_or(digest).to_lowercase()
+}
+
+/// Normalizes the digest and checks it is a hex-encoded sha256 digest.
+pub fn parse_digest(digest: &str) -> Result<String, CustodyError> {
+    let digest = normalize_digest(digest);
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(CustodyError::InvalidDigest(digest));
+    }
+    Ok(digest)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustodyChainVerification {
+    pub artifact_digest: String,
+    pub valid: bool,
+    pub length: usize,
+    pub head_hash: Option<String>,
+    pub revoked: bool,
+    pub error: Option<String>,
+}
+
+/// Checks that `events` (ordered oldest first) form an unbroken chain and
+/// that every event still matches its hash.
+pub fn verify_custody_chain(events: &[CustodyEvent]) -> Result<(), CustodyError> {
+    let mut previous: Option<&CustodyEvent> = None;
+    for event in events {
+        if event.compute_hash() != event.event_hash {
+            return Err(CustodyError::HashMismatch(event.sequence));
+        }
+        let expected_previous = previous.map(|previous| previous.event_hash.as_str());
+        let expected_sequence = previous.map_or(1, |previous| previous.sequence + 1);
+        if event.previous_hash.as_deref() != expected_previous || event.sequence != expected_sequence {
+            return Err(CustodyError::BrokenChain(event.sequence));
+        }
+        previous = Some(event);
+    }
+    Ok(())
+}
+
+impl CustodyChainVerification {
+    pub fn new(artifact_digest: &str, events: &[CustodyEvent]) -> Self {
+        let result = verify_custody_chain(events);
+        Self {
+            artifact_digest: normalize_digest(artifact_digest),
+            valid: result.is_ok(),
+            length: events.len(),
+            head_hash: events.last().map(|event| event.event_hash.clone()),
+            revoked: events.iter().any(|event| event.event_type == CustodyEventType::Revoked),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// An event that must already be in the chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequiredEvent {
+    pub event_type: CustodyEventType,
+    pub category: Option<String>,
+    pub environment: Option<String>,
+}
+
+impl RequiredEvent {
+    fn is_satisfied_by(&self, event: &CustodyEvent) -> bool {
+        event.event_type == self.event_type
+            && self.category.as_ref().is_none_or(|category| event.category.as_ref() == Some(category))
+            && self.environment.as_ref().is_none_or(|environment| event.environment.as_ref() == Some(environment))
+    }
+}
+
+/// Requires events to be present before a gated event, e.g. a `security`
+/// approval before any promotion to `prod*`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustodyPolicy {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub gated_event: CustodyEventType,
+    /// Glob over the gated event's environment; `None` gates every environment.
+    pub environment_pattern: Option<String>,
+    pub required_events: Vec<RequiredEvent>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustodyPolicyRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub gated_event: CustodyEventType,
+    pub environment_pattern: Option<String>,
+    pub required_events: Vec<RequiredEvent>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustodyPolicyViolation {
+    pub policy_id: Uuid,
+    pub policy_name: String,
+    pub missing: RequiredEvent,
+}
+
+impl CustodyPolicy {
+    pub fn from_request(tenant_id: Uuid, request: CustodyPolicyRequest) -> Result<Self, CustodyError> {
+        if request.required_events.is_empty() {
+            return Err(CustodyError::NoRequiredEvents);
+        }
+        if let Some(pattern) = &request.environment_pattern {
+            Pattern::new(pattern).map_err(|e| CustodyError::InvalidPattern(e.to_string()))?;
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: request.name,
+            description: request.description,
+            gated_event: request.gated_event,
+            environment_pattern: request.environment_pattern,
+            required_events: request.required_events,
+            enabled: request.enabled,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn applies_to(&self, event_type: CustodyEventType, environment: Option<&str>) -> bool {
+        if !self.enabled || self.gated_event != event_type {
+            return false;
+        }
+        match &self.environment_pattern {
+            None => true,
+            Some(pattern) => Pattern::new(pattern)
+                .map(|pattern| environment.is_some_and(|environment| pattern.matches(environment)))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Lists the required events missing from `events` for every policy that
+/// gates `event_type` into `environment`. An empty result allows the event.
+pub fn evaluate_custody_policies(
+    policies: &[CustodyPolicy],
+    events: &[CustodyEvent],
+    event_type: CustodyEventType,
+    environment: Option<&str>,
+) -> Vec<CustodyPolicyViolation> {
+    policies.iter()
+        .filter(|policy| policy.applies_to(event_type, environment))
+        .flat_map(|policy| {
+            policy.required_events.iter()
+                .filter(|required| !events.iter().any(|event| required.is_satisfied_by(event)))
+                .map(|required| CustodyPolicyViolation {
+                    policy_id: policy.id,
+                    policy_name: policy.name.clone(),
+                    missing: required.clone(),
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,338 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, SigningKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use crate::chain_of_custody::merkle::{self, Hash};
+use crate::chain_of_custody::rekor::{
+    decode_entry_public_key, public_key_pem, sha256_hex, sign_entry_timestamp, Checkpoint, EntryVerification,
+    HashValue, LogKey, ProposedEntry, RecordedEntry, RekorBackend, RekorError, RekorInclusionProof, RekorLogEntry,
+    Sha512Prehash,
+};
+use crate::provenance::dsse::{verify_envelope, Envelope, EnvelopeSignature};
+
+pub const EMBEDDED_REKOR_ORIGIN: &str = "traceguard-embedded-rekor";
+
+/// Files kept in the data directory: the hex Ed25519 seed of the log key,
+/// and one JSON line per entry in log order.
+const KEY_FILE: &str = "log-key";
+const ENTRIES_FILE: &str = "entries.jsonl";
+
+/// A transparency log that validates `hashedrekord` and `intoto` entries the
+/// way Rekor does and answers with inclusion proofs and signed entry
+/// timestamps. Use it directly as a `RekorBackend`, or serve `router()` to
+/// offer the Rekor v1 REST API. Logs from `open` persist to disk; logs from
+/// `new` and `generate` live in memory.
+pub struct EmbeddedRekor {
+    signing_key: SigningKey,
+    log_id: String,
+    state: Mutex<LogState>,
+}
+
+#[derive(Default)]
+struct LogState {
+    leaves: Vec<Hash>,
+    entries: Vec<StoredEntry>,
+    by_uuid: HashMap<String, usize>,
+    /// Entries are appended here before they are acknowledged.
+    journal: Option<File>,
+}
+
+impl LogState {
+    fn push(&mut self, leaf: Hash, stored: StoredEntry) -> usize {
+        let index = self.entries.len();
+        self.leaves.push(leaf);
+        self.by_uuid.insert(stored.uuid.clone(), index);
+        self.entries.push(stored);
+        index
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    uuid: String,
+    body: String,
+    integrated_time: i64,
+}
+
+impl EmbeddedRekor {
+    pub fn new(signing_key: SigningKey) -> Self {
+        let log_id = LogKey::Ed25519(signing_key.verifying_key()).log_id();
+        Self { signing_key, log_id, state: Mutex::new(LogState::default()) }
+    }
+
+    /// A log with a fresh key, which lives as long as the process.
+    pub fn generate() -> Self {
+        Self::new(SigningKey::generate(&mut rand::rngs::OsRng))
+    }
+
+    /// Opens the log kept in `data_dir`, creating the directory and the log
+    /// key on first use, and replays its entries.
+    pub fn open(data_dir: &std::path::Path) -> Result<Self, RekorError> {
+        let storage_error = |e: std::io::Error| RekorError::Storage(format!("{}: {}", data_dir.display(), e));
+        std::fs::create_dir_all(data_dir).map_err(storage_error)?;
+
+        let key_file = data_dir.join(KEY_FILE);
+        let signing_key = match std::fs::read_to_string(&key_file) {
+            Ok(seed) => {
+                let seed: [u8; 32] = hex::decode(seed.trim())
+                    .ok()
+                    .and_then(|seed| seed.try_into().ok())
+                    .ok_or_else(|| RekorError::Storage(format!("{} does not hold an Ed25519 seed", key_file.display())))?;
+                SigningKey::from_bytes(&seed)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+                std::fs::write(&key_file, hex::encode(signing_key.to_bytes())).map_err(storage_error)?;
+                signing_key
+            }
+            Err(e) => return Err(storage_error(e)),
+        };
+
+        let log = Self::new(signing_key);
+        let entries_file = data_dir.join(ENTRIES_FILE);
+        let mut state = log.state.lock().unwrap();
+        match std::fs::read_to_string(&entries_file) {
+            Ok(lines) => {
+                for line in lines.lines() {
+                    let stored: StoredEntry = serde_json::from_str(line)
+                        .map_err(|e| RekorError::Storage(format!("{}: {}", entries_file.display(), e)))?;
+                    let body = BASE64.decode(&stored.body).map_err(|e| RekorError::Storage(e.to_string()))?;
+                    let leaf = merkle::leaf_hash(&body);
+                    if hex::encode(leaf) != stored.uuid {
+                        return Err(RekorError::Storage(format!("entry {} does not match its body", stored.uuid)));
+                    }
+                    state.push(leaf, stored);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(storage_error(e)),
+        }
+        state.journal = Some(OpenOptions::new().create(true).append(true).open(&entries_file).map_err(storage_error)?);
+        drop(state);
+        Ok(log)
+    }
+
+    pub fn log_id(&self) -> &str {
+        &self.log_id
+    }
+
+    pub fn verifying_key(&self) -> ed25519_dalek::VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn log_key(&self) -> LogKey {
+        LogKey::Ed25519(self.verifying_key())
+    }
+
+    pub fn tree_size(&self) -> u64 {
+        self.state.lock().unwrap().leaves.len() as u64
+    }
+
+    /// Signed checkpoint for the current tree.
+    pub fn checkpoint(&self) -> String {
+        let state = self.state.lock().unwrap();
+        self.sign_checkpoint(&state.leaves)
+    }
+
+    fn sign_checkpoint(&self, leaves: &[Hash]) -> String {
+        Checkpoint {
+            origin: EMBEDDED_REKOR_ORIGIN.to_string(),
+            tree_size: leaves.len() as u64,
+            root_hash: merkle::root(leaves),
+        }
+        .sign(&self.signing_key)
+    }
+
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/api/v1/log", get(log_info))
+            .route("/api/v1/log/publicKey", get(log_public_key))
+            .route("/api/v1/log/entries", post(create_entry))
+            .route("/api/v1/log/entries/:uuid", get(get_entry))
+            .with_state(self)
+    }
+
+    /// Serves the Rekor API on an ephemeral loopback port and returns its address.
+    pub async fn spawn(self: Arc<Self>) -> std::io::Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server = axum::Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(self.router().into_make_service());
+        tokio::spawn(server);
+        Ok(addr)
+    }
+
+    fn append(&self, entry: &ProposedEntry) -> Result<RecordedEntry, RekorError> {
+        let body = serde_json::to_vec(&canonicalize(entry)?).map_err(|e| RekorError::InvalidEntry(e.to_string()))?;
+        let leaf = merkle::leaf_hash(&body);
+        let uuid = hex::encode(leaf);
+
+        let mut state = self.state.lock().unwrap();
+        if state.by_uuid.contains_key(&uuid) {
+            return Err(RekorError::Conflict(uuid));
+        }
+        let stored = StoredEntry { uuid, body: BASE64.encode(&body), integrated_time: chrono::Utc::now().timestamp() };
+        if let Some(journal) = state.journal.as_mut() {
+            let mut line = serde_json::to_vec(&stored).map_err(|e| RekorError::Storage(e.to_string()))?;
+            line.push(b'\n');
+            journal.write_all(&line)
+                .and_then(|_| journal.sync_data())
+                .map_err(|e| RekorError::Storage(e.to_string()))?;
+        }
+        let index = state.push(leaf, stored);
+        self.recorded(&state, index)
+    }
+
+    /// Builds the response for an entry with a proof against the current tree.
+    fn recorded(&self, state: &LogState, index: usize) -> Result<RecordedEntry, RekorError> {
+        let stored = &state.entries[index];
+        let mut entry = RekorLogEntry {
+            body: stored.body.clone(),
+            integrated_time: stored.integrated_time,
+            log_id: self.log_id.clone(),
+            log_index: index as i64,
+            verification: None,
+        };
+        let hashes = merkle::inclusion_proof(&state.leaves, index as u64)?;
+        entry.verification = Some(EntryVerification {
+            signed_entry_timestamp: Some(sign_entry_timestamp(&self.signing_key, &entry)),
+            inclusion_proof: Some(RekorInclusionProof {
+                checkpoint: Some(self.sign_checkpoint(&state.leaves)),
+                hashes: hashes.iter().map(hex::encode).collect(),
+                log_index: index as i64,
+                root_hash: hex::encode(merkle::root(&state.leaves)),
+                tree_size: state.leaves.len() as i64,
+            }),
+        });
+        Ok(RecordedEntry { uuid: stored.uuid.clone(), entry })
+    }
+
+    fn lookup(&self, uuid: &str) -> Result<RecordedEntry, RekorError> {
+        let state = self.state.lock().unwrap();
+        let leaf_hash = &uuid[uuid.len().saturating_sub(64)..];
+        let index = *state.by_uuid.get(leaf_hash).ok_or_else(|| RekorError::NotFound(uuid.to_string()))?;
+        self.recorded(&state, index)
+    }
+}
+
+#[async_trait]
+impl RekorBackend for EmbeddedRekor {
+    async fn create_entry(&self, entry: &ProposedEntry) -> Result<RecordedEntry, RekorError> {
+        self.append(entry)
+    }
+
+    async fn get_entry(&self, uuid: &str) -> Result<RecordedEntry, RekorError> {
+        self.lookup(uuid)
+    }
+}
+
+/// Verifies the entry's signatures and returns the form that is stored:
+/// lowercase digests, and for `intoto` the envelope and payload hashes in
+/// place of the payload.
+fn canonicalize(entry: &ProposedEntry) -> Result<ProposedEntry, RekorError> {
+    match entry {
+        ProposedEntry::HashedRekord { api_version, spec } => {
+            let mut spec = spec.clone();
+            spec.data.hash.value = spec.data.hash.value.to_lowercase();
+            // Ed25519 keys sign the digest with Ed25519ph, which Rekor pairs with sha512
+            if spec.data.hash.algorithm != "sha512" {
+                return Err(RekorError::InvalidEntry(format!("unsupported hash algorithm {}", spec.data.hash.algorithm)));
+            }
+            let digest = hex::decode(&spec.data.hash.value)
+                .map_err(|_| RekorError::InvalidEntry("hash value must be a hex sha512 digest".to_string()))?;
+            let prehash = Sha512Prehash::from_digest(&digest)?;
+            let key = decode_entry_public_key(&spec.signature.public_key.content)?;
+            let signature = BASE64.decode(&spec.signature.content)
+                .ok()
+                .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                .ok_or_else(|| RekorError::Signature("malformed signature".to_string()))?;
+            key.verify_prehashed(prehash, None, &signature).map_err(|e| RekorError::Signature(e.to_string()))?;
+            Ok(ProposedEntry::HashedRekord { api_version: api_version.clone(), spec })
+        }
+        ProposedEntry::Intoto { api_version, spec } => {
+            let mut spec = spec.clone();
+            let payload = spec.content.envelope.payload.take()
+                .ok_or_else(|| RekorError::InvalidEntry("envelope has no payload".to_string()))?;
+            if spec.content.envelope.signatures.is_empty() {
+                return Err(RekorError::InvalidEntry("envelope has no signatures".to_string()));
+            }
+            for signature in &spec.content.envelope.signatures {
+                let envelope = Envelope {
+                    payload_type: spec.content.envelope.payload_type.clone(),
+                    payload: payload.clone(),
+                    signatures: vec![EnvelopeSignature { keyid: String::new(), sig: signature.sig.clone() }],
+                };
+                verify_envelope(&envelope, &decode_entry_public_key(&signature.public_key)?)?;
+            }
+
+            let payload_bytes = BASE64.decode(&payload).map_err(|e| RekorError::InvalidEntry(e.to_string()))?;
+            let envelope_json = serde_json::to_vec(&json!({
+                "payloadType": spec.content.envelope.payload_type,
+                "payload": payload,
+                "signatures": spec.content.envelope.signatures,
+            }))
+            .map_err(|e| RekorError::InvalidEntry(e.to_string()))?;
+            spec.content.hash = Some(HashValue { algorithm: "sha256".to_string(), value: sha256_hex(&envelope_json) });
+            spec.content.payload_hash = Some(HashValue { algorithm: "sha256".to_string(), value: sha256_hex(&payload_bytes) });
+            Ok(ProposedEntry::Intoto { api_version: api_version.clone(), spec })
+        }
+    }
+}
+
+fn error_response(error: RekorError) -> Response {
+    let status = match &error {
+        RekorError::InvalidEntry(_) | RekorError::Signature(_) | RekorError::Dsse(_) => StatusCode::BAD_REQUEST,
+        RekorError::Conflict(_) => StatusCode::CONFLICT,
+        RekorError::NotFound(_) => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "code": status.as_u16(), "message": error.to_string() }))).into_response()
+}
+
+fn entries_response(status: StatusCode, recorded: RecordedEntry) -> Response {
+    let entries: BTreeMap<String, RekorLogEntry> = [(recorded.uuid, recorded.entry)].into_iter().collect();
+    (status, Json(entries)).into_response()
+}
+
+async fn create_entry(State(log): State<Arc<EmbeddedRekor>>, Json(entry): Json<ProposedEntry>) -> Response {
+    match log.append(&entry) {
+        Ok(recorded) => entries_response(StatusCode::CREATED, recorded),
+        Err(e) => error_response(e),
+    }
+}
+
+async fn get_entry(State(log): State<Arc<EmbeddedRekor>>, Path(uuid): Path<String>) -> Response {
+    match log.lookup(&uuid) {
+        Ok(recorded) => entries_response(StatusCode::OK, recorded),
+        Err(e) => error_response(e),
+    }
+}
+
+async fn log_info(State(log): State<Arc<EmbeddedRekor>>) -> Response {
+    let state = log.state.lock().unwrap();
+    Json(json!({
+        "rootHash": hex::encode(merkle::root(&state.leaves)),
+        "treeSize": state.leaves.len(),
+        "signedTreeHead": log.sign_checkpoint(&state.leaves),
+        "treeID": "0",
+    }))
+    .into_response()
+}
+
+async fn log_public_key(State(log): State<Arc<EmbeddedRekor>>) -> String {
+    public_key_pem(&log.verifying_key())
+}
@@ -0,0 +1,468 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL}, Engine};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use p256::ecdsa::{signature::{Signer, Verifier}, DerSignature, SigningKey, VerifyingKey};
+use p256::pkcs8::{DecodePublicKey, EncodePublicKey, LineEnding};
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, CustomExtension, DistinguishedName, DnType,
+    ExtendedKeyUsagePurpose, IsCa, KeyPair, KeyUsagePurpose, SanType, SubjectPublicKeyInfo, PKCS_ECDSA_P256_SHA256,
+};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::extensions::GeneralName;
+use x509_parser::pem::Pem;
+use x509_parser::time::ASN1Time;
+
+/// Fulcio's legacy OIDC issuer extension; its value is the raw issuer URL.
+pub const FULCIO_ISSUER_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 57264, 1, 1];
+pub const EMBEDDED_FULCIO_ISSUER: &str = "traceguard";
+/// Sigstore's signing certificates are valid for ten minutes; signing has to
+/// happen inside that window.
+const CERTIFICATE_VALIDITY: time::Duration = time::Duration::minutes(10);
+
+#[derive(Error, Debug)]
+pub enum FulcioError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Fulcio returned {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("Invalid identity token: {0}")]
+    InvalidToken(String),
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("Proof of possession does not match the public key")]
+    ProofOfPossession,
+    #[error("Certificate error: {0}")]
+    Certificate(String),
+    #[error("Certificate chain does not lead to a trusted root")]
+    UntrustedChain,
+}
+
+/// Which Fulcio-compatible CA keyless signing requests certificates from.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum FulcioConfig {
+    /// Keyless signing is off; only tenant keys are used.
+    #[default]
+    Disabled,
+    /// A Fulcio instance at `url`. `trust_bundle` is a PEM file with the CA
+    /// chain; without it the chain is fetched from the CA at startup.
+    SelfHosted { url: String, trust_bundle: Option<String> },
+    /// An in-process CA that accepts TraceGuard's own access tokens. Its
+    /// P-256 key lives in `key_file`, created on first start, so keyless
+    /// signatures stay verifiable across restarts.
+    Embedded { key_file: String },
+}
+
+/// The body of Fulcio's `POST /api/v2/signingCert`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningCertificateRequest {
+    pub credentials: Credentials,
+    pub public_key_request: PublicKeyRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Credentials {
+    pub oidc_identity_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyRequest {
+    pub public_key: FulcioPublicKey,
+    /// Base64 signature over the token's `sub` claim, made with the key.
+    pub proof_of_possession: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FulcioPublicKey {
+    pub algorithm: String,
+    /// PEM SubjectPublicKeyInfo.
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningCertificateResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_certificate_embedded_sct: Option<SignedCertificate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_certificate_detached_sct: Option<SignedCertificate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignedCertificate {
+    pub chain: CertificateChain,
+}
+
+/// PEM certificates, leaf first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificateChain {
+    pub certificates: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrustBundle {
+    pub chains: Vec<CertificateChain>,
+}
+
+impl SigningCertificateRequest {
+    /// Builds a request for `signing_key`, proving possession by signing the
+    /// token's subject.
+    pub fn new(identity_token: &str, signing_key: &SigningKey) -> Result<Self, FulcioError> {
+        let subject = token_subject(identity_token)?;
+        let proof: DerSignature = signing_key.sign(subject.as_bytes());
+        let content = signing_key.verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| FulcioError::InvalidPublicKey(e.to_string()))?;
+        Ok(Self {
+            credentials: Credentials { oidc_identity_token: identity_token.to_string() },
+            public_key_request: PublicKeyRequest {
+                public_key: FulcioPublicKey { algorithm: "ECDSA".to_string(), content },
+                proof_of_possession: BASE64.encode(proof.as_bytes()),
+            },
+        })
+    }
+}
+
+impl SigningCertificateResponse {
+    pub fn chain(self) -> Option<CertificateChain> {
+        self.signed_certificate_embedded_sct
+            .or(self.signed_certificate_detached_sct)
+            .map(|signed| signed.chain)
+    }
+}
+
+/// Reads the `sub` claim without checking the token; the CA does that.
+pub fn token_subject(identity_token: &str) -> Result<String, FulcioError> {
+    #[derive(Deserialize)]
+    struct Subject {
+        sub: String,
+    }
+
+    let claims = identity_token.split('.').nth(1)
+        .ok_or_else(|| FulcioError::InvalidToken("not a JWT".to_string()))?;
+    let claims = BASE64_URL.decode(claims.trim_end_matches('='))
+        .map_err(|e| FulcioError::InvalidToken(e.to_string()))?;
+    let claims: Subject = serde_json::from_slice(&claims).map_err(|e| FulcioError::InvalidToken(e.to_string()))?;
+    Ok(claims.sub)
+}
+
+#[async_trait]
+pub trait CertificateAuthority: Send + Sync {
+    async fn signing_certificate(&self, request: &SigningCertificateRequest) -> Result<CertificateChain, FulcioError>;
+
+    async fn trust_bundle(&self) -> Result<TrustBundle, FulcioError>;
+}
+
+/// Talks to a Fulcio instance over its v2 REST API.
+pub struct HttpFulcioClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HttpFulcioClient {
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.trim_end_matches('/').to_string(), client: reqwest::Client::new() }
+    }
+
+    async fn error_from(response: reqwest::Response) -> FulcioError {
+        let status = response.status().as_u16();
+        let message = response.json::<serde_json::Value>().await
+            .ok()
+            .and_then(|body| body.get("message").and_then(|message| message.as_str()).map(str::to_string))
+            .unwrap_or_default();
+        FulcioError::Api { status, message }
+    }
+}
+
+#[async_trait]
+impl CertificateAuthority for HttpFulcioClient {
+    async fn signing_certificate(&self, request: &SigningCertificateRequest) -> Result<CertificateChain, FulcioError> {
+        let response = self.client
+            .post(format!("{}/api/v2/signingCert", self.base_url))
+            .json(request)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        response.json::<SigningCertificateResponse>().await?
+            .chain()
+            .ok_or_else(|| FulcioError::Certificate("response has no certificate chain".to_string()))
+    }
+
+    async fn trust_bundle(&self) -> Result<TrustBundle, FulcioError> {
+        let response = self.client.get(format!("{}/api/v2/trustBundle", self.base_url)).send().await?;
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// A Fulcio-compatible CA that issues short-lived code signing certificates
+/// for holders of TraceGuard access tokens (HS256 JWTs signed with
+/// `token_secret`). The subject goes in the SAN as an email address when it
+/// looks like one and as a URI under the issuer otherwise.
+pub struct LocalFulcio {
+    issuer: String,
+    ca_key: KeyPair,
+    ca_cert: Certificate,
+    token_key: DecodingKey,
+}
+
+impl LocalFulcio {
+    pub fn new(ca_key: KeyPair, issuer: &str, token_secret: &[u8]) -> Result<Self, FulcioError> {
+        let mut params = CertificateParams::default();
+        params.distinguished_name = DistinguishedName::new();
+        params.distinguished_name.push(DnType::OrganizationName, "TraceGuard");
+        params.distinguished_name.push(DnType::CommonName, format!("{} keyless signing CA", issuer));
+        params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+        params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+        let ca_cert = params.self_signed(&ca_key).map_err(|e| FulcioError::Certificate(e.to_string()))?;
+        Ok(Self {
+            issuer: issuer.to_string(),
+            ca_key,
+            ca_cert,
+            token_key: DecodingKey::from_secret(token_secret),
+        })
+    }
+
+    /// A CA with a fresh P-256 key, which lives as long as the process.
+    pub fn generate(issuer: &str, token_secret: &[u8]) -> Result<Self, FulcioError> {
+        let ca_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).map_err(|e| FulcioError::Certificate(e.to_string()))?;
+        Self::new(ca_key, issuer, token_secret)
+    }
+
+    /// Loads the CA key from a PKCS#8 PEM file, creating the file first if
+    /// it does not exist.
+    pub fn load_or_create(key_file: &Path, issuer: &str, token_secret: &[u8]) -> Result<Self, FulcioError> {
+        let ca_key = match std::fs::read_to_string(key_file) {
+            Ok(pem) => KeyPair::from_pem(&pem).map_err(|e| FulcioError::Certificate(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let ca_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).map_err(|e| FulcioError::Certificate(e.to_string()))?;
+                std::fs::write(key_file, ca_key.serialize_pem())
+                    .map_err(|e| FulcioError::Certificate(format!("{}: {}", key_file.display(), e)))?;
+                ca_key
+            }
+            Err(e) => return Err(FulcioError::Certificate(format!("{}: {}", key_file.display(), e))),
+        };
+        Self::new(ca_key, issuer, token_secret)
+    }
+
+    pub fn root_certificate(&self) -> String {
+        self.ca_cert.pem()
+    }
+
+    pub fn issue(&self, request: &SigningCertificateRequest) -> Result<CertificateChain, FulcioError> {
+        #[derive(Deserialize)]
+        struct Claims {
+            sub: String,
+        }
+
+        let claims = jsonwebtoken::decode::<Claims>(
+            &request.credentials.oidc_identity_token,
+            &self.token_key,
+            &Validation::new(Algorithm::HS256),
+        )
+        .map_err(|e| FulcioError::InvalidToken(e.to_string()))?
+        .claims;
+
+        let public_key_pem = &request.public_key_request.public_key.content;
+        let verifying_key = VerifyingKey::from_public_key_pem(public_key_pem)
+            .map_err(|e| FulcioError::InvalidPublicKey(e.to_string()))?;
+        let proof = BASE64.decode(&request.public_key_request.proof_of_possession)
+            .ok()
+            .and_then(|bytes| DerSignature::from_bytes(&bytes).ok())
+            .ok_or(FulcioError::ProofOfPossession)?;
+        verifying_key.verify(claims.sub.as_bytes(), &proof).map_err(|_| FulcioError::ProofOfPossession)?;
+
+        let san = if claims.sub.contains('@') {
+            SanType::Rfc822Name(claims.sub.clone().try_into().map_err(|_| FulcioError::InvalidToken("invalid email subject".to_string()))?)
+        } else {
+            SanType::URI(subject_uri(&self.issuer, &claims.sub).try_into().map_err(|_| FulcioError::InvalidToken("invalid subject".to_string()))?)
+        };
+        let now = time::OffsetDateTime::now_utc();
+        let mut params = CertificateParams::default();
+        params.distinguished_name = DistinguishedName::new();
+        params.subject_alt_names = vec![san];
+        params.not_before = now;
+        params.not_after = now + CERTIFICATE_VALIDITY;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::CodeSigning];
+        params.custom_extensions = vec![CustomExtension::from_oid_content(FULCIO_ISSUER_OID, self.issuer.as_bytes().to_vec())];
+
+        let subject_key = SubjectPublicKeyInfo::from_pem(public_key_pem).map_err(|e| FulcioError::InvalidPublicKey(e.to_string()))?;
+        let leaf = params.signed_by(&subject_key, &self.ca_cert, &self.ca_key)
+            .map_err(|e| FulcioError::Certificate(e.to_string()))?;
+        Ok(CertificateChain { certificates: vec![leaf.pem(), self.ca_cert.pem()] })
+    }
+
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/api/v2/signingCert", post(signing_cert))
+            .route("/api/v2/trustBundle", get(trust_bundle))
+            .with_state(self)
+    }
+
+    /// Serves the Fulcio API on an ephemeral loopback port and returns its address.
+    pub async fn spawn(self: Arc<Self>) -> std::io::Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server = axum::Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(self.router().into_make_service());
+        tokio::spawn(server);
+        Ok(addr)
+    }
+}
+
+#[async_trait]
+impl CertificateAuthority for LocalFulcio {
+    async fn signing_certificate(&self, request: &SigningCertificateRequest) -> Result<CertificateChain, FulcioError> {
+        self.issue(request)
+    }
+
+    async fn trust_bundle(&self) -> Result<TrustBundle, FulcioError> {
+        Ok(TrustBundle { chains: vec![CertificateChain { certificates: vec![self.root_certificate()] }] })
+    }
+}
+
+fn subject_uri(issuer: &str, subject: &str) -> String {
+    if subject.contains("://") {
+        subject.to_string()
+    } else {
+        format!("{}#{}", issuer, subject)
+    }
+}
+
+async fn signing_cert(State(ca): State<Arc<LocalFulcio>>, Json(request): Json<SigningCertificateRequest>) -> Response {
+    match ca.issue(&request) {
+        Ok(chain) => (
+            StatusCode::CREATED,
+            Json(SigningCertificateResponse {
+                signed_certificate_embedded_sct: Some(SignedCertificate { chain }),
+                signed_certificate_detached_sct: None,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            let status = match e {
+                FulcioError::InvalidToken(_) => StatusCode::UNAUTHORIZED,
+                FulcioError::InvalidPublicKey(_) | FulcioError::ProofOfPossession => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({ "code": status.as_u16(), "message": e.to_string() }))).into_response()
+        }
+    }
+}
+
+async fn trust_bundle(State(ca): State<Arc<LocalFulcio>>) -> Json<TrustBundle> {
+    Json(TrustBundle { chains: vec![CertificateChain { certificates: vec![ca.root_certificate()] }] })
+}
+
+/// Who a verified signing certificate was issued to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificateIdentity {
+    /// The SAN email address or URI.
+    pub subject: String,
+    pub issuer: Option<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+/// Checks that `chain` is a code signing certificate valid at `at`, issued
+/// through intermediates to one of the PEM `roots`, and returns its identity
+/// and public key.
+pub fn verify_certificate_chain(
+    chain: &CertificateChain,
+    roots: &[String],
+    at: DateTime<Utc>,
+) -> Result<(CertificateIdentity, VerifyingKey), FulcioError> {
+    let pems = parse_pems(&chain.certificates)?;
+    let root_pems = parse_pems(roots)?;
+    let certs = parse_certificates(&pems)?;
+    let root_certs = parse_certificates(&root_pems)?;
+    let leaf = certs.first().ok_or_else(|| FulcioError::Certificate("empty certificate chain".to_string()))?;
+
+    let at = ASN1Time::from_timestamp(at.timestamp()).map_err(|e| FulcioError::Certificate(e.to_string()))?;
+    for (index, cert) in certs.iter().enumerate() {
+        if !cert.validity().is_valid_at(at) {
+            return Err(FulcioError::Certificate(format!("certificate {} is not valid at signing time", index)));
+        }
+        if index > 0 && !cert.is_ca() {
+            return Err(FulcioError::Certificate(format!("certificate {} is not a CA", index)));
+        }
+        if let Some(issuer) = certs.get(index + 1) {
+            cert.verify_signature(Some(issuer.public_key()))
+                .map_err(|_| FulcioError::Certificate(format!("certificate {} is not signed by its issuer", index)))?;
+        }
+    }
+    let top = certs.last().unwrap_or(leaf);
+    let top_der = &pems[pems.len() - 1].contents;
+    let anchored = root_pems.iter().zip(&root_certs).any(|(root_pem, root)| {
+        root_pem.contents == *top_der || top.verify_signature(Some(root.public_key())).is_ok()
+    });
+    if !anchored {
+        return Err(FulcioError::UntrustedChain);
+    }
+
+    let code_signing = leaf.extended_key_usage()
+        .map_err(|e| FulcioError::Certificate(e.to_string()))?
+        .is_some_and(|usage| usage.value.code_signing);
+    if !code_signing {
+        return Err(FulcioError::Certificate("leaf certificate is not for code signing".to_string()));
+    }
+    let subject = leaf.subject_alternative_name()
+        .map_err(|e| FulcioError::Certificate(e.to_string()))?
+        .and_then(|san| san.value.general_names.iter().find_map(|name| match name {
+            GeneralName::RFC822Name(email) => Some(email.to_string()),
+            GeneralName::URI(uri) => Some(uri.to_string()),
+            _ => None,
+        }))
+        .ok_or_else(|| FulcioError::Certificate("leaf certificate has no email or URI SAN".to_string()))?;
+    let issuer_oid = FULCIO_ISSUER_OID.iter().map(u64::to_string).collect::<Vec<_>>().join(".");
+    let issuer = leaf.extensions().iter()
+        .find(|extension| extension.oid.to_id_string() == issuer_oid)
+        .and_then(|extension| String::from_utf8(extension.value.to_vec()).ok());
+    let public_key = VerifyingKey::from_public_key_der(leaf.public_key().raw)
+        .map_err(|e| FulcioError::InvalidPublicKey(e.to_string()))?;
+
+    let validity = leaf.validity();
+    let identity = CertificateIdentity {
+        subject,
+        issuer,
+        not_before: DateTime::from_timestamp(validity.not_before.timestamp(), 0).unwrap_or_default(),
+        not_after: DateTime::from_timestamp(validity.not_after.timestamp(), 0).unwrap_or_default(),
+    };
+    Ok((identity, public_key))
+}
+
+fn parse_pems(certificates: &[String]) -> Result<Vec<Pem>, FulcioError> {
+    certificates.iter()
+        .flat_map(|pem| Pem::iter_from_buffer(pem.as_bytes()))
+        .map(|pem| pem.map_err(|e| FulcioError::Certificate(e.to_string())))
+        .collect()
+}
+
+fn parse_certificates(pems: &[Pem]) -> Result<Vec<X509Certificate<'_>>, FulcioError> {
+    pems.iter()
+        .map(|pem| pem.parse_x509().map_err(|e| FulcioError::Certificate(e.to_string())))
+        .collect()
+}
@@ -0,0 +1,285 @@
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+use thiserror::Error;
+
+/// RFC 6962 Merkle tree hashing over SHA-256. Leaves are prefixed with 0x00
+/// and interior nodes with 0x01, so a leaf can never be passed off as a node.
+pub type Hash = [u8; 32];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MerkleError {
+    #[error("Leaf index {0} is outside a tree of size {1}")]
+    IndexOutOfRange(u64, u64),
+    #[error("Tree size {0} is larger than tree size {1}")]
+    InvalidRange(u64, u64),
+    #[error("Proof has the wrong number of hashes")]
+    ProofLength,
+    #[error("Proof does not match the root hash")]
+    RootMismatch,
+    #[error("Invalid hash encoding: {0}")]
+    Encoding(String),
+    #[error("Hash of subtree {1} at level {0} is missing")]
+    MissingNode(u32, u64),
+}
+
+/// A complete subtree: the `2^level` leaves starting at leaf `index << level`.
+/// Its hash never changes once the log holds all of its leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId {
+    pub level: u32,
+    pub index: u64,
+}
+
+impl NodeId {
+    pub fn leaf(index: u64) -> Self {
+        Self { level: 0, index }
+    }
+
+    pub fn parent(&self) -> Self {
+        Self { level: self.level + 1, index: self.index / 2 }
+    }
+
+    pub fn is_right_child(&self) -> bool {
+        self.index % 2 == 1
+    }
+
+    /// The left sibling of a right child.
+    pub fn left_sibling(&self) -> Self {
+        Self { level: self.level, index: self.index - 1 }
+    }
+}
+
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+pub fn empty_root() -> Hash {
+    Sha256::digest([]).into()
+}
+
+pub fn decode_hash(encoded: &str) -> Result<Hash, MerkleError> {
+    hex::decode(encoded)
+        .map_err(|e| MerkleError::Encoding(e.to_string()))?
+        .try_into()
+        .map_err(|_| MerkleError::Encoding("hash must be 32 bytes".to_string()))
+}
+
+/// Largest power of two strictly smaller than `n`, for `n > 1`.
+fn split_point(n: u64) -> u64 {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+fn slice(leaves: &[Hash], range: Range<u64>) -> &[Hash] {
+    &leaves[range.start as usize..range.end as usize]
+}
+
+/// MTH(D[n]) computed from the leaf hashes.
+pub fn root(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => empty_root(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n as u64) as usize;
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+/// The complete subtrees that make up the subtree over `range`, left to
+/// right. Every range a root or proof below refers to starts at a multiple
+/// of its largest power of two, so it splits this way.
+pub fn range_nodes(range: Range<u64>) -> Vec<NodeId> {
+    let mut nodes = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let level = 63 - (range.end - start).leading_zeros();
+        nodes.push(NodeId { level, index: start >> level });
+        start += 1 << level;
+    }
+    nodes
+}
+
+/// The hash of the subtree over `range`, from the hashes of its
+/// `range_nodes`.
+pub fn range_root(range: Range<u64>, node: impl Fn(NodeId) -> Option<Hash>) -> Result<Hash, MerkleError> {
+    let mut hashes = range_nodes(range).into_iter()
+        .map(|id| node(id).ok_or(MerkleError::MissingNode(id.level, id.index)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(mut root) = hashes.pop() else {
+        return Ok(empty_root());
+    };
+    while let Some(left) = hashes.pop() {
+        root = node_hash(&left, &root);
+    }
+    Ok(root)
+}
+
+/// The subtrees whose roots make up PATH(index, D[tree_size]), in proof order.
+pub fn inclusion_ranges(index: u64, tree_size: u64) -> Result<Vec<Range<u64>>, MerkleError> {
+    if index >= tree_size {
+        return Err(MerkleError::IndexOutOfRange(index, tree_size));
+    }
+    let mut ranges = Vec::new();
+    path(index, 0..tree_size, &mut ranges);
+    Ok(ranges)
+}
+
+fn path(m: u64, range: Range<u64>, ranges: &mut Vec<Range<u64>>) {
+    if range.end - range.start <= 1 {
+        return;
+    }
+    let split = range.start + split_point(range.end - range.start);
+    if range.start + m < split {
+        path(m, range.start..split, ranges);
+        ranges.push(split..range.end);
+    } else {
+        path(range.start + m - split, split..range.end, ranges);
+        ranges.push(range.start..split);
+    }
+}
+
+/// PATH(m, D[n]): the audit path for leaf `index` in the tree made of `leaves`.
+pub fn inclusion_proof(leaves: &[Hash], index: u64) -> Result<Vec<Hash>, MerkleError> {
+    Ok(inclusion_ranges(index, leaves.len() as u64)?.into_iter()
+        .map(|range| root(slice(leaves, range)))
+        .collect())
+}
+
+/// The subtrees whose roots make up PROOF(first, D[second]), in proof order.
+pub fn consistency_ranges(first: u64, second: u64) -> Result<Vec<Range<u64>>, MerkleError> {
+    if first > second {
+        return Err(MerkleError::InvalidRange(first, second));
+    }
+    let mut ranges = Vec::new();
+    if first > 0 && first < second {
+        subproof(first, 0..second, true, &mut ranges);
+    }
+    Ok(ranges)
+}
+
+fn subproof(m: u64, range: Range<u64>, complete: bool, ranges: &mut Vec<Range<u64>>) {
+    let n = range.end - range.start;
+    if m == n {
+        if !complete {
+            ranges.push(range);
+        }
+        return;
+    }
+    let k = split_point(n);
+    let split = range.start + k;
+    if m <= k {
+        subproof(m, range.start..split, complete, ranges);
+        ranges.push(split..range.end);
+    } else {
+        subproof(m - k, split..range.end, false, ranges);
+        ranges.push(range.start..split);
+    }
+}
+
+/// PROOF(m, D[n]): proves the tree of the first `first` leaves is a prefix of
+/// the tree made of all `leaves`.
+pub fn consistency_proof(leaves: &[Hash], first: u64) -> Result<Vec<Hash>, MerkleError> {
+    Ok(consistency_ranges(first, leaves.len() as u64)?.into_iter()
+        .map(|range| root(slice(leaves, range)))
+        .collect())
+}
+
+/// Verifies an audit path as described in RFC 9162 section 2.1.3.2.
+pub fn verify_inclusion(leaf: &Hash, index: u64, tree_size: u64, proof: &[Hash], root_hash: &Hash) -> Result<(), MerkleError> {
+    if index >= tree_size {
+        return Err(MerkleError::IndexOutOfRange(index, tree_size));
+    }
+    let (mut f_n, mut s_n) = (index, tree_size - 1);
+    let mut r = *leaf;
+    for p in proof {
+        if s_n == 0 {
+            return Err(MerkleError::ProofLength);
+        }
+        if f_n & 1 == 1 || f_n == s_n {
+            r = node_hash(p, &r);
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+    if s_n != 0 {
+        return Err(MerkleError::ProofLength);
+    }
+    if &r != root_hash {
+        return Err(MerkleError::RootMismatch);
+    }
+    Ok(())
+}
+
+/// Verifies a consistency proof as described in RFC 9162 section 2.1.4.2.
+pub fn verify_consistency(first: u64, second: u64, first_root: &Hash, second_root: &Hash, proof: &[Hash]) -> Result<(), MerkleError> {
+    if first > second {
+        return Err(MerkleError::InvalidRange(first, second));
+    }
+    if first == second || first == 0 {
+        if !proof.is_empty() {
+            return Err(MerkleError::ProofLength);
+        }
+        if first == second && first_root != second_root {
+            return Err(MerkleError::RootMismatch);
+        }
+        return Ok(());
+    }
+
+    let mut path = proof.to_vec();
+    if first.is_power_of_two() {
+        path.insert(0, *first_root);
+    }
+    let (first_hash, rest) = path.split_first().ok_or(MerkleError::ProofLength)?;
+
+    let (mut f_n, mut s_n) = (first - 1, second - 1);
+    while f_n & 1 == 1 {
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+    let (mut f_r, mut s_r) = (*first_hash, *first_hash);
+    for c in rest {
+        if s_n == 0 {
+            return Err(MerkleError::ProofLength);
+        }
+        if f_n & 1 == 1 || f_n == s_n {
+            f_r = node_hash(c, &f_r);
+            s_r = node_hash(c, &s_r);
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            s_r = node_hash(&s_r, c);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+    if s_n != 0 {
+        return Err(MerkleError::ProofLength);
+    }
+    if &f_r != first_root || &s_r != second_root {
+        return Err(MerkleError::RootMismatch);
+    }
+    Ok(())
+}
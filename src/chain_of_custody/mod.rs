@@ -1,35 +1,108 @@
-use sigstore::rekor::RekorClient;
-use sigstore::tuf::Client as TufClient;
-use sigstore::cosign::SignerVerifier;
+pub mod audit_log;
+pub mod custody_events;
+pub mod embedded_rekor;
+pub mod fulcio;
+pub mod merkle;
+pub mod promotion;
+pub mod rekor;
+pub mod signing;
+pub mod timestamp;
+pub mod transparency_log;
+pub mod witness;
+
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use sha2::{Digest, Sha512};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::error;
 use crate::error::AppError;
+use crate::provenance::dsse::Envelope;
+use embedded_rekor::EmbeddedRekor;
+use rekor::{sign_hashed_rekord, HttpRekorClient, LogKey, ProposedEntry, RecordedEntry, RekorBackend, RekorConfig, RekorError};
 
 pub struct ChainOfCustody {
-    rekor_client: RekorClient,
-    tuf_client: TufClient,
-    signer_verifier: SignerVerifier,
+    rekor: Arc<dyn RekorBackend>,
+    /// Entries are only accepted when signed by this key.
+    log_key: LogKey,
 }
 
 impl ChainOfCustody {
-    pub async fn new() -> Result<Self, AppError> {
-        let rekor_client = RekorClient::new("https://rekor.sigstore.dev")?;
-        let tuf_client = TufClient::new("https://tuf-repo-cdn.sigstore.dev")?;
-        let signer_verifier = SignerVerifier::new()?;
-
-        Ok(Self {
-            rekor_client,
-            tuf_client,
-            signer_verifier,
+    pub fn new(rekor: Arc<dyn RekorBackend>, log_key: LogKey) -> Self {
+        Self { rekor, log_key }
+    }
+
+    pub fn from_config(config: &RekorConfig) -> Result<Self, RekorError> {
+        let read_key = |public_key_file: &str| {
+            let pem = std::fs::read_to_string(public_key_file)
+                .map_err(|e| RekorError::Storage(format!("{}: {}", public_key_file, e)))?;
+            LogKey::from_pem(&pem)
+        };
+        Ok(match config {
+            RekorConfig::Public { public_key_file } => Self::new(Arc::new(HttpRekorClient::public()), read_key(public_key_file)?),
+            RekorConfig::SelfHosted { url, public_key_file } => Self::new(Arc::new(HttpRekorClient::new(url)), read_key(public_key_file)?),
+            RekorConfig::Embedded { data_dir } => {
+                let rekor = EmbeddedRekor::open(Path::new(data_dir))?;
+                let log_key = rekor.log_key();
+                Self::new(Arc::new(rekor), log_key)
+            }
         })
     }
 
+    /// Signs `artifact` with the Ed25519 seed `key` (Ed25519ph over its
+    /// sha512 digest), records a `hashedrekord` entry and returns its UUID
+    /// once the entry checks out against the log key.
     pub async fn sign_and_record(&self, artifact: &[u8], key: &[u8]) -> Result<String, AppError> {
-        let signature = self.signer_verifier.sign(artifact, key)?;
-        let entry = self.rekor_client.create_entry(artifact, &signature).await?;
-        Ok(entry.uuid)
+        let seed: [u8; 32] = key.try_into()
+            .map_err(|_| AppError::ValidationError("Signing key must be a 32-byte Ed25519 seed".to_string()))?;
+        let signing_key = SigningKey::from_bytes(&seed);
+        let (digest, signature) = sign_hashed_rekord(&signing_key, artifact).map_err(|e| {
+            error!("Failed to sign artifact: {}", e);
+            AppError::InternalServerError
+        })?;
+
+        let entry = ProposedEntry::hashed_rekord(&digest, &signature.to_bytes(), &signing_key.verifying_key());
+        let recorded = self.record(&entry).await?;
+        Ok(recorded.uuid)
+    }
+
+    /// Records a signed DSSE envelope as an `intoto` entry.
+    pub async fn record_attestation(&self, envelope: &Envelope, public_key: &VerifyingKey) -> Result<RecordedEntry, AppError> {
+        self.record(&ProposedEntry::intoto(envelope, public_key)).await
+    }
+
+    /// Fetches an entry and checks its inclusion proof, signed entry
+    /// timestamp and checkpoint against the log key.
+    pub async fn get_verified_entry(&self, uuid: &str) -> Result<RecordedEntry, AppError> {
+        let recorded = self.rekor.get_entry(uuid).await.map_err(|e| {
+            error!("Failed to fetch transparency log entry {}: {}", uuid, e);
+            AppError::NotFound(format!("Transparency log entry {}", uuid))
+        })?;
+        recorded.verify(&self.log_key).map_err(|e| {
+            error!("Transparency log entry {} failed verification: {}", uuid, e);
+            AppError::ValidationError(e.to_string())
+        })?;
+        Ok(recorded)
     }
 
     pub async fn verify(&self, artifact: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, AppError> {
-        let is_valid = self.signer_verifier.verify(artifact, signature, public_key)?;
-        Ok(is_valid)
+        let public_key: [u8; 32] = public_key.try_into()
+            .map_err(|_| AppError::ValidationError("Public key must be 32 bytes".to_string()))?;
+        let public_key = VerifyingKey::from_bytes(&public_key).map_err(|e| AppError::ValidationError(e.to_string()))?;
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return Ok(false);
+        };
+        Ok(public_key.verify_prehashed(Sha512::new().chain_update(artifact), None, &signature).is_ok())
     }
-}
\ No newline at end of file
+
+    async fn record(&self, entry: &ProposedEntry) -> Result<RecordedEntry, AppError> {
+        let recorded = self.rekor.create_entry(entry).await.map_err(|e| {
+            error!("Failed to record {} entry in the transparency log: {}", entry.kind(), e);
+            AppError::InternalServerError
+        })?;
+        recorded.verify(&self.log_key).map_err(|e| {
+            error!("Transparency log returned an entry that failed verification: {}", e);
+            AppError::InternalServerError
+        })?;
+        Ok(recorded)
+    }
+}
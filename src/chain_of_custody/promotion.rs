@@ -0,0 +1,715 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, SubsecRound, Utc};
+use std::str::FromStr;
+use ed25519_dalek::VerifyingKey;
+use glob::Pattern;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::{
+    evaluate_custody_policies, normalize_digest, verify_custody_chain, AppendCustodyEventRequest, CustodyEvent,
+    CustodyEventType, CustodyPolicy,
+};
+use crate::models::VerificationReport;
+use crate::provenance::attestations::{Attestation, AttestationKind, VulnScanPredicate};
+use crate::provenance::dsse::{verify_envelope, DsseError, Envelope, EnvelopeSigner};
+use crate::provenance::revocation::{Revocation, RevokedArtifact};
+use crate::provenance::vsa::verified_build_level;
+
+/// Secret under which each tenant's promotion decision key is stored.
+pub const PROMOTION_SIGNING_KEY_ID: &str = "promotion_signing_key";
+pub const PROMOTION_DECISION_PAYLOAD_TYPE: &str = "application/vnd.traceguard.promotion-decision+json";
+
+#[derive(Error, Debug)]
+pub enum PromotionError {
+    #[error("Invalid environment pattern: {0}")]
+    InvalidPattern(String),
+    #[error("Invalid promotion policy: {0}")]
+    InvalidPolicy(String),
+    #[error("Invalid promotion request: {0}")]
+    InvalidRequest(String),
+    #[error("Decision does not match its payload")]
+    PayloadMismatch,
+    #[error("{0}")]
+    EnvironmentChange(String),
+    #[error("Unknown promotion outcome: {0}")]
+    UnknownOutcome(String),
+    #[error("Envelope error: {0}")]
+    Dsse(#[from] DsseError),
+}
+
+/// What a promotion policy requires of the artifact's evidence. Every rule
+/// is optional; a policy must set at least one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PromotionRules {
+    /// At least one provenance record for the artifact must pass verification.
+    #[serde(default)]
+    pub require_provenance: bool,
+    /// Minimum SLSA build level of the best passing verification.
+    #[serde(default)]
+    pub min_slsa_level: Option<u32>,
+    #[serde(default)]
+    pub sbom: Option<SbomRule>,
+    #[serde(default)]
+    pub vulnerabilities: Option<VulnerabilityThresholds>,
+    #[serde(default)]
+    pub licenses: Option<LicenseRule>,
+}
+
+/// Checked against the newest signed SPDX or CycloneDX attestation for the artifact.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SbomRule {
+    #[serde(default = "default_min_components")]
+    pub min_components: usize,
+    /// Share of components, from 0 to 1, that must each carry a version, a
+    /// package URL or CPE, and a license.
+    #[serde(default)]
+    pub min_coverage: f64,
+}
+
+fn default_min_components() -> usize {
+    1
+}
+
+/// Most findings allowed per severity in the newest signed vulnerability scan;
+/// `None` leaves a severity unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VulnerabilityThresholds {
+    pub critical: Option<usize>,
+    pub high: Option<usize>,
+    pub medium: Option<usize>,
+    pub low: Option<usize>,
+}
+
+/// SPDX license IDs, compared case-insensitively. An empty `allowed` list
+/// allows any license that is not denied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LicenseRule {
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    #[serde(default)]
+    pub denied: Vec<String>,
+}
+
+/// Evidence an artifact needs before it may be promoted into a matching
+/// environment, e.g. no critical vulnerabilities before `prod*`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionPolicy {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Glob over the target environment; `None` applies to every promotion.
+    pub environment_pattern: Option<String>,
+    #[serde(flatten)]
+    pub rules: PromotionRules,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromotionPolicyRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub environment_pattern: Option<String>,
+    #[serde(flatten)]
+    pub rules: PromotionRules,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl PromotionPolicy {
+    pub fn from_request(tenant_id: Uuid, request: PromotionPolicyRequest) -> Result<Self, PromotionError> {
+        if let Some(pattern) = &request.environment_pattern {
+            Pattern::new(pattern).map_err(|e| PromotionError::InvalidPattern(e.to_string()))?;
+        }
+        let rules = &request.rules;
+        if *rules == PromotionRules::default() {
+            return Err(PromotionError::InvalidPolicy("policy must set at least one rule".to_string()));
+        }
+        if let Some(sbom) = &rules.sbom {
+            if !(0.0..=1.0).contains(&sbom.min_coverage) {
+                return Err(PromotionError::InvalidPolicy("sbom.min_coverage must be between 0 and 1".to_string()));
+            }
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: request.name,
+            description: request.description,
+            environment_pattern: request.environment_pattern,
+            rules: request.rules,
+            enabled: request.enabled,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn applies_to(&self, environment: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match &self.environment_pattern {
+            None => true,
+            Some(pattern) => Pattern::new(pattern).map(|pattern| pattern.matches(environment)).unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromotionRequest {
+    pub from: String,
+    pub to: String,
+    /// Promotes despite failed checks. Needs the `override` permission on
+    /// `promotions`, and is written to the audit log.
+    pub override_reason: Option<String>,
+}
+
+impl PromotionRequest {
+    pub fn validate(&self) -> Result<(), PromotionError> {
+        if self.from.trim().is_empty() || self.to.trim().is_empty() {
+            return Err(PromotionError::InvalidRequest("source and target environments are required".to_string()));
+        }
+        if self.from == self.to {
+            return Err(PromotionError::InvalidRequest("source and target environments must differ".to_string()));
+        }
+        if self.override_reason.as_deref().is_some_and(|reason| reason.trim().is_empty()) {
+            return Err(PromotionError::InvalidRequest("override reason must not be empty".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Everything stored about the artifact that a promotion is judged on.
+#[derive(Debug, Default)]
+pub struct PromotionEvidence {
+    /// Fresh verification reports of the provenance records naming the artifact.
+    pub reports: Vec<VerificationReport>,
+    /// Attestations about the artifact, newest first. Only signed ones that
+    /// no revocation covers count as evidence.
+    pub attestations: Vec<Attestation>,
+    /// The custody chain, oldest first.
+    pub custody_events: Vec<CustodyEvent>,
+    pub custody_policies: Vec<CustodyPolicy>,
+    /// The tenant's revocations.
+    pub revocations: Vec<Revocation>,
+    /// Revocations that reach the artifact, directly or through its inputs.
+    pub revoked: Vec<RevokedArtifact>,
+}
+
+impl PromotionEvidence {
+    /// Attestations whose signature verified against a trusted key and that
+    /// no revocation covers, newest first.
+    pub fn trusted_attestations(&self) -> impl Iterator<Item = &Attestation> {
+        let now = Utc::now();
+        self.attestations.iter()
+            .filter(|attestation| attestation.verified_key_id.is_some())
+            .filter(move |attestation| !self.revocations.iter().any(|revocation| revocation.covers_attestation(attestation, now)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromotionCheckKind {
+    SourceEnvironment,
+    Revocation,
+    CustodyChain,
+    CustodyEvents,
+    Provenance,
+    Sbom,
+    Vulnerabilities,
+    Licenses,
+}
+
+impl PromotionCheckKind {
+    /// Whether an override may promote despite this check failing. Revoked
+    /// artifacts and broken custody chains never move.
+    pub fn overridable(&self) -> bool {
+        !matches!(self, PromotionCheckKind::Revocation | PromotionCheckKind::CustodyChain)
+    }
+}
+
+/// One line of a decision's rationale.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromotionCheck {
+    pub kind: PromotionCheckKind,
+    /// The promotion or custody policy the check comes from, if any.
+    pub policy_id: Option<Uuid>,
+    pub policy_name: Option<String>,
+    pub passed: bool,
+    pub reason: String,
+}
+
+impl PromotionCheck {
+    fn new(kind: PromotionCheckKind, policy: Option<&PromotionPolicy>, passed: bool, reason: String) -> Self {
+        Self {
+            kind,
+            policy_id: policy.map(|policy| policy.id),
+            policy_name: policy.map(|policy| policy.name.clone()),
+            passed,
+            reason,
+        }
+    }
+}
+
+/// The environment the artifact was last promoted or deployed to.
+pub fn current_environment(events: &[CustodyEvent]) -> Option<&str> {
+    events.iter()
+        .rev()
+        .find(|event| event.event_type.requires_environment())
+        .and_then(|event| event.environment.as_deref())
+}
+
+/// Checks an event appended directly to the custody chain, outside a
+/// promotion. Only a promotion decision may move the artifact, so `promoted`
+/// events are refused and `deployed` events must name the environment the
+/// artifact is already in.
+pub fn check_manual_event(request: &AppendCustodyEventRequest, events: &[CustodyEvent]) -> Result<(), PromotionError> {
+    match request.event_type {
+        CustodyEventType::Promoted => Err(PromotionError::EnvironmentChange(
+            "promoted events are written by the promotion API".to_string(),
+        )),
+        CustodyEventType::Deployed if request.environment.as_deref() != current_environment(events) => {
+            Err(PromotionError::EnvironmentChange(
+                "deployed events must name the environment the artifact was promoted to".to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks the artifact's evidence for a promotion from `from` to `to`
+/// against every policy that applies. The promotion may go ahead only if
+/// every returned check passed.
+pub fn evaluate_promotion(
+    policies: &[PromotionPolicy],
+    evidence: &PromotionEvidence,
+    from: &str,
+    to: &str,
+) -> Vec<PromotionCheck> {
+    let mut checks = Vec::new();
+
+    // An artifact that has never moved is promoted out of its initial environment
+    let current = current_environment(&evidence.custody_events);
+    checks.push(PromotionCheck::new(
+        PromotionCheckKind::SourceEnvironment,
+        None,
+        current.is_none_or(|current| current == from),
+        match current {
+            Some(current) => format!("Artifact is in {}", current),
+            None => format!("Artifact has not been promoted yet, so {} is its initial environment", from),
+        },
+    ));
+
+    let mut revocation_ids: Vec<String> = evidence.revoked.iter().map(|artifact| artifact.revocation_id.to_string()).collect();
+    revocation_ids.sort();
+    revocation_ids.dedup();
+    checks.push(PromotionCheck::new(
+        PromotionCheckKind::Revocation,
+        None,
+        revocation_ids.is_empty(),
+        if revocation_ids.is_empty() {
+            "No revocation reaches the artifact".to_string()
+        } else {
+            format!("Artifact is revoked by {}", revocation_ids.join(", "))
+        },
+    ));
+
+    let chain = verify_custody_chain(&evidence.custody_events);
+    let revoked = evidence.custody_events.iter().any(|event| event.event_type == CustodyEventType::Revoked);
+    checks.push(PromotionCheck::new(
+        PromotionCheckKind::CustodyChain,
+        None,
+        chain.is_ok() && !revoked,
+        match chain {
+            Err(e) => e.to_string(),
+            Ok(()) if revoked => "Custody chain records a revocation".to_string(),
+            Ok(()) => format!("Custody chain of {} events is intact", evidence.custody_events.len()),
+        },
+    ));
+
+    let violations = evaluate_custody_policies(&evidence.custody_policies, &evidence.custody_events, CustodyEventType::Promoted, Some(to));
+    for violation in &violations {
+        let mut required = violation.missing.event_type.as_str().to_string();
+        if let Some(category) = &violation.missing.category {
+            required = format!("{} ({})", required, category);
+        }
+        checks.push(PromotionCheck {
+            kind: PromotionCheckKind::CustodyEvents,
+            policy_id: Some(violation.policy_id),
+            policy_name: Some(violation.policy_name.clone()),
+            passed: false,
+            reason: format!("Requires a {} event", required),
+        });
+    }
+    if violations.is_empty() {
+        let gating = evidence.custody_policies.iter()
+            .filter(|policy| policy.applies_to(CustodyEventType::Promoted, Some(to)))
+            .count();
+        let reason = match gating {
+            0 => format!("No custody policy gates promotion to {}", to),
+            gating => format!("{} custody policies gating {} are satisfied", gating, to),
+        };
+        checks.push(PromotionCheck::new(PromotionCheckKind::CustodyEvents, None, true, reason));
+    }
+
+    let sbom = evidence.trusted_attestations().find_map(SbomQuality::from_attestation);
+    let scan = evidence.trusted_attestations().find(|attestation| attestation.kind == AttestationKind::VulnerabilityScan);
+    for policy in policies.iter().filter(|policy| policy.applies_to(to)) {
+        let rules = &policy.rules;
+        if rules.require_provenance || rules.min_slsa_level.is_some() {
+            checks.push(provenance_check(policy, &evidence.reports));
+        }
+        if let Some(rule) = &rules.sbom {
+            checks.push(sbom_check(policy, rule, sbom.as_ref()));
+        }
+        if let Some(thresholds) = &rules.vulnerabilities {
+            checks.push(vulnerability_check(policy, thresholds, scan));
+        }
+        if let Some(rule) = &rules.licenses {
+            checks.push(license_check(policy, rule, sbom.as_ref()));
+        }
+    }
+    checks
+}
+
+fn provenance_check(policy: &PromotionPolicy, reports: &[VerificationReport]) -> PromotionCheck {
+    let best = reports.iter()
+        .filter(|report| report.passed)
+        .max_by_key(|report| verified_build_level(report));
+    let Some(best) = best else {
+        let reason = if reports.is_empty() {
+            "No provenance names the artifact".to_string()
+        } else {
+            format!("None of {} provenance records passed verification", reports.len())
+        };
+        return PromotionCheck::new(PromotionCheckKind::Provenance, Some(policy), false, reason);
+    };
+    let level = verified_build_level(best);
+    let passed = policy.rules.min_slsa_level.is_none_or(|min| level >= min);
+    let mut reason = format!("Provenance {} verified at SLSA build level {}", best.provenance_id, level);
+    if let Some(min) = policy.rules.min_slsa_level {
+        reason = format!("{} (requires {})", reason, min);
+    }
+    PromotionCheck::new(PromotionCheckKind::Provenance, Some(policy), passed, reason)
+}
+
+fn sbom_check(policy: &PromotionPolicy, rule: &SbomRule, sbom: Option<&SbomQuality>) -> PromotionCheck {
+    let Some(sbom) = sbom else {
+        return PromotionCheck::new(PromotionCheckKind::Sbom, Some(policy), false, "No signed SBOM attestation for the artifact".to_string());
+    };
+    let mut problems = Vec::new();
+    if sbom.component_count < rule.min_components {
+        problems.push(format!("{} components, requires {}", sbom.component_count, rule.min_components));
+    }
+    for (field, count) in [("a version", sbom.versioned), ("a package URL or CPE", sbom.identified), ("a license", sbom.licensed)] {
+        let coverage = sbom.coverage(count);
+        if coverage < rule.min_coverage {
+            problems.push(format!("{:.0}% of components have {}, requires {:.0}%", coverage * 100.0, field, rule.min_coverage * 100.0));
+        }
+    }
+    let reason = if problems.is_empty() {
+        format!("SBOM {} lists {} components", sbom.attestation_id, sbom.component_count)
+    } else {
+        format!("SBOM {}: {}", sbom.attestation_id, problems.join("; "))
+    };
+    PromotionCheck::new(PromotionCheckKind::Sbom, Some(policy), problems.is_empty(), reason)
+}
+
+fn vulnerability_check(policy: &PromotionPolicy, thresholds: &VulnerabilityThresholds, scan: Option<&Attestation>) -> PromotionCheck {
+    let Some(scan) = scan else {
+        return PromotionCheck::new(PromotionCheckKind::Vulnerabilities, Some(policy), false, "No signed vulnerability scan attestation for the artifact".to_string());
+    };
+    let predicate = match VulnScanPredicate::deserialize(&scan.predicate) {
+        Ok(predicate) => predicate,
+        Err(e) => {
+            let reason = format!("Vulnerability scan {} is unreadable: {}", scan.id, e);
+            return PromotionCheck::new(PromotionCheckKind::Vulnerabilities, Some(policy), false, reason);
+        }
+    };
+    let counts = VulnerabilityCounts::from_scan_result(&predicate.scanner.result);
+    let exceeded: Vec<String> = [
+        ("critical", counts.critical, thresholds.critical),
+        ("high", counts.high, thresholds.high),
+        ("medium", counts.medium, thresholds.medium),
+        ("low", counts.low, thresholds.low),
+    ]
+        .into_iter()
+        .filter_map(|(severity, count, max)| max.filter(|max| count > *max).map(|max| format!("{} {} (max {})", count, severity, max)))
+        .collect();
+    let reason = if exceeded.is_empty() {
+        format!(
+            "Scan {} by {} found {} critical, {} high, {} medium, {} low",
+            scan.id, predicate.scanner.uri, counts.critical, counts.high, counts.medium, counts.low
+        )
+    } else {
+        format!("Scan {} by {} found {}", scan.id, predicate.scanner.uri, exceeded.join(", "))
+    };
+    PromotionCheck::new(PromotionCheckKind::Vulnerabilities, Some(policy), exceeded.is_empty(), reason)
+}
+
+fn license_check(policy: &PromotionPolicy, rule: &LicenseRule, sbom: Option<&SbomQuality>) -> PromotionCheck {
+    let Some(sbom) = sbom else {
+        return PromotionCheck::new(PromotionCheckKind::Licenses, Some(policy), false, "No signed SBOM attestation for the artifact".to_string());
+    };
+    let listed = |list: &[String], license: &str| list.iter().any(|entry| entry.eq_ignore_ascii_case(license));
+    let rejected: Vec<&String> = sbom.licenses.iter()
+        .filter(|license| listed(&rule.denied, license) || (!rule.allowed.is_empty() && !listed(&rule.allowed, license)))
+        .collect();
+    let reason = if rejected.is_empty() {
+        format!("SBOM {} uses {} licenses, all allowed", sbom.attestation_id, sbom.licenses.len())
+    } else {
+        let rejected: Vec<&str> = rejected.iter().map(|license| license.as_str()).collect();
+        format!("SBOM {} uses disallowed licenses: {}", sbom.attestation_id, rejected.join(", "))
+    };
+    PromotionCheck::new(PromotionCheckKind::Licenses, Some(policy), rejected.is_empty(), reason)
+}
+
+/// How completely an SBOM describes its components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SbomQuality {
+    pub attestation_id: Uuid,
+    pub component_count: usize,
+    pub versioned: usize,
+    pub identified: usize,
+    pub licensed: usize,
+    /// Distinct license IDs across all components, sorted.
+    pub licenses: Vec<String>,
+}
+
+impl SbomQuality {
+    /// Reads an SPDX or CycloneDX predicate; other attestations give `None`.
+    pub fn from_attestation(attestation: &Attestation) -> Option<Self> {
+        let predicate = &attestation.predicate;
+        let components: Vec<(bool, bool, Vec<String>)> = match attestation.kind {
+            AttestationKind::CycloneDx => predicate["components"].as_array()?
+                .iter()
+                .map(|component| {
+                    let licenses = component["licenses"].as_array()
+                        .map(|choices| choices.iter()
+                            .filter_map(|choice| choice["expression"].as_str()
+                                .or_else(|| choice["license"]["id"].as_str())
+                                .or_else(|| choice["license"]["name"].as_str()))
+                            .flat_map(license_ids)
+                            .collect())
+                        .unwrap_or_default();
+                    (has_text(&component["version"]), has_text(&component["purl"]) || has_text(&component["cpe"]), licenses)
+                })
+                .collect(),
+            AttestationKind::Spdx => predicate["packages"].as_array()?
+                .iter()
+                .map(|package| {
+                    let identified = package["externalRefs"].as_array().is_some_and(|refs| refs.iter().any(|reference| {
+                        matches!(reference["referenceType"].as_str(), Some("purl" | "cpe22Type" | "cpe23Type"))
+                    }));
+                    let licenses = [&package["licenseConcluded"], &package["licenseDeclared"]]
+                        .into_iter()
+                        .filter_map(Value::as_str)
+                        .find(|license| !matches!(*license, "NOASSERTION" | "NONE" | ""))
+                        .map(license_ids)
+                        .unwrap_or_default();
+                    (has_text(&package["versionInfo"]), identified, licenses)
+                })
+                .collect(),
+            _ => return None,
+        };
+
+        let mut licenses: Vec<String> = components.iter().flat_map(|(_, _, licenses)| licenses.iter().cloned()).collect();
+        licenses.sort();
+        licenses.dedup();
+        Some(Self {
+            attestation_id: attestation.id,
+            component_count: components.len(),
+            versioned: components.iter().filter(|(versioned, _, _)| *versioned).count(),
+            identified: components.iter().filter(|(_, identified, _)| *identified).count(),
+            licensed: components.iter().filter(|(_, _, licenses)| !licenses.is_empty()).count(),
+            licenses,
+        })
+    }
+
+    /// The share of components `count` covers; an empty SBOM covers nothing.
+    pub fn coverage(&self, count: usize) -> f64 {
+        if self.component_count == 0 { 0.0 } else { count as f64 / self.component_count as f64 }
+    }
+}
+
+fn has_text(value: &Value) -> bool {
+    value.as_str().is_some_and(|text| !text.trim().is_empty())
+}
+
+/// Splits an SPDX license expression into its license IDs. Exceptions
+/// (`WITH ...`) are dropped, and every alternative of an `OR` is kept, so a
+/// denied license anywhere in the expression counts.
+pub fn license_ids(expression: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut after_with = false;
+    for token in expression.split(|c: char| c.is_whitespace() || c == '(' || c == ')').filter(|token| !token.is_empty()) {
+        match token {
+            "AND" | "OR" | "and" | "or" => {}
+            "WITH" | "with" => after_with = true,
+            _ if after_with => after_with = false,
+            _ => ids.push(token.to_string()),
+        }
+    }
+    ids
+}
+
+/// Findings per severity in a scanner report. Reads Trivy (`Results[].Vulnerabilities[]`),
+/// Grype (`matches[].vulnerability`) and plain `vulnerabilities[]` lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VulnerabilityCounts {
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub unknown: usize,
+}
+
+impl VulnerabilityCounts {
+    pub fn from_scan_result(result: &Value) -> Self {
+        let trivy = result["Results"].as_array().into_iter().flatten()
+            .flat_map(|target| target["Vulnerabilities"].as_array().into_iter().flatten())
+            .map(|finding| &finding["Severity"]);
+        let grype = result["matches"].as_array().into_iter().flatten()
+            .map(|finding| &finding["vulnerability"]["severity"]);
+        let plain = result["vulnerabilities"].as_array().into_iter().flatten()
+            .map(|finding| &finding["severity"]);
+
+        let mut counts = Self::default();
+        for severity in trivy.chain(grype).chain(plain) {
+            match severity.as_str().unwrap_or("").to_ascii_lowercase().as_str() {
+                "critical" => counts.critical += 1,
+                "high" => counts.high += 1,
+                "medium" | "moderate" => counts.medium += 1,
+                "low" | "negligible" => counts.low += 1,
+                _ => counts.unknown += 1,
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromotionOutcome {
+    Approved,
+    Rejected,
+    /// Checks failed, and an authorized user promoted the artifact anyway.
+    Overridden,
+}
+
+impl PromotionOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromotionOutcome::Approved => "approved",
+            PromotionOutcome::Rejected => "rejected",
+            PromotionOutcome::Overridden => "overridden",
+        }
+    }
+
+    pub fn promotes(&self) -> bool {
+        !matches!(self, PromotionOutcome::Rejected)
+    }
+}
+
+impl FromStr for PromotionOutcome {
+    type Err = PromotionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "approved" => Ok(PromotionOutcome::Approved),
+            "rejected" => Ok(PromotionOutcome::Rejected),
+            "overridden" => Ok(PromotionOutcome::Overridden),
+            _ => Err(PromotionError::UnknownOutcome(value.to_string())),
+        }
+    }
+}
+
+/// The signed part of a decision: the outcome and the full rationale.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromotionDecisionBody {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub artifact_digest: String,
+    pub from_environment: String,
+    pub to_environment: String,
+    pub outcome: PromotionOutcome,
+    pub checks: Vec<PromotionCheck>,
+    /// Promotion policies that applied to the target environment.
+    pub policy_ids: Vec<Uuid>,
+    pub override_reason: Option<String>,
+    pub requested_by: Uuid,
+    /// Links the decision to the request's audit log entries.
+    pub request_id: Option<String>,
+    pub decided_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionDecision {
+    #[serde(flatten)]
+    pub decision: PromotionDecisionBody,
+    pub envelope: Envelope,
+}
+
+impl PromotionDecision {
+    /// Decides the promotion from `checks` and signs the result. An override
+    /// reason turns a rejection into an override, unless a check that cannot
+    /// be overridden failed; it is ignored when every check passed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign(
+        signer: &EnvelopeSigner,
+        tenant_id: Uuid,
+        artifact_digest: &str,
+        request: &PromotionRequest,
+        policies: &[PromotionPolicy],
+        checks: Vec<PromotionCheck>,
+        requested_by: Uuid,
+        request_id: Option<String>,
+    ) -> Result<Self, PromotionError> {
+        let mut failed = checks.iter().filter(|check| !check.passed).peekable();
+        let outcome = if failed.peek().is_none() {
+            PromotionOutcome::Approved
+        } else if request.override_reason.is_some() && failed.all(|check| check.kind.overridable()) {
+            PromotionOutcome::Overridden
+        } else {
+            PromotionOutcome::Rejected
+        };
+        let decision = PromotionDecisionBody {
+            id: Uuid::new_v4(),
+            tenant_id,
+            artifact_digest: normalize_digest(artifact_digest),
+            from_environment: request.from.clone(),
+            to_environment: request.to.clone(),
+            outcome,
+            checks,
+            policy_ids: policies.iter().filter(|policy| policy.applies_to(&request.to)).map(|policy| policy.id).collect(),
+            override_reason: if outcome == PromotionOutcome::Overridden { request.override_reason.clone() } else { None },
+            requested_by,
+            request_id,
+            // Stored with microsecond precision, so sign it that way
+            decided_at: Utc::now().trunc_subsecs(6),
+        };
+        let envelope = signer.sign_json(PROMOTION_DECISION_PAYLOAD_TYPE, &decision)?;
+        Ok(Self { decision, envelope })
+    }
+
+    /// Checks the signature and that the signed payload matches the unsigned fields.
+    pub fn verify(&self, key: &VerifyingKey) -> Result<(), PromotionError> {
+        verify_envelope(&self.envelope, key)?;
+        let signed: PromotionDecisionBody = self.envelope.decode_payload()?;
+        if signed != self.decision {
+            return Err(PromotionError::PayloadMismatch);
+        }
+        Ok(())
+    }
+
+    /// SHA-256 of the signed payload, used as the evidence digest of the
+    /// custody event a promotion appends.
+    pub fn payload_hash(&self) -> Result<String, PromotionError> {
+        Ok(hex::encode(Sha256::digest(self.envelope.payload_bytes()?)))
+    }
+}
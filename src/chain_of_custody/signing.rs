@@ -0,0 +1,472 @@
+use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, SubsecRound, Utc};
+use ed25519_dalek::Verifier as _;
+use p256::ecdsa::{signature::Signer as _, DerSignature};
+use p256::pkcs8::{DecodePublicKey, EncodePublicKey, LineEnding};
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::fulcio::{
+    verify_certificate_chain, CertificateAuthority, CertificateIdentity, FulcioConfig, FulcioError, HttpFulcioClient,
+    LocalFulcio, SigningCertificateRequest, EMBEDDED_FULCIO_ISSUER,
+};
+use crate::chain_of_custody::rekor::{parse_public_key_pem, public_key_pem, sha256_hex};
+use crate::provenance::dsse::{key_id_for, pae, Envelope, EnvelopeSignature};
+use crate::security::secret_management::{get_or_create_secret, SecretError, SecretManager};
+
+pub const SIGNATURE_BUNDLE_MEDIA_TYPE: &str = "application/vnd.traceguard.signature-bundle+json";
+pub const VERIFICATION_REPORT_PAYLOAD_TYPE: &str = "application/vnd.traceguard.verification-report+json";
+/// DSSE payload types callers may have signed on request. Types TraceGuard
+/// issues itself, such as in-toto statements and verification reports, are
+/// left out so the tenant key cannot be made to vouch for a forged one.
+pub const SIGNABLE_PAYLOAD_TYPES: &[&str] = &[
+    "application/json",
+    "application/vnd.cyclonedx+json",
+    "application/spdx+json",
+    "application/vnd.openvex+json",
+];
+
+#[derive(Error, Debug)]
+pub enum SigningError {
+    #[error("Secret management error: {0}")]
+    SecretError(#[from] SecretError),
+    #[error("Invalid key material: {0}")]
+    InvalidKey(String),
+    #[error("Invalid encoding: {0}")]
+    EncodingError(String),
+    #[error("Certificate authority error: {0}")]
+    Fulcio(#[from] FulcioError),
+    #[error("Keyless signing is not configured")]
+    KeylessDisabled,
+    #[error("Signature does not verify")]
+    SignatureMismatch,
+    #[error("Signed content does not match digest {0}")]
+    DigestMismatch(String),
+    #[error("Detached signatures need the signed content to verify")]
+    MissingContent,
+    #[error("Signature was made with key {0}, not the expected key")]
+    UnexpectedKey(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningAlgorithm {
+    #[default]
+    Ed25519,
+    EcdsaP256,
+}
+
+impl SigningAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::Ed25519 => "ed25519",
+            SigningAlgorithm::EcdsaP256 => "ecdsa_p256",
+        }
+    }
+
+    /// Where the tenant's document signing key for this algorithm is kept.
+    pub fn secret_id(&self) -> String {
+        format!("document_signing_key_{}", self.as_str())
+    }
+}
+
+/// A private key that signs TraceGuard-issued documents. ECDSA signatures
+/// are ASN.1 DER over SHA-256, as Sigstore tooling expects.
+pub enum DocumentKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    EcdsaP256(p256::ecdsa::SigningKey),
+}
+
+impl DocumentKey {
+    pub fn generate(algorithm: SigningAlgorithm) -> Self {
+        match algorithm {
+            SigningAlgorithm::Ed25519 => DocumentKey::Ed25519(ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng)),
+            SigningAlgorithm::EcdsaP256 => DocumentKey::EcdsaP256(p256::ecdsa::SigningKey::random(&mut rand::rngs::OsRng)),
+        }
+    }
+
+    /// Decodes a hex Ed25519 seed or P-256 scalar.
+    pub fn from_hex(algorithm: SigningAlgorithm, encoded: &str) -> Result<Self, SigningError> {
+        let bytes = hex::decode(encoded.trim()).map_err(|e| SigningError::InvalidKey(e.to_string()))?;
+        match algorithm {
+            SigningAlgorithm::Ed25519 => {
+                let seed: [u8; 32] = bytes.try_into()
+                    .map_err(|_| SigningError::InvalidKey("Ed25519 seed must be 32 bytes".to_string()))?;
+                Ok(DocumentKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(&seed)))
+            }
+            SigningAlgorithm::EcdsaP256 => p256::ecdsa::SigningKey::from_slice(&bytes)
+                .map(DocumentKey::EcdsaP256)
+                .map_err(|e| SigningError::InvalidKey(e.to_string())),
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        match self {
+            DocumentKey::Ed25519(key) => hex::encode(key.to_bytes()),
+            DocumentKey::EcdsaP256(key) => hex::encode(key.to_bytes()),
+        }
+    }
+
+    /// Loads the tenant's key for `algorithm`, generating and storing a new
+    /// one the first time it is requested.
+    pub async fn load_or_create<M: SecretManager>(secret_manager: &M, tenant_id: Uuid, algorithm: SigningAlgorithm) -> Result<Self, SigningError> {
+        Self::load_or_create_at(secret_manager, &algorithm.secret_id(), tenant_id, algorithm).await
+    }
+
+    /// Like `load_or_create`, for a key kept under `secret_id`.
+    pub async fn load_or_create_at<M: SecretManager>(secret_manager: &M, secret_id: &str, tenant_id: Uuid, algorithm: SigningAlgorithm) -> Result<Self, SigningError> {
+        let encoded = get_or_create_secret(secret_manager, secret_id, tenant_id, || Self::generate(algorithm).to_hex()).await?;
+        Self::from_hex(algorithm, &encoded)
+    }
+
+    /// Loads the tenant's key for `algorithm` without creating one; fails
+    /// with `SecretError::SecretNotFound` if it was never used.
+    pub async fn load<M: SecretManager>(secret_manager: &M, tenant_id: Uuid, algorithm: SigningAlgorithm) -> Result<Self, SigningError> {
+        let encoded = secret_manager.get_secret(&algorithm.secret_id(), tenant_id).await?;
+        Self::from_hex(algorithm, &encoded)
+    }
+
+    pub fn public_key(&self) -> DocumentPublicKey {
+        match self {
+            DocumentKey::Ed25519(key) => DocumentPublicKey::Ed25519(key.verifying_key()),
+            DocumentKey::EcdsaP256(key) => DocumentPublicKey::EcdsaP256(*key.verifying_key()),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            DocumentKey::Ed25519(key) => ed25519_dalek::Signer::sign(key, message).to_bytes().to_vec(),
+            DocumentKey::EcdsaP256(key) => {
+                let signature: DerSignature = key.sign(message);
+                signature.as_bytes().to_vec()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentPublicKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    EcdsaP256(p256::ecdsa::VerifyingKey),
+}
+
+impl DocumentPublicKey {
+    pub fn algorithm(&self) -> SigningAlgorithm {
+        match self {
+            DocumentPublicKey::Ed25519(_) => SigningAlgorithm::Ed25519,
+            DocumentPublicKey::EcdsaP256(_) => SigningAlgorithm::EcdsaP256,
+        }
+    }
+
+    /// Raw public key bytes: the Ed25519 point, or the uncompressed SEC1 point.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            DocumentPublicKey::Ed25519(key) => key.as_bytes().to_vec(),
+            DocumentPublicKey::EcdsaP256(key) => key.to_encoded_point(false).as_bytes().to_vec(),
+        }
+    }
+
+    /// Hex SHA-256 of the raw public key, matching `dsse::key_id_for`.
+    pub fn key_id(&self) -> String {
+        match self {
+            DocumentPublicKey::Ed25519(key) => key_id_for(key),
+            DocumentPublicKey::EcdsaP256(_) => sha256_hex(&self.to_bytes()),
+        }
+    }
+
+    pub fn to_pem(&self) -> String {
+        match self {
+            DocumentPublicKey::Ed25519(key) => public_key_pem(key),
+            DocumentPublicKey::EcdsaP256(key) => key.to_public_key_pem(LineEnding::LF).unwrap_or_default(),
+        }
+    }
+
+    pub fn from_pem(pem: &str) -> Result<Self, SigningError> {
+        if let Ok(key) = p256::ecdsa::VerifyingKey::from_public_key_pem(pem) {
+            return Ok(DocumentPublicKey::EcdsaP256(key));
+        }
+        parse_public_key_pem(pem)
+            .map(DocumentPublicKey::Ed25519)
+            .map_err(|_| SigningError::InvalidKey("expected an Ed25519 or P-256 public key".to_string()))
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), SigningError> {
+        let verified = match self {
+            DocumentPublicKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|signature| key.verify(message, &signature).is_ok()),
+            DocumentPublicKey::EcdsaP256(key) => DerSignature::from_bytes(signature)
+                .is_ok_and(|signature| key.verify(message, &signature).is_ok()),
+        };
+        if verified {
+            Ok(())
+        } else {
+            Err(SigningError::SignatureMismatch)
+        }
+    }
+}
+
+/// How a verifier finds the signing key: a tenant key named by ID, or a
+/// short-lived certificate from the keyless CA.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerificationMaterial {
+    PublicKey { key_id: String, algorithm: SigningAlgorithm, public_key: String },
+    /// PEM certificates, leaf first.
+    CertificateChain { certificates: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignedContent {
+    /// The document is embedded as the envelope payload.
+    DsseEnvelope { envelope: Envelope },
+    /// A base64 signature over the document bytes, shipped separately.
+    MessageSignature { signature: String },
+}
+
+/// Where a published signature sits in the tenant's transparency log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LogEntryRef {
+    pub leaf_index: u64,
+    pub leaf_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignatureBundle {
+    pub media_type: String,
+    pub id: Uuid,
+    /// Hex sha256 of the signed document.
+    pub digest: String,
+    pub verification_material: VerificationMaterial,
+    pub content: SignedContent,
+    pub signed_at: DateTime<Utc>,
+    pub log_entry: Option<LogEntryRef>,
+}
+
+/// What a successful verification established about the signer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerifiedSignature {
+    pub bundle_id: Uuid,
+    pub digest: String,
+    pub key_id: String,
+    pub algorithm: SigningAlgorithm,
+    /// Present for keyless signatures.
+    pub identity: Option<CertificateIdentity>,
+}
+
+/// A key ready to sign, with the material verifiers need to trust it.
+pub struct DocumentSigner {
+    key: DocumentKey,
+    material: VerificationMaterial,
+}
+
+impl DocumentSigner {
+    pub fn keyed(key: DocumentKey) -> Self {
+        let public_key = key.public_key();
+        let material = VerificationMaterial::PublicKey {
+            key_id: public_key.key_id(),
+            algorithm: public_key.algorithm(),
+            public_key: public_key.to_pem(),
+        };
+        Self { key, material }
+    }
+
+    pub fn key_id(&self) -> String {
+        self.key.public_key().key_id()
+    }
+
+    /// Signs `payload` as a DSSE envelope.
+    pub fn sign_envelope(&self, payload_type: &str, payload: &[u8]) -> SignatureBundle {
+        let envelope = self.envelope(payload_type, payload);
+        self.bundle(payload, SignedContent::DsseEnvelope { envelope })
+    }
+
+    /// The bare envelope, for documents that carry their own, such as VSAs.
+    pub fn envelope(&self, payload_type: &str, payload: &[u8]) -> Envelope {
+        let keyid = match &self.material {
+            VerificationMaterial::PublicKey { key_id, .. } => key_id.clone(),
+            // Keyless envelopes identify the signer through the certificate
+            VerificationMaterial::CertificateChain { .. } => String::new(),
+        };
+        Envelope {
+            payload_type: payload_type.to_string(),
+            payload: BASE64.encode(payload),
+            signatures: vec![EnvelopeSignature { keyid, sig: BASE64.encode(self.key.sign(&pae(payload_type, payload))) }],
+        }
+    }
+
+    /// Signs `content` directly, leaving it untouched.
+    pub fn sign_detached(&self, content: &[u8]) -> SignatureBundle {
+        let signature = BASE64.encode(self.key.sign(content));
+        self.bundle(content, SignedContent::MessageSignature { signature })
+    }
+
+    fn bundle(&self, document: &[u8], content: SignedContent) -> SignatureBundle {
+        SignatureBundle {
+            media_type: SIGNATURE_BUNDLE_MEDIA_TYPE.to_string(),
+            id: Uuid::new_v4(),
+            digest: sha256_hex(document),
+            verification_material: self.material.clone(),
+            content,
+            signed_at: Utc::now().trunc_subsecs(0),
+            log_entry: None,
+        }
+    }
+}
+
+impl SignatureBundle {
+    /// Checks the signature with `public_key`. `content` is required for
+    /// detached signatures and, for envelopes, must match the payload if given.
+    pub fn verify_with_key(&self, public_key: &DocumentPublicKey, content: Option<&[u8]>) -> Result<VerifiedSignature, SigningError> {
+        if let VerificationMaterial::PublicKey { key_id, .. } = &self.verification_material {
+            if *key_id != public_key.key_id() {
+                return Err(SigningError::UnexpectedKey(key_id.clone()));
+            }
+        }
+        self.verify_content(public_key, content)?;
+        Ok(VerifiedSignature {
+            bundle_id: self.id,
+            digest: self.digest.clone(),
+            key_id: public_key.key_id(),
+            algorithm: public_key.algorithm(),
+            identity: None,
+        })
+    }
+
+    /// Checks a keyless signature: the certificate must chain to one of the
+    /// PEM `roots` and be valid when the document was signed.
+    pub fn verify_keyless(&self, roots: &[String], content: Option<&[u8]>) -> Result<VerifiedSignature, SigningError> {
+        let VerificationMaterial::CertificateChain { certificates } = &self.verification_material else {
+            return Err(SigningError::InvalidKey("bundle was not signed keyless".to_string()));
+        };
+        let chain = crate::chain_of_custody::fulcio::CertificateChain { certificates: certificates.clone() };
+        let (identity, key) = verify_certificate_chain(&chain, roots, self.signed_at)?;
+        let public_key = DocumentPublicKey::EcdsaP256(key);
+        self.verify_content(&public_key, content)?;
+        Ok(VerifiedSignature {
+            bundle_id: self.id,
+            digest: self.digest.clone(),
+            key_id: public_key.key_id(),
+            algorithm: public_key.algorithm(),
+            identity: Some(identity),
+        })
+    }
+
+    /// The signed document: the envelope payload, or `content` for detached signatures.
+    pub fn document(&self) -> Result<Option<Vec<u8>>, SigningError> {
+        match &self.content {
+            SignedContent::DsseEnvelope { envelope } => envelope.payload_bytes()
+                .map(Some)
+                .map_err(|e| SigningError::EncodingError(e.to_string())),
+            SignedContent::MessageSignature { .. } => Ok(None),
+        }
+    }
+
+    fn verify_content(&self, public_key: &DocumentPublicKey, content: Option<&[u8]>) -> Result<(), SigningError> {
+        let document = match (self.document()?, content) {
+            (Some(payload), Some(content)) if payload != content => return Err(SigningError::DigestMismatch(self.digest.clone())),
+            (Some(payload), _) => payload,
+            (None, Some(content)) => content.to_vec(),
+            (None, None) => return Err(SigningError::MissingContent),
+        };
+        if sha256_hex(&document) != self.digest {
+            return Err(SigningError::DigestMismatch(self.digest.clone()));
+        }
+
+        match &self.content {
+            SignedContent::DsseEnvelope { envelope } => {
+                let message = pae(&envelope.payload_type, &document);
+                let verified = envelope.signatures.iter()
+                    .filter_map(|signature| BASE64.decode(&signature.sig).ok())
+                    .any(|signature| public_key.verify(&message, &signature).is_ok());
+                if verified {
+                    Ok(())
+                } else {
+                    Err(SigningError::SignatureMismatch)
+                }
+            }
+            SignedContent::MessageSignature { signature } => {
+                let signature = BASE64.decode(signature).map_err(|e| SigningError::EncodingError(e.to_string()))?;
+                public_key.verify(&document, &signature)
+            }
+        }
+    }
+}
+
+/// Signs outgoing documents with tenant keys from the secret manager, or
+/// keyless with short-lived certificates from a Fulcio-compatible CA.
+#[derive(Clone)]
+pub struct SigningService {
+    certificate_authority: Option<Arc<dyn CertificateAuthority>>,
+    trust_roots: Arc<Vec<String>>,
+}
+
+impl SigningService {
+    /// `trust_roots` are the PEM certificates keyless signatures must chain to.
+    pub fn new(certificate_authority: Option<Arc<dyn CertificateAuthority>>, trust_roots: Vec<String>) -> Self {
+        Self { certificate_authority, trust_roots: Arc::new(trust_roots) }
+    }
+
+    /// `token_secret` is the JWT secret the embedded CA checks access tokens with.
+    pub async fn from_config(config: &FulcioConfig, token_secret: &[u8]) -> Result<Self, SigningError> {
+        match config {
+            FulcioConfig::Disabled => Ok(Self::new(None, Vec::new())),
+            FulcioConfig::SelfHosted { url, trust_bundle } => {
+                let client = HttpFulcioClient::new(url);
+                let roots = match trust_bundle {
+                    Some(path) => vec![std::fs::read_to_string(path).map_err(|e| SigningError::InvalidKey(format!("{}: {}", path, e)))?],
+                    None => client.trust_bundle().await?.chains.into_iter().flat_map(|chain| chain.certificates).collect(),
+                };
+                Ok(Self::new(Some(Arc::new(client)), roots))
+            }
+            FulcioConfig::Embedded { key_file } => {
+                let ca = LocalFulcio::load_or_create(Path::new(key_file), EMBEDDED_FULCIO_ISSUER, token_secret)?;
+                let roots = vec![ca.root_certificate()];
+                Ok(Self::new(Some(Arc::new(ca)), roots))
+            }
+        }
+    }
+
+    pub fn keyless_enabled(&self) -> bool {
+        self.certificate_authority.is_some()
+    }
+
+    pub fn trust_roots(&self) -> &[String] {
+        &self.trust_roots
+    }
+
+    /// Signs documents TraceGuard issues itself with the tenant's Ed25519 key
+    /// kept under `secret_id`.
+    pub async fn issuer_signer<M: SecretManager>(&self, secret_manager: &M, tenant_id: Uuid, secret_id: &str) -> Result<DocumentSigner, SigningError> {
+        Ok(DocumentSigner::keyed(DocumentKey::load_or_create_at(secret_manager, secret_id, tenant_id, SigningAlgorithm::Ed25519).await?))
+    }
+
+    pub async fn keyed_signer<M: SecretManager>(&self, secret_manager: &M, tenant_id: Uuid, algorithm: SigningAlgorithm) -> Result<DocumentSigner, SigningError> {
+        Ok(DocumentSigner::keyed(DocumentKey::load_or_create(secret_manager, tenant_id, algorithm).await?))
+    }
+
+    /// Generates a throwaway P-256 key and certifies it for the holder of
+    /// `identity_token`. The key is dropped with the signer.
+    pub async fn keyless_signer(&self, identity_token: &str) -> Result<DocumentSigner, SigningError> {
+        let ca = self.certificate_authority.as_ref().ok_or(SigningError::KeylessDisabled)?;
+        let key = p256::ecdsa::SigningKey::random(&mut rand::rngs::OsRng);
+        let request = SigningCertificateRequest::new(identity_token, &key)?;
+        let chain = ca.signing_certificate(&request).await?;
+        Ok(DocumentSigner {
+            key: DocumentKey::EcdsaP256(key),
+            material: VerificationMaterial::CertificateChain { certificates: chain.certificates },
+        })
+    }
+
+    /// Verifies keyless bundles against the configured roots and keyed
+    /// bundles against `public_key`.
+    pub fn verify(&self, bundle: &SignatureBundle, public_key: Option<&DocumentPublicKey>, content: Option<&[u8]>) -> Result<VerifiedSignature, SigningError> {
+        match (&bundle.verification_material, public_key) {
+            (VerificationMaterial::CertificateChain { .. }, _) => bundle.verify_keyless(&self.trust_roots, content),
+            (VerificationMaterial::PublicKey { .. }, Some(public_key)) => bundle.verify_with_key(public_key, content),
+            (VerificationMaterial::PublicKey { key_id, .. }, None) => Err(SigningError::UnexpectedKey(key_id.clone())),
+        }
+    }
+}
@@ -0,0 +1,692 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use cmpv2::status::{PkiFailureInfoValues, PkiStatus, PkiStatusInfo};
+use cms::cert::x509::{attr::Attribute, spki::{AlgorithmIdentifier, AlgorithmIdentifierOwned}, Certificate};
+use cms::cert::{CertificateChoices, IssuerAndSerialNumber};
+use cms::content_info::{CmsVersion, ContentInfo};
+use cms::signed_data::{
+    CertificateSet, EncapsulatedContentInfo, SignedAttributes, SignedData, SignerIdentifier, SignerInfo, SignerInfos,
+};
+use der::asn1::{GeneralizedTime, Int, ObjectIdentifier, OctetString, SetOfVec};
+use der::{Any, Decode, Encode, Sequence, Tag};
+use p256::ecdsa::{signature::Signer, DerSignature, SigningKey};
+use p256::pkcs8::{DecodePrivateKey, EncodePrivateKey, LineEnding};
+use rand::RngCore;
+use rcgen::{CertificateParams, CustomExtension, DistinguishedName, DnType, KeyPair, KeyUsagePurpose, SerialNumber};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+use x509_parser::der_parser::asn1_rs::BitString;
+use x509_parser::pem::Pem;
+use x509_parser::prelude::FromDer;
+use x509_parser::time::ASN1Time;
+use x509_parser::x509::AlgorithmIdentifier as ParsedAlgorithmIdentifier;
+use x509_tsp::{Accuracy, MessageImprint, TimeStampReq, TimeStampResp, TspVersion, TstInfo};
+use crate::chain_of_custody::transparency_log::LogEntryKind;
+
+pub const TIMESTAMP_QUERY_MEDIA_TYPE: &str = "application/timestamp-query";
+pub const TIMESTAMP_REPLY_MEDIA_TYPE: &str = "application/timestamp-reply";
+/// anyPolicy; the embedded TSA issues every token under it.
+pub const EMBEDDED_TSA_POLICY: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.32.0");
+
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+const ID_CONTENT_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const ID_SIGNING_CERTIFICATE_V2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.47");
+const ID_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const ID_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+const ID_SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+const ID_ECDSA_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ID_RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const ID_SHA256_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+const ID_SHA384_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12");
+const ID_SHA512_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13");
+const EXTENDED_KEY_USAGE_OID: &[u64] = &[2, 5, 29, 37];
+/// `SEQUENCE { id-kp-timeStamping }`, the only EKU RFC 3161 allows on a TSA
+/// certificate.
+const TIME_STAMPING_EKU: &[u8] = &[0x30, 0x0a, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x08];
+const TSA_CERTIFICATE_VALIDITY: time::Duration = time::Duration::days(3650);
+
+#[derive(Error, Debug)]
+pub enum TimestampError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("TSA returned {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("TSA rejected the request: {0}")]
+    Rejected(String),
+    #[error("Invalid digest: {0}")]
+    InvalidDigest(String),
+    #[error("Malformed timestamp token: {0}")]
+    Malformed(String),
+    #[error("Timestamp token is for a different digest")]
+    ImprintMismatch,
+    #[error("Timestamp token does not answer this request")]
+    NonceMismatch,
+    #[error("Timestamp token signature is invalid")]
+    InvalidSignature,
+    #[error("Certificate error: {0}")]
+    Certificate(String),
+    #[error("Timestamp token is not signed by a trusted TSA")]
+    UntrustedSigner,
+    #[error("Timestamping is not configured")]
+    Disabled,
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<der::Error> for TimestampError {
+    fn from(e: der::Error) -> Self {
+        TimestampError::Malformed(e.to_string())
+    }
+}
+
+/// Which RFC 3161 time-stamping authority records are timestamped by.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum TimestampConfig {
+    /// Records are not timestamped.
+    #[default]
+    Disabled,
+    /// A TSA at `url`. Tokens must be signed by a certificate in one of the
+    /// PEM files in `certificates`, or issued by one.
+    Remote { url: String, certificates: Vec<String> },
+    /// An in-process TSA for air-gapped installs. Its P-256 key lives in
+    /// `key_file`, created on first start, so tokens stay verifiable across
+    /// restarts. `certificates` trusts further TSAs, e.g. a previous key.
+    Embedded {
+        key_file: String,
+        #[serde(default)]
+        certificates: Vec<String>,
+    },
+}
+
+/// `SigningCertificateV2` from RFC 5035, which binds the token to the TSA
+/// certificate.
+#[derive(Debug, Clone, Sequence)]
+struct SigningCertificateV2 {
+    certs: Vec<EssCertIdV2>,
+    #[asn1(optional = "true")]
+    policies: Option<Any>,
+}
+
+#[derive(Debug, Clone, Sequence)]
+struct EssCertIdV2 {
+    /// Defaults to sha256 when absent.
+    #[asn1(optional = "true")]
+    hash_algorithm: Option<AlgorithmIdentifierOwned>,
+    cert_hash: OctetString,
+    #[asn1(optional = "true")]
+    issuer_serial: Option<Any>,
+}
+
+/// A DER `TimeStampReq` for a sha256 digest. The nonce ties the response to
+/// this request; the TSA is asked to include its certificate.
+#[derive(Debug, Clone)]
+pub struct TimestampRequest {
+    pub digest: Vec<u8>,
+    pub nonce: Int,
+    der: Vec<u8>,
+}
+
+impl TimestampRequest {
+    pub fn new(digest: &[u8]) -> Result<Self, TimestampError> {
+        let nonce = random_int()?;
+        let request = TimeStampReq {
+            version: TspVersion::V1,
+            message_imprint: MessageImprint {
+                hash_algorithm: AlgorithmIdentifier { oid: ID_SHA256, parameters: None },
+                hashed_message: OctetString::new(digest)?,
+            },
+            req_policy: None,
+            nonce: Some(nonce.clone()),
+            cert_req: true,
+            extensions: None,
+        };
+        Ok(Self { digest: digest.to_vec(), nonce, der: request.to_der()? })
+    }
+
+    pub fn to_der(&self) -> &[u8] {
+        &self.der
+    }
+}
+
+/// Accepts a hex sha256 digest, with or without the `sha256:` prefix.
+pub fn parse_digest(digest: &str) -> Result<Vec<u8>, TimestampError> {
+    let hex_digest = digest.strip_prefix("sha256:").unwrap_or(digest);
+    let bytes = hex::decode(hex_digest).map_err(|e| TimestampError::InvalidDigest(e.to_string()))?;
+    if bytes.len() != 32 {
+        return Err(TimestampError::InvalidDigest("expected a sha256 digest".to_string()));
+    }
+    Ok(bytes)
+}
+
+#[async_trait]
+pub trait TimestampAuthority: Send + Sync {
+    /// Sends a DER `TimeStampReq` and returns the DER `TimeStampResp`.
+    async fn timestamp(&self, request: &[u8]) -> Result<Vec<u8>, TimestampError>;
+}
+
+/// Talks to a TSA over the RFC 3161 HTTP transport.
+pub struct HttpTimestampClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpTimestampClient {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string(), client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl TimestampAuthority for HttpTimestampClient {
+    async fn timestamp(&self, request: &[u8]) -> Result<Vec<u8>, TimestampError> {
+        let response = self.client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, TIMESTAMP_QUERY_MEDIA_TYPE)
+            .body(request.to_vec())
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response.text().await.unwrap_or_default();
+            return Err(TimestampError::Api { status, message });
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// A TSA that signs tokens with a P-256 key under a self-signed certificate
+/// carrying the critical timeStamping usage. Tokens always embed the
+/// certificate, so a verifier only needs to trust the key.
+pub struct LocalTimestampAuthority {
+    signing_key: SigningKey,
+    certificate: Certificate,
+    certificate_pem: String,
+}
+
+impl LocalTimestampAuthority {
+    pub fn new(signing_key: SigningKey) -> Result<Self, TimestampError> {
+        let pkcs8 = signing_key.to_pkcs8_der().map_err(|e| TimestampError::Certificate(e.to_string()))?;
+        let key_pair = KeyPair::try_from(pkcs8.as_bytes()).map_err(|e| TimestampError::Certificate(e.to_string()))?;
+
+        let now = time::OffsetDateTime::now_utc();
+        let mut params = CertificateParams::default();
+        params.distinguished_name = DistinguishedName::new();
+        params.distinguished_name.push(DnType::OrganizationName, "TraceGuard");
+        params.distinguished_name.push(DnType::CommonName, "TraceGuard timestamp authority");
+        params.serial_number = Some(SerialNumber::from_slice(&positive_random_bytes()));
+        params.not_before = now;
+        params.not_after = now + TSA_CERTIFICATE_VALIDITY;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+        // rcgen writes extended key usage as non-critical, which RFC 3161
+        // forbids, so it goes in as a raw extension.
+        let mut time_stamping = CustomExtension::from_oid_content(EXTENDED_KEY_USAGE_OID, TIME_STAMPING_EKU.to_vec());
+        time_stamping.set_criticality(true);
+        params.custom_extensions = vec![time_stamping];
+        let certificate = params.self_signed(&key_pair).map_err(|e| TimestampError::Certificate(e.to_string()))?;
+
+        Ok(Self {
+            signing_key,
+            certificate: Certificate::from_der(certificate.der())?,
+            certificate_pem: certificate.pem(),
+        })
+    }
+
+    /// A TSA with a fresh key, which lives as long as the process.
+    pub fn generate() -> Result<Self, TimestampError> {
+        Self::new(SigningKey::random(&mut rand::rngs::OsRng))
+    }
+
+    /// Loads the TSA key from a PKCS#8 PEM file, creating the file first if
+    /// it does not exist.
+    pub fn load_or_create(key_file: &Path) -> Result<Self, TimestampError> {
+        let signing_key = match std::fs::read_to_string(key_file) {
+            Ok(pem) => SigningKey::from_pkcs8_pem(&pem).map_err(|e| TimestampError::Certificate(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let signing_key = SigningKey::random(&mut rand::rngs::OsRng);
+                let pem = signing_key.to_pkcs8_pem(LineEnding::LF).map_err(|e| TimestampError::Certificate(e.to_string()))?;
+                std::fs::write(key_file, pem.as_bytes())?;
+                signing_key
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Self::new(signing_key)
+    }
+
+    pub fn certificate(&self) -> String {
+        self.certificate_pem.clone()
+    }
+
+    /// Answers a DER `TimeStampReq` with a DER `TimeStampResp`. Requests that
+    /// cannot be honoured get a rejection status rather than an error.
+    pub fn respond(&self, request: &[u8]) -> Vec<u8> {
+        let response = match self.issue(request) {
+            Ok(token) => TimeStampResp {
+                status: PkiStatusInfo { status: PkiStatus::Accepted, status_string: None, fail_info: None },
+                time_stamp_token: Some(token),
+            },
+            Err(failure) => TimeStampResp {
+                status: PkiStatusInfo { status: PkiStatus::Rejection, status_string: None, fail_info: Some(failure.into()) },
+                time_stamp_token: None,
+            },
+        };
+        response.to_der().unwrap_or_default()
+    }
+
+    fn issue(&self, request: &[u8]) -> Result<ContentInfo, PkiFailureInfoValues> {
+        let request = TimeStampReq::from_der(request).map_err(|_| PkiFailureInfoValues::BadDataFormat)?;
+        let imprint = &request.message_imprint;
+        if imprint.hash_algorithm.oid != ID_SHA256 || imprint.hashed_message.as_bytes().len() != 32 {
+            return Err(PkiFailureInfoValues::BadAlg);
+        }
+        if request.req_policy.is_some_and(|policy| policy != EMBEDDED_TSA_POLICY) {
+            return Err(PkiFailureInfoValues::UnacceptedPolicy);
+        }
+        self.sign(request).map_err(|_| PkiFailureInfoValues::SystemFailure)
+    }
+
+    fn sign(&self, request: TimeStampReq) -> Result<ContentInfo, der::Error> {
+        let now = Utc::now();
+        let tst_info = TstInfo {
+            version: TspVersion::V1,
+            policy: EMBEDDED_TSA_POLICY,
+            message_imprint: request.message_imprint,
+            serial_number: random_int()?,
+            gen_time: GeneralizedTime::from_unix_duration(std::time::Duration::from_secs(now.timestamp() as u64))?,
+            accuracy: Some(Accuracy { seconds: Some(1), millis: None, micros: None }),
+            ordering: false,
+            nonce: request.nonce,
+            tsa: None,
+            extensions: None,
+        };
+        let tst_der = tst_info.to_der()?;
+
+        let signing_certificate = SigningCertificateV2 {
+            certs: vec![EssCertIdV2 {
+                hash_algorithm: None,
+                cert_hash: OctetString::new(Sha256::digest(self.certificate.to_der()?).to_vec())?,
+                issuer_serial: None,
+            }],
+            policies: None,
+        };
+        let signed_attrs: SignedAttributes = SetOfVec::try_from(vec![
+            attribute(ID_CONTENT_TYPE, Any::encode_from(&ID_CT_TST_INFO)?)?,
+            attribute(ID_MESSAGE_DIGEST, Any::encode_from(&OctetString::new(Sha256::digest(&tst_der).to_vec())?)?)?,
+            attribute(ID_SIGNING_CERTIFICATE_V2, Any::encode_from(&signing_certificate)?)?,
+        ])?;
+        let signature: DerSignature = self.signing_key.sign(&signed_attrs.to_der()?);
+
+        let tbs = &self.certificate.tbs_certificate;
+        let signer_info = SignerInfo {
+            version: CmsVersion::V1,
+            sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+                issuer: tbs.issuer.clone(),
+                serial_number: tbs.serial_number.clone(),
+            }),
+            digest_alg: AlgorithmIdentifierOwned { oid: ID_SHA256, parameters: None },
+            signed_attrs: Some(signed_attrs),
+            signature_algorithm: AlgorithmIdentifierOwned { oid: ID_ECDSA_WITH_SHA256, parameters: None },
+            signature: OctetString::new(signature.as_bytes())?,
+            unsigned_attrs: None,
+        };
+        let certificates = if request.cert_req {
+            Some(CertificateSet(SetOfVec::try_from(vec![CertificateChoices::Certificate(self.certificate.clone())])?))
+        } else {
+            None
+        };
+        let signed_data = SignedData {
+            version: CmsVersion::V3,
+            digest_algorithms: SetOfVec::try_from(vec![AlgorithmIdentifierOwned { oid: ID_SHA256, parameters: None }])?,
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: ID_CT_TST_INFO,
+                econtent: Some(Any::new(Tag::OctetString, tst_der)?),
+            },
+            certificates,
+            crls: None,
+            signer_infos: SignerInfos(SetOfVec::try_from(vec![signer_info])?),
+        };
+        Ok(ContentInfo { content_type: ID_SIGNED_DATA, content: Any::encode_from(&signed_data)? })
+    }
+
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/", post(timestamp_query))
+            .with_state(self)
+    }
+
+    /// Serves the TSA on an ephemeral loopback port and returns its address.
+    pub async fn spawn(self: Arc<Self>) -> std::io::Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server = axum::Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(self.router().into_make_service());
+        tokio::spawn(server);
+        Ok(addr)
+    }
+}
+
+#[async_trait]
+impl TimestampAuthority for LocalTimestampAuthority {
+    async fn timestamp(&self, request: &[u8]) -> Result<Vec<u8>, TimestampError> {
+        Ok(self.respond(request))
+    }
+}
+
+async fn timestamp_query(State(tsa): State<Arc<LocalTimestampAuthority>>, body: Bytes) -> Response {
+    ([(header::CONTENT_TYPE, TIMESTAMP_REPLY_MEDIA_TYPE)], tsa.respond(&body)).into_response()
+}
+
+/// What a timestamp token that verified says.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerifiedTimestamp {
+    pub gen_time: DateTime<Utc>,
+    /// The token's serial number, hex.
+    pub serial_number: String,
+    pub policy: String,
+    /// Subject of the TSA certificate that signed the token.
+    pub tsa: String,
+}
+
+/// Takes the token out of a DER `TimeStampResp`, failing if the TSA refused.
+pub fn token_from_response(response: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    let response = TimeStampResp::from_der(response)?;
+    match response.status.status {
+        PkiStatus::Accepted | PkiStatus::GrantedWithMods => Ok(response.time_stamp_token
+            .ok_or_else(|| TimestampError::Malformed("response has no token".to_string()))?
+            .to_der()?),
+        status => Err(TimestampError::Rejected(match response.status.fail_info {
+            Some(fail_info) => format!("{:?} ({:?})", status, fail_info),
+            None => format!("{:?}", status),
+        })),
+    }
+}
+
+/// Checks a DER timestamp token: that it covers the sha256 `digest`, that
+/// the TSA's signature over it holds, and that the signing certificate is one
+/// of the PEM `trusted` certificates or was issued by one, was valid when the
+/// token was made and is meant for timestamping.
+pub fn verify_timestamp_token(token: &[u8], digest: &[u8], trusted: &[String]) -> Result<VerifiedTimestamp, TimestampError> {
+    let (signed_data, tst_der, tst_info) = parse_token(token)?;
+    let imprint = &tst_info.message_imprint;
+    if imprint.hash_algorithm.oid != ID_SHA256 || imprint.hashed_message.as_bytes() != digest {
+        return Err(TimestampError::ImprintMismatch);
+    }
+
+    let signer = match signed_data.signer_infos.0.as_slice() {
+        [signer] => signer,
+        _ => return Err(TimestampError::Malformed("expected exactly one signer".to_string())),
+    };
+    let signed_attrs = signer.signed_attrs.as_ref()
+        .ok_or_else(|| TimestampError::Malformed("token has no signed attributes".to_string()))?;
+    if attribute_value(signed_attrs, ID_CONTENT_TYPE)?.decode_as::<ObjectIdentifier>()? != ID_CT_TST_INFO {
+        return Err(TimestampError::Malformed("signed content type is not TSTInfo".to_string()));
+    }
+    let message_digest = attribute_value(signed_attrs, ID_MESSAGE_DIGEST)?.decode_as::<OctetString>()?;
+    if message_digest.as_bytes() != hash(&signer.digest_alg.oid, &tst_der)? {
+        return Err(TimestampError::InvalidSignature);
+    }
+
+    let trusted_pems = trusted.iter()
+        .flat_map(|pem| Pem::iter_from_buffer(pem.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TimestampError::Certificate(e.to_string()))?;
+    let embedded = signed_data.certificates.iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(certificate) => certificate.to_der().ok(),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let signer_der = embedded.iter()
+        .chain(trusted_pems.iter().map(|pem| &pem.contents))
+        .find(|der| identifies(&signer.sid, der))
+        .ok_or_else(|| TimestampError::Certificate("signing certificate not found".to_string()))?;
+    if let Ok(value) = attribute_value(signed_attrs, ID_SIGNING_CERTIFICATE_V2) {
+        let signing_certificate = value.decode_as::<SigningCertificateV2>()?;
+        let cert_id = signing_certificate.certs.first()
+            .ok_or_else(|| TimestampError::Malformed("signingCertificateV2 is empty".to_string()))?;
+        let algorithm = cert_id.hash_algorithm.as_ref().map_or(ID_SHA256, |algorithm| algorithm.oid);
+        if cert_id.cert_hash.as_bytes() != hash(&algorithm, signer_der)? {
+            return Err(TimestampError::Certificate("token names a different signing certificate".to_string()));
+        }
+    }
+
+    let (_, signer_cert) = x509_parser::parse_x509_certificate(signer_der)
+        .map_err(|e| TimestampError::Certificate(e.to_string()))?;
+    let algorithm_der = AlgorithmIdentifierOwned {
+        oid: signature_algorithm(&signer.signature_algorithm.oid, &signer.digest_alg.oid),
+        parameters: None,
+    }
+    .to_der()?;
+    let (_, algorithm) = ParsedAlgorithmIdentifier::from_der(&algorithm_der)
+        .map_err(|e| TimestampError::Malformed(e.to_string()))?;
+    x509_parser::verify::verify_signature(
+        signer_cert.public_key(),
+        &algorithm,
+        &BitString::new(0, signer.signature.as_bytes()),
+        &signed_attrs.to_der()?,
+    )
+    .map_err(|_| TimestampError::InvalidSignature)?;
+
+    let anchored = trusted_pems.iter().any(|pem| {
+        pem.contents == *signer_der
+            || pem.parse_x509().is_ok_and(|root| signer_cert.verify_signature(Some(root.public_key())).is_ok())
+    });
+    if !anchored {
+        return Err(TimestampError::UntrustedSigner);
+    }
+
+    let gen_time = tst_info.gen_time.to_unix_duration();
+    let gen_time = DateTime::from_timestamp(gen_time.as_secs() as i64, gen_time.subsec_nanos()).unwrap_or_default();
+    let at = ASN1Time::from_timestamp(gen_time.timestamp()).map_err(|e| TimestampError::Certificate(e.to_string()))?;
+    if !signer_cert.validity().is_valid_at(at) {
+        return Err(TimestampError::Certificate("TSA certificate was not valid at the token's time".to_string()));
+    }
+    let time_stamping = signer_cert.extended_key_usage()
+        .map_err(|e| TimestampError::Certificate(e.to_string()))?
+        .is_some_and(|usage| usage.value.time_stamping);
+    if !time_stamping {
+        return Err(TimestampError::Certificate("TSA certificate is not for timestamping".to_string()));
+    }
+
+    Ok(VerifiedTimestamp {
+        gen_time,
+        serial_number: hex::encode(tst_info.serial_number.as_bytes()),
+        policy: tst_info.policy.to_string(),
+        tsa: signer_cert.subject().to_string(),
+    })
+}
+
+/// Splits a DER `TimeStampToken` into its signed data and the TSTInfo, both
+/// raw and decoded.
+fn parse_token(token: &[u8]) -> Result<(SignedData, Vec<u8>, TstInfo), TimestampError> {
+    let content_info = ContentInfo::from_der(token)?;
+    if content_info.content_type != ID_SIGNED_DATA {
+        return Err(TimestampError::Malformed("token is not CMS signed data".to_string()));
+    }
+    let signed_data = content_info.content.decode_as::<SignedData>()?;
+    let encap = &signed_data.encap_content_info;
+    if encap.econtent_type != ID_CT_TST_INFO {
+        return Err(TimestampError::Malformed("token does not contain TSTInfo".to_string()));
+    }
+    let tst_der = encap.econtent.as_ref()
+        .ok_or_else(|| TimestampError::Malformed("token has no content".to_string()))?
+        .value()
+        .to_vec();
+    let tst_info = TstInfo::from_der(&tst_der)?;
+    Ok((signed_data, tst_der, tst_info))
+}
+
+fn attribute(oid: ObjectIdentifier, value: Any) -> Result<Attribute, der::Error> {
+    Ok(Attribute { oid, values: SetOfVec::try_from(vec![value])? })
+}
+
+fn attribute_value(attributes: &SignedAttributes, oid: ObjectIdentifier) -> Result<&Any, TimestampError> {
+    attributes.iter()
+        .find(|attribute| attribute.oid == oid)
+        .and_then(|attribute| attribute.values.iter().next())
+        .ok_or_else(|| TimestampError::Malformed(format!("missing signed attribute {}", oid)))
+}
+
+fn identifies(sid: &SignerIdentifier, certificate: &[u8]) -> bool {
+    let SignerIdentifier::IssuerAndSerialNumber(id) = sid else {
+        return false;
+    };
+    Certificate::from_der(certificate)
+        .is_ok_and(|certificate| certificate.tbs_certificate.issuer == id.issuer && certificate.tbs_certificate.serial_number == id.serial_number)
+}
+
+fn hash(algorithm: &ObjectIdentifier, data: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    match *algorithm {
+        ID_SHA256 => Ok(Sha256::digest(data).to_vec()),
+        ID_SHA384 => Ok(Sha384::digest(data).to_vec()),
+        ID_SHA512 => Ok(Sha512::digest(data).to_vec()),
+        _ => Err(TimestampError::Malformed(format!("unsupported digest algorithm {}", algorithm))),
+    }
+}
+
+/// Many TSAs name plain `rsaEncryption` as the signature algorithm and leave
+/// the hash to the digest algorithm.
+fn signature_algorithm(signature: &ObjectIdentifier, digest: &ObjectIdentifier) -> ObjectIdentifier {
+    match (*signature, *digest) {
+        (ID_RSA_ENCRYPTION, ID_SHA256) => ID_SHA256_WITH_RSA,
+        (ID_RSA_ENCRYPTION, ID_SHA384) => ID_SHA384_WITH_RSA,
+        (ID_RSA_ENCRYPTION, ID_SHA512) => ID_SHA512_WITH_RSA,
+        (signature, _) => signature,
+    }
+}
+
+/// 16 random bytes that encode as a positive, minimal DER integer.
+fn positive_random_bytes() -> [u8; 16] {
+    let mut bytes = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes[0] = (bytes[0] & 0x7f) | 0x01;
+    bytes
+}
+
+fn random_int() -> Result<Int, der::Error> {
+    Int::new(&positive_random_bytes())
+}
+
+/// An RFC 3161 token, as attached to a record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimestampToken {
+    /// Base64 DER `TimeStampToken`.
+    pub token: String,
+    pub gen_time: DateTime<Utc>,
+    pub serial_number: String,
+    pub tsa: String,
+}
+
+impl TimestampToken {
+    pub fn to_der(&self) -> Result<Vec<u8>, TimestampError> {
+        BASE64.decode(&self.token).map_err(|e| TimestampError::Malformed(e.to_string()))
+    }
+}
+
+/// A timestamp token over a record's digest. The digest is the same one the
+/// record's transparency log entry commits to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordTimestamp {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: LogEntryKind,
+    pub resource_id: Uuid,
+    pub digest: String,
+    pub timestamp: TimestampToken,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RecordTimestamp {
+    pub fn new(tenant_id: Uuid, kind: LogEntryKind, resource_id: Uuid, digest: &str, timestamp: TimestampToken) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            kind,
+            resource_id,
+            digest: digest.trim_start_matches("sha256:").to_lowercase(),
+            timestamp,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Gets timestamp tokens from the configured TSA and checks tokens against
+/// the trusted TSA certificates.
+#[derive(Clone)]
+pub struct TimestampService {
+    authority: Option<Arc<dyn TimestampAuthority>>,
+    trusted_certificates: Arc<Vec<String>>,
+}
+
+impl TimestampService {
+    pub fn new(authority: Option<Arc<dyn TimestampAuthority>>, trusted_certificates: Vec<String>) -> Self {
+        Self { authority, trusted_certificates: Arc::new(trusted_certificates) }
+    }
+
+    pub fn from_config(config: &TimestampConfig) -> Result<Self, TimestampError> {
+        match config {
+            TimestampConfig::Disabled => Ok(Self::new(None, Vec::new())),
+            TimestampConfig::Remote { url, certificates } => {
+                Ok(Self::new(Some(Arc::new(HttpTimestampClient::new(url))), read_certificates(certificates)?))
+            }
+            TimestampConfig::Embedded { key_file, certificates } => {
+                let tsa = LocalTimestampAuthority::load_or_create(Path::new(key_file))?;
+                let mut trusted = read_certificates(certificates)?;
+                trusted.push(tsa.certificate());
+                Ok(Self::new(Some(Arc::new(tsa)), trusted))
+            }
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.authority.is_some()
+    }
+
+    pub fn trusted_certificates(&self) -> &[String] {
+        &self.trusted_certificates
+    }
+
+    /// Gets a token over a hex sha256 digest and checks it before returning it.
+    pub async fn timestamp(&self, digest: &str) -> Result<TimestampToken, TimestampError> {
+        let authority = self.authority.as_ref().ok_or(TimestampError::Disabled)?;
+        let request = TimestampRequest::new(&parse_digest(digest)?)?;
+        let token = token_from_response(&authority.timestamp(request.to_der()).await?)?;
+
+        let verified = verify_timestamp_token(&token, &request.digest, &self.trusted_certificates)?;
+        if parse_token(&token)?.2.nonce.as_ref() != Some(&request.nonce) {
+            return Err(TimestampError::NonceMismatch);
+        }
+        Ok(TimestampToken {
+            token: BASE64.encode(&token),
+            gen_time: verified.gen_time,
+            serial_number: verified.serial_number,
+            tsa: verified.tsa,
+        })
+    }
+
+    pub fn verify(&self, token: &TimestampToken, digest: &str) -> Result<VerifiedTimestamp, TimestampError> {
+        verify_timestamp_token(&token.to_der()?, &parse_digest(digest)?, &self.trusted_certificates)
+    }
+}
+
+fn read_certificates(paths: &[String]) -> Result<Vec<String>, TimestampError> {
+    paths.iter().map(|path| Ok(std::fs::read_to_string(path)?)).collect()
+}
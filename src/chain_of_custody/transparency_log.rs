@@ -0,0 +1,307 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, SubsecRound, Utc};
+use ed25519_dalek::VerifyingKey;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::merkle::{self, Hash, MerkleError, NodeId};
+use crate::provenance::dsse::{verify_envelope, DsseError, Envelope, EnvelopeSigner};
+
+/// Secret under which each tenant's log signing key is stored.
+pub const TRANSPARENCY_LOG_KEY_ID: &str = "transparency_log_key";
+pub const TREE_HEAD_PAYLOAD_TYPE: &str = "application/vnd.traceguard.tree-head+json";
+
+#[derive(Error, Debug)]
+pub enum TransparencyLogError {
+    #[error("Merkle proof error: {0}")]
+    Merkle(#[from] MerkleError),
+    #[error("Envelope error: {0}")]
+    Dsse(#[from] DsseError),
+    #[error("Signed tree head does not match its payload")]
+    TreeHeadMismatch,
+    #[error("Unknown log entry kind: {0}")]
+    UnknownKind(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogEntryKind {
+    Sbom,
+    Provenance,
+    CustodyEvent,
+    Signature,
+    Attestation,
+}
+
+impl LogEntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogEntryKind::Sbom => "sbom",
+            LogEntryKind::Provenance => "provenance",
+            LogEntryKind::CustodyEvent => "custody_event",
+            LogEntryKind::Signature => "signature",
+            LogEntryKind::Attestation => "attestation",
+        }
+    }
+}
+
+impl FromStr for LogEntryKind {
+    type Err = TransparencyLogError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sbom" => Ok(LogEntryKind::Sbom),
+            "provenance" => Ok(LogEntryKind::Provenance),
+            "custody_event" => Ok(LogEntryKind::CustodyEvent),
+            "signature" => Ok(LogEntryKind::Signature),
+            "attestation" => Ok(LogEntryKind::Attestation),
+            _ => Err(TransparencyLogError::UnknownKind(value.to_string())),
+        }
+    }
+}
+
+/// The data a leaf commits to. The leaf hash is taken over the exact JSON
+/// bytes stored with the entry, so auditors can recompute it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LogEntryBody {
+    pub kind: LogEntryKind,
+    pub resource_id: Uuid,
+    /// sha256 of the recorded document.
+    pub digest: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl LogEntryBody {
+    pub fn new(kind: LogEntryKind, resource_id: Uuid, digest: String) -> Self {
+        Self { kind, resource_id, digest: digest.to_lowercase(), recorded_at: Utc::now() }
+    }
+
+    /// Records a document by the sha256 of its content.
+    pub fn for_content(kind: LogEntryKind, resource_id: Uuid, content: &[u8]) -> Self {
+        Self::new(kind, resource_id, hex::encode(Sha256::digest(content)))
+    }
+
+    pub fn leaf_data(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub tenant_id: Uuid,
+    pub leaf_index: u64,
+    pub leaf_hash: String,
+    /// The leaf data exactly as hashed.
+    pub leaf_data: String,
+    pub body: LogEntryBody,
+}
+
+impl LogEntry {
+    pub fn new(tenant_id: Uuid, leaf_index: u64, body: LogEntryBody) -> Result<Self, serde_json::Error> {
+        let leaf_data = body.leaf_data()?;
+        Ok(Self {
+            tenant_id,
+            leaf_index,
+            leaf_hash: hex::encode(merkle::leaf_hash(leaf_data.as_bytes())),
+            leaf_data,
+            body,
+        })
+    }
+}
+
+/// The signed statement of a log's size and root hash at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TreeHead {
+    /// Key ID of the log's signing key.
+    pub log_id: String,
+    pub tree_size: u64,
+    pub root_hash: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    pub tenant_id: Uuid,
+    #[serde(flatten)]
+    pub tree_head: TreeHead,
+    pub envelope: Envelope,
+}
+
+impl SignedTreeHead {
+    pub fn sign(signer: &EnvelopeSigner, tenant_id: Uuid, leaves: &[Hash]) -> Result<Self, TransparencyLogError> {
+        Self::sign_root(signer, tenant_id, leaves.len() as u64, &merkle::root(leaves))
+    }
+
+    /// Signs a head of `tree_size` leaves whose root was computed elsewhere,
+    /// e.g. by `tree_root` from stored subtree hashes.
+    pub fn sign_root(signer: &EnvelopeSigner, tenant_id: Uuid, tree_size: u64, root: &Hash) -> Result<Self, TransparencyLogError> {
+        let tree_head = TreeHead {
+            log_id: signer.key_id().to_string(),
+            tree_size,
+            root_hash: hex::encode(root),
+            // Stored with microsecond precision; the signed payload must match what is read back
+            timestamp: Utc::now().trunc_subsecs(6),
+        };
+        let envelope = signer.sign_json(TREE_HEAD_PAYLOAD_TYPE, &tree_head)?;
+        Ok(Self { tenant_id, tree_head, envelope })
+    }
+
+    /// Checks the log's signature and that the signed payload matches the
+    /// unsigned fields.
+    pub fn verify(&self, log_key: &VerifyingKey) -> Result<(), TransparencyLogError> {
+        verify_envelope(&self.envelope, log_key)?;
+        let signed: TreeHead = self.envelope.decode_payload()?;
+        if signed != self.tree_head {
+            return Err(TransparencyLogError::TreeHeadMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> Result<Hash, MerkleError> {
+        merkle::decode_hash(&self.tree_head.root_hash)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    pub tree_size: u64,
+    pub leaf_hash: String,
+    pub root_hash: String,
+    pub hashes: Vec<String>,
+}
+
+impl InclusionProof {
+    pub fn new(leaves: &[Hash], leaf_index: u64) -> Result<Self, MerkleError> {
+        let hashes = merkle::inclusion_proof(leaves, leaf_index)?;
+        Ok(Self {
+            leaf_index,
+            tree_size: leaves.len() as u64,
+            leaf_hash: hex::encode(leaves[leaf_index as usize]),
+            root_hash: hex::encode(merkle::root(leaves)),
+            hashes: hashes.iter().map(hex::encode).collect(),
+        })
+    }
+
+    /// The subtree hashes `from_nodes` needs for this proof.
+    pub fn nodes(leaf_index: u64, tree_size: u64) -> Result<Vec<NodeId>, MerkleError> {
+        let mut nodes = vec![NodeId::leaf(leaf_index)];
+        for range in merkle::inclusion_ranges(leaf_index, tree_size)? {
+            nodes.extend(merkle::range_nodes(range));
+        }
+        nodes.extend(merkle::range_nodes(0..tree_size));
+        Ok(nodes)
+    }
+
+    /// Builds the proof from stored subtree hashes, so the log's leaves need
+    /// not all be read.
+    pub fn from_nodes(nodes: &HashMap<NodeId, Hash>, leaf_index: u64, tree_size: u64) -> Result<Self, MerkleError> {
+        let hashes = merkle::inclusion_ranges(leaf_index, tree_size)?.into_iter()
+            .map(|range| merkle::range_root(range, |id| nodes.get(&id).copied()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let leaf = NodeId::leaf(leaf_index);
+        let leaf_hash = nodes.get(&leaf).ok_or(MerkleError::MissingNode(leaf.level, leaf.index))?;
+        Ok(Self {
+            leaf_index,
+            tree_size,
+            leaf_hash: hex::encode(leaf_hash),
+            root_hash: hex::encode(tree_root(nodes, tree_size)?),
+            hashes: hashes.iter().map(hex::encode).collect(),
+        })
+    }
+
+    /// Verifies the proof against a root the caller already trusts, e.g. from
+    /// a signed tree head of the same size.
+    pub fn verify(&self, root_hash: &Hash) -> Result<(), MerkleError> {
+        let hashes = decode_all(&self.hashes)?;
+        merkle::verify_inclusion(&merkle::decode_hash(&self.leaf_hash)?, self.leaf_index, self.tree_size, &hashes, root_hash)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    pub first: u64,
+    pub second: u64,
+    pub first_root: String,
+    pub second_root: String,
+    pub hashes: Vec<String>,
+}
+
+impl ConsistencyProof {
+    /// `leaves` are the leaves of the larger tree.
+    pub fn new(leaves: &[Hash], first: u64) -> Result<Self, MerkleError> {
+        let hashes = merkle::consistency_proof(leaves, first)?;
+        Ok(Self {
+            first,
+            second: leaves.len() as u64,
+            first_root: hex::encode(merkle::root(&leaves[..first as usize])),
+            second_root: hex::encode(merkle::root(leaves)),
+            hashes: hashes.iter().map(hex::encode).collect(),
+        })
+    }
+
+    /// The subtree hashes `from_nodes` needs for this proof.
+    pub fn nodes(first: u64, second: u64) -> Result<Vec<NodeId>, MerkleError> {
+        let mut nodes = Vec::new();
+        for range in merkle::consistency_ranges(first, second)? {
+            nodes.extend(merkle::range_nodes(range));
+        }
+        nodes.extend(merkle::range_nodes(0..first));
+        nodes.extend(merkle::range_nodes(0..second));
+        Ok(nodes)
+    }
+
+    /// Builds the proof from stored subtree hashes, so the log's leaves need
+    /// not all be read.
+    pub fn from_nodes(nodes: &HashMap<NodeId, Hash>, first: u64, second: u64) -> Result<Self, MerkleError> {
+        let hashes = merkle::consistency_ranges(first, second)?.into_iter()
+            .map(|range| merkle::range_root(range, |id| nodes.get(&id).copied()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            first,
+            second,
+            first_root: hex::encode(tree_root(nodes, first)?),
+            second_root: hex::encode(tree_root(nodes, second)?),
+            hashes: hashes.iter().map(hex::encode).collect(),
+        })
+    }
+
+    /// Verifies the proof between two roots the caller already trusts.
+    pub fn verify(&self, first_root: &Hash, second_root: &Hash) -> Result<(), MerkleError> {
+        let hashes = decode_all(&self.hashes)?;
+        merkle::verify_consistency(self.first, self.second, first_root, second_root, &hashes)
+    }
+}
+
+/// The root of the first `tree_size` leaves, from the hashes of
+/// `merkle::range_nodes(0..tree_size)`.
+pub fn tree_root(nodes: &HashMap<NodeId, Hash>, tree_size: u64) -> Result<Hash, MerkleError> {
+    merkle::range_root(0..tree_size, |id| nodes.get(&id).copied())
+}
+
+/// The subtrees that become complete when the leaf at `leaf_index` is
+/// appended, with their hashes: the leaf itself, then each ancestor whose
+/// right edge it is. `node` looks up subtrees stored before.
+pub fn completed_nodes(
+    leaf_index: u64,
+    leaf_hash: Hash,
+    node: impl Fn(NodeId) -> Option<Hash>,
+) -> Result<Vec<(NodeId, Hash)>, MerkleError> {
+    let mut id = NodeId::leaf(leaf_index);
+    let mut hash = leaf_hash;
+    let mut completed = vec![(id, hash)];
+    while id.is_right_child() {
+        let sibling = id.left_sibling();
+        let left = node(sibling).ok_or(MerkleError::MissingNode(sibling.level, sibling.index))?;
+        hash = merkle::node_hash(&left, &hash);
+        id = id.parent();
+        completed.push((id, hash));
+    }
+    Ok(completed)
+}
+
+fn decode_all(hashes: &[String]) -> Result<Vec<Hash>, MerkleError> {
+    hashes.iter().map(|hash| merkle::decode_hash(hash)).collect()
+}
@@ -0,0 +1,469 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::VerifyingKey;
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use std::net::IpAddr;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::merkle::MerkleError;
+use crate::chain_of_custody::transparency_log::{ConsistencyProof, SignedTreeHead, TransparencyLogError};
+use crate::provenance::dsse::{key_id_for, verify_envelope, DsseError, EnvelopeSignature, EnvelopeSigner};
+use crate::provenance::trusted_keys::parse_ed25519_public_key;
+
+/// Secret under which each tenant's witness key is stored.
+pub const WITNESS_KEY_ID: &str = "witness_key";
+pub const DEFAULT_WITNESS_INTERVAL_SECS: u64 = 300;
+
+/// Secret under which the API token for a witnessed peer is stored.
+pub fn peer_token_secret_id(peer_id: &Uuid) -> String {
+    format!("witness_peer_token:{}", peer_id)
+}
+
+#[derive(Error, Debug)]
+pub enum WitnessError {
+    #[error("Transparency log error: {0}")]
+    Log(#[from] TransparencyLogError),
+    #[error("Envelope error: {0}")]
+    Dsse(#[from] DsseError),
+    #[error("Merkle proof error: {0}")]
+    Merkle(#[from] MerkleError),
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Peer returned {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("Invalid tree head: {0}")]
+    Invalid(String),
+    #[error("Peer URL is not allowed: {0}")]
+    ForbiddenUrl(String),
+    #[error("Unknown split view kind: {0}")]
+    UnknownSplitViewKind(String),
+}
+
+/// How often every tenant's peers are witnessed in the background. Zero
+/// disables background rounds.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct WitnessConfig {
+    pub interval_secs: u64,
+}
+
+impl Default for WitnessConfig {
+    fn default() -> Self {
+        Self { interval_secs: DEFAULT_WITNESS_INTERVAL_SECS }
+    }
+}
+
+/// Another TraceGuard instance whose transparency log this tenant witnesses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessPeer {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    /// Base URL of the peer's API, e.g. `https://traceguard.eu.example.com`.
+    pub url: String,
+    /// Key ID of the peer's log signing key; tree heads must carry it.
+    pub log_id: String,
+    /// Hex-encoded raw Ed25519 log public key.
+    pub log_public_key: String,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WitnessPeerRequest {
+    pub name: String,
+    pub url: String,
+    /// The peer's `/api/transparency/public-key`.
+    pub log_public_key: String,
+    /// Bearer token for the peer's API, stored in the secret manager.
+    pub token: Option<String>,
+}
+
+impl WitnessPeer {
+    pub fn new(tenant_id: Uuid, created_by: Uuid, request: &WitnessPeerRequest) -> Result<Self, WitnessError> {
+        if request.name.trim().is_empty() {
+            return Err(WitnessError::Invalid("name is required".to_string()));
+        }
+        let url = request.url.trim().trim_end_matches('/');
+        check_peer_url(url)?;
+        let log_key = parse_ed25519_public_key(&request.log_public_key)?;
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: request.name.trim().to_string(),
+            url: url.to_string(),
+            log_id: key_id_for(&log_key),
+            log_public_key: hex::encode(log_key.as_bytes()),
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn log_key(&self) -> Result<VerifyingKey, DsseError> {
+        parse_ed25519_public_key(&self.log_public_key)
+    }
+}
+
+/// Peers are fetched by the server, so a peer URL must not point it at itself
+/// or its private network: it must be `https` without credentials, and its
+/// host must not be a local name or a non-public address.
+pub fn check_peer_url(url: &str) -> Result<Url, WitnessError> {
+    let parsed = Url::parse(url).map_err(|e| WitnessError::ForbiddenUrl(format!("{}: {}", url, e)))?;
+    if parsed.scheme() != "https" {
+        return Err(WitnessError::ForbiddenUrl(format!("{} is not an https URL", url)));
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err(WitnessError::ForbiddenUrl(format!("{} carries credentials; use the token instead", url)));
+    }
+    let host = parsed.host_str()
+        .ok_or_else(|| WitnessError::ForbiddenUrl(format!("{} has no host", url)))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") || host.ends_with(".internal") {
+        return Err(WitnessError::ForbiddenUrl(format!("{} is a local host name", host)));
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        if !is_public_address(ip) {
+            return Err(WitnessError::ForbiddenUrl(format!("{} is not a public address", ip)));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Resolves the peer's host and checks every address it resolves to is
+/// public. Run before each round, since a name can be re-pointed after the
+/// peer was added.
+pub async fn check_peer_addresses(url: &str) -> Result<(), WitnessError> {
+    let parsed = check_peer_url(url)?;
+    let host = parsed.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']').to_string();
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let addresses = tokio::net::lookup_host((host.as_str(), port)).await
+        .map_err(|e| WitnessError::ForbiddenUrl(format!("{} does not resolve: {}", host, e)))?;
+    for address in addresses {
+        if !is_public_address(address.ip()) {
+            return Err(WitnessError::ForbiddenUrl(format!("{} resolves to {}", host, address.ip())));
+        }
+    }
+    Ok(())
+}
+
+/// Whether an address is routable on the public internet, i.e. not loopback,
+/// private, link-local, shared, documentation or otherwise reserved.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (18..20).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_address(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+/// A witness whose co-signatures this tenant's log accepts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedWitness {
+    pub tenant_id: Uuid,
+    pub key_id: String,
+    pub name: String,
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrustedWitnessRequest {
+    pub name: String,
+    /// The witness's `/api/witness/public-key`.
+    pub public_key: String,
+}
+
+impl TrustedWitness {
+    pub fn new(tenant_id: Uuid, request: TrustedWitnessRequest) -> Result<Self, WitnessError> {
+        if request.name.trim().is_empty() {
+            return Err(WitnessError::Invalid("name is required".to_string()));
+        }
+        let key = parse_ed25519_public_key(&request.public_key)?;
+        Ok(Self {
+            tenant_id,
+            key_id: key_id_for(&key),
+            name: request.name.trim().to_string(),
+            public_key: hex::encode(key.as_bytes()),
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn verifying_key(&self) -> Result<VerifyingKey, DsseError> {
+        parse_ed25519_public_key(&self.public_key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitViewKind {
+    /// Two different roots signed for the same tree size.
+    ForkedRoot,
+    /// A signed head smaller than one seen before.
+    Rollback,
+    /// The log cannot prove its new head extends the one seen before.
+    Inconsistent,
+}
+
+impl SplitViewKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SplitViewKind::ForkedRoot => "forked_root",
+            SplitViewKind::Rollback => "rollback",
+            SplitViewKind::Inconsistent => "inconsistent",
+        }
+    }
+
+}
+
+impl FromStr for SplitViewKind {
+    type Err = WitnessError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "forked_root" => Ok(SplitViewKind::ForkedRoot),
+            "rollback" => Ok(SplitViewKind::Rollback),
+            "inconsistent" => Ok(SplitViewKind::Inconsistent),
+            _ => Err(WitnessError::UnknownSplitViewKind(value.to_string())),
+        }
+    }
+}
+
+/// Two validly signed tree heads that cannot both describe one append-only
+/// log: evidence that the log showed different histories to different
+/// viewers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitView {
+    pub kind: SplitViewKind,
+    pub log_id: String,
+    /// The head seen before, or the head the log stored.
+    pub expected: SignedTreeHead,
+    /// The conflicting head.
+    pub observed: SignedTreeHead,
+}
+
+impl SplitView {
+    /// Whether two split views are the same conflict. The last witnessed head
+    /// does not advance past a split view, so later rounds find it again.
+    pub fn same_conflict(&self, other: &SplitView) -> bool {
+        let same_head = |a: &SignedTreeHead, b: &SignedTreeHead| {
+            a.tree_head.tree_size == b.tree_head.tree_size && a.tree_head.root_hash == b.tree_head.root_hash
+        };
+        self.kind == other.kind
+            && self.log_id == other.log_id
+            && same_head(&self.expected, &other.expected)
+            && same_head(&self.observed, &other.observed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitViewAlert {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// The witnessed peer, when a witness raised the alert; `None` when this
+    /// tenant's own log received a conflicting co-signed head.
+    pub peer_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub split_view: SplitView,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl SplitViewAlert {
+    pub fn new(tenant_id: Uuid, peer_id: Option<Uuid>, split_view: SplitView) -> Self {
+        Self { id: Uuid::new_v4(), tenant_id, peer_id, split_view, detected_at: Utc::now() }
+    }
+}
+
+/// Checks a peer's new head against the last head witnessed from it.
+///
+/// Returns `Err` when the head itself is unusable (bad signature, wrong log,
+/// a proof for the wrong sizes) and `Ok(Some(_))` when two validly signed
+/// heads conflict. `proof` must prove `previous` against `head` whenever the
+/// log has grown.
+pub fn check_tree_head(
+    log_key: &VerifyingKey,
+    previous: Option<&SignedTreeHead>,
+    head: &SignedTreeHead,
+    proof: Option<&ConsistencyProof>,
+) -> Result<Option<SplitView>, WitnessError> {
+    head.verify(log_key)?;
+    let log_id = key_id_for(log_key);
+    if head.tree_head.log_id != log_id {
+        return Err(WitnessError::Invalid(format!("tree head is for log {}, expected {}", head.tree_head.log_id, log_id)));
+    }
+    let Some(previous) = previous else {
+        return Ok(None);
+    };
+
+    let split_view = |kind| Some(SplitView { kind, log_id: log_id.clone(), expected: previous.clone(), observed: head.clone() });
+    let (old_size, new_size) = (previous.tree_head.tree_size, head.tree_head.tree_size);
+    if new_size < old_size {
+        return Ok(split_view(SplitViewKind::Rollback));
+    }
+    if new_size == old_size {
+        return Ok(if head.tree_head.root_hash == previous.tree_head.root_hash {
+            None
+        } else {
+            split_view(SplitViewKind::ForkedRoot)
+        });
+    }
+
+    let proof = proof.ok_or_else(|| WitnessError::Invalid("a consistency proof is required".to_string()))?;
+    if proof.first != old_size || proof.second != new_size {
+        return Err(WitnessError::Invalid(format!(
+            "consistency proof is for sizes {} to {}, expected {} to {}", proof.first, proof.second, old_size, new_size
+        )));
+    }
+    match proof.verify(&previous.root()?, &head.root()?) {
+        Ok(()) => Ok(None),
+        Err(_) => Ok(split_view(SplitViewKind::Inconsistent)),
+    }
+}
+
+/// Adds the witness's signature over the head's payload to its envelope.
+pub fn cosign_tree_head(signer: &EnvelopeSigner, head: &mut SignedTreeHead) -> Result<(), WitnessError> {
+    let signature = signer.cosign(&head.envelope)?;
+    add_cosignature(head, signature);
+    Ok(())
+}
+
+/// Adds a signature unless the envelope already carries one from that key.
+pub fn add_cosignature(head: &mut SignedTreeHead, signature: EnvelopeSignature) {
+    if !head.envelope.signatures.iter().any(|existing| existing.keyid == signature.keyid) {
+        head.envelope.signatures.push(signature);
+    }
+}
+
+/// The signatures on a head that verify against one of `witnesses`.
+pub fn witness_signatures(head: &SignedTreeHead, witnesses: &[TrustedWitness]) -> Vec<(String, EnvelopeSignature)> {
+    let mut signatures = Vec::new();
+    for witness in witnesses {
+        let Ok(key) = witness.verifying_key() else {
+            continue;
+        };
+        let found = head.envelope.signatures.iter()
+            .filter(|signature| signature.keyid == witness.key_id)
+            .find(|signature| {
+                let mut single = head.envelope.clone();
+                single.signatures = vec![(*signature).clone()];
+                verify_envelope(&single, &key).is_ok()
+            });
+        if let Some(signature) = found {
+            signatures.push((witness.name.clone(), signature.clone()));
+        }
+    }
+    signatures
+}
+
+/// What one witnessing round of a peer found.
+#[derive(Debug, Clone, Serialize)]
+pub struct WitnessRound {
+    pub peer_id: Uuid,
+    /// The peer's head, carrying this witness's signature unless a split
+    /// view was found.
+    pub head: SignedTreeHead,
+    pub cosigned: bool,
+    /// Whether the peer accepted the co-signed head.
+    pub submitted: bool,
+    pub submit_error: Option<String>,
+    pub alert: Option<SplitViewAlert>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PeerApiError {
+    #[serde(default)]
+    error: String,
+}
+
+/// Talks to a peer's transparency log API.
+pub struct WitnessClient {
+    http: reqwest::Client,
+}
+
+impl Default for WitnessClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WitnessClient {
+    /// Redirects are not followed, so a peer cannot bounce requests to an
+    /// address its URL was checked against.
+    pub fn new() -> Self {
+        let http = reqwest::Client::builder()
+            .redirect(Policy::none())
+            .build()
+            .expect("HTTP client configuration is valid");
+        Self { http }
+    }
+
+    pub async fn fetch_tree_head(&self, peer: &WitnessPeer, token: Option<&str>) -> Result<SignedTreeHead, WitnessError> {
+        let request = self.http.get(format!("{}/api/transparency/tree-head", peer.url));
+        Self::read(Self::authorize(request, token).send().await?).await
+    }
+
+    pub async fn fetch_consistency_proof(
+        &self,
+        peer: &WitnessPeer,
+        token: Option<&str>,
+        first: u64,
+        second: u64,
+    ) -> Result<ConsistencyProof, WitnessError> {
+        let request = self.http.get(format!("{}/api/transparency/consistency", peer.url))
+            .query(&[("first", first), ("second", second)]);
+        Self::read(Self::authorize(request, token).send().await?).await
+    }
+
+    /// Hands the co-signed head back to the peer, which returns it with every
+    /// co-signature it holds.
+    pub async fn submit_cosigned_head(
+        &self,
+        peer: &WitnessPeer,
+        token: Option<&str>,
+        head: &SignedTreeHead,
+    ) -> Result<SignedTreeHead, WitnessError> {
+        let request = self.http.post(format!("{}/api/transparency/cosignatures", peer.url)).json(head);
+        Self::read(Self::authorize(request, token).send().await?).await
+    }
+
+    fn authorize(request: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+        match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn read<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T, WitnessError> {
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.json::<PeerApiError>().await.map(|error| error.error).unwrap_or_default();
+            return Err(WitnessError::Api { status: status.as_u16(), message });
+        }
+        Ok(response.json().await?)
+    }
+}
@@ -1,5 +1,11 @@
 use serde::Deserialize;
 use config::{Config, ConfigError, Environment, File};
+use crate::chain_of_custody::audit_log::AuditConfig;
+use crate::chain_of_custody::fulcio::FulcioConfig;
+use crate::chain_of_custody::rekor::RekorConfig;
+use crate::chain_of_custody::timestamp::TimestampConfig;
+use crate::chain_of_custody::witness::WitnessConfig;
+use crate::provenance::cosign::CosignConfig;
 
 #[derive(Debug, Deserialize)]
 pub struct Settings {
@@ -12,6 +18,35 @@ pub struct Settings {
     pub minio_access_key: String,
     pub minio_secret_key: String,
     pub minio_use_ssl: bool,
+    /// Vault holding tenants' signing keys, e.g. `vault_addr =
+    /// "http://127.0.0.1:8200"`; the token is best set as `APP_VAULT_TOKEN`.
+    pub vault_addr: String,
+    pub vault_token: String,
+    /// Transparency log for custody records, e.g. `rekor.backend = "self_hosted"`
+    /// with `rekor.url`. Defaults to the public Rekor instance.
+    #[serde(default)]
+    pub rekor: RekorConfig,
+    /// CA for keyless document signing, e.g. `fulcio.backend = "embedded"`.
+    /// Keyless signing is off by default.
+    #[serde(default)]
+    pub fulcio: FulcioConfig,
+    /// RFC 3161 TSA for record timestamps, e.g. `tsa.backend = "remote"` with
+    /// `tsa.url` and `tsa.certificates`. Timestamping is off by default.
+    #[serde(default)]
+    pub tsa: TimestampConfig,
+    /// OCI registry holding cosign signatures and attestations, e.g.
+    /// `cosign.registry = "http://localhost:5000"`. Lookups are off without one.
+    #[serde(default)]
+    pub cosign: CosignConfig,
+    /// Seconds between signed audit log checkpoints, e.g.
+    /// `audit.checkpoint_interval_secs = 300`, and the reverse proxies whose
+    /// `X-Forwarded-For` is believed, e.g. `audit.trusted_proxies = ["10.0.0.2"]`.
+    #[serde(default)]
+    pub audit: AuditConfig,
+    /// Seconds between witnessing rounds of every tenant's peers, e.g.
+    /// `witness.interval_secs = 300`.
+    #[serde(default)]
+    pub witness: WitnessConfig,
 }
 
 impl Settings {
@@ -0,0 +1,505 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::provenance::lineage::{LineageEdge, LineageGraph};
+
+pub const PROV_NAMESPACE: &str = "http://www.w3.org/ns/prov#";
+pub const RDF_NAMESPACE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub const RDFS_NAMESPACE: &str = "http://www.w3.org/2000/01/rdf-schema#";
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+pub const TRACEGUARD_PROV_NAMESPACE: &str = "https://traceguard.dev/ns/prov#";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DataProvenanceError {
+    #[error("Dataset name and version must not be empty")]
+    MissingName,
+    #[error("A dataset version needs a digest or a file manifest")]
+    MissingDigest,
+    #[error("Invalid sha256 digest for {0}")]
+    InvalidDigest(String),
+    #[error("File {0} appears more than once in the manifest")]
+    DuplicateFile(String),
+    #[error("Digest {given} does not match the manifest digest {computed}")]
+    ManifestMismatch { given: String, computed: String },
+    #[error("A transformation needs a job name and at least one output")]
+    IncompleteTransformation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetFile {
+    pub path: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+}
+
+/// An immutable version of a dataset, e.g. a table snapshot or a directory of
+/// Parquet files. `digest` identifies the content: for versions registered
+/// with a manifest it is the manifest digest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetVersion {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub version: String,
+    pub uri: Option<String>,
+    pub digest: String,
+    pub files: Vec<DatasetFile>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterDatasetVersionRequest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub files: Vec<DatasetFile>,
+}
+
+impl DatasetVersion {
+    pub fn new(tenant_id: Uuid, created_by: Uuid, request: RegisterDatasetVersionRequest) -> Result<Self, DataProvenanceError> {
+        let name = request.name.trim().to_string();
+        let version = request.version.trim().to_string();
+        if name.is_empty() || version.is_empty() {
+            return Err(DataProvenanceError::MissingName);
+        }
+
+        let mut files = request.files;
+        for file in &mut files {
+            file.sha256 = parse_digest(&file.sha256).map_err(|_| DataProvenanceError::InvalidDigest(file.path.clone()))?;
+        }
+        let given = request.digest
+            .map(|digest| parse_digest(&digest).map_err(|_| DataProvenanceError::InvalidDigest(name.clone())))
+            .transpose()?;
+        let digest = match (given, files.is_empty()) {
+            (None, true) => return Err(DataProvenanceError::MissingDigest),
+            (Some(given), true) => given,
+            (given, false) => {
+                let computed = manifest_digest(&mut files)?;
+                match given {
+                    Some(given) if given != computed => {
+                        return Err(DataProvenanceError::ManifestMismatch { given, computed });
+                    }
+                    _ => computed,
+                }
+            }
+        };
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name,
+            version,
+            uri: request.uri,
+            digest,
+            files,
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Name used for this version in lineage edges.
+    pub fn lineage_name(&self) -> String {
+        format!("dataset:{}@{}", self.name, self.version)
+    }
+}
+
+/// Sorts the manifest by path and hashes it, so the digest does not depend
+/// on the order files were listed in.
+pub fn manifest_digest(files: &mut [DatasetFile]) -> Result<String, DataProvenanceError> {
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut hasher = Sha256::new();
+    for (index, file) in files.iter().enumerate() {
+        if index > 0 && files[index - 1].path == file.path {
+            return Err(DataProvenanceError::DuplicateFile(file.path.clone()));
+        }
+        hasher.update(format!("{}\0{}\0{}\n", file.path, file.sha256, file.size_bytes));
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// The code that ran a transformation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeVersion {
+    pub uri: String,
+    /// A git commit or other content digest of the code.
+    pub commit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetRef {
+    pub dataset_version_id: Uuid,
+    pub name: String,
+    pub version: String,
+    pub digest: String,
+}
+
+impl From<&DatasetVersion> for DatasetRef {
+    fn from(dataset: &DatasetVersion) -> Self {
+        Self {
+            dataset_version_id: dataset.id,
+            name: dataset.name.clone(),
+            version: dataset.version.clone(),
+            digest: dataset.digest.clone(),
+        }
+    }
+}
+
+/// One run of a job that read input dataset versions and wrote output ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transformation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub job_name: String,
+    pub run_id: Option<String>,
+    pub code: Option<CodeVersion>,
+    pub inputs: Vec<DatasetRef>,
+    pub outputs: Vec<DatasetRef>,
+    /// Free-form metadata about the run, kept as submitted.
+    pub facets: Value,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordTransformationRequest {
+    pub job_name: String,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub code: Option<CodeVersion>,
+    /// Dataset version IDs read by the run.
+    #[serde(default)]
+    pub inputs: Vec<Uuid>,
+    /// Dataset version IDs written by the run.
+    pub outputs: Vec<Uuid>,
+    #[serde(default)]
+    pub facets: Value,
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl Transformation {
+    /// Builds a transformation from a request whose dataset IDs have been
+    /// resolved to `inputs` and `outputs`.
+    pub fn new(
+        tenant_id: Uuid,
+        created_by: Uuid,
+        request: RecordTransformationRequest,
+        inputs: &[DatasetVersion],
+        outputs: &[DatasetVersion],
+    ) -> Result<Self, DataProvenanceError> {
+        if request.job_name.trim().is_empty() || outputs.is_empty() {
+            return Err(DataProvenanceError::IncompleteTransformation);
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            job_name: request.job_name.trim().to_string(),
+            run_id: request.run_id,
+            code: request.code,
+            inputs: inputs.iter().map(DatasetRef::from).collect(),
+            outputs: outputs.iter().map(DatasetRef::from).collect(),
+            facets: request.facets,
+            started_at: request.started_at,
+            ended_at: request.ended_at,
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Lineage edges from every input, and the code version, to every output.
+    /// They share the table used for build provenance, so data and software
+    /// lineage can be traversed together.
+    pub fn lineage_edges(&self) -> Vec<LineageEdge> {
+        let inputs: Vec<(String, String)> = self.inputs.iter()
+            .map(|input| (input.digest.clone(), format!("dataset:{}@{}", input.name, input.version)))
+            .chain(self.code.iter().map(|code| (code.commit.clone(), code.uri.clone())))
+            .collect();
+
+        self.outputs.iter()
+            .flat_map(|output| {
+                inputs.iter().map(move |(digest, uri)| LineageEdge {
+                    provenance_id: self.id,
+                    input_digest: digest.clone(),
+                    input_uri: uri.clone(),
+                    output_digest: output.digest.clone(),
+                    output_name: format!("dataset:{}@{}", output.name, output.version),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The dataset versions and transformations upstream of a dataset version.
+#[derive(Debug, Serialize)]
+pub struct DataLineage {
+    pub root: DatasetVersion,
+    pub datasets: Vec<DatasetVersion>,
+    pub transformations: Vec<Transformation>,
+    /// Code versions that ran the transformations.
+    pub code: Vec<CodeVersion>,
+    /// Build provenance records on the upstream path, e.g. for a UDF jar.
+    pub provenance_ids: Vec<Uuid>,
+    pub truncated: bool,
+}
+
+impl DataLineage {
+    /// Assembles the lineage from an upstream traversal of `root.digest` and
+    /// the dataset versions and transformations it reached.
+    pub fn new(root: DatasetVersion, graph: &LineageGraph, datasets: Vec<DatasetVersion>, transformations: Vec<Transformation>) -> Self {
+        let transformation_ids: HashSet<Uuid> = transformations.iter().map(|transformation| transformation.id).collect();
+        let provenance_ids: BTreeSet<Uuid> = graph.edges.iter()
+            .map(|edge| edge.provenance_id)
+            .filter(|id| !transformation_ids.contains(id))
+            .collect();
+        let mut seen = HashSet::new();
+        let code = transformations.iter()
+            .filter_map(|transformation| transformation.code.clone())
+            .filter(|code| seen.insert((code.uri.clone(), code.commit.clone())))
+            .collect();
+        Self {
+            datasets: datasets.into_iter().filter(|dataset| dataset.id != root.id).collect(),
+            root,
+            transformations,
+            code,
+            provenance_ids: provenance_ids.into_iter().collect(),
+            truncated: graph.truncated,
+        }
+    }
+
+    pub fn to_prov(&self) -> ProvDocument {
+        let mut document = ProvDocument::default();
+        let mut datasets: BTreeMap<Uuid, &DatasetVersion> = self.datasets.iter().map(|dataset| (dataset.id, dataset)).collect();
+        datasets.insert(self.root.id, &self.root);
+
+        for dataset in datasets.values() {
+            let entity = dataset_iri(dataset.id);
+            document.add(&entity, RDF_TYPE, ProvTerm::iri(format!("{}Entity", PROV_NAMESPACE)));
+            document.add(&entity, RDFS_LABEL, ProvTerm::literal(dataset.lineage_name()));
+            document.add(&entity, &tg("sha256"), ProvTerm::literal(dataset.digest.clone()));
+            document.add(&entity, &prov("generatedAtTime"), ProvTerm::date_time(dataset.created_at));
+            if let Some(uri) = &dataset.uri {
+                document.add(&entity, &tg("uri"), ProvTerm::literal(uri.clone()));
+            }
+        }
+
+        for transformation in &self.transformations {
+            let activity = transformation_iri(transformation.id);
+            let agent = format!("urn:traceguard:user:{}", transformation.created_by);
+            document.add(&activity, RDF_TYPE, ProvTerm::iri(format!("{}Activity", PROV_NAMESPACE)));
+            document.add(&activity, RDFS_LABEL, ProvTerm::literal(transformation.job_name.clone()));
+            document.add(&activity, &prov("wasAssociatedWith"), ProvTerm::iri(agent.clone()));
+            document.add(&agent, RDF_TYPE, ProvTerm::iri(format!("{}Agent", PROV_NAMESPACE)));
+            if let Some(run_id) = &transformation.run_id {
+                document.add(&activity, &tg("runId"), ProvTerm::literal(run_id.clone()));
+            }
+            if let Some(started_at) = transformation.started_at {
+                document.add(&activity, &prov("startedAtTime"), ProvTerm::date_time(started_at));
+            }
+            if let Some(ended_at) = transformation.ended_at {
+                document.add(&activity, &prov("endedAtTime"), ProvTerm::date_time(ended_at));
+            }
+            if let Some(code) = &transformation.code {
+                let code_entity = code_iri(code);
+                document.add(&code_entity, RDF_TYPE, ProvTerm::iri(format!("{}Entity", PROV_NAMESPACE)));
+                document.add(&code_entity, RDFS_LABEL, ProvTerm::literal(code.uri.clone()));
+                document.add(&code_entity, &tg("commit"), ProvTerm::literal(code.commit.clone()));
+                document.add(&activity, &prov("used"), ProvTerm::iri(code_entity));
+            }
+            for input in &transformation.inputs {
+                document.add(&activity, &prov("used"), ProvTerm::iri(dataset_iri(input.dataset_version_id)));
+            }
+            for output in &transformation.outputs {
+                let entity = dataset_iri(output.dataset_version_id);
+                document.add(&entity, &prov("wasGeneratedBy"), ProvTerm::iri(activity.clone()));
+                for input in &transformation.inputs {
+                    document.add(&entity, &prov("wasDerivedFrom"), ProvTerm::iri(dataset_iri(input.dataset_version_id)));
+                }
+            }
+        }
+        document
+    }
+}
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+
+fn prov(term: &str) -> String {
+    format!("{}{}", PROV_NAMESPACE, term)
+}
+
+fn tg(term: &str) -> String {
+    format!("{}{}", TRACEGUARD_PROV_NAMESPACE, term)
+}
+
+fn dataset_iri(id: Uuid) -> String {
+    format!("urn:traceguard:dataset:{}", id)
+}
+
+fn transformation_iri(id: Uuid) -> String {
+    format!("urn:traceguard:transformation:{}", id)
+}
+
+fn code_iri(code: &CodeVersion) -> String {
+    format!("urn:traceguard:code:{}", hex::encode(Sha256::digest(format!("{}@{}", code.uri, code.commit))))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProvTerm {
+    Iri(String),
+    Literal { value: String, datatype: Option<String> },
+}
+
+impl ProvTerm {
+    fn iri(value: impl Into<String>) -> Self {
+        ProvTerm::Iri(value.into())
+    }
+
+    fn literal(value: impl Into<String>) -> Self {
+        ProvTerm::Literal { value: value.into(), datatype: None }
+    }
+
+    fn date_time(value: DateTime<Utc>) -> Self {
+        ProvTerm::Literal { value: value.to_rfc3339(), datatype: Some(format!("{}dateTime", XSD_NAMESPACE)) }
+    }
+}
+
+/// A PROV-O graph as subject → predicate → objects, serializable as JSON-LD or Turtle.
+#[derive(Debug, Default)]
+pub struct ProvDocument {
+    statements: BTreeMap<String, BTreeMap<String, BTreeSet<ProvTerm>>>,
+}
+
+const PREFIXES: [(&str, &str); 5] = [
+    ("prov", PROV_NAMESPACE),
+    ("rdf", RDF_NAMESPACE),
+    ("rdfs", RDFS_NAMESPACE),
+    ("xsd", XSD_NAMESPACE),
+    ("tg", TRACEGUARD_PROV_NAMESPACE),
+];
+
+impl ProvDocument {
+    pub fn add(&mut self, subject: &str, predicate: &str, object: ProvTerm) {
+        self.statements.entry(subject.to_string()).or_default().entry(predicate.to_string()).or_default().insert(object);
+    }
+
+    pub fn contains(&self, subject: &str, predicate: &str, object: &ProvTerm) -> bool {
+        self.statements.get(subject)
+            .and_then(|predicates| predicates.get(predicate))
+            .map_or(false, |objects| objects.contains(object))
+    }
+
+    pub fn to_json_ld(&self) -> Value {
+        let context: Map<String, Value> = PREFIXES.iter()
+            .map(|(prefix, namespace)| (prefix.to_string(), json!(namespace)))
+            .collect();
+        let graph: Vec<Value> = self.statements.iter()
+            .map(|(subject, predicates)| {
+                let mut node = Map::new();
+                node.insert("@id".to_string(), json!(subject));
+                for (predicate, objects) in predicates {
+                    if predicate == RDF_TYPE {
+                        let types: Vec<Value> = objects.iter()
+                            .filter_map(|object| match object {
+                                ProvTerm::Iri(iri) => Some(json!(compact(iri))),
+                                ProvTerm::Literal { .. } => None,
+                            })
+                            .collect();
+                        node.insert("@type".to_string(), Value::Array(types));
+                        continue;
+                    }
+                    let values = objects.iter()
+                        .map(|object| match object {
+                            ProvTerm::Iri(iri) => json!({ "@id": iri }),
+                            ProvTerm::Literal { value, datatype: Some(datatype) } => json!({ "@value": value, "@type": compact(datatype) }),
+                            ProvTerm::Literal { value, datatype: None } => json!(value),
+                        })
+                        .collect();
+                    node.insert(compact(predicate), Value::Array(values));
+                }
+                Value::Object(node)
+            })
+            .collect();
+        json!({ "@context": context, "@graph": graph })
+    }
+
+    pub fn to_turtle(&self) -> String {
+        let mut turtle: String = PREFIXES.iter()
+            .map(|(prefix, namespace)| format!("@prefix {}: <{}> .\n", prefix, namespace))
+            .collect();
+        for (subject, predicates) in &self.statements {
+            turtle.push('\n');
+            turtle.push_str(&turtle_iri(subject));
+            let predicates: Vec<String> = predicates.iter()
+                .map(|(predicate, objects)| {
+                    let predicate = if predicate == RDF_TYPE { "a".to_string() } else { turtle_iri(predicate) };
+                    let objects: Vec<String> = objects.iter()
+                        .map(|object| match object {
+                            ProvTerm::Iri(iri) => turtle_iri(iri),
+                            ProvTerm::Literal { value, datatype } => {
+                                let literal = format!("\"{}\"", escape_turtle(value));
+                                match datatype {
+                                    Some(datatype) => format!("{}^^{}", literal, turtle_iri(datatype)),
+                                    None => literal,
+                                }
+                            }
+                        })
+                        .collect();
+                    format!("    {} {}", predicate, objects.join(", "))
+                })
+                .collect();
+            turtle.push('\n');
+            turtle.push_str(&predicates.join(" ;\n"));
+            turtle.push_str(" .\n");
+        }
+        turtle
+    }
+}
+
+/// Shortens an IRI to `prefix:local` when it is in a known namespace.
+fn compact(iri: &str) -> String {
+    PREFIXES.iter()
+        .find_map(|(prefix, namespace)| {
+            let local = iri.strip_prefix(namespace)?;
+            Some(format!("{}:{}", prefix, local)).filter(|_| !local.is_empty() && local.chars().all(|c| c.is_ascii_alphanumeric()))
+        })
+        .unwrap_or_else(|| iri.to_string())
+}
+
+fn turtle_iri(iri: &str) -> String {
+    let compacted = compact(iri);
+    if compacted != iri {
+        return compacted;
+    }
+    // Characters that may not appear in an IRIREF are percent-encoded
+    let escaped: String = iri.chars()
+        .map(|c| match c {
+            '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\' => format!("%{:02X}", c as u32),
+            c if c <= ' ' => format!("%{:02X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect();
+    format!("<{}>", escaped)
+}
+
+fn escape_turtle(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
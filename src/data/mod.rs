@@ -0,0 +1,2 @@
+pub mod data_provenance;
+pub mod openlineage;
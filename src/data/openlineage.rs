@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::data::data_provenance::{CodeVersion, RegisterDatasetVersionRequest};
+
+/// Version given to input datasets that carry no version facet and have
+/// never been registered.
+pub const UNVERSIONED: &str = "unversioned";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EventType {
+    Start,
+    Running,
+    Complete,
+    Abort,
+    Fail,
+    Other,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::Start => "START",
+            EventType::Running => "RUNNING",
+            EventType::Complete => "COMPLETE",
+            EventType::Abort => "ABORT",
+            EventType::Fail => "FAIL",
+            EventType::Other => "OTHER",
+        }
+    }
+}
+
+/// An OpenLineage `RunEvent`. Unknown fields are ignored; facets are kept as-is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvent {
+    #[serde(default)]
+    pub event_type: Option<EventType>,
+    pub event_time: DateTime<Utc>,
+    pub run: Run,
+    pub job: Job,
+    #[serde(default)]
+    pub inputs: Vec<OpenLineageDataset>,
+    #[serde(default)]
+    pub outputs: Vec<OpenLineageDataset>,
+    pub producer: String,
+    #[serde(rename = "schemaURL", default)]
+    pub schema_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub run_id: Uuid,
+    #[serde(default)]
+    pub facets: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub namespace: String,
+    pub name: String,
+    #[serde(default)]
+    pub facets: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenLineageDataset {
+    pub namespace: String,
+    pub name: String,
+    #[serde(default)]
+    pub facets: Map<String, Value>,
+    #[serde(default)]
+    pub input_facets: Map<String, Value>,
+    #[serde(default)]
+    pub output_facets: Map<String, Value>,
+}
+
+impl RunEvent {
+    /// Events without an `eventType` are treated as `OTHER`, as the spec allows.
+    pub fn event_type(&self) -> EventType {
+        self.event_type.unwrap_or(EventType::Other)
+    }
+
+    pub fn job_name(&self) -> String {
+        format!("{}/{}", self.job.namespace, self.job.name)
+    }
+
+    /// The code version from the job's `sourceCodeLocation` facet.
+    pub fn code_version(&self) -> Option<CodeVersion> {
+        let location = self.job.facets.get("sourceCodeLocation")?;
+        Some(CodeVersion {
+            uri: location["url"].as_str().or_else(|| location["repoUrl"].as_str())?.to_string(),
+            commit: location["version"].as_str()?.to_string(),
+        })
+    }
+
+    /// All run, job and dataset facets, keyed so they can be shown next to
+    /// the transformation without loss.
+    pub fn transformation_facets(&self) -> Value {
+        let datasets = |datasets: &[OpenLineageDataset]| -> Map<String, Value> {
+            datasets.iter()
+                .map(|dataset| (dataset.qualified_name(), json!({
+                    "facets": dataset.facets,
+                    "inputFacets": dataset.input_facets,
+                    "outputFacets": dataset.output_facets,
+                })))
+                .collect()
+        };
+        json!({
+            "openlineage": {
+                "producer": self.producer,
+                "schemaURL": self.schema_url,
+                "run": self.run.facets,
+                "job": self.job.facets,
+                "inputs": datasets(&self.inputs),
+                "outputs": datasets(&self.outputs),
+            }
+        })
+    }
+}
+
+impl OpenLineageDataset {
+    /// `namespace/name`, e.g. `postgres://db:5432/analytics.public.orders`.
+    pub fn qualified_name(&self) -> String {
+        format!("{}/{}", self.namespace, self.name)
+    }
+
+    /// The version from the `version` dataset facet, e.g. an Iceberg snapshot ID.
+    pub fn version_facet(&self) -> Option<String> {
+        match self.facets.get("version")?.get("datasetVersion")? {
+            Value::String(version) => Some(version.clone()),
+            Value::Number(version) => Some(version.to_string()),
+            _ => None,
+        }
+    }
+
+    /// OpenLineage does not carry content digests, so the version is
+    /// identified by a digest of its qualified name and version instead.
+    pub fn register_request(&self, version: String) -> RegisterDatasetVersionRequest {
+        RegisterDatasetVersionRequest {
+            name: self.qualified_name(),
+            digest: Some(identity_digest(&self.qualified_name(), &version)),
+            version,
+            uri: Some(self.qualified_name()),
+            files: Vec::new(),
+        }
+    }
+}
+
+pub fn identity_digest(name: &str, version: &str) -> String {
+    hex::encode(Sha256::digest(format!("openlineage:{}@{}", name, version)))
+}
+
+/// What an accepted event produced.
+#[derive(Debug, Serialize)]
+pub struct LineageEventResult {
+    pub event_id: Uuid,
+    pub event_type: EventType,
+    /// Set when a `COMPLETE` event was recorded as a transformation.
+    pub transformation_id: Option<Uuid>,
+}
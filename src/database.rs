@@ -1,4 +1,34 @@
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions};
+use sqlx::types::Json;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use crate::chain_of_custody::audit_log::{AuditChannel, AuditCheckpoint, AuditCheckpointBody, AuditEntry, AuditQuery, AuditRecord};
+use crate::chain_of_custody::custody_events::{CustodyEvent, CustodyEventType, CustodyPolicy, EvidenceRef, RequiredEvent};
+use crate::chain_of_custody::merkle::{self, Hash, NodeId};
+use crate::chain_of_custody::promotion::{
+    PromotionCheck, PromotionDecision, PromotionDecisionBody, PromotionOutcome, PromotionPolicy, PromotionRules,
+};
+use crate::chain_of_custody::signing::{LogEntryRef, SignatureBundle};
+use crate::chain_of_custody::timestamp::{RecordTimestamp, TimestampToken};
+use crate::chain_of_custody::transparency_log::{completed_nodes, LogEntry, LogEntryBody, LogEntryKind, SignedTreeHead, TreeHead};
+use crate::chain_of_custody::witness::{SplitView, SplitViewAlert, SplitViewKind, TrustedWitness, WitnessPeer};
+use crate::data::data_provenance::{CodeVersion, DatasetFile, DatasetRef, DatasetVersion, Transformation};
+use crate::data::openlineage::{EventType, RunEvent};
+use crate::models::model_registry::{DatasetVersionRef, ModelCard, ModelVersion, RegisteredModel, WeightDigest};
+use crate::models::model_signing::{ModelSignature, ModelSignatureBundle};
+use crate::provenance::attestations::{Attestation, AttestationKind};
+use crate::provenance::cosign::{CosignImport, ImportedRecordKind};
+use crate::provenance::dsse::{Envelope, EnvelopeSignature};
+use crate::provenance::lineage::{record_edges, LineageEdge, LineageStore};
+use crate::provenance::policy::BuilderTrustPolicy;
+use crate::provenance::query::{ProvenanceCursor, ProvenanceQuery};
+use crate::provenance::reproducibility::ReproducibilityReport;
+use crate::provenance::source::{AllowedSigner, SourceTrustPolicy, SourceVerification};
+use crate::provenance::revisions::{ProvenanceRevision, RevisionKind};
+use crate::provenance::revocation::{Revocation, RevocationTarget};
+use crate::models::provenance::ProvenanceRecord;
+use crate::provenance::trusted_keys::TrustedKey;
+use crate::provenance::vsa::{VerificationResult, VerificationSummary};
 use tracing::{error, info};
 use thiserror::Error;
 
@@ -8,6 +38,10 @@ pub enum DatabaseError {
     ConnectionError(#[from] sqlx::Error),
     #[error("Query execution failed: {0}")]
     QueryError(#[from] sqlx::Error),
+    #[error("Failed to serialize record: {0}")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("Invalid stored value: {0}")]
+    InvalidValue(String),
 }
 
 pub struct Database {
@@ -63,4 +97,2741 @@ impl Database {
     }
 
     // Implement similar methods for provenance records
-}
\ No newline at end of file
+
+    pub async fn create_verification_report(&self, report: &crate::models::VerificationReport) -> Result<(), DatabaseError> {
+        info!("Storing verification report for provenance record {}", report.provenance_id);
+        sqlx::query!(
+            "INSERT INTO provenance_verifications (id, provenance_id, verified_by, verified_at, passed, checks) VALUES ($1, $2, $3, $4, $5, $6)",
+            report.id,
+            report.provenance_id,
+            report.verified_by,
+            report.verified_at,
+            report.passed,
+            Json(&report.checks) as _
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store verification report: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_verification_reports(&self, tenant_id: &Uuid, provenance_id: &Uuid) -> Result<Vec<crate::models::VerificationReport>, DatabaseError> {
+        info!("Fetching verification history for provenance record {}", provenance_id);
+        let rows = sqlx::query!(
+            r#"
+            SELECT v.id, v.provenance_id, v.verified_by, v.verified_at, v.passed,
+                   v.checks as "checks: Json<Vec<crate::models::VerificationCheck>>"
+            FROM provenance_verifications v
+            JOIN provenance_index i ON i.provenance_id = v.provenance_id
+            WHERE v.provenance_id = $1 AND i.tenant_id = $2
+            ORDER BY v.verified_at DESC
+            "#,
+            provenance_id,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch verification history: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter()
+            .map(|row| crate::models::VerificationReport {
+                id: row.id,
+                provenance_id: row.provenance_id,
+                verified_by: row.verified_by,
+                verified_at: row.verified_at,
+                passed: row.passed,
+                checks: row.checks.0,
+            })
+            .collect())
+    }
+
+    pub async fn create_builder_policy(&self, policy: &BuilderTrustPolicy) -> Result<(), DatabaseError> {
+        info!("Creating builder trust policy {} for tenant {}", policy.name, policy.tenant_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO builder_trust_policies
+                (id, tenant_id, name, description, subject_pattern, allowed_builder_ids,
+                 allowed_build_types, allowed_source_uris, allowed_source_refs, require_reproducible,
+                 enabled, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+            policy.id,
+            policy.tenant_id,
+            policy.name,
+            policy.description,
+            policy.subject_pattern,
+            &policy.allowed_builder_ids,
+            &policy.allowed_build_types,
+            &policy.allowed_source_uris,
+            &policy.allowed_source_refs,
+            policy.require_reproducible,
+            policy.enabled,
+            policy.created_at,
+            policy.updated_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to create builder trust policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn get_builder_policy(&self, tenant_id: &Uuid, id: &Uuid) -> Result<Option<BuilderTrustPolicy>, DatabaseError> {
+        sqlx::query_as!(
+            BuilderTrustPolicy,
+            "SELECT * FROM builder_trust_policies WHERE tenant_id = $1 AND id = $2",
+            tenant_id,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch builder trust policy: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn list_builder_policies(&self, tenant_id: &Uuid) -> Result<Vec<BuilderTrustPolicy>, DatabaseError> {
+        sqlx::query_as!(
+            BuilderTrustPolicy,
+            "SELECT * FROM builder_trust_policies WHERE tenant_id = $1 ORDER BY name",
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list builder trust policies: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn update_builder_policy(&self, policy: &BuilderTrustPolicy) -> Result<(), DatabaseError> {
+        info!("Updating builder trust policy {}", policy.id);
+        sqlx::query!(
+            r#"
+            UPDATE builder_trust_policies
+            SET name = $3, description = $4, subject_pattern = $5, allowed_builder_ids = $6,
+                allowed_build_types = $7, allowed_source_uris = $8, allowed_source_refs = $9,
+                require_reproducible = $10, enabled = $11, updated_at = $12
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+            policy.tenant_id,
+            policy.id,
+            policy.name,
+            policy.description,
+            policy.subject_pattern,
+            &policy.allowed_builder_ids,
+            &policy.allowed_build_types,
+            &policy.allowed_source_uris,
+            &policy.allowed_source_refs,
+            policy.require_reproducible,
+            policy.enabled,
+            policy.updated_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to update builder trust policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn delete_builder_policy(&self, tenant_id: &Uuid, id: &Uuid) -> Result<bool, DatabaseError> {
+        info!("Deleting builder trust policy {}", id);
+        let result = sqlx::query!(
+            "DELETE FROM builder_trust_policies WHERE tenant_id = $1 AND id = $2",
+            tenant_id,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete builder trust policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn create_verification_summary(&self, summary: &VerificationSummary) -> Result<(), DatabaseError> {
+        info!("Storing verification summary {} for provenance record {}", summary.id, summary.provenance_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO verification_summaries
+                (id, tenant_id, provenance_id, verification_report_id, subject_digests,
+                 verification_result, verified_level, envelope, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            summary.id,
+            summary.tenant_id,
+            summary.provenance_id,
+            summary.verification_report_id,
+            &summary.subject_digests,
+            summary.verification_result.as_str(),
+            summary.verified_level,
+            Json(&summary.envelope) as _,
+            summary.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store verification summary: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_verification_summaries_by_digest(&self, tenant_id: &Uuid, digest: &str) -> Result<Vec<VerificationSummary>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, provenance_id, verification_report_id, subject_digests,
+                   verification_result, verified_level,
+                   envelope as "envelope: Json<Envelope>",
+                   created_at
+            FROM verification_summaries
+            WHERE tenant_id = $1 AND $2 = ANY(subject_digests)
+            ORDER BY created_at DESC
+            "#,
+            tenant_id,
+            digest
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch verification summaries: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter()
+            .map(|row| Ok(VerificationSummary {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                provenance_id: row.provenance_id,
+                verification_report_id: row.verification_report_id,
+                subject_digests: row.subject_digests,
+                verification_result: row.verification_result.parse::<VerificationResult>()
+                    .map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                verified_level: row.verified_level,
+                envelope: row.envelope.0,
+                created_at: row.created_at,
+            }))
+            .collect()
+    }
+
+    pub async fn create_trusted_key(&self, key: &TrustedKey) -> Result<(), DatabaseError> {
+        info!("Registering trusted key {} for tenant {}", key.key_id, key.tenant_id);
+        sqlx::query!(
+            "INSERT INTO trusted_keys (id, tenant_id, key_id, algorithm, public_key, description, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            key.id,
+            key.tenant_id,
+            key.key_id,
+            key.algorithm,
+            key.public_key,
+            key.description,
+            key.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to register trusted key: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_trusted_keys(&self, tenant_id: &Uuid) -> Result<Vec<TrustedKey>, DatabaseError> {
+        sqlx::query_as!(
+            TrustedKey,
+            "SELECT * FROM trusted_keys WHERE tenant_id = $1 ORDER BY created_at",
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list trusted keys: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn create_reproducibility_report(&self, report: &ReproducibilityReport) -> Result<(), DatabaseError> {
+        info!("Storing reproducibility report {} for subject {}", report.id, report.subject_name);
+        sqlx::query!(
+            r#"
+            INSERT INTO reproducibility_checks
+                (id, tenant_id, subject_name, subject_digest, provenance_ids, independent_builders, reproduced, report, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            report.id,
+            report.tenant_id,
+            report.subject_name,
+            report.subject_digest,
+            &report.provenance_ids,
+            &report.independent_builders,
+            report.reproduced,
+            Json(report) as _,
+            report.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store reproducibility report: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_reproducibility_reports(&self, tenant_id: &Uuid, digest: &str) -> Result<Vec<ReproducibilityReport>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT report as "report: Json<ReproducibilityReport>"
+            FROM reproducibility_checks
+            WHERE tenant_id = $1 AND subject_digest = $2
+            ORDER BY created_at DESC
+            "#,
+            tenant_id,
+            digest
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch reproducibility reports: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| row.report.0).collect())
+    }
+
+    /// Returns the subset of `digests` that have been independently reproduced.
+    pub async fn list_reproduced_digests(&self, tenant_id: &Uuid, digests: &[String]) -> Result<HashSet<String>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT subject_digest as "subject_digest!"
+            FROM reproducibility_checks
+            WHERE tenant_id = $1 AND reproduced AND subject_digest = ANY($2)
+            "#,
+            tenant_id,
+            digests
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch reproduced digests: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| row.subject_digest).collect())
+    }
+
+    /// Lists a record's revisions, oldest first. Revisions carry no tenant of
+    /// their own, so the record's index row scopes them to `tenant_id`.
+    pub async fn list_provenance_revisions(&self, tenant_id: &Uuid, provenance_id: &Uuid) -> Result<Vec<ProvenanceRevision>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT r.id, r.provenance_id, r.revision, r.kind,
+                   r.content as "content: Json<ProvenanceRecord>",
+                   r.content_hash, r.previous_hash, r.chain_hash, r.reason, r.created_by, r.created_at
+            FROM provenance_revisions r
+            JOIN provenance_index i ON i.provenance_id = r.provenance_id
+            WHERE r.provenance_id = $1 AND i.tenant_id = $2
+            ORDER BY r.revision
+            "#,
+            provenance_id,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch provenance revisions: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter()
+            .map(|row| Ok(ProvenanceRevision {
+                id: row.id,
+                provenance_id: row.provenance_id,
+                revision: row.revision,
+                kind: row.kind.parse::<RevisionKind>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                content: row.content.map(|content| content.0),
+                content_hash: row.content_hash,
+                previous_hash: row.previous_hash,
+                chain_hash: row.chain_hash,
+                reason: row.reason,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            }))
+            .collect()
+    }
+
+    /// Stores the initial revisions, lineage edges, index rows and log entries
+    /// of newly created or ingested records in a single transaction. Either
+    /// every record in the batch becomes visible or none does. The records'
+    /// content is stored beforehand.
+    pub async fn ingest_provenance_batch(&self, tenant_id: &Uuid, batch: &[ProvenanceRevision]) -> Result<(), DatabaseError> {
+        info!("Ingesting batch of {} provenance records", batch.len());
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        let mut log_entries = Vec::with_capacity(batch.len());
+        for revision in batch {
+            insert_provenance_revision(&mut tx, revision).await?;
+            if let Some(record) = &revision.content {
+                insert_lineage_edges(&mut tx, tenant_id, &record_edges(record)).await?;
+                upsert_provenance_index_row(&mut tx, tenant_id, record).await?;
+            }
+            log_entries.push(LogEntryBody::new(LogEntryKind::Provenance, revision.provenance_id, revision.content_hash.clone()));
+        }
+        append_log_entries(&mut tx, tenant_id, &log_entries).await?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit provenance ingest batch: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Appends an updated revision, replaces the record's lineage edges with
+    /// those of the new content, and refreshes its index row and log entry in
+    /// a single transaction. The `(provenance_id, revision)` uniqueness
+    /// constraint rejects a concurrent writer that raced on the same
+    /// predecessor, so a record never ends up with one writer's revision and
+    /// another's index row. The revision's content is stored beforehand.
+    pub async fn record_provenance_update(&self, tenant_id: &Uuid, revision: &ProvenanceRevision) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        insert_provenance_revision(&mut tx, revision).await?;
+        sqlx::query!(
+            "DELETE FROM provenance_lineage_edges WHERE provenance_id = $1 AND tenant_id = $2",
+            revision.provenance_id,
+            tenant_id
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to remove superseded lineage edges: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        if let Some(record) = &revision.content {
+            insert_lineage_edges(&mut tx, tenant_id, &record_edges(record)).await?;
+            upsert_provenance_index_row(&mut tx, tenant_id, record).await?;
+        }
+        let log_entry = LogEntryBody::new(LogEntryKind::Provenance, revision.provenance_id, revision.content_hash.clone());
+        append_log_entries(&mut tx, tenant_id, std::slice::from_ref(&log_entry)).await?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit provenance revision: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Appends a tombstone, hides the record from the index and logs the
+    /// deletion in a single transaction.
+    pub async fn record_provenance_tombstone(&self, tenant_id: &Uuid, tombstone: &ProvenanceRevision) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        insert_provenance_revision(&mut tx, tombstone).await?;
+        sqlx::query!(
+            "UPDATE provenance_index SET deleted = TRUE WHERE provenance_id = $1 AND tenant_id = $2",
+            tombstone.provenance_id,
+            tenant_id
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to mark provenance record deleted: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        let log_entry = LogEntryBody::new(LogEntryKind::Provenance, tombstone.provenance_id, tombstone.content_hash.clone());
+        append_log_entries(&mut tx, tenant_id, std::slice::from_ref(&log_entry)).await?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit provenance tombstone: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Runs a filtered, keyset-paginated query. Fetches `limit` rows after
+    /// `cursor`, newest first.
+    pub async fn query_provenance(
+        &self,
+        tenant_id: &Uuid,
+        query: &ProvenanceQuery,
+        cursor: Option<ProvenanceCursor>,
+        limit: i64,
+    ) -> Result<Vec<ProvenanceRecord>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT content as "content: Json<ProvenanceRecord>"
+            FROM provenance_index
+            WHERE tenant_id = $1
+              AND NOT deleted
+              AND ($2::text IS NULL OR $2 = ANY(subject_digests))
+              AND ($3::text IS NULL OR builder_id = $3)
+              AND ($4::text IS NULL OR build_type = $4)
+              AND ($5::text IS NULL OR source_uri = $5)
+              AND ($6::text IS NULL OR source_ref = $6)
+              AND ($7::uuid IS NULL OR created_by = $7)
+              AND ($8::timestamptz IS NULL OR created_at >= $8)
+              AND ($9::timestamptz IS NULL OR created_at < $9)
+              AND ($10::timestamptz IS NULL OR (created_at, provenance_id) < ($10, $11::uuid))
+            ORDER BY created_at DESC, provenance_id DESC
+            LIMIT $12
+            "#,
+            tenant_id,
+            query.subject_digest,
+            query.builder_id,
+            query.build_type,
+            query.source_uri,
+            query.source_ref,
+            query.created_by,
+            query.created_after,
+            query.created_before,
+            cursor.map(|cursor| cursor.created_at),
+            cursor.map(|cursor| cursor.id),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to query provenance records: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| row.content.0).collect())
+    }
+
+    pub async fn create_attestation(&self, attestation: &Attestation) -> Result<(), DatabaseError> {
+        info!("Storing {} attestation {}", attestation.kind.as_str(), attestation.id);
+        sqlx::query!(
+            r#"
+            INSERT INTO attestations
+                (id, tenant_id, predicate_type, kind, subject_names, subject_digests, predicate, passed,
+                 verified_key_id, envelope, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            attestation.id,
+            attestation.tenant_id,
+            attestation.predicate_type,
+            attestation.kind.as_str(),
+            &attestation.subject_names,
+            &attestation.subject_digests,
+            Json(&attestation.predicate) as _,
+            attestation.passed,
+            attestation.verified_key_id,
+            Json(&attestation.envelope) as _,
+            attestation.created_by,
+            attestation.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store attestation: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Lists attestations about an artifact, optionally restricted to one kind.
+    pub async fn list_attestations_by_digest(
+        &self,
+        tenant_id: &Uuid,
+        digest: &str,
+        kind: Option<AttestationKind>,
+    ) -> Result<Vec<Attestation>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, predicate_type, kind, subject_names, subject_digests,
+                   predicate as "predicate: Json<serde_json::Value>",
+                   passed, verified_key_id,
+                   envelope as "envelope: Json<Envelope>",
+                   created_by, created_at
+            FROM attestations
+            WHERE tenant_id = $1 AND $2 = ANY(subject_digests) AND ($3::text IS NULL OR kind = $3)
+            ORDER BY created_at DESC
+            "#,
+            tenant_id,
+            digest,
+            kind.map(|kind| kind.as_str())
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch attestations: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter()
+            .map(|row| Ok(Attestation {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                predicate_type: row.predicate_type,
+                kind: row.kind.parse::<AttestationKind>()
+                    .map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                subject_names: row.subject_names,
+                subject_digests: row.subject_digests,
+                predicate: row.predicate.0,
+                passed: row.passed,
+                verified_key_id: row.verified_key_id,
+                envelope: row.envelope.0,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            }))
+            .collect()
+    }
+
+    pub async fn get_source_trust_policy(&self, tenant_id: &Uuid) -> Result<Option<SourceTrustPolicy>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT tenant_id, allowed_signers as "allowed_signers: Json<Vec<AllowedSigner>>", protected_refs, updated_at
+            FROM source_trust_policies
+            WHERE tenant_id = $1
+            "#,
+            tenant_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch source trust policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.map(|row| SourceTrustPolicy {
+            tenant_id: row.tenant_id,
+            allowed_signers: row.allowed_signers.0,
+            protected_refs: row.protected_refs,
+            updated_at: row.updated_at,
+        }))
+    }
+
+    pub async fn upsert_source_trust_policy(&self, policy: &SourceTrustPolicy) -> Result<(), DatabaseError> {
+        info!("Updating source trust policy for tenant {}", policy.tenant_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO source_trust_policies (tenant_id, allowed_signers, protected_refs, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                allowed_signers = EXCLUDED.allowed_signers,
+                protected_refs = EXCLUDED.protected_refs,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            policy.tenant_id,
+            Json(&policy.allowed_signers) as _,
+            &policy.protected_refs,
+            policy.updated_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store source trust policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn create_source_verification(&self, verification: &SourceVerification) -> Result<(), DatabaseError> {
+        info!("Storing source verification {} for provenance record {}", verification.id, verification.provenance_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO source_verifications
+                (id, tenant_id, provenance_id, source_commit, source_ref, passed, checks, verified_by, verified_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            verification.id,
+            verification.tenant_id,
+            verification.provenance_id,
+            verification.commit,
+            verification.source_ref,
+            verification.passed,
+            Json(&verification.checks) as _,
+            verification.verified_by,
+            verification.verified_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store source verification: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_source_verifications(&self, tenant_id: &Uuid, provenance_id: &Uuid) -> Result<Vec<SourceVerification>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, provenance_id, source_commit, source_ref, passed,
+                   checks as "checks: Json<Vec<crate::models::VerificationCheck>>",
+                   verified_by, verified_at
+            FROM source_verifications
+            WHERE tenant_id = $1 AND provenance_id = $2
+            ORDER BY verified_at DESC
+            "#,
+            tenant_id,
+            provenance_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch source verifications: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter()
+            .map(|row| SourceVerification {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                provenance_id: row.provenance_id,
+                commit: row.source_commit,
+                source_ref: row.source_ref,
+                passed: row.passed,
+                checks: row.checks.0,
+                verified_by: row.verified_by,
+                verified_at: row.verified_at,
+            })
+            .collect())
+    }
+
+    pub async fn create_registered_model(&self, model: &RegisteredModel) -> Result<(), DatabaseError> {
+        info!("Registering model {} for tenant {}", model.name, model.tenant_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO registered_models (id, tenant_id, name, description, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            model.id,
+            model.tenant_id,
+            model.name,
+            model.description,
+            model.created_by,
+            model.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to register model: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn get_registered_model(&self, tenant_id: &Uuid, name: &str) -> Result<Option<RegisteredModel>, DatabaseError> {
+        sqlx::query_as!(
+            RegisteredModel,
+            r#"
+            SELECT id, tenant_id, name, description, created_by, created_at
+            FROM registered_models
+            WHERE tenant_id = $1 AND name = $2
+            "#,
+            tenant_id,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch model: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn list_registered_models(&self, tenant_id: &Uuid) -> Result<Vec<RegisteredModel>, DatabaseError> {
+        sqlx::query_as!(
+            RegisteredModel,
+            r#"
+            SELECT id, tenant_id, name, description, created_by, created_at
+            FROM registered_models
+            WHERE tenant_id = $1
+            ORDER BY name
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch models: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn create_model_version(&self, version: &ModelVersion) -> Result<(), DatabaseError> {
+        info!("Registering version {} of model {}", version.version, version.model_name);
+        sqlx::query!(
+            r#"
+            INSERT INTO model_versions
+                (id, tenant_id, model_id, version, weights, weights_sha256, weights_uri, card,
+                 training_provenance_id, datasets, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            version.id,
+            version.tenant_id,
+            version.model_id,
+            version.version,
+            version.weights.as_ref().map(Json) as _,
+            version.weights.as_ref().map(|weights| weights.sha256.clone()),
+            version.weights_uri,
+            Json(&version.card) as _,
+            version.training_provenance_id,
+            Json(&version.datasets) as _,
+            version.created_by,
+            version.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store model version: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn set_model_weights(&self, version_id: &Uuid, weights: &WeightDigest) -> Result<(), DatabaseError> {
+        info!("Recording weights {} for model version {}", weights.sha256, version_id);
+        sqlx::query!(
+            "UPDATE model_versions SET weights = $2, weights_sha256 = $3 WHERE id = $1",
+            version_id,
+            Json(weights) as _,
+            weights.sha256
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store model weights digest: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Lists versions of a model, newest first. With `version` set, returns at most that version.
+    pub async fn list_model_versions(
+        &self,
+        tenant_id: &Uuid,
+        model_name: &str,
+        version: Option<&str>,
+    ) -> Result<Vec<ModelVersion>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT v.id, v.tenant_id, v.model_id, m.name as model_name, v.version,
+                   v.weights as "weights: Json<WeightDigest>",
+                   v.weights_uri,
+                   v.card as "card: Json<ModelCard>",
+                   v.training_provenance_id,
+                   v.datasets as "datasets: Json<Vec<DatasetVersionRef>>",
+                   v.created_by, v.created_at
+            FROM model_versions v
+            JOIN registered_models m ON m.id = v.model_id
+            WHERE v.tenant_id = $1 AND m.name = $2 AND ($3::text IS NULL OR v.version = $3)
+            ORDER BY v.created_at DESC
+            "#,
+            tenant_id,
+            model_name,
+            version
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch model versions: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter()
+            .map(|row| ModelVersion {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                model_id: row.model_id,
+                model_name: row.model_name,
+                version: row.version,
+                weights: row.weights.map(|weights| weights.0),
+                weights_uri: row.weights_uri,
+                card: row.card.0,
+                training_provenance_id: row.training_provenance_id,
+                datasets: row.datasets.0,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    /// Stores a dataset version, returning false without storing it if the
+    /// tenant already has that version of the dataset.
+    pub async fn create_dataset_version(&self, dataset: &DatasetVersion) -> Result<bool, DatabaseError> {
+        info!("Registering dataset {} version {}", dataset.name, dataset.version);
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO dataset_versions (id, tenant_id, name, version, uri, digest, files, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (tenant_id, name, version) DO NOTHING
+            "#,
+            dataset.id,
+            dataset.tenant_id,
+            dataset.name,
+            dataset.version,
+            dataset.uri,
+            dataset.digest,
+            Json(&dataset.files) as _,
+            dataset.created_by,
+            dataset.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store dataset version: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Fetches dataset versions matching any of the filters, newest first.
+    /// `name` with `version` selects one version; `name` alone lists a dataset.
+    pub async fn list_dataset_versions(
+        &self,
+        tenant_id: &Uuid,
+        name: Option<&str>,
+        version: Option<&str>,
+        ids: Option<&[Uuid]>,
+        digests: Option<&[String]>,
+    ) -> Result<Vec<DatasetVersion>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, name, version, uri, digest,
+                   files as "files: Json<Vec<DatasetFile>>",
+                   created_by, created_at
+            FROM dataset_versions
+            WHERE tenant_id = $1
+              AND ($2::text IS NULL OR name = $2)
+              AND ($3::text IS NULL OR version = $3)
+              AND ($4::uuid[] IS NULL OR id = ANY($4))
+              AND ($5::text[] IS NULL OR digest = ANY($5))
+            ORDER BY created_at DESC
+            "#,
+            tenant_id,
+            name,
+            version,
+            ids,
+            digests
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch dataset versions: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter()
+            .map(|row| DatasetVersion {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                name: row.name,
+                version: row.version,
+                uri: row.uri,
+                digest: row.digest,
+                files: row.files.0,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    /// Stores a transformation together with its lineage edges. Returns false
+    /// without storing anything if the run is already recorded for the job.
+    pub async fn create_transformation(&self, transformation: &Transformation) -> Result<bool, DatabaseError> {
+        info!("Recording transformation {} for job {}", transformation.id, transformation.job_name);
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO data_transformations
+                (id, tenant_id, job_name, run_id, code, inputs, outputs, facets, started_at, ended_at, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (tenant_id, job_name, run_id) WHERE run_id IS NOT NULL DO NOTHING
+            "#,
+            transformation.id,
+            transformation.tenant_id,
+            transformation.job_name,
+            transformation.run_id,
+            transformation.code.as_ref().map(Json) as _,
+            Json(&transformation.inputs) as _,
+            Json(&transformation.outputs) as _,
+            Json(&transformation.facets) as _,
+            transformation.started_at,
+            transformation.ended_at,
+            transformation.created_by,
+            transformation.created_at
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to store transformation: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        insert_lineage_edges(&mut tx, &transformation.tenant_id, &transformation.lineage_edges()).await?;
+        tx.commit().await.map_err(DatabaseError::QueryError)?;
+
+        Ok(true)
+    }
+
+    pub async fn list_transformations(&self, tenant_id: &Uuid, ids: &[Uuid]) -> Result<Vec<Transformation>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, job_name, run_id,
+                   code as "code: Json<CodeVersion>",
+                   inputs as "inputs: Json<Vec<DatasetRef>>",
+                   outputs as "outputs: Json<Vec<DatasetRef>>",
+                   facets as "facets: Json<serde_json::Value>",
+                   started_at, ended_at, created_by, created_at
+            FROM data_transformations
+            WHERE tenant_id = $1 AND id = ANY($2)
+            ORDER BY created_at
+            "#,
+            tenant_id,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch transformations: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter()
+            .map(|row| Transformation {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                job_name: row.job_name,
+                run_id: row.run_id,
+                code: row.code.map(|code| code.0),
+                inputs: row.inputs.0,
+                outputs: row.outputs.0,
+                facets: row.facets.0,
+                started_at: row.started_at,
+                ended_at: row.ended_at,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    pub async fn create_openlineage_event(&self, tenant_id: &Uuid, id: &Uuid, event: &RunEvent) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO openlineage_events (id, tenant_id, run_id, event_type, event_time, job_name, event)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            id,
+            tenant_id,
+            event.run.run_id,
+            event.event_type().as_str(),
+            event.event_time,
+            event.job_name(),
+            Json(event) as _
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store OpenLineage event: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Time of the earliest event of `event_type` received for a run.
+    pub async fn openlineage_event_time(
+        &self,
+        tenant_id: &Uuid,
+        run_id: &Uuid,
+        event_type: EventType,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT MIN(event_time) as event_time
+            FROM openlineage_events
+            WHERE tenant_id = $1 AND run_id = $2 AND event_type = $3
+            "#,
+            tenant_id,
+            run_id,
+            event_type.as_str()
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch OpenLineage event time: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.event_time)
+    }
+
+    pub async fn find_transformation_for_run(&self, tenant_id: &Uuid, job_name: &str, run_id: &str) -> Result<Option<Uuid>, DatabaseError> {
+        let row = sqlx::query!(
+            "SELECT id FROM data_transformations WHERE tenant_id = $1 AND job_name = $2 AND run_id = $3",
+            tenant_id,
+            job_name,
+            run_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch transformation for run: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.map(|row| row.id))
+    }
+
+    pub async fn create_model_signature(&self, signature: &ModelSignature) -> Result<(), DatabaseError> {
+        info!("Storing signature {} for model version {}", signature.id, signature.model_version_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO model_signatures (id, tenant_id, model_version_id, key_id, root_digest, bundle, signed_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            signature.id,
+            signature.tenant_id,
+            signature.model_version_id,
+            signature.key_id,
+            signature.root_digest,
+            Json(&signature.bundle) as _,
+            signature.signed_by,
+            signature.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store model signature: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn get_latest_model_signature(&self, tenant_id: &Uuid, model_version_id: &Uuid) -> Result<Option<ModelSignature>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, model_version_id, key_id, root_digest,
+                   bundle as "bundle: Json<ModelSignatureBundle>",
+                   signed_by, created_at
+            FROM model_signatures
+            WHERE tenant_id = $1 AND model_version_id = $2
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            tenant_id,
+            model_version_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch model signature: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.map(|row| ModelSignature {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            model_version_id: row.model_version_id,
+            key_id: row.key_id,
+            root_digest: row.root_digest,
+            bundle: row.bundle.0,
+            signed_by: row.signed_by,
+            created_at: row.created_at,
+        }))
+    }
+
+    /// Appends entries to the tenant's transparency log in one transaction and
+    /// returns them with their leaf indices.
+    pub async fn append_log_entries(&self, tenant_id: &Uuid, bodies: &[LogEntryBody]) -> Result<Vec<LogEntry>, DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        let entries = append_log_entries(&mut tx, tenant_id, bodies).await?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit transparency log entries: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(entries)
+    }
+
+    pub async fn transparency_log_size(&self, tenant_id: &Uuid) -> Result<u64, DatabaseError> {
+        let size = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM transparency_log_entries WHERE tenant_id = $1"#,
+            tenant_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to count transparency log entries: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(size as u64)
+    }
+
+    /// Stored hashes of the given complete subtrees; ids the log does not
+    /// hold yet are absent from the map.
+    pub async fn transparency_nodes(&self, tenant_id: &Uuid, ids: &[NodeId]) -> Result<HashMap<NodeId, Hash>, DatabaseError> {
+        let mut conn = self.pool.acquire().await.map_err(DatabaseError::ConnectionError)?;
+        fetch_transparency_nodes(&mut conn, tenant_id, ids).await
+    }
+
+    pub async fn get_log_entry(&self, tenant_id: &Uuid, leaf_index: u64) -> Result<Option<LogEntry>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT tenant_id, leaf_index, leaf_hash, leaf_data, body as "body: Json<LogEntryBody>"
+            FROM transparency_log_entries
+            WHERE tenant_id = $1 AND leaf_index = $2
+            "#,
+            tenant_id,
+            leaf_index as i64
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch transparency log entry: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.map(|row| LogEntry {
+            tenant_id: row.tenant_id,
+            leaf_index: row.leaf_index as u64,
+            leaf_hash: row.leaf_hash,
+            leaf_data: row.leaf_data,
+            body: row.body.0,
+        }))
+    }
+
+    pub async fn find_log_entries(&self, tenant_id: &Uuid, digest: Option<&str>, resource_id: Option<&Uuid>) -> Result<Vec<LogEntry>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT tenant_id, leaf_index, leaf_hash, leaf_data, body as "body: Json<LogEntryBody>"
+            FROM transparency_log_entries
+            WHERE tenant_id = $1
+              AND ($2::text IS NULL OR digest = $2)
+              AND ($3::uuid IS NULL OR resource_id = $3)
+            ORDER BY leaf_index
+            "#,
+            tenant_id,
+            digest,
+            resource_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to search transparency log: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| LogEntry {
+            tenant_id: row.tenant_id,
+            leaf_index: row.leaf_index as u64,
+            leaf_hash: row.leaf_hash,
+            leaf_data: row.leaf_data,
+            body: row.body.0,
+        }).collect())
+    }
+
+    /// Stores a signed tree head. A head already stored for the same size is
+    /// kept, so concurrent signers agree on one head per size.
+    pub async fn create_tree_head(&self, head: &SignedTreeHead) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO transparency_tree_heads (tenant_id, tree_size, root_hash, log_id, timestamp, envelope)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, tree_size) DO NOTHING
+            "#,
+            head.tenant_id,
+            head.tree_head.tree_size as i64,
+            head.tree_head.root_hash,
+            head.tree_head.log_id,
+            head.tree_head.timestamp,
+            Json(&head.envelope) as _
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store signed tree head: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// The signed head for `tree_size`, or the largest one when `None`.
+    pub async fn get_tree_head(&self, tenant_id: &Uuid, tree_size: Option<u64>) -> Result<Option<SignedTreeHead>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT tenant_id, tree_size, root_hash, log_id, timestamp,
+                   envelope as "envelope: Json<Envelope>"
+            FROM transparency_tree_heads
+            WHERE tenant_id = $1 AND ($2::bigint IS NULL OR tree_size = $2)
+            ORDER BY tree_size DESC
+            LIMIT 1
+            "#,
+            tenant_id,
+            tree_size.map(|size| size as i64)
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch signed tree head: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(row.map(|row| SignedTreeHead {
+            tenant_id: row.tenant_id,
+            tree_head: TreeHead {
+                log_id: row.log_id,
+                tree_size: row.tree_size as u64,
+                root_hash: row.root_hash,
+                timestamp: row.timestamp,
+            },
+            envelope: row.envelope.0,
+        }))
+    }
+
+    /// Stores an event and records its hash in the transparency log. The
+    /// `(tenant_id, artifact_digest, sequence)` uniqueness constraint rejects
+    /// a concurrent writer that appended after the same head.
+    pub async fn create_custody_event(&self, event: &CustodyEvent) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        insert_custody_event(&mut tx, event).await?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit custody event: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// The artifact's custody chain, oldest event first.
+    pub async fn list_custody_events(&self, tenant_id: &Uuid, artifact_digest: &str) -> Result<Vec<CustodyEvent>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, artifact_digest, sequence, event_type, category, environment, actor,
+                   evidence as "evidence: Json<Vec<EvidenceRef>>", details, previous_hash, event_hash, timestamp
+            FROM custody_events
+            WHERE tenant_id = $1 AND artifact_digest = $2
+            ORDER BY sequence
+            "#,
+            tenant_id,
+            artifact_digest
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch custody events: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| CustodyEvent {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            artifact_digest: row.artifact_digest,
+            sequence: row.sequence,
+            // An unknown type no longer matches the event hash, so verification flags it
+            event_type: row.event_type.parse().unwrap_or(CustodyEventType::Built),
+            category: row.category,
+            environment: row.environment,
+            actor: row.actor,
+            evidence: row.evidence.0,
+            details: row.details,
+            previous_hash: row.previous_hash,
+            event_hash: row.event_hash,
+            timestamp: row.timestamp,
+        }).collect())
+    }
+
+    pub async fn create_custody_policy(&self, policy: &CustodyPolicy) -> Result<(), DatabaseError> {
+        info!("Creating custody policy {} for tenant {}", policy.name, policy.tenant_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO custody_policies
+                (id, tenant_id, name, description, gated_event, environment_pattern, required_events, enabled, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            policy.id,
+            policy.tenant_id,
+            policy.name,
+            policy.description,
+            policy.gated_event.as_str(),
+            policy.environment_pattern,
+            Json(&policy.required_events) as _,
+            policy.enabled,
+            policy.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to create custody policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_custody_policies(&self, tenant_id: &Uuid) -> Result<Vec<CustodyPolicy>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, name, description, gated_event, environment_pattern,
+                   required_events as "required_events: Json<Vec<RequiredEvent>>", enabled, created_at
+            FROM custody_policies
+            WHERE tenant_id = $1
+            ORDER BY name
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list custody policies: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        // A policy that cannot be read must not be skipped, or its gate would open
+        rows.into_iter().map(|row| Ok(CustodyPolicy {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            description: row.description,
+            gated_event: row.gated_event.parse::<CustodyEventType>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+            environment_pattern: row.environment_pattern,
+            required_events: row.required_events.0,
+            enabled: row.enabled,
+            created_at: row.created_at,
+        })).collect()
+    }
+
+    pub async fn delete_custody_policy(&self, tenant_id: &Uuid, id: &Uuid) -> Result<bool, DatabaseError> {
+        info!("Deleting custody policy {}", id);
+        let result = sqlx::query!(
+            "DELETE FROM custody_policies WHERE tenant_id = $1 AND id = $2",
+            tenant_id,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete custody policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Records the signature in the transparency log under the signed
+    /// document's digest and stores the bundle, with its log position, so it
+    /// can be fetched later.
+    pub async fn publish_document_signature(&self, tenant_id: &Uuid, signed_by: &Uuid, bundle: &SignatureBundle) -> Result<SignatureBundle, DatabaseError> {
+        info!("Publishing document signature {} for digest {}", bundle.id, bundle.digest);
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        let log_entry = LogEntryBody::new(LogEntryKind::Signature, bundle.id, bundle.digest.clone());
+        let entries = append_log_entries(&mut tx, tenant_id, &[log_entry]).await?;
+        let mut published = bundle.clone();
+        published.log_entry = entries.first().map(|entry| LogEntryRef {
+            leaf_index: entry.leaf_index,
+            leaf_hash: entry.leaf_hash.clone(),
+        });
+        sqlx::query!(
+            r#"
+            INSERT INTO document_signatures (id, tenant_id, digest, bundle, signed_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            published.id,
+            tenant_id,
+            published.digest,
+            Json(&published) as _,
+            signed_by,
+            published.signed_at
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to store document signature: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit document signature: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(published)
+    }
+
+    pub async fn get_document_signature(&self, tenant_id: &Uuid, id: &Uuid) -> Result<Option<SignatureBundle>, DatabaseError> {
+        let bundle = sqlx::query_scalar!(
+            r#"SELECT bundle as "bundle: Json<SignatureBundle>" FROM document_signatures WHERE tenant_id = $1 AND id = $2"#,
+            tenant_id,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch document signature: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(bundle.map(|bundle| bundle.0))
+    }
+
+    pub async fn create_record_timestamp(&self, timestamp: &RecordTimestamp) -> Result<(), DatabaseError> {
+        info!("Storing timestamp token for {} {}", timestamp.kind.as_str(), timestamp.resource_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO record_timestamps (id, tenant_id, kind, resource_id, digest, timestamp_token, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            timestamp.id,
+            timestamp.tenant_id,
+            timestamp.kind.as_str(),
+            timestamp.resource_id,
+            timestamp.digest,
+            Json(&timestamp.timestamp) as _,
+            timestamp.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store timestamp token: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_record_timestamps(&self, tenant_id: &Uuid, kind: LogEntryKind, resource_id: &Uuid) -> Result<Vec<RecordTimestamp>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, kind, resource_id, digest,
+                   timestamp_token as "timestamp_token: Json<TimestampToken>", created_at
+            FROM record_timestamps
+            WHERE tenant_id = $1 AND kind = $2 AND resource_id = $3
+            ORDER BY created_at
+            "#,
+            tenant_id,
+            kind.as_str(),
+            resource_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch timestamp tokens: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| RecordTimestamp {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            kind,
+            resource_id: row.resource_id,
+            digest: row.digest,
+            timestamp: row.timestamp_token.0,
+            created_at: row.created_at,
+        }).collect())
+    }
+
+    pub async fn get_record_timestamp(&self, tenant_id: &Uuid, id: &Uuid) -> Result<Option<RecordTimestamp>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, kind, resource_id, digest,
+                   timestamp_token as "timestamp_token: Json<TimestampToken>", created_at
+            FROM record_timestamps
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+            tenant_id,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch timestamp token: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        row.map(|row| Ok(RecordTimestamp {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            kind: row.kind.parse::<LogEntryKind>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+            resource_id: row.resource_id,
+            digest: row.digest,
+            timestamp: row.timestamp_token.0,
+            created_at: row.created_at,
+        }))
+        .transpose()
+    }
+
+    pub async fn get_cosign_import(&self, tenant_id: &Uuid, layer_digest: &str) -> Result<Option<CosignImport>, DatabaseError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT tenant_id, layer_digest, repository, image_digest, record_kind, record_id, imported_at
+            FROM cosign_imports
+            WHERE tenant_id = $1 AND layer_digest = $2
+            "#,
+            tenant_id,
+            layer_digest
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch cosign import: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        row.map(|row| Ok(CosignImport {
+            tenant_id: row.tenant_id,
+            repository: row.repository,
+            image_digest: row.image_digest,
+            layer_digest: row.layer_digest,
+            record_kind: row.record_kind.parse::<ImportedRecordKind>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+            record_id: row.record_id,
+            imported_at: row.imported_at,
+        })).transpose()
+    }
+
+    /// Records where a registry attestation was imported to. A layer that is
+    /// already recorded keeps its first import.
+    pub async fn create_cosign_import(&self, import: &CosignImport) -> Result<(), DatabaseError> {
+        info!("Recording cosign import of {} as {} {}", import.layer_digest, import.record_kind.as_str(), import.record_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO cosign_imports (tenant_id, layer_digest, repository, image_digest, record_kind, record_id, imported_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (tenant_id, layer_digest) DO NOTHING
+            "#,
+            import.tenant_id,
+            import.layer_digest,
+            import.repository,
+            import.image_digest,
+            import.record_kind.as_str(),
+            import.record_id,
+            import.imported_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to record cosign import: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn create_revocation(&self, revocation: &Revocation) -> Result<(), DatabaseError> {
+        info!("Revoking {} {}", revocation.target_type.as_str(), revocation.target);
+        sqlx::query!(
+            r#"
+            INSERT INTO revocations (id, tenant_id, target_type, target, reason, effective_at, revoked_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            revocation.id,
+            revocation.tenant_id,
+            revocation.target_type.as_str(),
+            revocation.target,
+            revocation.reason,
+            revocation.effective_at,
+            revocation.revoked_by,
+            revocation.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store revocation: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_revocations(&self, tenant_id: &Uuid) -> Result<Vec<Revocation>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, target_type, target, reason, effective_at, revoked_by, created_at
+            FROM revocations
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch revocations: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        // A revocation that cannot be read must not be dropped, or its target would be trusted again
+        rows.into_iter()
+            .map(|row| Ok(Revocation {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                target_type: row.target_type.parse::<RevocationTarget>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                target: row.target,
+                reason: row.reason,
+                effective_at: row.effective_at,
+                revoked_by: row.revoked_by,
+                created_at: row.created_at,
+            }))
+            .collect()
+    }
+
+    /// Live provenance records that key, builder or attestation revocations
+    /// may cover: those stored since `since`, and those with one of `ids`.
+    /// With `subjects`, only records producing one of them.
+    pub async fn list_revocation_candidates(
+        &self,
+        tenant_id: &Uuid,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        ids: &[Uuid],
+        subjects: Option<&[String]>,
+    ) -> Result<Vec<ProvenanceRecord>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT content as "content: Json<ProvenanceRecord>"
+            FROM provenance_index
+            WHERE tenant_id = $1 AND NOT deleted AND (created_at >= $2 OR provenance_id = ANY($3))
+              AND ($4::text[] IS NULL OR subject_digests && $4)
+            "#,
+            tenant_id,
+            since,
+            ids,
+            subjects as Option<&[String]>
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch revocation candidates: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| row.content.0).collect())
+    }
+
+    /// Attestations that key or attestation revocations may cover: those
+    /// verified with a key and stored since `since`, and those with one of `ids`.
+    pub async fn list_revocable_attestations(
+        &self,
+        tenant_id: &Uuid,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        ids: &[Uuid],
+    ) -> Result<Vec<Attestation>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, predicate_type, kind, subject_names, subject_digests,
+                   predicate as "predicate: Json<serde_json::Value>",
+                   passed, verified_key_id,
+                   envelope as "envelope: Json<Envelope>",
+                   created_by, created_at
+            FROM attestations
+            WHERE tenant_id = $1
+              AND ((verified_key_id IS NOT NULL AND created_at >= $2) OR id = ANY($3))
+            "#,
+            tenant_id,
+            since,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch revocable attestations: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter()
+            .map(|row| Ok(Attestation {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                predicate_type: row.predicate_type,
+                kind: row.kind.parse::<AttestationKind>()
+                    .map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                subject_names: row.subject_names,
+                subject_digests: row.subject_digests,
+                predicate: row.predicate.0,
+                passed: row.passed,
+                verified_key_id: row.verified_key_id,
+                envelope: row.envelope.0,
+                created_by: row.created_by,
+                created_at: row.created_at,
+            }))
+            .collect()
+    }
+
+    /// Links a record into its tenant's audit chain. The per-tenant advisory
+    /// lock keeps sequence numbers dense and each entry pointing at the head
+    /// it was appended after.
+    pub async fn append_audit_entry(&self, record: AuditRecord) -> Result<AuditEntry, DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))",
+            format!("audit:{}", record.tenant_id)
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to lock audit log: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        let head = fetch_audit_head(&mut tx, &record.tenant_id).await?;
+        let entry = AuditEntry::append(head.as_ref(), record);
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_entries
+                (id, tenant_id, sequence, actor, channel, action, resource, status, before_hash, after_hash,
+                 request_id, source_ip, previous_hash, entry_hash, timestamp)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#,
+            entry.id,
+            entry.record.tenant_id,
+            entry.sequence,
+            entry.record.actor,
+            entry.record.channel.as_str(),
+            entry.record.action,
+            entry.record.resource,
+            entry.record.status,
+            entry.record.before_hash,
+            entry.record.after_hash,
+            entry.record.request_id,
+            entry.record.source_ip,
+            entry.previous_hash,
+            entry.entry_hash,
+            entry.timestamp
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to append audit entry: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit audit entry: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(entry)
+    }
+
+    pub async fn get_audit_head(&self, tenant_id: &Uuid) -> Result<Option<AuditEntry>, DatabaseError> {
+        let mut conn = self.pool.acquire().await.map_err(DatabaseError::QueryError)?;
+        fetch_audit_head(&mut conn, tenant_id).await
+    }
+
+    /// Entries matching `query`, ordered by sequence, at most `limit` of them.
+    pub async fn list_audit_entries(&self, tenant_id: &Uuid, query: &AuditQuery, limit: i64) -> Result<Vec<AuditEntry>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, sequence, actor, channel, action, resource, status, before_hash, after_hash,
+                   request_id, source_ip, previous_hash, entry_hash, timestamp
+            FROM audit_entries
+            WHERE tenant_id = $1
+              AND ($2::uuid IS NULL OR actor = $2)
+              AND ($3::text IS NULL OR action = $3)
+              AND ($4::text IS NULL OR starts_with(resource, $4))
+              AND ($5::text IS NULL OR request_id = $5)
+              AND ($6::timestamptz IS NULL OR timestamp >= $6)
+              AND ($7::timestamptz IS NULL OR timestamp < $7)
+              AND ($8::bigint IS NULL OR sequence > $8)
+            ORDER BY sequence
+            LIMIT $9
+            "#,
+            tenant_id,
+            query.actor,
+            query.action,
+            query.resource,
+            query.request_id,
+            query.after,
+            query.before,
+            query.after_sequence,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to query audit log: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter().map(|row| Ok(AuditEntry {
+            id: row.id,
+            sequence: row.sequence,
+            record: AuditRecord {
+                tenant_id: row.tenant_id,
+                actor: row.actor,
+                channel: row.channel.parse::<AuditChannel>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                action: row.action,
+                resource: row.resource,
+                status: row.status,
+                before_hash: row.before_hash,
+                after_hash: row.after_hash,
+                request_id: row.request_id,
+                source_ip: row.source_ip,
+            },
+            previous_hash: row.previous_hash,
+            entry_hash: row.entry_hash,
+            timestamp: row.timestamp,
+        })).collect()
+    }
+
+    /// Stores a checkpoint. One already stored at the same sequence is kept.
+    pub async fn create_audit_checkpoint(&self, checkpoint: &AuditCheckpoint) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_checkpoints (tenant_id, sequence, head_hash, log_id, timestamp, envelope)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, sequence) DO NOTHING
+            "#,
+            checkpoint.checkpoint.tenant_id,
+            checkpoint.checkpoint.sequence,
+            checkpoint.checkpoint.head_hash,
+            checkpoint.checkpoint.log_id,
+            checkpoint.checkpoint.timestamp,
+            Json(&checkpoint.envelope) as _
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store audit checkpoint: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// Checkpoints newest first, at most `limit` of them.
+    pub async fn list_audit_checkpoints(&self, tenant_id: &Uuid, limit: i64) -> Result<Vec<AuditCheckpoint>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT tenant_id, sequence, head_hash, log_id, timestamp,
+                   envelope as "envelope: Json<Envelope>"
+            FROM audit_checkpoints
+            WHERE tenant_id = $1
+            ORDER BY sequence DESC
+            LIMIT $2
+            "#,
+            tenant_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch audit checkpoints: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| AuditCheckpoint {
+            checkpoint: AuditCheckpointBody {
+                log_id: row.log_id,
+                tenant_id: row.tenant_id,
+                sequence: row.sequence,
+                head_hash: row.head_hash,
+                timestamp: row.timestamp,
+            },
+            envelope: row.envelope.0,
+        }).collect())
+    }
+
+    /// Tenants whose audit log has grown since their latest checkpoint.
+    pub async fn list_audit_tenants_pending_checkpoint(&self) -> Result<Vec<Uuid>, DatabaseError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT e.tenant_id as "tenant_id!"
+            FROM audit_entries e
+            GROUP BY e.tenant_id
+            HAVING MAX(e.sequence) > COALESCE(
+                (SELECT MAX(c.sequence) FROM audit_checkpoints c WHERE c.tenant_id = e.tenant_id), 0)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to find tenants pending an audit checkpoint: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn create_witness_peer(&self, peer: &WitnessPeer) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO witness_peers (id, tenant_id, name, url, log_id, log_public_key, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            peer.id,
+            peer.tenant_id,
+            peer.name,
+            peer.url,
+            peer.log_id,
+            peer.log_public_key,
+            peer.created_by,
+            peer.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store witness peer: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    /// The tenant's witnessed peers, or every tenant's when `tenant_id` is `None`.
+    pub async fn list_witness_peers(&self, tenant_id: Option<&Uuid>) -> Result<Vec<WitnessPeer>, DatabaseError> {
+        sqlx::query_as!(
+            WitnessPeer,
+            r#"
+            SELECT id, tenant_id, name, url, log_id, log_public_key, created_by, created_at
+            FROM witness_peers
+            WHERE $1::uuid IS NULL OR tenant_id = $1
+            ORDER BY name
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch witness peers: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    /// Heads witnessed from a peer, largest first, at most `limit` of them.
+    pub async fn list_witnessed_heads(&self, peer_id: &Uuid, limit: i64) -> Result<Vec<SignedTreeHead>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT head as "head: Json<SignedTreeHead>"
+            FROM witnessed_tree_heads
+            WHERE peer_id = $1
+            ORDER BY tree_size DESC
+            LIMIT $2
+            "#,
+            peer_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch witnessed tree heads: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| row.head.0).collect())
+    }
+
+    /// Stores a co-signed head. One already witnessed at the same size is kept.
+    pub async fn create_witnessed_head(&self, peer_id: &Uuid, head: &SignedTreeHead) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO witnessed_tree_heads (peer_id, tree_size, root_hash, head)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (peer_id, tree_size) DO NOTHING
+            "#,
+            peer_id,
+            head.tree_head.tree_size as i64,
+            head.tree_head.root_hash,
+            Json(head) as _
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store witnessed tree head: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn create_trusted_witness(&self, witness: &TrustedWitness) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO trusted_witnesses (tenant_id, key_id, name, public_key, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, key_id) DO UPDATE SET name = EXCLUDED.name
+            "#,
+            witness.tenant_id,
+            witness.key_id,
+            witness.name,
+            witness.public_key,
+            witness.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store trusted witness: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_trusted_witnesses(&self, tenant_id: &Uuid) -> Result<Vec<TrustedWitness>, DatabaseError> {
+        sqlx::query_as!(
+            TrustedWitness,
+            r#"
+            SELECT tenant_id, key_id, name, public_key, created_at
+            FROM trusted_witnesses
+            WHERE tenant_id = $1
+            ORDER BY name
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch trusted witnesses: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    pub async fn create_tree_head_cosignature(
+        &self,
+        tenant_id: &Uuid,
+        tree_size: u64,
+        witness_name: &str,
+        signature: &EnvelopeSignature,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO tree_head_cosignatures (tenant_id, tree_size, witness_key_id, witness_name, signature)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, tree_size, witness_key_id) DO NOTHING
+            "#,
+            tenant_id,
+            tree_size as i64,
+            signature.keyid,
+            witness_name,
+            signature.sig
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store tree head co-signature: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_tree_head_cosignatures(&self, tenant_id: &Uuid, tree_size: u64) -> Result<Vec<EnvelopeSignature>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT witness_key_id, signature
+            FROM tree_head_cosignatures
+            WHERE tenant_id = $1 AND tree_size = $2
+            ORDER BY created_at
+            "#,
+            tenant_id,
+            tree_size as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch tree head co-signatures: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| EnvelopeSignature { keyid: row.witness_key_id, sig: row.signature }).collect())
+    }
+
+    pub async fn create_split_view_alert(&self, alert: &SplitViewAlert) -> Result<(), DatabaseError> {
+        error!(
+            "Split view ({}) of log {} at tree size {}",
+            alert.split_view.kind.as_str(), alert.split_view.log_id, alert.split_view.observed.tree_head.tree_size
+        );
+        sqlx::query!(
+            r#"
+            INSERT INTO split_view_alerts (id, tenant_id, peer_id, kind, log_id, expected, observed, detected_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            alert.id,
+            alert.tenant_id,
+            alert.peer_id,
+            alert.split_view.kind.as_str(),
+            alert.split_view.log_id,
+            Json(&alert.split_view.expected) as _,
+            Json(&alert.split_view.observed) as _,
+            alert.detected_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to store split view alert: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_split_view_alerts(&self, tenant_id: &Uuid) -> Result<Vec<SplitViewAlert>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, peer_id, kind, log_id,
+                   expected as "expected: Json<SignedTreeHead>",
+                   observed as "observed: Json<SignedTreeHead>",
+                   detected_at
+            FROM split_view_alerts
+            WHERE tenant_id = $1
+            ORDER BY detected_at DESC
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch split view alerts: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter()
+            .map(|row| Ok(SplitViewAlert {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                peer_id: row.peer_id,
+                split_view: SplitView {
+                    kind: row.kind.parse::<SplitViewKind>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                    log_id: row.log_id,
+                    expected: row.expected.0,
+                    observed: row.observed.0,
+                },
+                detected_at: row.detected_at,
+            }))
+            .collect()
+    }
+
+    pub async fn create_promotion_policy(&self, policy: &PromotionPolicy) -> Result<(), DatabaseError> {
+        info!("Creating promotion policy {} for tenant {}", policy.name, policy.tenant_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO promotion_policies
+                (id, tenant_id, name, description, environment_pattern, rules, enabled, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            policy.id,
+            policy.tenant_id,
+            policy.name,
+            policy.description,
+            policy.environment_pattern,
+            Json(&policy.rules) as _,
+            policy.enabled,
+            policy.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to create promotion policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn list_promotion_policies(&self, tenant_id: &Uuid) -> Result<Vec<PromotionPolicy>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, name, description, environment_pattern,
+                   rules as "rules: Json<PromotionRules>", enabled, created_at
+            FROM promotion_policies
+            WHERE tenant_id = $1
+            ORDER BY name
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list promotion policies: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(rows.into_iter().map(|row| PromotionPolicy {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            description: row.description,
+            environment_pattern: row.environment_pattern,
+            rules: row.rules.0,
+            enabled: row.enabled,
+            created_at: row.created_at,
+        }).collect())
+    }
+
+    pub async fn delete_promotion_policy(&self, tenant_id: &Uuid, id: &Uuid) -> Result<bool, DatabaseError> {
+        info!("Deleting promotion policy {}", id);
+        let result = sqlx::query!(
+            "DELETE FROM promotion_policies WHERE tenant_id = $1 AND id = $2",
+            tenant_id,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete promotion policy: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stores a decision and, for one that promotes, the `Promoted` custody
+    /// event it causes, in a single transaction. The artifact's custody chain
+    /// is locked like the audit log and its head re-read, so the event links
+    /// to the head the decision was evaluated against. If the chain has moved
+    /// since, nothing is stored and `false` is returned.
+    pub async fn record_promotion(&self, decision: &PromotionDecision, event: Option<&CustodyEvent>) -> Result<bool, DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::QueryError)?;
+        if let Some(event) = event {
+            sqlx::query!(
+                "SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))",
+                format!("custody:{}:{}", event.tenant_id, event.artifact_digest)
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to lock custody chain: {}", e);
+                DatabaseError::QueryError(e)
+            })?;
+            let head_hash = sqlx::query_scalar!(
+                r#"
+                SELECT event_hash FROM custody_events
+                WHERE tenant_id = $1 AND artifact_digest = $2
+                ORDER BY sequence DESC
+                LIMIT 1
+                "#,
+                event.tenant_id,
+                event.artifact_digest
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch custody chain head: {}", e);
+                DatabaseError::QueryError(e)
+            })?;
+            if head_hash != event.previous_hash {
+                return Ok(false);
+            }
+        }
+
+        insert_promotion_decision(&mut tx, decision).await?;
+        if let Some(event) = event {
+            insert_custody_event(&mut tx, event).await?;
+        }
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit promotion decision: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        Ok(true)
+    }
+
+    /// Lists the decisions taken about an artifact, oldest first.
+    pub async fn list_promotion_decisions(&self, tenant_id: &Uuid, artifact_digest: &str) -> Result<Vec<PromotionDecision>, DatabaseError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tenant_id, artifact_digest, from_environment, to_environment, outcome,
+                   checks as "checks: Json<Vec<PromotionCheck>>", policy_ids, override_reason, requested_by,
+                   request_id, envelope as "envelope: Json<Envelope>", decided_at
+            FROM promotion_decisions
+            WHERE tenant_id = $1 AND artifact_digest = $2
+            ORDER BY decided_at
+            "#,
+            tenant_id,
+            artifact_digest
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to list promotion decisions: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+
+        rows.into_iter().map(|row| Ok(PromotionDecision {
+            decision: PromotionDecisionBody {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                artifact_digest: row.artifact_digest,
+                from_environment: row.from_environment,
+                to_environment: row.to_environment,
+                outcome: row.outcome.parse::<PromotionOutcome>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+                checks: row.checks.0,
+                policy_ids: row.policy_ids,
+                override_reason: row.override_reason,
+                requested_by: row.requested_by,
+                request_id: row.request_id,
+                decided_at: row.decided_at,
+            },
+            envelope: row.envelope.0,
+        })).collect()
+    }
+}
+
+/// Only edges of live records are followed. Edges are replaced whenever a record
+/// is updated, so they always describe its current revision.
+#[async_trait::async_trait]
+impl LineageStore for Database {
+    async fn edges_producing(&self, tenant_id: &Uuid, digests: &[String]) -> Result<Vec<LineageEdge>, DatabaseError> {
+        sqlx::query_as!(
+            LineageEdge,
+            r#"
+            SELECT e.provenance_id, e.input_digest, e.input_uri, e.output_digest, e.output_name
+            FROM provenance_lineage_edges e
+            JOIN provenance_index i ON i.provenance_id = e.provenance_id AND i.tenant_id = e.tenant_id
+            WHERE e.tenant_id = $1 AND e.output_digest = ANY($2) AND NOT i.deleted
+            "#,
+            tenant_id,
+            digests
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch upstream lineage edges: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+
+    async fn edges_consuming(&self, tenant_id: &Uuid, digests: &[String]) -> Result<Vec<LineageEdge>, DatabaseError> {
+        sqlx::query_as!(
+            LineageEdge,
+            r#"
+            SELECT e.provenance_id, e.input_digest, e.input_uri, e.output_digest, e.output_name
+            FROM provenance_lineage_edges e
+            JOIN provenance_index i ON i.provenance_id = e.provenance_id AND i.tenant_id = e.tenant_id
+            WHERE e.tenant_id = $1 AND e.input_digest = ANY($2) AND NOT i.deleted
+            "#,
+            tenant_id,
+            digests
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch downstream lineage edges: {}", e);
+            DatabaseError::QueryError(e)
+        })
+    }
+}
+
+async fn insert_custody_event(conn: &mut PgConnection, event: &CustodyEvent) -> Result<(), DatabaseError> {
+    info!("Appending {} custody event for artifact {}", event.event_type.as_str(), event.artifact_digest);
+    sqlx::query!(
+        r#"
+        INSERT INTO custody_events
+            (id, tenant_id, artifact_digest, sequence, event_type, category, environment,
+             actor, evidence, details, previous_hash, event_hash, timestamp)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        "#,
+        event.id,
+        event.tenant_id,
+        event.artifact_digest,
+        event.sequence,
+        event.event_type.as_str(),
+        event.category,
+        event.environment,
+        event.actor,
+        Json(&event.evidence) as _,
+        event.details,
+        event.previous_hash,
+        event.event_hash,
+        event.timestamp
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to create custody event: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+    let log_entry = LogEntryBody::new(LogEntryKind::CustodyEvent, event.id, event.event_hash.clone());
+    append_log_entries(conn, &event.tenant_id, &[log_entry]).await?;
+
+    Ok(())
+}
+
+async fn insert_promotion_decision(conn: &mut PgConnection, decision: &PromotionDecision) -> Result<(), DatabaseError> {
+    let body = &decision.decision;
+    info!("Recording {} promotion decision {} for {}", body.outcome.as_str(), body.id, body.artifact_digest);
+    sqlx::query!(
+        r#"
+        INSERT INTO promotion_decisions
+            (id, tenant_id, artifact_digest, from_environment, to_environment, outcome, checks, policy_ids,
+             override_reason, requested_by, request_id, envelope, decided_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        "#,
+        body.id,
+        body.tenant_id,
+        body.artifact_digest,
+        body.from_environment,
+        body.to_environment,
+        body.outcome.as_str(),
+        Json(&body.checks) as _,
+        &body.policy_ids,
+        body.override_reason,
+        body.requested_by,
+        body.request_id,
+        Json(&decision.envelope) as _,
+        body.decided_at
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to store promotion decision: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+
+    Ok(())
+}
+
+async fn insert_lineage_edges(conn: &mut PgConnection, tenant_id: &Uuid, edges: &[LineageEdge]) -> Result<(), DatabaseError> {
+    for edge in edges {
+        sqlx::query!(
+            r#"
+            INSERT INTO provenance_lineage_edges
+                (tenant_id, provenance_id, input_digest, input_uri, output_digest, output_name)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (provenance_id, input_digest, output_digest) DO NOTHING
+            "#,
+            tenant_id,
+            edge.provenance_id,
+            edge.input_digest,
+            edge.input_uri,
+            edge.output_digest,
+            edge.output_name
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Failed to store lineage edge: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+    }
+
+    Ok(())
+}
+
+async fn insert_provenance_revision(conn: &mut PgConnection, revision: &ProvenanceRevision) -> Result<(), DatabaseError> {
+    info!("Appending revision {} to provenance record {}", revision.revision, revision.provenance_id);
+    sqlx::query!(
+        r#"
+        INSERT INTO provenance_revisions
+            (id, provenance_id, revision, kind, content, content_hash, previous_hash, chain_hash, reason, created_by, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#,
+        revision.id,
+        revision.provenance_id,
+        revision.revision,
+        revision.kind.as_str(),
+        revision.content.as_ref().map(Json) as _,
+        revision.content_hash,
+        revision.previous_hash,
+        revision.chain_hash,
+        revision.reason,
+        revision.created_by,
+        revision.created_at
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to append provenance revision: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+
+    Ok(())
+}
+
+async fn upsert_provenance_index_row(conn: &mut PgConnection, tenant_id: &Uuid, record: &ProvenanceRecord) -> Result<(), DatabaseError> {
+    let normalized = record.slsa_provenance.normalize();
+    sqlx::query!(
+        r#"
+        INSERT INTO provenance_index
+            (provenance_id, tenant_id, subject_digests, builder_id, build_type, source_uri, source_ref,
+             created_by, created_at, content)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (provenance_id) DO UPDATE SET
+            subject_digests = EXCLUDED.subject_digests,
+            builder_id = EXCLUDED.builder_id,
+            build_type = EXCLUDED.build_type,
+            source_uri = EXCLUDED.source_uri,
+            source_ref = EXCLUDED.source_ref,
+            content = EXCLUDED.content
+        "#,
+        record.id,
+        tenant_id,
+        &normalized.subject_digests,
+        normalized.builder_id,
+        normalized.build_type,
+        normalized.source_uri,
+        normalized.source_ref,
+        record.created_by,
+        record.created_at,
+        Json(record) as _
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to index provenance record: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+
+    Ok(())
+}
+
+/// Appends under a per-tenant advisory lock so leaf indices are dense and
+/// assigned in commit order.
+async fn append_log_entries(conn: &mut PgConnection, tenant_id: &Uuid, bodies: &[LogEntryBody]) -> Result<Vec<LogEntry>, DatabaseError> {
+    if bodies.is_empty() {
+        return Ok(Vec::new());
+    }
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))", tenant_id.to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Failed to lock transparency log: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+    let next_index = sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(leaf_index) + 1, 0) as "next!" FROM transparency_log_entries WHERE tenant_id = $1"#,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to read transparency log size: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+
+    // Every subtree the new leaves complete has its left half either among
+    // them or among the complete subtrees covering the log so far
+    let mut nodes = fetch_transparency_nodes(&mut *conn, tenant_id, &merkle::range_nodes(0..next_index as u64)).await?;
+
+    let mut entries = Vec::with_capacity(bodies.len());
+    for (offset, body) in bodies.iter().enumerate() {
+        let entry = LogEntry::new(*tenant_id, next_index as u64 + offset as u64, body.clone())?;
+        let leaf_hash = merkle::decode_hash(&entry.leaf_hash).map_err(|e| DatabaseError::InvalidValue(e.to_string()))?;
+        let completed = completed_nodes(entry.leaf_index, leaf_hash, |id| nodes.get(&id).copied())
+            .map_err(|e| DatabaseError::InvalidValue(e.to_string()))?;
+        sqlx::query!(
+            r#"
+            INSERT INTO transparency_log_entries
+                (tenant_id, leaf_index, leaf_hash, leaf_data, body, kind, resource_id, digest)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            tenant_id,
+            entry.leaf_index as i64,
+            entry.leaf_hash,
+            entry.leaf_data,
+            Json(&entry.body) as _,
+            entry.body.kind.as_str(),
+            entry.body.resource_id,
+            entry.body.digest
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            error!("Failed to append transparency log entry: {}", e);
+            DatabaseError::QueryError(e)
+        })?;
+        for (id, hash) in completed {
+            sqlx::query!(
+                "INSERT INTO transparency_log_nodes (tenant_id, level, node_index, hash) VALUES ($1, $2, $3, $4)",
+                tenant_id,
+                id.level as i32,
+                id.index as i64,
+                hex::encode(hash)
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("Failed to store transparency log node: {}", e);
+                DatabaseError::QueryError(e)
+            })?;
+            nodes.insert(id, hash);
+        }
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+async fn fetch_transparency_nodes(conn: &mut PgConnection, tenant_id: &Uuid, ids: &[NodeId]) -> Result<HashMap<NodeId, Hash>, DatabaseError> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let levels: Vec<i32> = ids.iter().map(|id| id.level as i32).collect();
+    let indices: Vec<i64> = ids.iter().map(|id| id.index as i64).collect();
+    let rows = sqlx::query!(
+        r#"
+        SELECT level, node_index, hash
+        FROM transparency_log_nodes
+        WHERE tenant_id = $1
+          AND (level, node_index) IN (SELECT * FROM UNNEST($2::INT[], $3::BIGINT[]))
+        "#,
+        tenant_id,
+        &levels,
+        &indices
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch transparency log nodes: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+
+    rows.into_iter()
+        .map(|row| {
+            let hash = merkle::decode_hash(&row.hash).map_err(|e| DatabaseError::InvalidValue(e.to_string()))?;
+            Ok((NodeId { level: row.level as u32, index: row.node_index as u64 }, hash))
+        })
+        .collect()
+}
+
+async fn fetch_audit_head(conn: &mut PgConnection, tenant_id: &Uuid) -> Result<Option<AuditEntry>, DatabaseError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id, tenant_id, sequence, actor, channel, action, resource, status, before_hash, after_hash,
+               request_id, source_ip, previous_hash, entry_hash, timestamp
+        FROM audit_entries
+        WHERE tenant_id = $1
+        ORDER BY sequence DESC
+        LIMIT 1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch audit log head: {}", e);
+        DatabaseError::QueryError(e)
+    })?;
+
+    row.map(|row| Ok(AuditEntry {
+        id: row.id,
+        sequence: row.sequence,
+        record: AuditRecord {
+            tenant_id: row.tenant_id,
+            actor: row.actor,
+            channel: row.channel.parse::<AuditChannel>().map_err(|e| DatabaseError::InvalidValue(e.to_string()))?,
+            action: row.action,
+            resource: row.resource,
+            status: row.status,
+            before_hash: row.before_hash,
+            after_hash: row.after_hash,
+            request_id: row.request_id,
+            source_ip: row.source_ip,
+        },
+        previous_hash: row.previous_hash,
+        entry_hash: row.entry_hash,
+        timestamp: row.timestamp,
+    })).transpose()
+}
@@ -15,17 +15,21 @@ use axum::{
 };
 use sqlx::PgPool;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 mod api;
 mod auth;
+mod chain_of_custody;
 mod config;
 mod database;
 mod error;
 mod grpc;
 mod models;
+mod provenance;
 mod security;
 mod storage;
 mod sbom;
+mod websocket;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -62,7 +66,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         settings.minio_use_ssl,
     ).await?;
 
-    let grpc_service = grpc::create_grpc_service(db.clone(), storage.clone());
+    let vault = || security::secret_management::VaultSecretManager::new(&settings.vault_addr, &settings.vault_token);
+    let live_updates = websocket::LiveUpdates::new();
+    if settings.audit.checkpoint_interval_secs > 0 {
+        api::spawn_audit_checkpoints(db.clone(), vault()?, Duration::from_secs(settings.audit.checkpoint_interval_secs));
+    }
+    if settings.witness.interval_secs > 0 {
+        api::spawn_witness(db.clone(), vault()?, live_updates.clone(), Duration::from_secs(settings.witness.interval_secs));
+    }
+
+    let grpc_service = grpc::create_grpc_service(db.clone(), storage.clone(), &settings.jwt_secret);
 
     let addr = format!("{}:{}", settings.server_host, settings.server_port).parse()?;
     println!("gRPC server listening on {}", addr);
@@ -3,6 +3,13 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+pub mod model_registry;
+pub mod model_signing;
+pub mod provenance;
+pub mod verification;
+
+pub use verification::{CheckStatus, VerificationCheck, VerificationCheckKind, VerificationReport};
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: Uuid,
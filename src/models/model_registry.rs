@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Read;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::models::provenance::ProvenanceRecord;
+
+/// Weights are hashed in chunks of this size so multi-GB files never need to
+/// be held in memory, and so a corrupted region can be located by chunk.
+pub const WEIGHT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// CycloneDX component type for models in an ML-BOM.
+pub const CYCLONEDX_MODEL_COMPONENT_TYPE: &str = "machine-learning-model";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ModelRegistryError {
+    #[error("Model name must not be empty")]
+    MissingName,
+    #[error("Model version must not be empty")]
+    MissingVersion,
+    #[error("Dataset {0} must have a sha256 digest")]
+    InvalidDatasetDigest(String),
+    #[error("ML-BOM does not contain a machine-learning-model component")]
+    NoModelComponent,
+    #[error("Weights digest {0} is not a subject of the training provenance")]
+    ProvenanceMismatch(String),
+}
+
+/// A named model whose versions are registered over time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegisteredModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterModelRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl RegisteredModel {
+    pub fn new(tenant_id: Uuid, created_by: Uuid, request: RegisterModelRequest) -> Result<Self, ModelRegistryError> {
+        let name = request.name.trim().to_string();
+        if name.is_empty() {
+            return Err(ModelRegistryError::MissingName);
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name,
+            description: request.description,
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+/// Digest of a weights file: the sha256 of the whole file, which is what
+/// build provenance names as a subject, plus the sha256 of each chunk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeightDigest {
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub chunk_size: u64,
+    pub chunk_digests: Vec<String>,
+}
+
+/// Incremental hasher for model weights. Input may arrive in pieces of any
+/// size; chunk boundaries are always at multiples of the chunk size.
+pub struct ChunkedHasher {
+    chunk_size: usize,
+    total: Sha256,
+    chunk: Sha256,
+    chunk_len: usize,
+    size_bytes: u64,
+    chunk_digests: Vec<String>,
+}
+
+impl ChunkedHasher {
+    pub fn new() -> Self {
+        Self::with_chunk_size(WEIGHT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            total: Sha256::new(),
+            chunk: Sha256::new(),
+            chunk_len: 0,
+            size_bytes: 0,
+            chunk_digests: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total.update(data);
+        self.size_bytes += data.len() as u64;
+        while !data.is_empty() {
+            let take = (self.chunk_size - self.chunk_len).min(data.len());
+            self.chunk.update(&data[..take]);
+            self.chunk_len += take;
+            data = &data[take..];
+            if self.chunk_len == self.chunk_size {
+                let chunk = std::mem::replace(&mut self.chunk, Sha256::new());
+                self.chunk_digests.push(hex::encode(chunk.finalize()));
+                self.chunk_len = 0;
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> WeightDigest {
+        if self.chunk_len > 0 {
+            self.chunk_digests.push(hex::encode(self.chunk.finalize()));
+        }
+        WeightDigest {
+            sha256: hex::encode(self.total.finalize()),
+            size_bytes: self.size_bytes,
+            chunk_size: self.chunk_size as u64,
+            chunk_digests: self.chunk_digests,
+        }
+    }
+}
+
+impl Default for ChunkedHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hashes weights from a reader, e.g. a file opened by the CLI.
+pub fn hash_weights<R: Read>(mut reader: R) -> std::io::Result<WeightDigest> {
+    let mut hasher = ChunkedHasher::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finalize());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+/// Model card metadata, following the CycloneDX 1.5 `modelCard` fields.
+/// Hyperparameters have no CycloneDX field and are exported as properties.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelCard {
+    #[serde(default)]
+    pub approach: Option<String>,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub architecture_family: Option<String>,
+    #[serde(default)]
+    pub architecture: Option<String>,
+    #[serde(default)]
+    pub hyperparameters: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub metrics: Vec<PerformanceMetric>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceMetric {
+    #[serde(rename = "type")]
+    pub metric_type: String,
+    pub value: String,
+    #[serde(default)]
+    pub slice: Option<String>,
+}
+
+/// A dataset version a model was trained or evaluated on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetVersionRef {
+    pub name: String,
+    pub version: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterModelVersionRequest {
+    pub version: String,
+    /// Where the weights are stored, e.g. an object store or Hugging Face URI.
+    #[serde(default)]
+    pub weights_uri: Option<String>,
+    #[serde(default)]
+    pub card: Option<ModelCard>,
+    /// A CycloneDX ML-BOM; its model card is used when `card` is absent.
+    #[serde(default)]
+    pub mlbom: Option<Value>,
+    #[serde(default)]
+    pub training_provenance_id: Option<Uuid>,
+    #[serde(default)]
+    pub datasets: Vec<DatasetVersionRef>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelVersion {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub model_id: Uuid,
+    pub model_name: String,
+    pub version: String,
+    /// Set once the weights have been uploaded and hashed.
+    pub weights: Option<WeightDigest>,
+    pub weights_uri: Option<String>,
+    pub card: ModelCard,
+    pub training_provenance_id: Option<Uuid>,
+    pub datasets: Vec<DatasetVersionRef>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModelVersion {
+    pub fn new(model: &RegisteredModel, created_by: Uuid, request: RegisterModelVersionRequest) -> Result<Self, ModelRegistryError> {
+        let version = request.version.trim().to_string();
+        if version.is_empty() {
+            return Err(ModelRegistryError::MissingVersion);
+        }
+        let datasets = request.datasets.into_iter()
+            .map(|mut dataset| {
+                dataset.sha256 = parse_digest(&dataset.sha256)
+                    .map_err(|_| ModelRegistryError::InvalidDatasetDigest(dataset.name.clone()))?;
+                Ok(dataset)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let card = match (request.card, request.mlbom) {
+            (Some(card), _) => card,
+            (None, Some(mlbom)) => ModelCard::from_cyclonedx(&mlbom)?,
+            (None, None) => ModelCard::default(),
+        };
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id: model.tenant_id,
+            model_id: model.id,
+            model_name: model.name.clone(),
+            version,
+            weights: None,
+            weights_uri: request.weights_uri,
+            card,
+            training_provenance_id: request.training_provenance_id,
+            datasets,
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Checks that the training provenance names these weights as a subject.
+    pub fn check_training_provenance(&self, weights: &WeightDigest, record: &ProvenanceRecord) -> Result<(), ModelRegistryError> {
+        let produced = record.slsa_provenance.subject.iter()
+            .any(|subject| subject.digest.sha256.eq_ignore_ascii_case(&weights.sha256));
+        if produced {
+            Ok(())
+        } else {
+            Err(ModelRegistryError::ProvenanceMismatch(weights.sha256.clone()))
+        }
+    }
+
+    /// Exports this version as a CycloneDX 1.5 ML-BOM. Training datasets are
+    /// listed as `data` components referenced from the model card.
+    pub fn to_cyclonedx(&self) -> Value {
+        let bom_ref = format!("model:{}@{}", self.model_name, self.version);
+        let datasets: Vec<Value> = self.datasets.iter()
+            .map(|dataset| json!({
+                "type": "data",
+                "bom-ref": format!("dataset:{}@{}", dataset.name, dataset.version),
+                "name": dataset.name,
+                "version": dataset.version,
+                "hashes": [{ "alg": "SHA-256", "content": dataset.sha256 }],
+            }))
+            .collect();
+
+        let mut model_parameters = json!({
+            "datasets": self.datasets.iter()
+                .map(|dataset| json!({ "ref": format!("dataset:{}@{}", dataset.name, dataset.version) }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(approach) = &self.card.approach {
+            model_parameters["approach"] = json!({ "type": approach });
+        }
+        for (key, value) in [
+            ("task", &self.card.task),
+            ("architectureFamily", &self.card.architecture_family),
+            ("modelArchitecture", &self.card.architecture),
+        ] {
+            if let Some(value) = value {
+                model_parameters[key] = json!(value);
+            }
+        }
+
+        let mut model = json!({
+            "type": CYCLONEDX_MODEL_COMPONENT_TYPE,
+            "bom-ref": bom_ref,
+            "name": self.model_name,
+            "version": self.version,
+            "modelCard": {
+                "modelParameters": model_parameters,
+                "quantitativeAnalysis": {
+                    "performanceMetrics": self.card.metrics,
+                },
+            },
+            "properties": self.card.hyperparameters.iter()
+                .map(|(name, value)| json!({
+                    "name": format!("traceguard:hyperparameter:{}", name),
+                    "value": value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()),
+                }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(weights) = &self.weights {
+            model["hashes"] = json!([{ "alg": "SHA-256", "content": weights.sha256 }]);
+        }
+        if let Some(uri) = &self.weights_uri {
+            model["externalReferences"] = json!([{ "type": "distribution", "url": uri }]);
+        }
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "serialNumber": format!("urn:uuid:{}", self.id),
+            "version": 1,
+            "metadata": {
+                "timestamp": self.created_at.to_rfc3339(),
+                "component": model.clone(),
+            },
+            "components": std::iter::once(model).chain(datasets).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl ModelCard {
+    /// Reads the model card of the first machine-learning-model component,
+    /// checking `metadata.component` before `components`.
+    pub fn from_cyclonedx(bom: &Value) -> Result<Self, ModelRegistryError> {
+        let model = std::iter::once(&bom["metadata"]["component"])
+            .chain(bom["components"].as_array().into_iter().flatten())
+            .find(|component| component["type"] == CYCLONEDX_MODEL_COMPONENT_TYPE)
+            .ok_or(ModelRegistryError::NoModelComponent)?;
+        let parameters = &model["modelCard"]["modelParameters"];
+        let text = |value: &Value| value.as_str().map(str::to_string);
+
+        Ok(Self {
+            approach: text(&parameters["approach"]["type"]),
+            task: text(&parameters["task"]),
+            architecture_family: text(&parameters["architectureFamily"]),
+            architecture: text(&parameters["modelArchitecture"]),
+            hyperparameters: model["properties"].as_array().into_iter().flatten()
+                .filter_map(|property| {
+                    let name = property["name"].as_str()?.strip_prefix("traceguard:hyperparameter:")?;
+                    // Properties are strings; numbers and booleans are restored from their JSON text
+                    let value = property["value"].as_str()?;
+                    Some((name.to_string(), serde_json::from_str(value).unwrap_or_else(|_| json!(value))))
+                })
+                .collect(),
+            metrics: model["modelCard"]["quantitativeAnalysis"]["performanceMetrics"]
+                .as_array().into_iter().flatten()
+                .filter_map(|metric| serde_json::from_value(metric.clone()).ok())
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::VerifyingKey;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::models::model_registry::hash_weights;
+use crate::provenance::dsse::{key_id_for, verify_envelope, DsseError, Envelope, EnvelopeSigner, IN_TOTO_PAYLOAD_TYPE};
+use crate::provenance::intoto::{ResourceDescriptor, Statement};
+
+/// Predicate type of the OpenSSF model-signing v1.0 format.
+pub const MODEL_SIGNING_PREDICATE_TYPE: &str = "https://model_signing/signature/v1.0";
+pub const SIGSTORE_BUNDLE_MEDIA_TYPE: &str = "application/vnd.dev.sigstore.bundle.v0.3+json";
+/// Secret under which each tenant's model signing key is stored.
+pub const MODEL_SIGNING_KEY_ID: &str = "model_signing_key";
+/// Name of the detached signature file conventionally stored next to a model.
+pub const MODEL_SIGNATURE_FILE: &str = "model.sig";
+
+#[derive(Error, Debug)]
+pub enum ModelSigningError {
+    #[error("Failed to read model directory: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Model directory contains a symlink at {0}")]
+    Symlink(String),
+    #[error("Envelope error: {0}")]
+    Dsse(#[from] DsseError),
+    #[error("Bundle is not an OpenSSF model signature: {0}")]
+    InvalidBundle(String),
+    #[error("Manifest has no files")]
+    EmptyManifest,
+    #[error("File {0} appears more than once in the manifest")]
+    DuplicateFile(String),
+    #[error("File {0} does not have a hex SHA-256 digest")]
+    InvalidDigest(String),
+}
+
+/// Digest of one file in the model directory, named by its path relative to
+/// the model root with `/` separators.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManifestResource {
+    pub name: String,
+    pub algorithm: String,
+    pub digest: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Serialization {
+    pub method: String,
+    pub hash_type: String,
+    pub allow_symlinks: bool,
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+}
+
+/// The `predicate` of a model-signing statement.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelSignaturePredicate {
+    pub serialization: Serialization,
+    pub resources: Vec<ManifestResource>,
+}
+
+/// Per-file digests of a model directory. Deserializing goes through
+/// `ModelManifest::new`, so every manifest holds valid SHA-256 digests.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "UncheckedManifest")]
+pub struct ModelManifest {
+    pub model_name: String,
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+    pub resources: Vec<ManifestResource>,
+}
+
+#[derive(Deserialize)]
+struct UncheckedManifest {
+    model_name: String,
+    #[serde(default)]
+    ignore_paths: Vec<String>,
+    resources: Vec<ManifestResource>,
+}
+
+impl TryFrom<UncheckedManifest> for ModelManifest {
+    type Error = ModelSigningError;
+
+    fn try_from(unchecked: UncheckedManifest) -> Result<Self, Self::Error> {
+        Self::new(unchecked.model_name, unchecked.ignore_paths, unchecked.resources)
+    }
+}
+
+impl ModelManifest {
+    pub fn new(model_name: String, ignore_paths: Vec<String>, mut resources: Vec<ManifestResource>) -> Result<Self, ModelSigningError> {
+        if resources.is_empty() {
+            return Err(ModelSigningError::EmptyManifest);
+        }
+        resources.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(pair) = resources.windows(2).find(|pair| pair[0].name == pair[1].name) {
+            return Err(ModelSigningError::DuplicateFile(pair[0].name.clone()));
+        }
+        for resource in &mut resources {
+            let is_sha256 = resource.algorithm.eq_ignore_ascii_case("sha256")
+                && resource.digest.len() == 64
+                && resource.digest.bytes().all(|b| b.is_ascii_hexdigit());
+            if !is_sha256 {
+                return Err(ModelSigningError::InvalidDigest(resource.name.clone()));
+            }
+            resource.algorithm = "sha256".to_string();
+            resource.digest = resource.digest.to_lowercase();
+        }
+        Ok(Self { model_name, ignore_paths, resources })
+    }
+
+    /// Hashes every regular file under `root`, streaming each through the
+    /// chunked weights hasher. Symlinks are rejected, as in the OpenSSF
+    /// default serialization, and `ignore_paths` are skipped along with any
+    /// `.git` directory and detached `model.sig`.
+    pub fn from_directory(root: &Path, model_name: &str, ignore_paths: &[String]) -> Result<Self, ModelSigningError> {
+        let mut resources = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                let name = path.strip_prefix(root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if name == ".git" || name == MODEL_SIGNATURE_FILE || is_ignored(&name, ignore_paths) {
+                    continue;
+                }
+
+                let file_type = entry.file_type()?;
+                if file_type.is_symlink() {
+                    return Err(ModelSigningError::Symlink(name));
+                } else if file_type.is_dir() {
+                    pending.push(path);
+                } else if file_type.is_file() {
+                    let digest = hash_weights(File::open(&path)?)?;
+                    resources.push(ManifestResource { name, algorithm: "sha256".to_string(), digest: digest.sha256 });
+                }
+            }
+        }
+        Self::new(model_name.to_string(), ignore_paths.to_vec(), resources)
+    }
+
+    /// The subject digest: SHA-256 over the raw digests of all files in name
+    /// order, matching the reference model-signing implementation.
+    pub fn root_digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        for resource in &self.resources {
+            hasher.update(hex::decode(&resource.digest).expect("manifest digests are checked in ModelManifest::new"));
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    pub fn to_statement(&self) -> Statement<ModelSignaturePredicate> {
+        Statement::new(
+            vec![ResourceDescriptor::sha256(Some(self.model_name.clone()), None, &self.root_digest())],
+            MODEL_SIGNING_PREDICATE_TYPE,
+            ModelSignaturePredicate {
+                serialization: Serialization {
+                    method: "files".to_string(),
+                    hash_type: "sha256".to_string(),
+                    allow_symlinks: false,
+                    ignore_paths: self.ignore_paths.clone(),
+                },
+                resources: self.resources.clone(),
+            },
+        )
+    }
+
+    /// Lists files that differ between the signed manifest and this one.
+    pub fn compare(&self, signed: &ModelManifest) -> ManifestDiff {
+        let signed: BTreeMap<&str, &str> = signed.resources.iter().map(|r| (r.name.as_str(), r.digest.as_str())).collect();
+        let current: BTreeMap<&str, &str> = self.resources.iter().map(|r| (r.name.as_str(), r.digest.as_str())).collect();
+        ManifestDiff {
+            changed: current.iter()
+                .filter(|(name, digest)| signed.get(*name).map_or(false, |signed| signed != *digest))
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            added: current.keys().filter(|name| !signed.contains_key(*name)).map(|name| name.to_string()).collect(),
+            removed: signed.keys().filter(|name| !current.contains_key(*name)).map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+fn is_ignored(name: &str, ignore_paths: &[String]) -> bool {
+    ignore_paths.iter().any(|ignored| {
+        let ignored = ignored.trim_matches('/');
+        name == ignored || name.starts_with(&format!("{}/", ignored))
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ManifestDiff {
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A Sigstore bundle holding a DSSE envelope, keyed by public key hint
+/// rather than a Fulcio certificate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSignatureBundle {
+    pub media_type: String,
+    pub verification_material: VerificationMaterial,
+    pub dsse_envelope: Envelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMaterial {
+    pub public_key: PublicKeyHint,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PublicKeyHint {
+    pub hint: String,
+}
+
+pub fn sign_manifest(signer: &EnvelopeSigner, manifest: &ModelManifest) -> Result<ModelSignatureBundle, ModelSigningError> {
+    Ok(ModelSignatureBundle {
+        media_type: SIGSTORE_BUNDLE_MEDIA_TYPE.to_string(),
+        verification_material: VerificationMaterial { public_key: PublicKeyHint { hint: signer.key_id().to_string() } },
+        dsse_envelope: signer.sign_json(IN_TOTO_PAYLOAD_TYPE, &manifest.to_statement())?,
+    })
+}
+
+/// A signature issued by TraceGuard for a registered model version.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelSignature {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub model_version_id: Uuid,
+    pub key_id: String,
+    pub root_digest: String,
+    pub bundle: ModelSignatureBundle,
+    pub signed_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModelSignature {
+    pub fn new(tenant_id: Uuid, model_version_id: Uuid, manifest: &ModelManifest, bundle: ModelSignatureBundle, signed_by: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            model_version_id,
+            key_id: bundle.verification_material.public_key.hint.clone(),
+            root_digest: manifest.root_digest(),
+            bundle,
+            signed_by,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelVerification {
+    pub verified: bool,
+    pub key_id: String,
+    pub root_digest: String,
+    #[serde(flatten)]
+    pub diff: ManifestDiff,
+}
+
+/// Checks the bundle signature and that the signed manifest is internally
+/// consistent, returning the manifest it signs.
+pub fn signed_manifest(bundle: &ModelSignatureBundle, key: &VerifyingKey) -> Result<ModelManifest, ModelSigningError> {
+    verify_envelope(&bundle.dsse_envelope, key)?;
+    let statement: Statement<ModelSignaturePredicate> = bundle.dsse_envelope.decode_payload()
+        .map_err(|e| ModelSigningError::InvalidBundle(e.to_string()))?;
+    if statement.predicate_type != MODEL_SIGNING_PREDICATE_TYPE {
+        return Err(ModelSigningError::InvalidBundle(format!("unexpected predicate type {}", statement.predicate_type)));
+    }
+    let subject = statement.subject.first()
+        .ok_or_else(|| ModelSigningError::InvalidBundle("statement has no subject".to_string()))?;
+
+    let manifest = ModelManifest::new(
+        subject.name.clone().unwrap_or_default(),
+        statement.predicate.serialization.ignore_paths,
+        statement.predicate.resources,
+    )?;
+    if subject.sha256_digest().map(str::to_lowercase) != Some(manifest.root_digest()) {
+        return Err(ModelSigningError::InvalidBundle("subject digest does not match the signed resources".to_string()));
+    }
+    Ok(manifest)
+}
+
+/// Verifies that `current` (e.g. the directory about to be loaded at deploy
+/// time) matches the signed manifest exactly.
+pub fn verify_manifest(bundle: &ModelSignatureBundle, key: &VerifyingKey, current: &ModelManifest) -> Result<ModelVerification, ModelSigningError> {
+    let signed = signed_manifest(bundle, key)?;
+    let diff = current.compare(&signed);
+    Ok(ModelVerification {
+        verified: diff.is_empty(),
+        key_id: key_id_for(key),
+        root_digest: signed.root_digest(),
+        diff,
+    })
+}
+
+/// Hashes `root` with the signed ignore paths and verifies it against the bundle.
+pub fn verify_directory(bundle: &ModelSignatureBundle, key: &VerifyingKey, root: &Path) -> Result<ModelVerification, ModelSigningError> {
+    let signed = signed_manifest(bundle, key)?;
+    let current = ModelManifest::from_directory(root, &signed.model_name, &signed.ignore_paths)?;
+    verify_manifest(bundle, key, &current)
+}
@@ -1,7 +1,18 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use sha2::{Sha256, Digest};
 use opentelemetry::{global, KeyValue};
+use serde_json::json;
 use tracing::{info, warn};
+use crate::chain_of_custody::custody_events::normalize_digest;
+use crate::chain_of_custody::timestamp::VerifiedTimestamp;
+use crate::models::{VerificationCheck, VerificationCheckKind, VerificationReport};
+use crate::provenance::dsse::{DsseError, Envelope, IN_TOTO_PAYLOAD_TYPE};
+use crate::provenance::intoto::Statement;
+use crate::provenance::slsa_v1::{to_slsa_provenance, ProvenancePredicateV1, SLSA_PROVENANCE_V1_PREDICATE_TYPE};
+use crate::provenance::policy::{BuilderTrustPolicy, PolicyEvaluation, PolicyField, PolicyViolation};
+use crate::provenance::revocation::{record_revocations, signer_key_ids, Revocation, RevokedArtifact};
+use crate::provenance::trusted_keys::{verifying_trusted_key, TrustedKey};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProvenanceRecord {
@@ -9,14 +20,51 @@ pub struct ProvenanceRecord {
     pub created_by: uuid::Uuid,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub slsa_provenance: SLSAProvenance,
+    /// The signed envelope the provenance was submitted in, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub envelope: Option<Envelope>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SLSAProvenance {
+    #[serde(default)]
+    pub subject: Vec<SLSASubject>,
     pub builder: SLSABuilder,
     pub build_type: String,
     pub invocation: SLSAInvocation,
     pub materials: Vec<SLSAMaterial>,
+    #[serde(default)]
+    pub metadata: SLSAMetadata,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SLSAMetadata {
+    #[serde(default)]
+    pub build_invocation_id: Option<String>,
+    /// Claimed by the builder; independently checked by the reproducibility workflow.
+    #[serde(default)]
+    pub reproducible: bool,
+    #[serde(default)]
+    pub completeness: SLSACompleteness,
+}
+
+/// Which inputs the builder claims to have declared in full (SLSA v0.2).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SLSACompleteness {
+    #[serde(default)]
+    pub parameters: bool,
+    #[serde(default)]
+    pub environment: bool,
+    #[serde(default)]
+    pub materials: bool,
+}
+
+impl SLSACompleteness {
+    /// A build whose environment and materials are all declared could not
+    /// have pulled in anything undeclared.
+    pub fn hermetic(&self) -> bool {
+        self.environment && self.materials
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,7 +85,22 @@ pub struct SLSAConfigSource {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SLSADigest {
+    #[serde(default)]
     pub sha256: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+}
+
+impl SLSADigest {
+    pub fn sha256(sha256: impl Into<String>) -> Self {
+        Self { sha256: sha256.into(), git_commit: None }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SLSASubject {
+    pub name: String,
+    pub digest: SLSADigest,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -46,6 +109,51 @@ pub struct SLSAMaterial {
     pub digest: SLSADigest,
 }
 
+/// Flattened view of the fields that policies and queries match against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedProvenance {
+    pub subject_names: Vec<String>,
+    pub subject_digests: Vec<String>,
+    pub builder_id: String,
+    pub build_type: String,
+    pub source_uri: String,
+    pub source_ref: Option<String>,
+    pub source_digest: String,
+    /// Set when every subject has been independently reproduced.
+    #[serde(default)]
+    pub reproduced: bool,
+}
+
+impl SLSAProvenance {
+    pub fn normalize(&self) -> NormalizedProvenance {
+        let config_source = &self.invocation.config_source;
+        // Config source URIs look like `git+https://github.com/org/repo@refs/heads/main`
+        let uri = config_source.uri.strip_prefix("git+").unwrap_or(&config_source.uri);
+        let (source_uri, source_ref) = match uri.rsplit_once('@') {
+            Some((repo, git_ref)) if !git_ref.contains('/') || git_ref.starts_with("refs/") => {
+                (repo.to_string(), Some(git_ref.to_string()))
+            }
+            _ => (uri.to_string(), None),
+        };
+        let source_uri = source_uri
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches(".git")
+            .to_string();
+
+        NormalizedProvenance {
+            subject_names: self.subject.iter().map(|subject| subject.name.clone()).collect(),
+            subject_digests: self.subject.iter().map(|subject| normalize_digest(&subject.digest.sha256)).collect(),
+            builder_id: self.builder.id.clone(),
+            build_type: self.build_type.clone(),
+            source_uri,
+            source_ref,
+            source_digest: normalize_digest(config_source.digest.git_commit.as_ref().unwrap_or(&config_source.digest.sha256)),
+            reproduced: false,
+        }
+    }
+}
+
 impl ProvenanceRecord {
     pub fn from_slsa(slsa: SLSAProvenance) -> Self {
         Self {
@@ -53,10 +161,30 @@ impl ProvenanceRecord {
             created_by: uuid::Uuid::nil(), // This should be set by the API
             created_at: chrono::Utc::now(),
             slsa_provenance: slsa,
+            envelope: None,
+        }
+    }
+
+    /// Builds a record from a DSSE envelope carrying an SLSA v1.0 provenance statement.
+    pub fn from_envelope(envelope: Envelope) -> Result<Self, DsseError> {
+        if envelope.payload_type != IN_TOTO_PAYLOAD_TYPE {
+            return Err(DsseError::UnsupportedPayload(envelope.payload_type.clone()));
+        }
+        let statement: Statement<ProvenancePredicateV1> = envelope.decode_payload()?;
+        if statement.predicate_type != SLSA_PROVENANCE_V1_PREDICATE_TYPE {
+            return Err(DsseError::UnsupportedPayload(statement.predicate_type));
         }
+
+        let mut record = Self::from_slsa(to_slsa_provenance(&statement));
+        record.envelope = Some(envelope);
+        Ok(record)
     }
 
-    pub fn verify_slsa(&self) -> bool {
+    pub fn verify_slsa(&self) -> VerificationReport {
+        self.verify_slsa_with(&VerificationOptions::default())
+    }
+
+    pub fn verify_slsa_with(&self, options: &VerificationOptions) -> VerificationReport {
         let tracer = global::tracer("provenance_verification");
         let span = tracer.start("verify_slsa");
         let _guard = span.enter();
@@ -64,69 +192,264 @@ impl ProvenanceRecord {
         let meter = global::meter("provenance_metrics");
         let verification_counter = meter.u64_counter("slsa_verifications").init();
 
-        let result = self.perform_slsa_verification();
+        let report = VerificationReport::new(self.id, self.perform_slsa_verification(options));
+
+        verification_counter.add(1, &[KeyValue::new("result", report.passed.to_string())]);
+        span.set_attribute(KeyValue::new("verification_result", report.passed.to_string()));
 
-        verification_counter.add(1, &[KeyValue::new("result", result.to_string())]);
-        span.set_attribute(KeyValue::new("verification_result", result.to_string()));
+        if report.passed {
+            info!("SLSA provenance verification passed");
+        } else {
+            warn!("SLSA provenance verification failed");
+        }
 
-        result
+        report
     }
 
-    fn perform_slsa_verification(&self) -> bool {
+    fn perform_slsa_verification(&self, options: &VerificationOptions) -> Vec<VerificationCheck> {
         let tracer = global::tracer("provenance_verification");
 
-        // Verify builder ID
-        let builder_span = tracer.start("verify_builder_id");
-        let builder_valid = !self.slsa_provenance.builder.id.is_empty();
-        builder_span.set_attribute(KeyValue::new("valid", builder_valid.to_string()));
-        builder_span.end();
-        if !builder_valid {
+        let mut normalized = self.slsa_provenance.normalize();
+        normalized.reproduced = !normalized.subject_digests.is_empty()
+            && normalized.subject_digests.iter().all(|digest| options.reproduced_digests.contains(digest));
+        let evaluations: Vec<PolicyEvaluation> = options.policies.iter()
+            .filter_map(|policy| policy.evaluate(&normalized))
+            .collect();
+
+        let checks = vec![
+            self.verify_signature(&options.trusted_keys),
+            self.verify_builder_trust(&evaluations),
+            self.verify_subject_digest(),
+            self.verify_materials(),
+            self.verify_policy(&evaluations),
+            self.verify_freshness(options),
+            self.verify_revocation(options),
+            verify_timestamp(options),
+        ];
+
+        for check in &checks {
+            let check_span = tracer.start(format!("verify_{}", check.kind.as_str()));
+            check_span.set_attribute(KeyValue::new("status", format!("{:?}", check.status)));
+            check_span.end();
+        }
+
+        checks
+    }
+
+    fn verify_signature(&self, trusted_keys: &[TrustedKey]) -> VerificationCheck {
+        let envelope = match &self.envelope {
+            Some(envelope) => envelope,
+            None => return VerificationCheck::skip(VerificationCheckKind::Signature, "Provenance was not submitted in a signed envelope"),
+        };
+
+        let signed_by: Vec<&str> = envelope.signatures.iter().map(|signature| signature.keyid.as_str()).collect();
+        let verified_key = verifying_trusted_key(envelope, trusted_keys);
+
+        match verified_key {
+            Some(key) => VerificationCheck::pass(
+                VerificationCheckKind::Signature,
+                "Envelope signature verified with a trusted key",
+                json!({ "key_id": key.key_id, "signed_by": signed_by }),
+            ),
+            None => {
+                warn!("Provenance envelope is not signed by a trusted key");
+                VerificationCheck::fail(
+                    VerificationCheckKind::Signature,
+                    "Envelope signature does not match any trusted key",
+                    json!({ "signed_by": signed_by, "trusted_key_count": trusted_keys.len() }),
+                )
+            }
+        }
+    }
+
+    fn verify_builder_trust(&self, evaluations: &[PolicyEvaluation]) -> VerificationCheck {
+        let builder_id = &self.slsa_provenance.builder.id;
+        if builder_id.is_empty() {
             warn!("Invalid builder ID in SLSA provenance");
-            return false;
+            return VerificationCheck::fail(VerificationCheckKind::BuilderTrust, "Builder ID is empty", json!({ "builder_id": builder_id }));
+        }
+        if evaluations.is_empty() {
+            return VerificationCheck::pass(VerificationCheckKind::BuilderTrust, "Builder ID is present; no trust policy applies", json!({ "builder_id": builder_id }));
         }
 
-        // Verify build type
-        let build_type_span = tracer.start("verify_build_type");
-        let build_type_valid = !self.slsa_provenance.build_type.is_empty();
-        build_type_span.set_attribute(KeyValue::new("valid", build_type_valid.to_string()));
-        build_type_span.end();
-        if !build_type_valid {
-            warn!("Invalid build type in SLSA provenance");
-            return false;
+        let violations: Vec<&PolicyViolation> = evaluations.iter()
+            .flat_map(|evaluation| evaluation.violations.iter())
+            .filter(|violation| violation.field == PolicyField::BuilderId)
+            .collect();
+        let builder_pinned = evaluations.iter().any(|evaluation| evaluation.builder_pinned);
+        let evidence = json!({
+            "builder_id": builder_id,
+            "policies": evaluations,
+            "violations": violations,
+            "builder_pinned": builder_pinned,
+        });
+        if !violations.is_empty() {
+            warn!("Builder {} is not trusted by policy", builder_id);
+            return VerificationCheck::fail(VerificationCheckKind::BuilderTrust, "Builder is not trusted for this artifact", evidence);
+        }
+        VerificationCheck::pass(VerificationCheckKind::BuilderTrust, "Builder is trusted by all applicable policies", evidence)
+    }
+
+    fn verify_subject_digest(&self) -> VerificationCheck {
+        let subjects = &self.slsa_provenance.subject;
+        if subjects.is_empty() {
+            return VerificationCheck::skip(VerificationCheckKind::SubjectDigest, "Provenance does not declare any subjects");
+        }
+
+        let invalid: Vec<&str> = subjects.iter()
+            .filter(|subject| !is_valid_sha256(&subject.digest.sha256))
+            .map(|subject| subject.name.as_str())
+            .collect();
+        if !invalid.is_empty() {
+            warn!("Invalid subject digest in SLSA provenance: {:?}", invalid);
+            return VerificationCheck::fail(VerificationCheckKind::SubjectDigest, "Subjects with a malformed sha256 digest", json!({ "invalid_subjects": invalid }));
         }
 
-        // Verify invocation
-        let invocation_span = tracer.start("verify_invocation");
-        let invocation_valid = !self.slsa_provenance.invocation.config_source.uri.is_empty();
-        invocation_span.set_attribute(KeyValue::new("valid", invocation_valid.to_string()));
-        invocation_span.end();
-        if !invocation_valid {
+        let digests: Vec<&str> = subjects.iter().map(|subject| subject.digest.sha256.as_str()).collect();
+        VerificationCheck::pass(VerificationCheckKind::SubjectDigest, "All subject digests are well-formed", json!({ "digests": digests }))
+    }
+
+    fn verify_materials(&self) -> VerificationCheck {
+        let invalid: Vec<&str> = self.slsa_provenance.materials.iter()
+            .filter(|material| material.uri.is_empty() || !is_valid_sha256(&material.digest.sha256))
+            .map(|material| material.uri.as_str())
+            .collect();
+        if !invalid.is_empty() {
+            warn!("Invalid materials in SLSA provenance: {:?}", invalid);
+            return VerificationCheck::fail(VerificationCheckKind::Materials, "Materials with a missing URI or malformed sha256 digest", json!({ "invalid_materials": invalid }));
+        }
+        VerificationCheck::pass(
+            VerificationCheckKind::Materials,
+            format!("{} materials verified", self.slsa_provenance.materials.len()),
+            json!({
+                "count": self.slsa_provenance.materials.len(),
+                "hermetic": self.slsa_provenance.metadata.completeness.hermetic(),
+            }),
+        )
+    }
+
+    fn verify_policy(&self, evaluations: &[PolicyEvaluation]) -> VerificationCheck {
+        // Baseline policy: build type and config source must be declared
+        let build_type = &self.slsa_provenance.build_type;
+        let config_source = &self.slsa_provenance.invocation.config_source.uri;
+        let violations: Vec<&PolicyViolation> = evaluations.iter()
+            .flat_map(|evaluation| evaluation.violations.iter())
+            .filter(|violation| violation.field != PolicyField::BuilderId)
+            .collect();
+        let evidence = json!({ "build_type": build_type, "config_source": config_source, "violations": violations });
+
+        if build_type.is_empty() {
+            warn!("Invalid build type in SLSA provenance");
+            return VerificationCheck::fail(VerificationCheckKind::Policy, "Build type is empty", evidence);
+        }
+        if config_source.is_empty() {
             warn!("Invalid invocation in SLSA provenance");
-            return false;
+            return VerificationCheck::fail(VerificationCheckKind::Policy, "Invocation config source is empty", evidence);
+        }
+        if !violations.is_empty() {
+            warn!("SLSA provenance violates {} policy constraints", violations.len());
+            return VerificationCheck::fail(VerificationCheckKind::Policy, "Provenance violates tenant policy", evidence);
         }
+        VerificationCheck::pass(VerificationCheckKind::Policy, "Baseline and tenant policies satisfied", evidence)
+    }
 
-        // Verify materials
-        let materials_span = tracer.start("verify_materials");
-        let materials_valid = self.slsa_provenance.materials.iter().all(|material| {
-            let valid = !material.uri.is_empty() && !material.digest.sha256.is_empty() && is_valid_sha256(&material.digest.sha256);
-            if !valid {
-                warn!("Invalid material in SLSA provenance: {}", material.uri);
-            }
-            valid
+    fn verify_freshness(&self, options: &VerificationOptions) -> VerificationCheck {
+        let max_age = match options.max_age {
+            Some(max_age) => max_age,
+            None => return VerificationCheck::skip(VerificationCheckKind::Freshness, "No maximum provenance age configured"),
+        };
+
+        let age = chrono::Utc::now() - self.created_at;
+        let evidence = json!({
+            "created_at": self.created_at,
+            "age_seconds": age.num_seconds(),
+            "max_age_seconds": max_age.num_seconds(),
         });
-        materials_span.set_attribute(KeyValue::new("valid", materials_valid.to_string()));
-        materials_span.end();
+        if age > max_age {
+            return VerificationCheck::fail(VerificationCheckKind::Freshness, "Provenance is older than the allowed maximum age", evidence);
+        }
+        VerificationCheck::pass(VerificationCheckKind::Freshness, "Provenance is within the allowed age", evidence)
+    }
 
-        if materials_valid {
-            info!("SLSA provenance verification passed");
-        } else {
-            warn!("SLSA provenance verification failed");
+    /// Digests whose revocation makes the record untrusted: its subjects,
+    /// its materials and its source.
+    pub fn involved_digests(&self) -> Vec<String> {
+        let normalized = self.slsa_provenance.normalize();
+        let mut digests = normalized.subject_digests;
+        digests.extend(self.slsa_provenance.materials.iter().map(|material| normalize_digest(&material.digest.sha256)));
+        digests.push(normalized.source_digest);
+        digests
+    }
+
+    /// Fails when a revocation covers the record itself, or when any of its
+    /// subjects or inputs is untrusted, directly or through its own inputs.
+    fn verify_revocation(&self, options: &VerificationOptions) -> VerificationCheck {
+        let key_ids = signer_key_ids(self, &options.trusted_keys);
+        let covering: Vec<&Revocation> = record_revocations(&options.revocations, self, &key_ids, chrono::Utc::now());
+
+        let involved = self.involved_digests();
+        let digests: HashSet<&str> = involved.iter().map(String::as_str).collect();
+        let revoked: Vec<&RevokedArtifact> = options.revoked_artifacts.iter()
+            .filter(|artifact| digests.contains(artifact.digest.as_str()))
+            .collect();
+
+        let evidence = json!({ "revocations": covering, "revoked_artifacts": revoked });
+        if !covering.is_empty() {
+            warn!("Provenance record {} is covered by {} revocations", self.id, covering.len());
+            return VerificationCheck::fail(VerificationCheckKind::Revocation, "Provenance is covered by a revocation", evidence);
+        }
+        if !revoked.is_empty() {
+            warn!("Provenance record {} involves {} revoked artifacts", self.id, revoked.len());
+            return VerificationCheck::fail(VerificationCheckKind::Revocation, "A subject or input of this build has been revoked", evidence);
         }
+        VerificationCheck::pass(VerificationCheckKind::Revocation, "No revocation applies to the record or its inputs", evidence)
+    }
+}
+
+/// Passes when a TSA token over the verified revision checks out against the
+/// trusted TSA certificates, and fails when the revision has tokens but none
+/// of them does.
+fn verify_timestamp(options: &VerificationOptions) -> VerificationCheck {
+    if options.timestamps.is_empty() {
+        return VerificationCheck::skip(VerificationCheckKind::Timestamp, "Revision has no timestamp token");
+    }
+    if let Some(verified) = options.timestamps.iter().find_map(|result| result.as_ref().ok()) {
+        let message = format!("Timestamped by {} at {}", verified.tsa, verified.gen_time);
+        return VerificationCheck::pass(VerificationCheckKind::Timestamp, message, json!(verified));
+    }
+    let errors: Vec<&String> = options.timestamps.iter().filter_map(|result| result.as_ref().err()).collect();
+    VerificationCheck::fail(VerificationCheckKind::Timestamp, "No timestamp token verifies", json!({ "errors": errors }))
+}
+
+/// Query parameters of the endpoints that verify a stored record.
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifyParams {
+    /// Fails the freshness check of records created longer ago than this.
+    /// Without it the check is skipped.
+    pub max_age_secs: Option<u64>,
+}
 
-        materials_valid
+impl VerifyParams {
+    pub fn max_age(&self) -> Option<chrono::Duration> {
+        // An age too large to represent allows every record, like no limit
+        self.max_age_secs.and_then(|secs| chrono::Duration::from_std(std::time::Duration::from_secs(secs)).ok())
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct VerificationOptions {
+    pub max_age: Option<chrono::Duration>,
+    pub policies: Vec<BuilderTrustPolicy>,
+    pub trusted_keys: Vec<TrustedKey>,
+    pub reproduced_digests: HashSet<String>,
+    pub revocations: Vec<Revocation>,
+    /// Untrusted artifacts, including everything downstream of a revocation.
+    pub revoked_artifacts: Vec<RevokedArtifact>,
+    /// Each stored timestamp token over the revision, checked against the
+    /// trusted TSAs, or why it did not check out.
+    pub timestamps: Vec<Result<VerifiedTimestamp, String>>,
+}
+
 fn is_valid_sha256(hash: &str) -> bool {
     hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
 }
\ No newline at end of file
@@ -1,20 +1,23 @@
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{CheckStatus, VerificationCheckKind};
     use chrono::Utc;
 
     #[test]
     fn test_provenance_record_from_slsa() {
         let slsa = SLSAProvenance {
+            subject: vec![],
             builder: SLSABuilder { id: "test-builder".to_string() },
             build_type: "test-build-type".to_string(),
             invocation: SLSAInvocation {
                 config_source: SLSAConfigSource {
                     uri: "test-uri".to_string(),
-                    digest: SLSADigest { sha256: "test-sha256".to_string() },
+                    digest: SLSADigest::sha256("test-sha256".to_string()),
                 },
             },
             materials: vec![],
+            metadata: SLSAMetadata::default(),
         };
 
         let record = ProvenanceRecord::from_slsa(slsa.clone());
@@ -29,23 +32,30 @@ mod tests {
             id: uuid::Uuid::new_v4(),
             created_by: uuid::Uuid::new_v4(),
             created_at: Utc::now(),
+            envelope: None,
             slsa_provenance: SLSAProvenance {
+                subject: vec![],
                 builder: SLSABuilder { id: "test-builder".to_string() },
                 build_type: "test-build-type".to_string(),
                 invocation: SLSAInvocation {
                     config_source: SLSAConfigSource {
                         uri: "test-uri".to_string(),
-                        digest: SLSADigest { sha256: "a".repeat(64) },
+                        digest: SLSADigest::sha256("a".repeat(64)),
                     },
                 },
                 materials: vec![SLSAMaterial {
                     uri: "test-material".to_string(),
-                    digest: SLSADigest { sha256: "b".repeat(64) },
+                    digest: SLSADigest::sha256("b".repeat(64)),
                 }],
+                metadata: SLSAMetadata::default(),
             },
         };
 
-        assert!(record.verify_slsa());
+        let report = record.verify_slsa();
+        assert!(report.passed);
+        assert_eq!(report.provenance_id, record.id);
+        assert_eq!(report.check(VerificationCheckKind::Materials).unwrap().status, CheckStatus::Pass);
+        assert_eq!(report.check(VerificationCheckKind::Signature).unwrap().status, CheckStatus::Skip);
     }
 
     #[test]
@@ -54,19 +64,56 @@ mod tests {
             id: uuid::Uuid::new_v4(),
             created_by: uuid::Uuid::new_v4(),
             created_at: Utc::now(),
+            envelope: None,
             slsa_provenance: SLSAProvenance {
+                subject: vec![],
                 builder: SLSABuilder { id: "".to_string() },
                 build_type: "test-build-type".to_string(),
                 invocation: SLSAInvocation {
                     config_source: SLSAConfigSource {
                         uri: "test-uri".to_string(),
-                        digest: SLSADigest { sha256: "invalid-sha256".to_string() },
+                        digest: SLSADigest::sha256("invalid-sha256".to_string()),
                     },
                 },
                 materials: vec![],
+                metadata: SLSAMetadata::default(),
             },
         };
 
-        assert!(!record.verify_slsa());
+        let report = record.verify_slsa();
+        assert!(!report.passed);
+        assert_eq!(report.check(VerificationCheckKind::BuilderTrust).unwrap().status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_verify_slsa_stale() {
+        let record = ProvenanceRecord {
+            id: uuid::Uuid::new_v4(),
+            created_by: uuid::Uuid::new_v4(),
+            created_at: Utc::now() - chrono::Duration::days(30),
+            envelope: None,
+            slsa_provenance: SLSAProvenance {
+                subject: vec![SLSASubject {
+                    name: "test-subject".to_string(),
+                    digest: SLSADigest::sha256("c".repeat(64)),
+                }],
+                builder: SLSABuilder { id: "test-builder".to_string() },
+                build_type: "test-build-type".to_string(),
+                invocation: SLSAInvocation {
+                    config_source: SLSAConfigSource {
+                        uri: "test-uri".to_string(),
+                        digest: SLSADigest::sha256("a".repeat(64)),
+                    },
+                },
+                materials: vec![],
+                metadata: SLSAMetadata::default(),
+            },
+        };
+
+        let options = VerificationOptions { max_age: Some(chrono::Duration::days(7)), ..Default::default() };
+        let report = record.verify_slsa_with(&options);
+        assert!(!report.passed);
+        assert_eq!(report.check(VerificationCheckKind::SubjectDigest).unwrap().status, CheckStatus::Pass);
+        assert_eq!(report.failed_checks().map(|check| check.kind).collect::<Vec<_>>(), vec![VerificationCheckKind::Freshness]);
     }
 }
\ No newline at end of file
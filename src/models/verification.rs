@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationCheckKind {
+    Signature,
+    BuilderTrust,
+    SubjectDigest,
+    Materials,
+    Policy,
+    Freshness,
+    SourceCommit,
+    SourceSignature,
+    SourceReachability,
+    Revocation,
+    Timestamp,
+}
+
+impl VerificationCheckKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationCheckKind::Signature => "signature",
+            VerificationCheckKind::BuilderTrust => "builder_trust",
+            VerificationCheckKind::SubjectDigest => "subject_digest",
+            VerificationCheckKind::Materials => "materials",
+            VerificationCheckKind::Policy => "policy",
+            VerificationCheckKind::Freshness => "freshness",
+            VerificationCheckKind::SourceCommit => "source_commit",
+            VerificationCheckKind::SourceSignature => "source_signature",
+            VerificationCheckKind::SourceReachability => "source_reachability",
+            VerificationCheckKind::Revocation => "revocation",
+            VerificationCheckKind::Timestamp => "timestamp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationCheck {
+    pub kind: VerificationCheckKind,
+    pub status: CheckStatus,
+    pub message: String,
+    pub evidence: serde_json::Value,
+}
+
+impl VerificationCheck {
+    pub fn pass(kind: VerificationCheckKind, message: impl Into<String>, evidence: serde_json::Value) -> Self {
+        Self { kind, status: CheckStatus::Pass, message: message.into(), evidence }
+    }
+
+    pub fn fail(kind: VerificationCheckKind, message: impl Into<String>, evidence: serde_json::Value) -> Self {
+        Self { kind, status: CheckStatus::Fail, message: message.into(), evidence }
+    }
+
+    pub fn skip(kind: VerificationCheckKind, message: impl Into<String>) -> Self {
+        Self { kind, status: CheckStatus::Skip, message: message.into(), evidence: serde_json::Value::Null }
+    }
+}
+
+/// Outcome of verifying a single provenance record. A report passes when no
+/// check failed; skipped checks do not count against it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub id: Uuid,
+    pub provenance_id: Uuid,
+    pub verified_by: Uuid,
+    pub verified_at: DateTime<Utc>,
+    pub passed: bool,
+    pub checks: Vec<VerificationCheck>,
+}
+
+impl VerificationReport {
+    pub fn new(provenance_id: Uuid, checks: Vec<VerificationCheck>) -> Self {
+        let passed = checks.iter().all(|check| check.status != CheckStatus::Fail);
+        Self {
+            id: Uuid::new_v4(),
+            provenance_id,
+            verified_by: Uuid::nil(), // This should be set by the API
+            verified_at: Utc::now(),
+            passed,
+            checks,
+        }
+    }
+
+    pub fn check(&self, kind: VerificationCheckKind) -> Option<&VerificationCheck> {
+        self.checks.iter().find(|check| check.kind == kind)
+    }
+
+    pub fn failed_checks(&self) -> impl Iterator<Item = &VerificationCheck> {
+        self.checks.iter().filter(|check| check.status == CheckStatus::Fail)
+    }
+}
@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::provenance::dsse::{verify_envelope, DsseError, Envelope, IN_TOTO_PAYLOAD_TYPE};
+use crate::provenance::intoto::{ResourceDescriptor, Statement};
+use crate::provenance::slsa_v1::SLSA_PROVENANCE_V1_PREDICATE_TYPE;
+use crate::provenance::trusted_keys::TrustedKey;
+
+pub const TEST_RESULT_PREDICATE_TYPE: &str = "https://in-toto.io/attestation/test-result/v0.1";
+pub const COSIGN_VULN_PREDICATE_TYPE: &str = "https://cosign.sigstore.dev/attestation/vuln/v1";
+pub const SPDX_PREDICATE_TYPE: &str = "https://spdx.dev/Document";
+pub const CYCLONEDX_PREDICATE_TYPE: &str = "https://cyclonedx.org/bom";
+pub const CODE_REVIEW_PREDICATE_TYPE: &str = "https://in-toto.io/attestation/human-review/vcs/v0.1";
+pub const RELEASE_PREDICATE_TYPE: &str = "https://in-toto.io/attestation/release/v0.1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttestationKind {
+    TestResult,
+    VulnerabilityScan,
+    Spdx,
+    CycloneDx,
+    CodeReview,
+    Release,
+    Other,
+}
+
+impl AttestationKind {
+    /// SBOM predicate types are versioned by suffix (`.../Document/v2.3`,
+    /// `.../bom/v1.5`), so they match on prefix.
+    pub fn from_predicate_type(predicate_type: &str) -> Self {
+        match predicate_type {
+            TEST_RESULT_PREDICATE_TYPE => AttestationKind::TestResult,
+            COSIGN_VULN_PREDICATE_TYPE => AttestationKind::VulnerabilityScan,
+            CODE_REVIEW_PREDICATE_TYPE => AttestationKind::CodeReview,
+            RELEASE_PREDICATE_TYPE => AttestationKind::Release,
+            t if t.starts_with(SPDX_PREDICATE_TYPE) => AttestationKind::Spdx,
+            t if t.starts_with(CYCLONEDX_PREDICATE_TYPE) => AttestationKind::CycloneDx,
+            _ => AttestationKind::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttestationKind::TestResult => "test_result",
+            AttestationKind::VulnerabilityScan => "vulnerability_scan",
+            AttestationKind::Spdx => "spdx",
+            AttestationKind::CycloneDx => "cyclonedx",
+            AttestationKind::CodeReview => "code_review",
+            AttestationKind::Release => "release",
+            AttestationKind::Other => "other",
+        }
+    }
+}
+
+impl FromStr for AttestationKind {
+    type Err = AttestationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "test_result" => Ok(AttestationKind::TestResult),
+            "vulnerability_scan" => Ok(AttestationKind::VulnerabilityScan),
+            "spdx" => Ok(AttestationKind::Spdx),
+            "cyclonedx" => Ok(AttestationKind::CycloneDx),
+            "code_review" => Ok(AttestationKind::CodeReview),
+            "release" => Ok(AttestationKind::Release),
+            "other" => Ok(AttestationKind::Other),
+            _ => Err(AttestationError::UnknownKind(value.to_string())),
+        }
+    }
+}
+
+/// in-toto test-result predicate v0.1.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestResultPredicate {
+    /// One of `PASSED`, `WARNED` or `FAILED`.
+    pub result: String,
+    #[serde(default)]
+    pub configuration: Vec<ResourceDescriptor>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub passed_tests: Vec<String>,
+    #[serde(default)]
+    pub warned_tests: Vec<String>,
+    #[serde(default)]
+    pub failed_tests: Vec<String>,
+}
+
+/// Cosign vulnerability scan predicate. The scanner's own report format is
+/// kept as-is in `scanner.result`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VulnScanPredicate {
+    #[serde(default)]
+    pub invocation: Value,
+    pub scanner: VulnScanner,
+    #[serde(default)]
+    pub metadata: VulnScanMetadata,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VulnScanner {
+    pub uri: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub db: Option<VulnScannerDb>,
+    #[serde(default)]
+    pub result: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VulnScannerDb {
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VulnScanMetadata {
+    #[serde(default)]
+    pub scan_started_on: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub scan_finished_on: Option<DateTime<Utc>>,
+}
+
+/// The fields of an SBOM predicate needed for indexing. The full document stays
+/// in the stored predicate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SbomSummary {
+    pub spec_version: Option<String>,
+    pub name: Option<String>,
+    pub component_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeReviewPredicate {
+    /// The reviewed change, e.g. a pull request URL or commit.
+    pub change: ResourceDescriptor,
+    #[serde(default)]
+    pub reviewers: Vec<Reviewer>,
+    #[serde(default)]
+    pub review_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reviewer {
+    pub id: String,
+    pub approved: bool,
+}
+
+impl CodeReviewPredicate {
+    pub fn approved(&self) -> bool {
+        self.reviewers.iter().any(|reviewer| reviewer.approved)
+    }
+}
+
+/// in-toto release predicate v0.1.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleasePredicate {
+    pub purl: String,
+    #[serde(default)]
+    pub release_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedPredicate {
+    TestResult(TestResultPredicate),
+    VulnerabilityScan(VulnScanPredicate),
+    Sbom(SbomSummary),
+    CodeReview(CodeReviewPredicate),
+    Release(ReleasePredicate),
+    Other(Value),
+}
+
+#[derive(Error, Debug)]
+pub enum AttestationError {
+    #[error("Envelope error: {0}")]
+    Dsse(#[from] DsseError),
+    #[error("Invalid {kind} predicate: {source}")]
+    InvalidPredicate {
+        kind: &'static str,
+        source: serde_json::Error,
+    },
+    #[error("SLSA provenance must be submitted to the provenance endpoint")]
+    IsProvenance,
+    #[error("Statement has no subject with a sha256 digest")]
+    NoSubjects,
+    #[error("Unknown attestation kind: {0}")]
+    UnknownKind(String),
+}
+
+/// A signed in-toto statement other than SLSA provenance, indexed by the
+/// digests of the artifacts it describes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Attestation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub predicate_type: String,
+    pub kind: AttestationKind,
+    pub subject_names: Vec<String>,
+    pub subject_digests: Vec<String>,
+    pub predicate: Value,
+    /// Pass or fail outcome for kinds that have one (tests, code review), so
+    /// policies can filter without re-parsing the predicate.
+    pub passed: Option<bool>,
+    /// Trusted key that verified the envelope signature, if any.
+    pub verified_key_id: Option<String>,
+    pub envelope: Envelope,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Attestation {
+    pub fn from_envelope(tenant_id: Uuid, created_by: Uuid, envelope: Envelope) -> Result<Self, AttestationError> {
+        if envelope.payload_type != IN_TOTO_PAYLOAD_TYPE {
+            return Err(DsseError::UnsupportedPayload(envelope.payload_type.clone()).into());
+        }
+        let statement: Statement<Value> = envelope.decode_payload()?;
+        if statement.predicate_type.starts_with("https://slsa.dev/provenance/")
+            || statement.predicate_type == SLSA_PROVENANCE_V1_PREDICATE_TYPE
+        {
+            return Err(AttestationError::IsProvenance);
+        }
+
+        let subjects: Vec<(String, String)> = statement.subject.iter()
+            .filter_map(|subject| {
+                let digest = subject.sha256_digest()?.to_lowercase();
+                Some((subject.name.clone().unwrap_or_default(), digest))
+            })
+            .collect();
+        if subjects.is_empty() {
+            return Err(AttestationError::NoSubjects);
+        }
+
+        let kind = AttestationKind::from_predicate_type(&statement.predicate_type);
+        let typed = parse_predicate(kind, &statement.predicate)?;
+        let passed = match &typed {
+            TypedPredicate::TestResult(predicate) => Some(predicate.result != "FAILED"),
+            TypedPredicate::CodeReview(predicate) => Some(predicate.approved()),
+            _ => None,
+        };
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            predicate_type: statement.predicate_type,
+            kind,
+            subject_names: subjects.iter().map(|(name, _)| name.clone()).collect(),
+            subject_digests: subjects.into_iter().map(|(_, digest)| digest).collect(),
+            predicate: statement.predicate,
+            passed,
+            verified_key_id: None,
+            envelope,
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Records the first trusted key whose signature on the envelope verifies.
+    pub fn verify_signature(&mut self, trusted_keys: &[TrustedKey]) {
+        self.verified_key_id = trusted_keys.iter()
+            .find(|key| key.verifying_key().map_or(false, |verifying_key| verify_envelope(&self.envelope, &verifying_key).is_ok()))
+            .map(|key| key.key_id.clone());
+    }
+
+    pub fn typed_predicate(&self) -> Result<TypedPredicate, AttestationError> {
+        parse_predicate(self.kind, &self.predicate)
+    }
+}
+
+pub fn parse_predicate(kind: AttestationKind, predicate: &Value) -> Result<TypedPredicate, AttestationError> {
+    fn typed<T: for<'de> Deserialize<'de>>(kind: AttestationKind, predicate: &Value) -> Result<T, AttestationError> {
+        T::deserialize(predicate).map_err(|source| AttestationError::InvalidPredicate { kind: kind.as_str(), source })
+    }
+
+    Ok(match kind {
+        AttestationKind::TestResult => TypedPredicate::TestResult(typed(kind, predicate)?),
+        AttestationKind::VulnerabilityScan => TypedPredicate::VulnerabilityScan(typed(kind, predicate)?),
+        AttestationKind::CodeReview => TypedPredicate::CodeReview(typed(kind, predicate)?),
+        AttestationKind::Release => TypedPredicate::Release(typed(kind, predicate)?),
+        AttestationKind::Spdx => TypedPredicate::Sbom(SbomSummary {
+            spec_version: predicate["spdxVersion"].as_str().map(str::to_string),
+            name: predicate["name"].as_str().map(str::to_string),
+            component_count: predicate["packages"].as_array().map_or(0, Vec::len),
+        }),
+        AttestationKind::CycloneDx => TypedPredicate::Sbom(SbomSummary {
+            spec_version: predicate["specVersion"].as_str().map(str::to_string),
+            name: predicate["metadata"]["component"]["name"].as_str().map(str::to_string),
+            component_count: predicate["components"].as_array().map_or(0, Vec::len),
+        }),
+        AttestationKind::Other => TypedPredicate::Other(predicate.clone()),
+    })
+}
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::chain_of_custody::signing::DocumentPublicKey;
+use crate::provenance::dsse::{pae, Envelope, IN_TOTO_PAYLOAD_TYPE};
+use crate::provenance::intoto::Statement;
+use crate::provenance::oci_registry::{Descriptor, HttpRegistryClient, OciRegistry, RegistryError};
+use crate::provenance::trusted_keys::TrustedKey;
+
+pub const SIMPLE_SIGNING_MEDIA_TYPE: &str = "application/vnd.dev.cosign.simplesigning.v1+json";
+pub const DSSE_ENVELOPE_MEDIA_TYPE: &str = "application/vnd.dsse.envelope.v1+json";
+pub const COSIGN_SIGNATURE_ANNOTATION: &str = "dev.cosignproject.cosign/signature";
+pub const COSIGN_CERTIFICATE_ANNOTATION: &str = "dev.sigstore.cosign/certificate";
+pub const COSIGN_SIGNATURE_TYPE: &str = "cosign container image signature";
+
+#[derive(Error, Debug)]
+pub enum CosignError {
+    #[error("Registry error: {0}")]
+    Registry(#[from] RegistryError),
+    #[error("Invalid image digest: {0}")]
+    InvalidDigest(String),
+    #[error("No OCI registry is configured")]
+    Disabled,
+    #[error("Invalid public key in {0}")]
+    InvalidKey(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unknown imported record kind: {0}")]
+    UnknownRecordKind(String),
+}
+
+/// Where cosign signatures and attestations are looked up, e.g. a local
+/// `registry:2` at `http://localhost:5000`.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct CosignConfig {
+    /// Registry base URL. Lookups are disabled without one.
+    pub registry: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// PEM files with cosign public keys (P-256 or Ed25519) trusted for every
+    /// tenant, in addition to each tenant's trusted keys.
+    #[serde(default)]
+    pub public_keys: Vec<String>,
+}
+
+/// The tag cosign stores an image's signatures under: `sha256-<hex>.sig`.
+pub fn signature_tag(digest: &str) -> String {
+    format!("{}.sig", digest.replace(':', "-"))
+}
+
+/// The tag cosign stores an image's attestations under: `sha256-<hex>.att`.
+pub fn attestation_tag(digest: &str) -> String {
+    format!("{}.att", digest.replace(':', "-"))
+}
+
+/// Accepts `sha256:<hex>` or bare hex and returns the `sha256:<hex>` form.
+pub fn normalize_image_digest(digest: &str) -> Result<String, CosignError> {
+    let hex_digest = parse_digest(digest).map_err(|_| CosignError::InvalidDigest(digest.to_string()))?;
+    Ok(format!("sha256:{}", hex_digest))
+}
+
+/// The payload cosign signs for an image: a "simple signing" document.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SimpleSigningPayload {
+    pub critical: Critical,
+    #[serde(default)]
+    pub optional: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Critical {
+    pub identity: Identity,
+    pub image: Image,
+    #[serde(rename = "type")]
+    pub signature_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Identity {
+    #[serde(rename = "docker-reference")]
+    pub docker_reference: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Image {
+    #[serde(rename = "docker-manifest-digest")]
+    pub docker_manifest_digest: String,
+}
+
+impl SimpleSigningPayload {
+    pub fn new(docker_reference: &str, digest: &str) -> Self {
+        Self {
+            critical: Critical {
+                identity: Identity { docker_reference: docker_reference.to_string() },
+                image: Image { docker_manifest_digest: digest.to_string() },
+                signature_type: COSIGN_SIGNATURE_TYPE.to_string(),
+            },
+            optional: None,
+        }
+    }
+}
+
+/// One layer of an image's `.sig` manifest.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CosignSignature {
+    pub layer_digest: String,
+    pub docker_reference: Option<String>,
+    /// The layer carries a Fulcio certificate instead of relying on a key.
+    pub keyless: bool,
+    /// Trusted key the signature verified with, if any.
+    pub verified_key_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// One layer of an image's `.att` manifest: a DSSE envelope around an
+/// in-toto statement.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CosignAttestation {
+    pub layer_digest: String,
+    pub predicate_type: Option<String>,
+    pub verified_key_id: Option<String>,
+    pub error: Option<String>,
+    /// Where the attestation landed in the provenance store.
+    pub import: Option<CosignImport>,
+    #[serde(skip)]
+    pub envelope: Option<Envelope>,
+}
+
+impl CosignAttestation {
+    /// Verified, well-formed attestations are the ones that get imported.
+    pub fn verified(&self) -> bool {
+        self.verified_key_id.is_some() && self.error.is_none()
+    }
+
+    pub fn is_provenance(&self) -> bool {
+        self.predicate_type.as_deref().is_some_and(|predicate_type| predicate_type.starts_with("https://slsa.dev/provenance/"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportedRecordKind {
+    Provenance,
+    Attestation,
+}
+
+impl ImportedRecordKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportedRecordKind::Provenance => "provenance",
+            ImportedRecordKind::Attestation => "attestation",
+        }
+    }
+
+}
+
+impl FromStr for ImportedRecordKind {
+    type Err = CosignError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "provenance" => Ok(ImportedRecordKind::Provenance),
+            "attestation" => Ok(ImportedRecordKind::Attestation),
+            _ => Err(CosignError::UnknownRecordKind(value.to_string())),
+        }
+    }
+}
+
+/// Links a registry attestation layer to the record imported from it, so a
+/// layer is imported once however often the image is looked up.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CosignImport {
+    pub tenant_id: Uuid,
+    pub repository: String,
+    pub image_digest: String,
+    pub layer_digest: String,
+    pub record_kind: ImportedRecordKind,
+    pub record_id: Uuid,
+    pub imported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CosignLookup {
+    pub repository: String,
+    pub digest: String,
+    pub signatures: Vec<CosignSignature>,
+    pub attestations: Vec<CosignAttestation>,
+}
+
+/// Checks cosign signatures against a set of public keys.
+pub struct CosignVerifier {
+    keys: Vec<DocumentPublicKey>,
+}
+
+impl CosignVerifier {
+    pub fn new(keys: Vec<DocumentPublicKey>) -> Self {
+        Self { keys }
+    }
+
+    /// The configured keys plus a tenant's Ed25519 trusted keys.
+    pub fn with_trusted_keys(keys: &[DocumentPublicKey], trusted_keys: &[TrustedKey]) -> Self {
+        let mut keys = keys.to_vec();
+        keys.extend(trusted_keys.iter().filter_map(|key| key.verifying_key().ok()).map(DocumentPublicKey::Ed25519));
+        Self::new(keys)
+    }
+
+    fn verifying_key_id(&self, message: &[u8], signature: &str) -> Option<String> {
+        let signature = BASE64.decode(signature).ok()?;
+        self.keys.iter()
+            .find(|key| key.verify(message, &signature).is_ok())
+            .map(DocumentPublicKey::key_id)
+    }
+
+    /// Checks a `.sig` layer: the payload must name `digest` and the
+    /// annotated signature must verify over the payload bytes.
+    pub fn verify_signature(&self, digest: &str, layer: &Descriptor, payload: &[u8]) -> CosignSignature {
+        let mut signature = CosignSignature {
+            layer_digest: layer.digest.clone(),
+            docker_reference: None,
+            keyless: layer.annotations.contains_key(COSIGN_CERTIFICATE_ANNOTATION),
+            verified_key_id: None,
+            error: None,
+        };
+        let simple_signing: SimpleSigningPayload = match serde_json::from_slice(payload) {
+            Ok(simple_signing) => simple_signing,
+            Err(e) => {
+                signature.error = Some(format!("Invalid simple signing payload: {}", e));
+                return signature;
+            }
+        };
+        signature.docker_reference = Some(simple_signing.critical.identity.docker_reference.clone());
+        if simple_signing.critical.image.docker_manifest_digest != digest {
+            signature.error = Some(format!("Signature is for {}", simple_signing.critical.image.docker_manifest_digest));
+            return signature;
+        }
+
+        let annotation = layer.annotations.get(COSIGN_SIGNATURE_ANNOTATION).map(String::as_str).unwrap_or_default();
+        signature.verified_key_id = self.verifying_key_id(payload, annotation);
+        if signature.verified_key_id.is_none() {
+            signature.error = Some(if signature.keyless {
+                "Keyless signatures are not verified; register the signer's key instead".to_string()
+            } else {
+                "Signature does not match any trusted key".to_string()
+            });
+        }
+        signature
+    }
+
+    /// Checks an `.att` layer: the statement must have `digest` as a subject
+    /// and an envelope signature must verify over its pre-authentication
+    /// encoding.
+    pub fn verify_attestation(&self, digest: &str, layer: &Descriptor, payload: &[u8]) -> CosignAttestation {
+        let mut attestation = CosignAttestation {
+            layer_digest: layer.digest.clone(),
+            predicate_type: None,
+            verified_key_id: None,
+            error: None,
+            import: None,
+            envelope: None,
+        };
+        let envelope: Envelope = match serde_json::from_slice(payload) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                attestation.error = Some(format!("Not a DSSE envelope: {}", e));
+                return attestation;
+            }
+        };
+        if envelope.payload_type != IN_TOTO_PAYLOAD_TYPE {
+            attestation.error = Some(format!("Unsupported payload type {}", envelope.payload_type));
+            return attestation;
+        }
+        let (statement, payload) = match envelope.payload_bytes()
+            .and_then(|payload| Ok((envelope.decode_payload::<Statement<Value>>()?, payload)))
+        {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                attestation.error = Some(e.to_string());
+                return attestation;
+            }
+        };
+        attestation.predicate_type = Some(statement.predicate_type.clone());
+        let hex_digest = digest.trim_start_matches("sha256:");
+        if !statement.subject.iter().any(|subject| subject.sha256_digest().is_some_and(|subject| subject.eq_ignore_ascii_case(hex_digest))) {
+            attestation.error = Some(format!("Statement does not have {} as a subject", digest));
+            return attestation;
+        }
+
+        let message = pae(&envelope.payload_type, &payload);
+        attestation.verified_key_id = envelope.signatures.iter().find_map(|signature| self.verifying_key_id(&message, &signature.sig));
+        if attestation.verified_key_id.is_none() {
+            attestation.error = Some("Envelope signature does not match any trusted key".to_string());
+        }
+        attestation.envelope = Some(envelope);
+        attestation
+    }
+
+    /// Fetches and verifies everything cosign attached to `digest` in
+    /// `repository`. A layer that cannot be fetched or verified is reported
+    /// with its error rather than failing the lookup.
+    pub async fn lookup(&self, registry: &dyn OciRegistry, repository: &str, digest: &str) -> Result<CosignLookup, CosignError> {
+        let digest = normalize_image_digest(digest)?;
+        let mut lookup = CosignLookup {
+            repository: repository.to_string(),
+            digest: digest.clone(),
+            signatures: Vec::new(),
+            attestations: Vec::new(),
+        };
+
+        if let Some(manifest) = registry.manifest(repository, &signature_tag(&digest)).await? {
+            for layer in manifest.layers.iter().filter(|layer| layer.media_type == SIMPLE_SIGNING_MEDIA_TYPE) {
+                lookup.signatures.push(match registry.blob(repository, &layer.digest).await {
+                    Ok(payload) => self.verify_signature(&digest, layer, &payload),
+                    Err(e) => CosignSignature {
+                        layer_digest: layer.digest.clone(),
+                        docker_reference: None,
+                        keyless: false,
+                        verified_key_id: None,
+                        error: Some(e.to_string()),
+                    },
+                });
+            }
+        }
+        if let Some(manifest) = registry.manifest(repository, &attestation_tag(&digest)).await? {
+            for layer in manifest.layers.iter().filter(|layer| layer.media_type == DSSE_ENVELOPE_MEDIA_TYPE) {
+                lookup.attestations.push(match registry.blob(repository, &layer.digest).await {
+                    Ok(payload) => self.verify_attestation(&digest, layer, &payload),
+                    Err(e) => CosignAttestation {
+                        layer_digest: layer.digest.clone(),
+                        predicate_type: None,
+                        verified_key_id: None,
+                        error: Some(e.to_string()),
+                        import: None,
+                        envelope: None,
+                    },
+                });
+            }
+        }
+        Ok(lookup)
+    }
+}
+
+/// The configured registry and the cosign keys every tenant trusts.
+#[derive(Clone)]
+pub struct CosignService {
+    registry: Option<Arc<dyn OciRegistry>>,
+    public_keys: Arc<Vec<DocumentPublicKey>>,
+}
+
+impl CosignService {
+    pub fn new(registry: Option<Arc<dyn OciRegistry>>, public_keys: Vec<DocumentPublicKey>) -> Self {
+        Self { registry, public_keys: Arc::new(public_keys) }
+    }
+
+    pub fn from_config(config: &CosignConfig) -> Result<Self, CosignError> {
+        let registry = config.registry.as_deref().map(|url| {
+            let credentials = config.username.clone().zip(config.password.clone());
+            Arc::new(HttpRegistryClient::new(url, credentials)) as Arc<dyn OciRegistry>
+        });
+        let public_keys = config.public_keys.iter()
+            .map(|path| {
+                let pem = std::fs::read_to_string(path)?;
+                DocumentPublicKey::from_pem(&pem).map_err(|_| CosignError::InvalidKey(path.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(registry, public_keys))
+    }
+
+    pub async fn lookup(&self, trusted_keys: &[TrustedKey], repository: &str, digest: &str) -> Result<CosignLookup, CosignError> {
+        let registry = self.registry.as_ref().ok_or(CosignError::Disabled)?;
+        CosignVerifier::with_trusted_keys(&self.public_keys, trusted_keys)
+            .lookup(registry.as_ref(), repository, digest)
+            .await
+    }
+}
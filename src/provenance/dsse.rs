@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+use crate::security::secret_management::{get_or_create_secret, SecretError, SecretManager};
+
+pub const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+#[derive(Error, Debug)]
+pub enum DsseError {
+    #[error("Secret management error: {0}")]
+    SecretError(#[from] SecretError),
+    #[error("Invalid key material: {0}")]
+    InvalidKey(String),
+    #[error("Invalid envelope encoding: {0}")]
+    EncodingError(String),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Unsupported payload: {0}")]
+    UnsupportedPayload(String),
+    #[error("No signature on the envelope matches key {0}")]
+    SignatureMismatch(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub payload_type: String,
+    pub payload: String,
+    pub signatures: Vec<EnvelopeSignature>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnvelopeSignature {
+    pub keyid: String,
+    pub sig: String,
+}
+
+impl Envelope {
+    pub fn payload_bytes(&self) -> Result<Vec<u8>, DsseError> {
+        BASE64.decode(&self.payload).map_err(|e| DsseError::EncodingError(e.to_string()))
+    }
+
+    pub fn decode_payload<T: for<'de> Deserialize<'de>>(&self) -> Result<T, DsseError> {
+        Ok(serde_json::from_slice(&self.payload_bytes()?)?)
+    }
+}
+
+/// DSSE pre-authentication encoding: `DSSEv1 SP LEN(type) SP type SP LEN(body) SP body`.
+pub fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = format!("DSSEv1 {} {} {} ", payload_type.len(), payload_type, payload.len()).into_bytes();
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+/// An Ed25519 key owned by TraceGuard and stored per tenant in the secret manager.
+pub struct EnvelopeSigner {
+    key_id: String,
+    signing_key: SigningKey,
+}
+
+impl EnvelopeSigner {
+    pub fn from_signing_key(signing_key: SigningKey) -> Self {
+        let key_id = key_id_for(&signing_key.verifying_key());
+        Self { key_id, signing_key }
+    }
+
+    /// Loads the tenant's key stored under `secret_id`, generating and storing a
+    /// new one the first time it is requested.
+    pub async fn load_or_create<M: SecretManager>(secret_manager: &M, secret_id: &str, tenant_id: Uuid) -> Result<Self, DsseError> {
+        let encoded = get_or_create_secret(secret_manager, secret_id, tenant_id, || {
+            hex::encode(SigningKey::generate(&mut rand::rngs::OsRng).to_bytes())
+        })
+        .await?;
+        Self::from_hex(&encoded)
+    }
+
+    /// Loads the tenant's key stored under `secret_id` without creating one;
+    /// fails with `SecretError::SecretNotFound` if it was never used.
+    pub async fn load<M: SecretManager>(secret_manager: &M, secret_id: &str, tenant_id: Uuid) -> Result<Self, DsseError> {
+        let encoded = secret_manager.get_secret(secret_id, tenant_id).await?;
+        Self::from_hex(&encoded)
+    }
+
+    fn from_hex(encoded: &str) -> Result<Self, DsseError> {
+        let seed: [u8; 32] = hex::decode(encoded.trim())
+            .map_err(|e| DsseError::InvalidKey(e.to_string()))?
+            .try_into()
+            .map_err(|_| DsseError::InvalidKey("Ed25519 seed must be 32 bytes".to_string()))?;
+        Ok(Self::from_signing_key(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn sign(&self, payload_type: &str, payload: &[u8]) -> Envelope {
+        let signature = self.signing_key.sign(&pae(payload_type, payload));
+        Envelope {
+            payload_type: payload_type.to_string(),
+            payload: BASE64.encode(payload),
+            signatures: vec![EnvelopeSignature {
+                keyid: self.key_id.clone(),
+                sig: BASE64.encode(signature.to_bytes()),
+            }],
+        }
+    }
+
+    pub fn sign_json<T: Serialize>(&self, payload_type: &str, payload: &T) -> Result<Envelope, DsseError> {
+        Ok(self.sign(payload_type, &serde_json::to_vec(payload)?))
+    }
+
+    /// Signs an envelope someone else produced, over the same payload, so the
+    /// signature can be added alongside theirs.
+    pub fn cosign(&self, envelope: &Envelope) -> Result<EnvelopeSignature, DsseError> {
+        let signature = self.signing_key.sign(&pae(&envelope.payload_type, &envelope.payload_bytes()?));
+        Ok(EnvelopeSignature {
+            keyid: self.key_id.clone(),
+            sig: BASE64.encode(signature.to_bytes()),
+        })
+    }
+}
+
+pub fn verify_envelope(envelope: &Envelope, verifying_key: &VerifyingKey) -> Result<(), DsseError> {
+    let key_id = key_id_for(verifying_key);
+    let message = pae(&envelope.payload_type, &envelope.payload_bytes()?);
+
+    let verified = envelope.signatures.iter()
+        .filter(|signature| signature.keyid.is_empty() || signature.keyid == key_id)
+        .filter_map(|signature| BASE64.decode(&signature.sig).ok())
+        .filter_map(|bytes| Signature::from_slice(&bytes).ok())
+        .any(|signature| verifying_key.verify(&message, &signature).is_ok());
+
+    if verified {
+        Ok(())
+    } else {
+        Err(DsseError::SignatureMismatch(key_id))
+    }
+}
+
+/// Key IDs are the hex SHA-256 of the raw public key bytes.
+pub fn key_id_for(verifying_key: &VerifyingKey) -> String {
+    hex::encode(Sha256::digest(verifying_key.as_bytes()))
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+use flate2::read::GzDecoder;
+use std::io::Read;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::models::provenance::ProvenanceRecord;
+use crate::provenance::dsse::Envelope;
+
+/// Items are committed in transactions of at most this many records.
+pub const INGEST_BATCH_SIZE: usize = 100;
+
+/// Largest gzip upload, once decompressed, that is read.
+pub const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestFormat {
+    JsonLines,
+    Tar,
+    TarGz,
+}
+
+impl IngestFormat {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next().unwrap_or_default().trim() {
+            "application/x-ndjson" | "application/jsonl" | "application/jsonlines" => Some(IngestFormat::JsonLines),
+            "application/x-tar" => Some(IngestFormat::Tar),
+            "application/gzip" | "application/x-gzip" | "application/x-gtar" => Some(IngestFormat::TarGz),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum IngestError {
+    #[error("Failed to read archive: {0}")]
+    Archive(#[from] std::io::Error),
+}
+
+/// One attestation found in the upload, before it has been stored.
+#[derive(Debug)]
+pub struct ParsedAttestation {
+    /// Where the item came from, e.g. `line 3` or `attestations/build.intoto.jsonl:2`.
+    pub source: String,
+    pub record: Result<ProvenanceRecord, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestStatus {
+    Ingested,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IngestItemResult {
+    pub index: usize,
+    pub source: String,
+    pub status: IngestStatus,
+    pub provenance_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkIngestReport {
+    pub total: usize,
+    pub ingested: usize,
+    pub failed: usize,
+    pub items: Vec<IngestItemResult>,
+}
+
+impl BulkIngestReport {
+    pub fn new(items: Vec<IngestItemResult>) -> Self {
+        let ingested = items.iter().filter(|item| item.status == IngestStatus::Ingested).count();
+        Self {
+            total: items.len(),
+            ingested,
+            failed: items.len() - ingested,
+            items,
+        }
+    }
+}
+
+/// Splits an upload into individual attestations. Structural errors in one
+/// item never prevent the others from being parsed.
+pub fn parse_upload(format: IngestFormat, body: &[u8]) -> Result<Vec<ParsedAttestation>, IngestError> {
+    parse_upload_limited(format, body, MAX_DECOMPRESSED_SIZE)
+}
+
+/// Like [`parse_upload`], but fails once a gzip upload decompresses to more
+/// than `max_decompressed` bytes.
+pub fn parse_upload_limited(format: IngestFormat, body: &[u8], max_decompressed: u64) -> Result<Vec<ParsedAttestation>, IngestError> {
+    match format {
+        IngestFormat::JsonLines => Ok(parse_json_lines("line", body)),
+        IngestFormat::Tar => parse_tar(tar::Archive::new(body)),
+        IngestFormat::TarGz => parse_tar(tar::Archive::new(LimitedReader::new(GzDecoder::new(body), max_decompressed))),
+    }
+}
+
+/// Fails the read, instead of truncating it, once more than `remaining`
+/// bytes come out of `inner`, so a small compressed upload cannot expand
+/// without bound.
+struct LimitedReader<R> {
+    inner: R,
+    limit: u64,
+    remaining: u64,
+}
+
+impl<R: Read> LimitedReader<R> {
+    fn new(inner: R, limit: u64) -> Self {
+        Self { inner, limit, remaining: limit }
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 {
+            let mut probe = [0u8; 1];
+            if self.inner.read(&mut probe)? > 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("decompressed upload exceeds {} bytes", self.limit),
+                ));
+            }
+            return Ok(0);
+        }
+        let max = buf.len().min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let read = self.inner.read(&mut buf[..max])?;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Archive members ending in `.jsonl` hold one envelope per line; any other
+/// `.json` member holds a single envelope. Other members are ignored.
+fn parse_tar<R: Read>(mut archive: tar::Archive<R>) -> Result<Vec<ParsedAttestation>, IngestError> {
+    let mut attestations = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+
+        if path.ends_with(".jsonl") {
+            attestations.extend(parse_json_lines(&path, &contents));
+        } else if path.ends_with(".json") {
+            attestations.push(ParsedAttestation { record: parse_envelope(&contents), source: path });
+        }
+    }
+    Ok(attestations)
+}
+
+fn parse_json_lines(label: &str, body: &[u8]) -> Vec<ParsedAttestation> {
+    body.split(|byte| *byte == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|(number, line)| ParsedAttestation {
+            source: if label == "line" {
+                format!("line {}", number + 1)
+            } else {
+                format!("{}:{}", label, number + 1)
+            },
+            record: parse_envelope(line),
+        })
+        .collect()
+}
+
+fn parse_envelope(bytes: &[u8]) -> Result<ProvenanceRecord, String> {
+    let envelope: Envelope = serde_json::from_slice(bytes).map_err(|e| format!("Not a DSSE envelope: {}", e))?;
+    ProvenanceRecord::from_envelope(envelope).map_err(|e| e.to_string())
+}
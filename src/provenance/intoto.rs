@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const STATEMENT_TYPE_V1: &str = "https://in-toto.io/Statement/v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement<P> {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<ResourceDescriptor>,
+    pub predicate_type: String,
+    pub predicate: P,
+}
+
+impl<P> Statement<P> {
+    pub fn new(subject: Vec<ResourceDescriptor>, predicate_type: &str, predicate: P) -> Self {
+        Self {
+            statement_type: STATEMENT_TYPE_V1.to_string(),
+            subject,
+            predicate_type: predicate_type.to_string(),
+            predicate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub digest: BTreeMap<String, String>,
+}
+
+impl ResourceDescriptor {
+    pub fn sha256(name: Option<String>, uri: Option<String>, sha256: &str) -> Self {
+        let mut digest = BTreeMap::new();
+        digest.insert("sha256".to_string(), sha256.to_string());
+        Self { name, uri, digest }
+    }
+
+    pub fn sha256_digest(&self) -> Option<&str> {
+        self.digest.get("sha256").map(String::as_str)
+    }
+}
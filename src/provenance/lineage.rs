@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::normalize_digest;
+use crate::database::DatabaseError;
+use crate::models::provenance::ProvenanceRecord;
+
+pub const DEFAULT_LINEAGE_DEPTH: u32 = 5;
+pub const MAX_LINEAGE_DEPTH: u32 = 20;
+
+/// One input-to-output hop recorded by a provenance record: the material
+/// `input_digest` was consumed to produce the subject `output_digest`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineageEdge {
+    pub provenance_id: Uuid,
+    pub input_digest: String,
+    pub input_uri: String,
+    pub output_digest: String,
+    pub output_name: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineageDirection {
+    Upstream,
+    Downstream,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageNode {
+    pub digest: String,
+    pub names: BTreeSet<String>,
+    pub depth: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageGraph {
+    pub root: String,
+    pub direction: LineageDirection,
+    pub max_depth: u32,
+    pub nodes: Vec<LineageNode>,
+    pub edges: Vec<LineageEdge>,
+    /// True when nodes exist beyond `max_depth` that were not expanded.
+    pub truncated: bool,
+    /// Each cycle is listed as the sequence of digests that closes on itself.
+    pub cycles: Vec<Vec<String>>,
+}
+
+#[async_trait]
+pub trait LineageStore {
+    /// Edges whose output is one of `digests`.
+    async fn edges_producing(&self, tenant_id: &Uuid, digests: &[String]) -> Result<Vec<LineageEdge>, DatabaseError>;
+    /// Edges whose input is one of `digests`.
+    async fn edges_consuming(&self, tenant_id: &Uuid, digests: &[String]) -> Result<Vec<LineageEdge>, DatabaseError>;
+}
+
+/// Extracts the lineage edges a record contributes. The config source is
+/// treated as a material so that builds can be traced back to a source commit.
+pub fn record_edges(record: &ProvenanceRecord) -> Vec<LineageEdge> {
+    let provenance = &record.slsa_provenance;
+    let config_source = &provenance.invocation.config_source;
+    let source_digest = normalize_digest(config_source.digest.git_commit.as_ref().unwrap_or(&config_source.digest.sha256));
+
+    // Digests are stored lowercase so lookups match however the producer cased them
+    let inputs: Vec<(String, String)> = provenance.materials.iter()
+        .map(|material| (normalize_digest(&material.digest.sha256), material.uri.clone()))
+        .chain(std::iter::once((source_digest, config_source.uri.clone())))
+        .filter(|(digest, _)| !digest.is_empty())
+        .collect();
+
+    provenance.subject.iter()
+        .flat_map(|subject| {
+            inputs.iter().map(move |(digest, uri)| LineageEdge {
+                provenance_id: record.id,
+                input_digest: digest.clone(),
+                input_uri: uri.clone(),
+                output_digest: normalize_digest(&subject.digest.sha256),
+                output_name: subject.name.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Walks the lineage graph breadth-first from `root` up to `max_depth` hops.
+pub async fn traverse<L: LineageStore + Sync>(
+    store: &L,
+    tenant_id: &Uuid,
+    root: &str,
+    direction: LineageDirection,
+    max_depth: u32,
+) -> Result<LineageGraph, DatabaseError> {
+    let max_depth = max_depth.min(MAX_LINEAGE_DEPTH);
+    let mut nodes: BTreeMap<String, LineageNode> = BTreeMap::new();
+    let mut edges: BTreeSet<LineageEdge> = BTreeSet::new();
+    nodes.insert(root.to_string(), LineageNode { digest: root.to_string(), names: BTreeSet::new(), depth: 0 });
+
+    let mut frontier = vec![root.to_string()];
+    let mut truncated = false;
+    for depth in 1..=max_depth + 1 {
+        if frontier.is_empty() {
+            break;
+        }
+        let hops = match direction {
+            LineageDirection::Upstream => store.edges_producing(tenant_id, &frontier).await?,
+            LineageDirection::Downstream => store.edges_consuming(tenant_id, &frontier).await?,
+        };
+        if depth > max_depth {
+            truncated = !hops.is_empty();
+            break;
+        }
+
+        let mut next = Vec::new();
+        for edge in hops {
+            let (from_name, to_digest, to_name) = match direction {
+                LineageDirection::Upstream => (&edge.output_name, &edge.input_digest, &edge.input_uri),
+                LineageDirection::Downstream => (&edge.input_uri, &edge.output_digest, &edge.output_name),
+            };
+            let from_digest = match direction {
+                LineageDirection::Upstream => &edge.output_digest,
+                LineageDirection::Downstream => &edge.input_digest,
+            };
+            if let Some(node) = nodes.get_mut(from_digest) {
+                node.names.insert(from_name.clone());
+            }
+            // Already-visited nodes are not expanded again, which also stops cycles
+            let node = nodes.entry(to_digest.clone()).or_insert_with(|| {
+                next.push(to_digest.clone());
+                LineageNode { digest: to_digest.clone(), names: BTreeSet::new(), depth }
+            });
+            node.names.insert(to_name.clone());
+            edges.insert(edge);
+        }
+        frontier = next;
+    }
+
+    let edges: Vec<LineageEdge> = edges.into_iter().collect();
+    let cycles = find_cycles(&edges);
+    Ok(LineageGraph {
+        root: root.to_string(),
+        direction,
+        max_depth,
+        nodes: nodes.into_values().collect(),
+        edges,
+        truncated,
+        cycles,
+    })
+}
+
+/// Finds cycles in the input-to-output graph with an iterative DFS that
+/// tracks the current path; an edge back onto the path closes a cycle.
+pub fn find_cycles(edges: &[LineageEdge]) -> Vec<Vec<String>> {
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in edges {
+        adjacency.entry(edge.input_digest.as_str()).or_default().push(edge.output_digest.as_str());
+    }
+
+    let mut cycles = Vec::new();
+    let mut finished: HashSet<&str> = HashSet::new();
+    let mut starts: Vec<&str> = adjacency.keys().copied().collect();
+    starts.sort();
+
+    for start in starts {
+        if finished.contains(start) {
+            continue;
+        }
+        let mut path: Vec<&str> = vec![start];
+        let mut stack: Vec<std::vec::IntoIter<&str>> = vec![adjacency.get(start).cloned().unwrap_or_default().into_iter()];
+        while let Some(children) = stack.last_mut() {
+            match children.next() {
+                Some(child) if path.contains(&child) => {
+                    let position = path.iter().position(|digest| *digest == child).unwrap();
+                    let mut cycle: Vec<String> = path[position..].iter().map(|digest| digest.to_string()).collect();
+                    cycle.push(child.to_string());
+                    cycles.push(cycle);
+                }
+                Some(child) if !finished.contains(child) => {
+                    path.push(child);
+                    stack.push(adjacency.get(child).cloned().unwrap_or_default().into_iter());
+                }
+                Some(_) => {}
+                None => {
+                    stack.pop();
+                    if let Some(done) = path.pop() {
+                        finished.insert(done);
+                    }
+                }
+            }
+        }
+    }
+    cycles
+}
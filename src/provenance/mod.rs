@@ -0,0 +1,18 @@
+pub mod attestations;
+pub mod cosign;
+pub mod dsse;
+pub mod generator;
+pub mod ingest;
+pub mod intoto;
+pub mod lineage;
+pub mod oci_registry;
+pub mod policy;
+pub mod provenance_api;
+pub mod query;
+pub mod reproducibility;
+pub mod revocation;
+pub mod revisions;
+pub mod slsa_v1;
+pub mod source;
+pub mod trusted_keys;
+pub mod vsa;
@@ -0,0 +1,288 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use async_trait::async_trait;
+use reqwest::header::{ACCEPT, AUTHORIZATION, WWW_AUTHENTICATE};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+pub const OCI_MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const DOCKER_MANIFEST_MEDIA_TYPE: &str = "application/vnd.docker.distribution.manifest.v2+json";
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Registry returned {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("Registry authentication failed: {0}")]
+    Unauthorized(String),
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+    #[error("Blob does not match digest {0}")]
+    DigestMismatch(String),
+}
+
+/// An OCI content descriptor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl Descriptor {
+    pub fn for_content(media_type: &str, content: &[u8]) -> Self {
+        Self {
+            media_type: media_type.to_string(),
+            digest: sha256_digest(content),
+            size: content.len() as u64,
+            annotations: BTreeMap::new(),
+        }
+    }
+}
+
+/// An OCI image manifest, or the Docker v2 schema 2 manifest it grew from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageManifest {
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub config: Descriptor,
+    pub layers: Vec<Descriptor>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl ImageManifest {
+    pub fn new(config: Descriptor, layers: Vec<Descriptor>) -> Self {
+        Self {
+            schema_version: 2,
+            media_type: Some(OCI_MANIFEST_MEDIA_TYPE.to_string()),
+            config,
+            layers,
+            annotations: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn sha256_digest(content: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(content)))
+}
+
+/// Read access to an OCI distribution registry.
+#[async_trait]
+pub trait OciRegistry: Send + Sync {
+    /// Fetches the manifest for a tag or digest, or `None` if there is none.
+    async fn manifest(&self, repository: &str, reference: &str) -> Result<Option<ImageManifest>, RegistryError>;
+
+    /// Fetches a blob and checks it against its digest.
+    async fn blob(&self, repository: &str, digest: &str) -> Result<Vec<u8>, RegistryError>;
+}
+
+/// Talks to a registry over the OCI distribution API. Credentials are sent
+/// as basic auth, and exchanged for a bearer token when the registry asks
+/// for one.
+pub struct HttpRegistryClient {
+    base_url: String,
+    credentials: Option<(String, String)>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+impl HttpRegistryClient {
+    pub fn new(base_url: &str, credentials: Option<(String, String)>) -> Self {
+        Self { base_url: base_url.trim_end_matches('/').to_string(), credentials, client: reqwest::Client::new() }
+    }
+
+    async fn get(&self, url: &str, accept: &str) -> Result<reqwest::Response, RegistryError> {
+        let mut request = self.client.get(url).header(ACCEPT, accept);
+        if let Some((username, password)) = &self.credentials {
+            request = request.basic_auth(username, Some(password));
+        }
+        let response = request.send().await?;
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        let challenge = response.headers().get(WWW_AUTHENTICATE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let Some(params) = challenge.strip_prefix("Bearer ") else {
+            return Err(RegistryError::Unauthorized(format!("unsupported challenge {:?}", challenge)));
+        };
+        let token = self.bearer_token(&challenge_params(params)).await?;
+        Ok(self.client.get(url).header(ACCEPT, accept).header(AUTHORIZATION, format!("Bearer {}", token)).send().await?)
+    }
+
+    async fn bearer_token(&self, params: &HashMap<String, String>) -> Result<String, RegistryError> {
+        let realm = params.get("realm").ok_or_else(|| RegistryError::Unauthorized("challenge has no realm".to_string()))?;
+        let query: Vec<(&str, &str)> = ["service", "scope"].iter()
+            .filter_map(|key| params.get(*key).map(|value| (*key, value.as_str())))
+            .collect();
+        let mut request = self.client.get(realm).query(&query);
+        if let Some((username, password)) = &self.credentials {
+            request = request.basic_auth(username, Some(password));
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(RegistryError::Unauthorized(format!("token endpoint returned {}", response.status())));
+        }
+        let token: TokenResponse = response.json().await?;
+        token.token.or(token.access_token).ok_or_else(|| RegistryError::Unauthorized("token response has no token".to_string()))
+    }
+
+    async fn error_from(response: reqwest::Response) -> RegistryError {
+        let status = response.status().as_u16();
+        let message = response.text().await.unwrap_or_default();
+        RegistryError::Api { status, message }
+    }
+}
+
+/// Parses the `key="value"` pairs of a `WWW-Authenticate` challenge. Scopes
+/// can contain commas, so values are split on quotes rather than commas.
+fn challenge_params(params: &str) -> HashMap<String, String> {
+    let mut parsed = HashMap::new();
+    let mut rest = params.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let (value, remainder) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        parsed.insert(key, value.to_string());
+        rest = remainder.trim_start_matches(',').trim();
+    }
+    parsed
+}
+
+#[async_trait]
+impl OciRegistry for HttpRegistryClient {
+    async fn manifest(&self, repository: &str, reference: &str) -> Result<Option<ImageManifest>, RegistryError> {
+        let url = format!("{}/v2/{}/manifests/{}", self.base_url, repository, reference);
+        let response = self.get(&url, &format!("{}, {}", OCI_MANIFEST_MEDIA_TYPE, DOCKER_MANIFEST_MEDIA_TYPE)).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        let manifest = response.json().await.map_err(|e| RegistryError::InvalidManifest(e.to_string()))?;
+        Ok(Some(manifest))
+    }
+
+    async fn blob(&self, repository: &str, digest: &str) -> Result<Vec<u8>, RegistryError> {
+        let url = format!("{}/v2/{}/blobs/{}", self.base_url, repository, digest);
+        let response = self.get(&url, "application/octet-stream").await?;
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        let content = response.bytes().await?.to_vec();
+        if sha256_digest(&content) != digest {
+            return Err(RegistryError::DigestMismatch(digest.to_string()));
+        }
+        Ok(content)
+    }
+}
+
+/// An in-memory registry for tests and air-gapped demos. It serves the read
+/// side of the distribution API, enough for `HttpRegistryClient` and cosign's
+/// lookups.
+#[derive(Default)]
+pub struct MemoryRegistry {
+    manifests: RwLock<HashMap<(String, String), ImageManifest>>,
+    blobs: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores a blob and returns its descriptor.
+    pub fn push_blob(&self, media_type: &str, content: &[u8]) -> Descriptor {
+        let descriptor = Descriptor::for_content(media_type, content);
+        self.blobs.write().unwrap().insert(descriptor.digest.clone(), content.to_vec());
+        descriptor
+    }
+
+    pub fn push_manifest(&self, repository: &str, tag: &str, manifest: ImageManifest) {
+        self.manifests.write().unwrap().insert((repository.to_string(), tag.to_string()), manifest);
+    }
+
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/v2/", get(api_version))
+            .route("/v2/*path", get(registry_get))
+            .with_state(self)
+    }
+
+    /// Serves the registry on an ephemeral loopback port and returns its address.
+    pub async fn spawn(self: Arc<Self>) -> std::io::Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server = axum::Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(self.router().into_make_service());
+        tokio::spawn(server);
+        Ok(addr)
+    }
+}
+
+#[async_trait]
+impl OciRegistry for MemoryRegistry {
+    async fn manifest(&self, repository: &str, reference: &str) -> Result<Option<ImageManifest>, RegistryError> {
+        Ok(self.manifests.read().unwrap().get(&(repository.to_string(), reference.to_string())).cloned())
+    }
+
+    async fn blob(&self, _repository: &str, digest: &str) -> Result<Vec<u8>, RegistryError> {
+        self.blobs.read().unwrap().get(digest).cloned().ok_or_else(|| RegistryError::Api {
+            status: 404,
+            message: format!("blob {} not found", digest),
+        })
+    }
+}
+
+async fn api_version() -> Json<serde_json::Value> {
+    Json(serde_json::json!({}))
+}
+
+/// Routes `<repository>/manifests/<reference>` and `<repository>/blobs/<digest>`;
+/// repository names may contain slashes.
+async fn registry_get(State(registry): State<Arc<MemoryRegistry>>, Path(path): Path<String>) -> Response {
+    let path = path.trim_start_matches('/');
+    if let Some((repository, reference)) = path.rsplit_once("/manifests/") {
+        return match registry.manifests.read().unwrap().get(&(repository.to_string(), reference.to_string())) {
+            Some(manifest) => {
+                let media_type = manifest.media_type.clone().unwrap_or_else(|| OCI_MANIFEST_MEDIA_TYPE.to_string());
+                ([(header::CONTENT_TYPE, media_type)], Json(manifest.clone())).into_response()
+            }
+            None => StatusCode::NOT_FOUND.into_response(),
+        };
+    }
+    if let Some((_, digest)) = path.rsplit_once("/blobs/") {
+        return match registry.blobs.read().unwrap().get(digest) {
+            Some(content) => content.clone().into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        };
+    }
+    StatusCode::NOT_FOUND.into_response()
+}
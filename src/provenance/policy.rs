@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use glob::Pattern;
+use uuid::Uuid;
+use thiserror::Error;
+use crate::models::provenance::NormalizedProvenance;
+
+/// A tenant-scoped rule declaring which builders and sources are trusted to
+/// produce artifacts whose subject names match `subject_pattern`.
+///
+/// Every constraint list holds glob patterns; an empty list leaves that field
+/// unconstrained.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuilderTrustPolicy {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub subject_pattern: String,
+    pub allowed_builder_ids: Vec<String>,
+    pub allowed_build_types: Vec<String>,
+    pub allowed_source_uris: Vec<String>,
+    pub allowed_source_refs: Vec<String>,
+    /// Require every subject to have been independently reproduced.
+    pub require_reproducible: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BuilderTrustPolicyRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub subject_pattern: String,
+    #[serde(default)]
+    pub allowed_builder_ids: Vec<String>,
+    #[serde(default)]
+    pub allowed_build_types: Vec<String>,
+    #[serde(default)]
+    pub allowed_source_uris: Vec<String>,
+    #[serde(default)]
+    pub allowed_source_refs: Vec<String>,
+    #[serde(default)]
+    pub require_reproducible: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyField {
+    BuilderId,
+    BuildType,
+    SourceUri,
+    SourceRef,
+    Reproducible,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub policy_id: Uuid,
+    pub field: PolicyField,
+    pub actual: Option<String>,
+    pub allowed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyEvaluation {
+    pub policy_id: Uuid,
+    pub policy_name: String,
+    pub matched_subjects: Vec<String>,
+    pub violations: Vec<PolicyViolation>,
+    /// Whether the policy names the builders it allows, rather than leaving
+    /// the builder unconstrained.
+    #[serde(default)]
+    pub builder_pinned: bool,
+}
+
+impl PolicyEvaluation {
+    pub fn satisfied(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Invalid glob pattern '{pattern}': {source}")]
+    InvalidPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+}
+
+impl BuilderTrustPolicy {
+    pub fn from_request(tenant_id: Uuid, request: BuilderTrustPolicyRequest) -> Result<Self, PolicyError> {
+        request.validate()?;
+        let now = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            name: request.name,
+            description: request.description,
+            subject_pattern: request.subject_pattern,
+            allowed_builder_ids: request.allowed_builder_ids,
+            allowed_build_types: request.allowed_build_types,
+            allowed_source_uris: request.allowed_source_uris,
+            allowed_source_refs: request.allowed_source_refs,
+            require_reproducible: request.require_reproducible,
+            enabled: request.enabled,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn apply_update(&mut self, request: BuilderTrustPolicyRequest) -> Result<(), PolicyError> {
+        request.validate()?;
+        self.name = request.name;
+        self.description = request.description;
+        self.subject_pattern = request.subject_pattern;
+        self.allowed_builder_ids = request.allowed_builder_ids;
+        self.allowed_build_types = request.allowed_build_types;
+        self.allowed_source_uris = request.allowed_source_uris;
+        self.allowed_source_refs = request.allowed_source_refs;
+        self.require_reproducible = request.require_reproducible;
+        self.enabled = request.enabled;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Evaluates the policy against normalized provenance. Returns `None` when
+    /// the policy is disabled or none of the provenance subjects match it.
+    pub fn evaluate(&self, provenance: &NormalizedProvenance) -> Option<PolicyEvaluation> {
+        if !self.enabled {
+            return None;
+        }
+
+        let matched_subjects: Vec<String> = provenance.subject_names.iter()
+            .filter(|name| glob_matches(&self.subject_pattern, name))
+            .cloned()
+            .collect();
+        if matched_subjects.is_empty() {
+            return None;
+        }
+
+        let constraints = [
+            (PolicyField::BuilderId, &self.allowed_builder_ids, Some(provenance.builder_id.as_str())),
+            (PolicyField::BuildType, &self.allowed_build_types, Some(provenance.build_type.as_str())),
+            (PolicyField::SourceUri, &self.allowed_source_uris, Some(provenance.source_uri.as_str())),
+            (PolicyField::SourceRef, &self.allowed_source_refs, provenance.source_ref.as_deref()),
+        ];
+
+        let mut violations: Vec<PolicyViolation> = constraints.iter()
+            .filter(|(_, allowed, actual)| !allowed.is_empty() && !actual.map_or(false, |value| matches_any(allowed, value)))
+            .map(|(field, allowed, actual)| PolicyViolation {
+                policy_id: self.id,
+                field: *field,
+                actual: actual.map(str::to_string),
+                allowed: allowed.to_vec(),
+            })
+            .collect();
+        if self.require_reproducible && !provenance.reproduced {
+            violations.push(PolicyViolation {
+                policy_id: self.id,
+                field: PolicyField::Reproducible,
+                actual: Some("false".to_string()),
+                allowed: vec!["true".to_string()],
+            });
+        }
+
+        Some(PolicyEvaluation {
+            policy_id: self.id,
+            policy_name: self.name.clone(),
+            matched_subjects,
+            violations,
+            builder_pinned: !self.allowed_builder_ids.is_empty(),
+        })
+    }
+}
+
+impl BuilderTrustPolicyRequest {
+    fn validate(&self) -> Result<(), PolicyError> {
+        std::iter::once(&self.subject_pattern)
+            .chain(&self.allowed_builder_ids)
+            .chain(&self.allowed_build_types)
+            .chain(&self.allowed_source_uris)
+            .chain(&self.allowed_source_refs)
+            .try_for_each(|pattern| {
+                Pattern::new(pattern)
+                    .map(|_| ())
+                    .map_err(|source| PolicyError::InvalidPattern { pattern: pattern.clone(), source })
+            })
+    }
+}
+
+pub fn glob_matches(pattern: &str, value: &str) -> bool {
+    Pattern::new(pattern).map_or(false, |pattern| pattern.matches(value))
+}
+
+fn matches_any(patterns: &[String], value: &str) -> bool {
+    patterns.iter().any(|pattern| glob_matches(pattern, value))
+}
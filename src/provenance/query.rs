@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::models::provenance::ProvenanceRecord;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+/// Filters over normalized provenance fields. Every filter is optional and
+/// filters combine with AND. Results are ordered newest first.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProvenanceQuery {
+    pub subject_digest: Option<String>,
+    pub builder_id: Option<String>,
+    pub build_type: Option<String>,
+    pub source_uri: Option<String>,
+    pub source_ref: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    /// Opaque cursor returned as `next_cursor` by the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvenancePage {
+    pub records: Vec<ProvenanceRecord>,
+    pub next_cursor: Option<String>,
+}
+
+/// Keyset position after the last record of a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvenanceCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum QueryError {
+    #[error("Invalid cursor")]
+    InvalidCursor,
+    #[error("created_after must be before created_before")]
+    InvalidRange,
+    #[error("subject_digest must be a sha256 digest")]
+    InvalidDigest,
+}
+
+impl ProvenanceCursor {
+    pub fn encode(&self) -> String {
+        BASE64_URL.encode(format!("{}|{}", self.created_at.to_rfc3339(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, QueryError> {
+        let decoded = BASE64_URL.decode(cursor).map_err(|_| QueryError::InvalidCursor)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| QueryError::InvalidCursor)?;
+        let (created_at, id) = decoded.split_once('|').ok_or(QueryError::InvalidCursor)?;
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at)
+                .map_err(|_| QueryError::InvalidCursor)?
+                .with_timezone(&Utc),
+            id: id.parse().map_err(|_| QueryError::InvalidCursor)?,
+        })
+    }
+}
+
+impl ProvenanceQuery {
+    /// Validates the query and normalizes digest input, returning the decoded cursor.
+    pub fn prepare(&mut self) -> Result<Option<ProvenanceCursor>, QueryError> {
+        if let (Some(after), Some(before)) = (self.created_after, self.created_before) {
+            if after >= before {
+                return Err(QueryError::InvalidRange);
+            }
+        }
+        if let Some(digest) = &self.subject_digest {
+            self.subject_digest = Some(parse_digest(digest).map_err(|_| QueryError::InvalidDigest)?);
+        }
+        if let Some(uri) = &self.source_uri {
+            // Match the normalized form stored in the index, e.g. `github.com/org/repo`
+            let uri = uri.strip_prefix("git+").unwrap_or(uri);
+            let uri = uri.trim_start_matches("https://").trim_start_matches("http://");
+            self.source_uri = Some(uri.trim_end_matches(".git").to_string());
+        }
+        self.cursor.as_deref().map(ProvenanceCursor::decode).transpose()
+    }
+
+    pub fn page_size(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// Builds a page from up to `page_size + 1` rows; the extra row only signals
+/// that another page exists.
+pub fn paginate(mut records: Vec<ProvenanceRecord>, page_size: i64) -> ProvenancePage {
+    let has_more = records.len() as i64 > page_size;
+    records.truncate(page_size as usize);
+    let next_cursor = records.last()
+        .filter(|_| has_more)
+        .map(|record| ProvenanceCursor { created_at: record.created_at, id: record.id }.encode());
+    ProvenancePage { records, next_cursor }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+use crate::models::provenance::ProvenanceRecord;
+use crate::provenance::trusted_keys::{verifying_trusted_key, TrustedKey};
+
+/// Result of comparing independent provenance attestations for one subject.
+///
+/// A subject counts as reproduced when at least two attestations from distinct
+/// builders report the same output digest. A builder only counts when its
+/// attestation is signed by a trusted key, and no two counted builders share
+/// a key, so one signer cannot pose as several builders. Material and
+/// parameter differences are reported for investigation but do not by
+/// themselves fail the check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReproducibilityReport {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub subject_name: String,
+    pub subject_digest: Option<String>,
+    pub provenance_ids: Vec<Uuid>,
+    pub independent_builders: Vec<String>,
+    pub reproduced: bool,
+    pub output_diffs: Vec<ValueDiff>,
+    pub material_diffs: Vec<ValueDiff>,
+    pub parameter_diffs: Vec<ValueDiff>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A key whose value differs between attestations. `values` maps each
+/// provenance record to its value, or `None` when the record lacks the key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValueDiff {
+    pub key: String,
+    pub values: BTreeMap<Uuid, Option<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ReproducibilityError {
+    #[error("At least two attestations are required, got {0}")]
+    NotEnoughAttestations(usize),
+    #[error("Attestation {0} does not describe subject {1}")]
+    SubjectMissing(Uuid, String),
+}
+
+pub fn compare_attestations(
+    tenant_id: Uuid,
+    subject_name: &str,
+    records: &[ProvenanceRecord],
+    trusted_keys: &[TrustedKey],
+) -> Result<ReproducibilityReport, ReproducibilityError> {
+    if records.len() < 2 {
+        return Err(ReproducibilityError::NotEnoughAttestations(records.len()));
+    }
+
+    let mut outputs = Vec::new();
+    let mut materials = Vec::new();
+    let mut parameters = Vec::new();
+    for record in records {
+        let subject = record.slsa_provenance.subject.iter()
+            .find(|subject| subject.name == subject_name)
+            .ok_or_else(|| ReproducibilityError::SubjectMissing(record.id, subject_name.to_string()))?;
+
+        let mut output = BTreeMap::new();
+        output.insert(subject_name.to_string(), subject.digest.sha256.clone());
+        outputs.push((record.id, output));
+        materials.push((record.id, material_digests(record)));
+        parameters.push((record.id, build_parameters(record)));
+    }
+
+    let output_diffs = diff(&outputs);
+    let builders = independent_builders(records, trusted_keys);
+    let reproduced = output_diffs.is_empty() && builders.len() >= 2;
+    let subject_digest = outputs.first()
+        .filter(|_| output_diffs.is_empty())
+        .and_then(|(_, output)| output.get(subject_name).cloned());
+
+    Ok(ReproducibilityReport {
+        id: Uuid::new_v4(),
+        tenant_id,
+        subject_name: subject_name.to_string(),
+        subject_digest,
+        provenance_ids: records.iter().map(|record| record.id).collect(),
+        independent_builders: builders,
+        reproduced,
+        output_diffs,
+        material_diffs: diff(&materials),
+        parameter_diffs: diff(&parameters),
+        created_at: Utc::now(),
+    })
+}
+
+/// Builder IDs of the signed attestations, each counted once and only if
+/// its signing key has not already been counted for another builder.
+fn independent_builders(records: &[ProvenanceRecord], trusted_keys: &[TrustedKey]) -> Vec<String> {
+    let mut builders = Vec::new();
+    let mut keys = BTreeSet::new();
+    for record in records {
+        let Some(key) = record.envelope.as_ref().and_then(|envelope| verifying_trusted_key(envelope, trusted_keys)) else {
+            continue;
+        };
+        let builder = &record.slsa_provenance.builder.id;
+        if !builders.contains(builder) && keys.insert(key.key_id.as_str()) {
+            builders.push(builder.clone());
+        }
+    }
+    builders.sort();
+    builders
+}
+
+fn material_digests(record: &ProvenanceRecord) -> BTreeMap<String, String> {
+    record.slsa_provenance.materials.iter()
+        .map(|material| (material.uri.clone(), material.digest.sha256.clone()))
+        .collect()
+}
+
+fn build_parameters(record: &ProvenanceRecord) -> BTreeMap<String, String> {
+    let provenance = &record.slsa_provenance;
+    let config_source = &provenance.invocation.config_source;
+    let mut parameters = BTreeMap::new();
+    parameters.insert("build_type".to_string(), provenance.build_type.clone());
+    parameters.insert("config_source.uri".to_string(), config_source.uri.clone());
+    parameters.insert("config_source.sha256".to_string(), config_source.digest.sha256.clone());
+    if let Some(commit) = &config_source.digest.git_commit {
+        parameters.insert("config_source.git_commit".to_string(), commit.clone());
+    }
+    parameters
+}
+
+fn diff(sets: &[(Uuid, BTreeMap<String, String>)]) -> Vec<ValueDiff> {
+    let keys: BTreeSet<&String> = sets.iter().flat_map(|(_, values)| values.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let values: BTreeMap<Uuid, Option<String>> = sets.iter()
+                .map(|(id, values)| (*id, values.get(key).cloned()))
+                .collect();
+            let distinct: BTreeSet<&Option<String>> = values.values().collect();
+            if distinct.len() > 1 {
+                Some(ValueDiff { key: key.clone(), values })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::models::provenance::ProvenanceRecord;
+
+/// An immutable snapshot of a provenance record. Revisions are append-only:
+/// an update adds a new revision and a delete adds a tombstone, so every
+/// historical version stays retrievable for audit.
+///
+/// `chain_hash` commits to the previous revision's `chain_hash`, so altering or
+/// removing any earlier revision breaks every later one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvenanceRevision {
+    pub id: Uuid,
+    pub provenance_id: Uuid,
+    pub revision: i32,
+    pub kind: RevisionKind,
+    /// `None` for tombstones.
+    pub content: Option<ProvenanceRecord>,
+    pub content_hash: String,
+    pub previous_hash: Option<String>,
+    pub chain_hash: String,
+    pub reason: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionKind {
+    Created,
+    Updated,
+    Tombstone,
+}
+
+impl RevisionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionKind::Created => "created",
+            RevisionKind::Updated => "updated",
+            RevisionKind::Tombstone => "tombstone",
+        }
+    }
+
+}
+
+impl FromStr for RevisionKind {
+    type Err = RevisionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "created" => Ok(RevisionKind::Created),
+            "updated" => Ok(RevisionKind::Updated),
+            "tombstone" => Ok(RevisionKind::Tombstone),
+            _ => Err(RevisionError::UnknownKind(value.to_string())),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RevisionError {
+    #[error("Provenance record {0} has been deleted")]
+    Deleted(Uuid),
+    #[error("A tombstone requires a reason")]
+    MissingReason,
+    #[error("Revision {0} content does not match its content hash")]
+    ContentMismatch(i32),
+    #[error("Revision {0} does not link to the preceding revision")]
+    BrokenChain(i32),
+    #[error("Failed to hash revision content: {0}")]
+    Serialization(String),
+    #[error("Unknown revision kind: {0}")]
+    UnknownKind(String),
+}
+
+impl ProvenanceRevision {
+    /// The first revision of a newly created record.
+    pub fn initial(record: ProvenanceRecord) -> Result<Self, RevisionError> {
+        let created_by = record.created_by;
+        Self::build(record.id, 1, RevisionKind::Created, Some(record), None, None, created_by)
+    }
+
+    /// Appends an updated version of the record after `previous`.
+    pub fn update(previous: &ProvenanceRevision, record: ProvenanceRecord, updated_by: Uuid) -> Result<Self, RevisionError> {
+        previous.ensure_live()?;
+        Self::build(
+            previous.provenance_id,
+            previous.revision + 1,
+            RevisionKind::Updated,
+            Some(record),
+            Some(previous.chain_hash.clone()),
+            None,
+            updated_by,
+        )
+    }
+
+    /// Appends a tombstone marking the record deleted. Content is not carried forward.
+    pub fn tombstone(previous: &ProvenanceRevision, reason: String, deleted_by: Uuid) -> Result<Self, RevisionError> {
+        previous.ensure_live()?;
+        if reason.trim().is_empty() {
+            return Err(RevisionError::MissingReason);
+        }
+        Self::build(
+            previous.provenance_id,
+            previous.revision + 1,
+            RevisionKind::Tombstone,
+            None,
+            Some(previous.chain_hash.clone()),
+            Some(reason),
+            deleted_by,
+        )
+    }
+
+    pub fn is_tombstone(&self) -> bool {
+        self.kind == RevisionKind::Tombstone
+    }
+
+    fn ensure_live(&self) -> Result<(), RevisionError> {
+        if self.is_tombstone() {
+            Err(RevisionError::Deleted(self.provenance_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn build(
+        provenance_id: Uuid,
+        revision: i32,
+        kind: RevisionKind,
+        content: Option<ProvenanceRecord>,
+        previous_hash: Option<String>,
+        reason: Option<String>,
+        created_by: Uuid,
+    ) -> Result<Self, RevisionError> {
+        let content_hash = content_hash(content.as_ref())?;
+        let chain_hash = chain_hash(provenance_id, revision, kind, &content_hash, previous_hash.as_deref(), reason.as_deref());
+        Ok(Self {
+            id: Uuid::new_v4(),
+            provenance_id,
+            revision,
+            kind,
+            content,
+            content_hash,
+            previous_hash,
+            chain_hash,
+            reason,
+            created_by,
+            created_at: Utc::now(),
+        })
+    }
+}
+
+/// Checks that `revisions` (ordered oldest first) form an unbroken hash chain
+/// and that every revision's content still matches its recorded hash.
+pub fn verify_chain(revisions: &[ProvenanceRevision]) -> Result<(), RevisionError> {
+    let mut previous: Option<&ProvenanceRevision> = None;
+    for revision in revisions {
+        if content_hash(revision.content.as_ref())? != revision.content_hash {
+            return Err(RevisionError::ContentMismatch(revision.revision));
+        }
+
+        let expected_previous = previous.map(|previous| previous.chain_hash.as_str());
+        let expected_number = previous.map_or(1, |previous| previous.revision + 1);
+        let expected_chain = chain_hash(
+            revision.provenance_id,
+            revision.revision,
+            revision.kind,
+            &revision.content_hash,
+            revision.previous_hash.as_deref(),
+            revision.reason.as_deref(),
+        );
+        if revision.previous_hash.as_deref() != expected_previous
+            || revision.revision != expected_number
+            || revision.chain_hash != expected_chain
+        {
+            return Err(RevisionError::BrokenChain(revision.revision));
+        }
+        previous = Some(revision);
+    }
+    Ok(())
+}
+
+fn content_hash(content: Option<&ProvenanceRecord>) -> Result<String, RevisionError> {
+    let bytes = match content {
+        Some(record) => serde_json::to_vec(record).map_err(|e| RevisionError::Serialization(e.to_string()))?,
+        None => Vec::new(),
+    };
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+fn chain_hash(
+    provenance_id: Uuid,
+    revision: i32,
+    kind: RevisionKind,
+    content_hash: &str,
+    previous_hash: Option<&str>,
+    reason: Option<&str>,
+) -> String {
+    let mut hasher = Sha256::new();
+    for field in [
+        provenance_id.to_string().as_str(),
+        revision.to_string().as_str(),
+        kind.as_str(),
+        content_hash,
+        previous_hash.unwrap_or(""),
+        reason.unwrap_or(""),
+    ] {
+        // Length-prefix each field so adjacent values cannot be shifted between fields
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
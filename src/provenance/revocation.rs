@@ -0,0 +1,323 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::chain_of_custody::custody_events::parse_digest;
+use crate::database::DatabaseError;
+use crate::models::provenance::ProvenanceRecord;
+use crate::provenance::attestations::Attestation;
+use crate::provenance::dsse::verify_envelope;
+use crate::provenance::lineage::LineageStore;
+use crate::provenance::trusted_keys::TrustedKey;
+
+#[derive(Error, Debug)]
+pub enum RevocationError {
+    #[error("Invalid revocation: {0}")]
+    Invalid(String),
+    #[error("Unknown revocation target: {0}")]
+    UnknownTarget(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationTarget {
+    /// A signing key, by key ID.
+    Key,
+    /// A builder identity, by SLSA builder ID.
+    Builder,
+    /// A single stored attestation or provenance record, by ID.
+    Attestation,
+    /// An artifact, by sha256 digest.
+    Artifact,
+}
+
+impl RevocationTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevocationTarget::Key => "key",
+            RevocationTarget::Builder => "builder",
+            RevocationTarget::Attestation => "attestation",
+            RevocationTarget::Artifact => "artifact",
+        }
+    }
+
+}
+
+impl FromStr for RevocationTarget {
+    type Err = RevocationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "key" => Ok(RevocationTarget::Key),
+            "builder" => Ok(RevocationTarget::Builder),
+            "attestation" => Ok(RevocationTarget::Attestation),
+            "artifact" => Ok(RevocationTarget::Artifact),
+            _ => Err(RevocationError::UnknownTarget(value.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RevocationRequest {
+    pub target_type: RevocationTarget,
+    pub target: String,
+    pub reason: String,
+    /// When trust in the target ended, e.g. when a key leaked. Defaults to now.
+    pub effective_at: Option<DateTime<Utc>>,
+}
+
+/// Marks a key, builder, attestation or artifact as untrusted from
+/// `effective_at` on.
+///
+/// Key and builder revocations cover the records stored at or after
+/// `effective_at`, so records stored before a key leaked keep their trust.
+/// Attestation and artifact revocations cover their target once
+/// `effective_at` has passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revocation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub target_type: RevocationTarget,
+    pub target: String,
+    pub reason: String,
+    pub effective_at: DateTime<Utc>,
+    pub revoked_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Revocation {
+    pub fn new(tenant_id: Uuid, revoked_by: Uuid, request: RevocationRequest) -> Result<Self, RevocationError> {
+        let target = request.target.trim();
+        if target.is_empty() {
+            return Err(RevocationError::Invalid("target is required".to_string()));
+        }
+        if request.reason.trim().is_empty() {
+            return Err(RevocationError::Invalid("reason is required".to_string()));
+        }
+        let target = match request.target_type {
+            RevocationTarget::Attestation => Uuid::parse_str(target)
+                .map_err(|_| RevocationError::Invalid("attestation target must be a UUID".to_string()))?
+                .to_string(),
+            RevocationTarget::Artifact => parse_digest(target)
+                .map_err(|_| RevocationError::Invalid("artifact target must be a sha256 digest".to_string()))?,
+            RevocationTarget::Key | RevocationTarget::Builder => target.to_string(),
+        };
+
+        let now = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            target_type: request.target_type,
+            target,
+            reason: request.reason,
+            effective_at: request.effective_at.unwrap_or(now),
+            revoked_by,
+            created_at: now,
+        })
+    }
+
+    pub fn in_effect(&self, at: DateTime<Utc>) -> bool {
+        self.effective_at <= at
+    }
+
+    /// Whether the revocation covers a provenance record signed by `signer_key_ids`.
+    pub fn covers_record(&self, record: &ProvenanceRecord, signer_key_ids: &[String], at: DateTime<Utc>) -> bool {
+        match self.target_type {
+            RevocationTarget::Key => record.created_at >= self.effective_at && signer_key_ids.contains(&self.target),
+            RevocationTarget::Builder => record.created_at >= self.effective_at && record.slsa_provenance.builder.id == self.target,
+            RevocationTarget::Attestation => self.in_effect(at) && record.id.to_string() == self.target,
+            RevocationTarget::Artifact => false,
+        }
+    }
+
+    pub fn covers_attestation(&self, attestation: &Attestation, at: DateTime<Utc>) -> bool {
+        match self.target_type {
+            RevocationTarget::Key => {
+                attestation.created_at >= self.effective_at && attestation.verified_key_id.as_deref() == Some(self.target.as_str())
+            }
+            RevocationTarget::Attestation => self.in_effect(at) && attestation.id.to_string() == self.target,
+            RevocationTarget::Builder | RevocationTarget::Artifact => false,
+        }
+    }
+}
+
+/// What to load to evaluate `revocations`: records stored since the earliest
+/// key or builder revocation took effect, and the records named by
+/// attestation revocations.
+pub fn candidate_filter(revocations: &[Revocation]) -> (Option<DateTime<Utc>>, Vec<Uuid>) {
+    let since = revocations.iter()
+        .filter(|revocation| matches!(revocation.target_type, RevocationTarget::Key | RevocationTarget::Builder))
+        .map(|revocation| revocation.effective_at)
+        .min();
+    let ids = revocations.iter()
+        .filter(|revocation| revocation.target_type == RevocationTarget::Attestation)
+        .filter_map(|revocation| Uuid::parse_str(&revocation.target).ok())
+        .collect();
+    (since, ids)
+}
+
+/// Key IDs that signed a record's envelope: the IDs named in its signatures,
+/// plus any trusted key that verifies it, since signers often leave `keyid`
+/// empty.
+pub fn signer_key_ids(record: &ProvenanceRecord, trusted_keys: &[TrustedKey]) -> Vec<String> {
+    let Some(envelope) = &record.envelope else {
+        return Vec::new();
+    };
+    let mut key_ids: Vec<String> = envelope.signatures.iter()
+        .map(|signature| signature.keyid.clone())
+        .filter(|key_id| !key_id.is_empty())
+        .collect();
+    for key in trusted_keys {
+        let verified = key.verifying_key().is_ok_and(|verifying_key| verify_envelope(envelope, &verifying_key).is_ok());
+        if verified && !key_ids.contains(&key.key_id) {
+            key_ids.push(key.key_id.clone());
+        }
+    }
+    key_ids
+}
+
+pub fn record_revocations<'a>(
+    revocations: &'a [Revocation],
+    record: &ProvenanceRecord,
+    signer_key_ids: &[String],
+    at: DateTime<Utc>,
+) -> Vec<&'a Revocation> {
+    revocations.iter().filter(|revocation| revocation.covers_record(record, signer_key_ids, at)).collect()
+}
+
+/// What a revocation flags: the provenance records and attestations it
+/// covers, and every artifact that is untrusted because of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationImpact {
+    pub revocation: Revocation,
+    pub provenance_ids: Vec<Uuid>,
+    pub attestation_ids: Vec<Uuid>,
+    pub artifacts: Vec<RevokedArtifact>,
+}
+
+/// An artifact that is untrusted because of a revocation, either directly or
+/// because it was built from an untrusted artifact.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RevokedArtifact {
+    pub digest: String,
+    pub revocation_id: Uuid,
+    /// Build steps from the directly revoked artifact; 0 for the artifact itself.
+    pub depth: u32,
+    /// The covered record for directly revoked subjects, or the build that
+    /// consumed an untrusted input.
+    pub provenance_id: Option<Uuid>,
+    /// The untrusted input this artifact was built from.
+    pub revoked_input: Option<String>,
+}
+
+impl RevokedArtifact {
+    fn direct(digest: &str, revocation_id: Uuid, provenance_id: Option<Uuid>) -> Self {
+        Self { digest: digest.to_string(), revocation_id, depth: 0, provenance_id, revoked_input: None }
+    }
+}
+
+/// The artifacts a set of revocations makes untrusted directly: revoked
+/// artifacts, and the subjects of every covered record in `records`.
+pub fn direct_revocations(
+    revocations: &[Revocation],
+    records: &[ProvenanceRecord],
+    trusted_keys: &[TrustedKey],
+    at: DateTime<Utc>,
+) -> Vec<RevokedArtifact> {
+    let mut revoked: Vec<RevokedArtifact> = revocations.iter()
+        .filter(|revocation| revocation.target_type == RevocationTarget::Artifact && revocation.in_effect(at))
+        .map(|revocation| RevokedArtifact::direct(&revocation.target, revocation.id, None))
+        .collect();
+
+    let check_keys = revocations.iter().any(|revocation| revocation.target_type == RevocationTarget::Key);
+    for record in records {
+        let key_ids = if check_keys { signer_key_ids(record, trusted_keys) } else { Vec::new() };
+        for revocation in record_revocations(revocations, record, &key_ids, at) {
+            for subject in &record.slsa_provenance.subject {
+                revoked.push(RevokedArtifact::direct(&subject.digest.sha256, revocation.id, Some(record.id)));
+            }
+        }
+    }
+    revoked
+}
+
+/// `digests` and every artifact they were built from, following the lineage
+/// graph upstream.
+pub async fn upstream_closure<L: LineageStore + Sync>(
+    store: &L,
+    tenant_id: &Uuid,
+    digests: &[String],
+) -> Result<HashSet<String>, DatabaseError> {
+    let mut closure: HashSet<String> = digests.iter().cloned().collect();
+    let mut frontier: Vec<String> = closure.iter().cloned().collect();
+    while !frontier.is_empty() {
+        frontier = store.edges_producing(tenant_id, &frontier).await?
+            .into_iter()
+            .filter(|edge| closure.insert(edge.input_digest.clone()))
+            .map(|edge| edge.input_digest)
+            .collect();
+    }
+    Ok(closure)
+}
+
+/// Follows the lineage graph downstream from directly revoked artifacts, so
+/// that everything built from an untrusted input is flagged too. Each
+/// artifact is reported once per revocation, at its shortest distance.
+pub async fn propagate<L: LineageStore + Sync>(
+    store: &L,
+    tenant_id: &Uuid,
+    direct: Vec<RevokedArtifact>,
+) -> Result<Vec<RevokedArtifact>, DatabaseError> {
+    propagate_within(store, tenant_id, direct, None).await
+}
+
+/// Like `propagate`, but only through artifacts in `within`. With `within`
+/// from `upstream_closure`, this finds exactly the revocations reaching the
+/// closure's starting digests, since every path to them stays inside it.
+pub async fn propagate_within<L: LineageStore + Sync>(
+    store: &L,
+    tenant_id: &Uuid,
+    direct: Vec<RevokedArtifact>,
+    within: Option<&HashSet<String>>,
+) -> Result<Vec<RevokedArtifact>, DatabaseError> {
+    let mut seen: HashSet<(String, Uuid)> = HashSet::new();
+    let mut revoked = Vec::new();
+    let mut frontier = Vec::new();
+    for artifact in direct {
+        if seen.insert((artifact.digest.clone(), artifact.revocation_id)) {
+            frontier.push(artifact.clone());
+            revoked.push(artifact);
+        }
+    }
+
+    // Visited pairs are not expanded again, which also stops cycles
+    while !frontier.is_empty() {
+        let digests: Vec<String> = frontier.iter()
+            .map(|artifact| artifact.digest.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let edges = store.edges_consuming(tenant_id, &digests).await?;
+
+        let mut next = Vec::new();
+        for input in &frontier {
+            let in_scope = |digest: &String| within.is_none_or(|within| within.contains(digest));
+            for edge in edges.iter().filter(|edge| edge.input_digest == input.digest && in_scope(&edge.output_digest)) {
+                if seen.insert((edge.output_digest.clone(), input.revocation_id)) {
+                    next.push(RevokedArtifact {
+                        digest: edge.output_digest.clone(),
+                        revocation_id: input.revocation_id,
+                        depth: input.depth + 1,
+                        provenance_id: Some(edge.provenance_id),
+                        revoked_input: Some(input.digest.clone()),
+                    });
+                }
+            }
+        }
+        revoked.extend(next.iter().cloned());
+        frontier = next;
+    }
+    Ok(revoked)
+}
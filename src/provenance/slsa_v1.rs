@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use crate::models::provenance::{
+    SLSABuilder, SLSACompleteness, SLSAConfigSource, SLSADigest, SLSAInvocation, SLSAMaterial, SLSAMetadata, SLSAProvenance, SLSASubject,
+};
+use crate::provenance::intoto::{ResourceDescriptor, Statement};
+
+pub const SLSA_PROVENANCE_V1_PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenancePredicateV1 {
+    pub build_definition: BuildDefinition,
+    pub run_details: RunDetails,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDefinition {
+    pub build_type: String,
+    pub external_parameters: serde_json::Value,
+    #[serde(default)]
+    pub internal_parameters: serde_json::Value,
+    #[serde(default)]
+    pub resolved_dependencies: Vec<ResourceDescriptor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDetails {
+    pub builder: BuilderV1,
+    #[serde(default)]
+    pub metadata: BuildMetadataV1,
+    #[serde(default)]
+    pub byproducts: Vec<ResourceDescriptor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuilderV1 {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub version: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildMetadataV1 {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invocation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_on: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_on: Option<DateTime<Utc>>,
+}
+
+/// Maps an SLSA v1.0 statement onto the record model used by `create_provenance`.
+///
+/// The first resolved dependency with a `gitCommit` digest becomes the config
+/// source; every dependency with a sha256 digest becomes a material.
+pub fn to_slsa_provenance(statement: &Statement<ProvenancePredicateV1>) -> SLSAProvenance {
+    let definition = &statement.predicate.build_definition;
+
+    let source = definition.resolved_dependencies.iter()
+        .find(|dependency| dependency.digest.contains_key("gitCommit"));
+    let config_source = SLSAConfigSource {
+        uri: source.and_then(|dependency| dependency.uri.clone()).unwrap_or_default(),
+        digest: SLSADigest {
+            sha256: source.and_then(|dependency| dependency.sha256_digest()).unwrap_or_default().to_string(),
+            git_commit: source.and_then(|dependency| dependency.digest.get("gitCommit").cloned()),
+        },
+    };
+
+    SLSAProvenance {
+        subject: statement.subject.iter()
+            .filter_map(|subject| {
+                Some(SLSASubject {
+                    name: subject.name.clone().unwrap_or_default(),
+                    digest: SLSADigest::sha256(subject.sha256_digest()?),
+                })
+            })
+            .collect(),
+        builder: SLSABuilder { id: statement.predicate.run_details.builder.id.clone() },
+        build_type: definition.build_type.clone(),
+        invocation: SLSAInvocation { config_source },
+        materials: definition.resolved_dependencies.iter()
+            .filter_map(|dependency| {
+                Some(SLSAMaterial {
+                    uri: dependency.uri.clone().or_else(|| dependency.name.clone())?,
+                    digest: SLSADigest::sha256(dependency.sha256_digest()?),
+                })
+            })
+            .collect(),
+        metadata: SLSAMetadata {
+            build_invocation_id: statement.predicate.run_details.metadata.invocation_id.clone(),
+            reproducible: false,
+            // v1 provenance no longer records completeness
+            completeness: SLSACompleteness::default(),
+        },
+    }
+}
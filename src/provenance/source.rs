@@ -0,0 +1,482 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+use crate::models::provenance::ProvenanceRecord;
+use crate::models::{CheckStatus, VerificationCheck, VerificationCheckKind};
+use crate::provenance::policy::glob_matches;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureFormat {
+    Gpg,
+    Ssh,
+    X509,
+}
+
+/// A key allowed to sign source commits and tags.
+///
+/// `identity` is what a good signature must report: the principal (usually an
+/// email) for SSH, and the key or certificate fingerprint for GPG and x509.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AllowedSigner {
+    pub format: SignatureFormat,
+    pub identity: String,
+    /// SSH public key line, ASCII-armored OpenPGP key, or PEM certificate.
+    pub public_key: String,
+}
+
+/// Per-tenant rules for the source side of provenance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceTrustPolicy {
+    pub tenant_id: Uuid,
+    pub allowed_signers: Vec<AllowedSigner>,
+    /// Glob patterns over full ref names, e.g. `refs/heads/main` or `refs/tags/v*`.
+    pub protected_refs: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceTrustPolicyRequest {
+    #[serde(default)]
+    pub allowed_signers: Vec<AllowedSigner>,
+    #[serde(default)]
+    pub protected_refs: Vec<String>,
+}
+
+impl SourceTrustPolicy {
+    pub fn from_request(tenant_id: Uuid, request: SourceTrustPolicyRequest) -> Self {
+        Self {
+            tenant_id,
+            allowed_signers: request.allowed_signers,
+            protected_refs: request.protected_refs,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Result of checking a provenance record's source commit against a repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceVerification {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub provenance_id: Uuid,
+    pub commit: Option<String>,
+    pub source_ref: Option<String>,
+    pub passed: bool,
+    pub checks: Vec<VerificationCheck>,
+    pub verified_by: Uuid,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Error, Debug)]
+pub enum SourceError {
+    #[error("Failed to run git: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a git repository: {0}")]
+    NotARepository(String),
+    #[error("Invalid git bundle: {0}")]
+    InvalidBundle(String),
+    #[error("Not a full commit SHA: {0}")]
+    InvalidCommit(String),
+    #[error("Failed to import signing key: {0}")]
+    KeyImport(String),
+}
+
+/// Whether `commit` is a full, lowercase SHA-1 or SHA-256 object name. Only
+/// such names are handed to git, so a recorded commit can never be read as an
+/// option, a revision expression or an abbreviated name.
+pub fn is_full_commit_sha(commit: &str) -> bool {
+    matches!(commit.len(), 40 | 64) && commit.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn require_full_commit_sha(commit: &str) -> Result<(), SourceError> {
+    if is_full_commit_sha(commit) {
+        Ok(())
+    } else {
+        Err(SourceError::InvalidCommit(commit.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectKind {
+    Commit,
+    Tag,
+}
+
+/// A git repository opened for verification. Repositories created from an
+/// uploaded bundle live in a scratch directory that is removed on drop.
+pub struct GitRepository {
+    path: PathBuf,
+    scratch: Option<PathBuf>,
+}
+
+impl GitRepository {
+    pub fn open(path: &Path) -> Result<Self, SourceError> {
+        let repository = Self { path: path.to_path_buf(), scratch: None };
+        if !repository.git(&["rev-parse", "--git-dir"], &[])?.status.success() {
+            return Err(SourceError::NotARepository(path.display().to_string()));
+        }
+        Ok(repository)
+    }
+
+    /// Unpacks a `git bundle` into a temporary bare repository.
+    pub fn from_bundle(bundle: &[u8]) -> Result<Self, SourceError> {
+        let scratch = scratch_dir("bundle")?;
+        let bundle_path = scratch.join("upload.bundle");
+        std::fs::write(&bundle_path, bundle)?;
+        let path = scratch.join("repo.git");
+        let repository = Self { path: path.clone(), scratch: Some(scratch) };
+
+        let init = Command::new("git").arg("init").arg("--bare").arg("-q").arg(&path).output()?;
+        if !init.status.success() {
+            return Err(SourceError::InvalidBundle(String::from_utf8_lossy(&init.stderr).into_owned()));
+        }
+        let bundle_arg = bundle_path.to_string_lossy().into_owned();
+        let fetch = repository.git(&["fetch", "-q", &bundle_arg, "+refs/*:refs/*"], &[])?;
+        if !fetch.status.success() {
+            return Err(SourceError::InvalidBundle(String::from_utf8_lossy(&fetch.stderr).into_owned()));
+        }
+        Ok(repository)
+    }
+
+    pub fn commit_exists(&self, commit: &str) -> Result<bool, SourceError> {
+        require_full_commit_sha(commit)?;
+        Ok(self.git(&["cat-file", "-e", &format!("{}^{{commit}}", commit)], &[])?.status.success())
+    }
+
+    /// Full ref names that contain `commit` and match one of `patterns`.
+    pub fn protected_refs_containing(&self, commit: &str, patterns: &[String]) -> Result<Vec<String>, SourceError> {
+        require_full_commit_sha(commit)?;
+        let output = self.git(&["for-each-ref", "--format=%(refname)", "--contains", commit], &[])?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|name| patterns.iter().any(|pattern| glob_matches(pattern, name)))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// The commit a tag points to, if the tag exists.
+    pub fn tag_target(&self, tag_ref: &str) -> Result<Option<String>, SourceError> {
+        let output = self.git(&["rev-parse", "--verify", "-q", &format!("{}^{{commit}}", tag_ref)], &[])?;
+        Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()).filter(|_| output.status.success()))
+    }
+
+    /// Verifies the signature on a commit or annotated tag against the allowed
+    /// signers, returning the identity that signed it.
+    fn verify_signature(&self, object: &str, kind: ObjectKind, signers: &[AllowedSigner]) -> Result<String, String> {
+        let object_type = if kind == ObjectKind::Commit { "commit" } else { "tag" };
+        let raw = self.git(&["cat-file", object_type, object], &[]).map_err(|e| e.to_string())?;
+        let format = signature_format(&String::from_utf8_lossy(&raw.stdout))
+            .ok_or_else(|| format!("{} is not signed", object_type))?;
+
+        let candidates: Vec<&AllowedSigner> = signers.iter().filter(|signer| signer.format == format).collect();
+        if candidates.is_empty() {
+            return Err(format!("No allowed {:?} signers are configured", format));
+        }
+
+        let keyring = Keyring::new(format, &candidates).map_err(|e| e.to_string())?;
+        let verify = if kind == ObjectKind::Commit { "verify-commit" } else { "verify-tag" };
+        let mut args: Vec<String> = keyring.git_config();
+        args.extend([verify.to_string(), "--raw".to_string(), object.to_string()]);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.git(&args, &keyring.env()).map_err(|e| e.to_string())?;
+        let status = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        let signed_by = signer_identity(format, &status);
+        match signed_by {
+            Some(identity) if output.status.success() => candidates.iter()
+                .find(|signer| signer.identity.eq_ignore_ascii_case(&identity))
+                .map(|signer| signer.identity.clone())
+                .ok_or_else(|| format!("Signed by {}, which is not an allowed signer", identity)),
+            _ => Err(format!("Signature did not verify: {}", status.trim())),
+        }
+    }
+
+    fn git(&self, args: &[&str], env: &[(String, String)]) -> Result<Output, SourceError> {
+        Ok(Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .envs(env.iter().map(|(key, value)| (key.as_str(), value.as_str())))
+            .stdin(Stdio::null())
+            .output()?)
+    }
+}
+
+impl Drop for GitRepository {
+    fn drop(&mut self) {
+        if let Some(scratch) = &self.scratch {
+            let _ = std::fs::remove_dir_all(scratch);
+        }
+    }
+}
+
+/// Checks the source commit referenced by `record`: that it exists in the
+/// repository, is signed by an allowed signer (directly or through a signed
+/// tag named as the source ref) and is reachable from a protected ref.
+pub fn verify_source(repository: &GitRepository, record: &ProvenanceRecord, policy: &SourceTrustPolicy) -> Vec<VerificationCheck> {
+    let commit = match &record.slsa_provenance.invocation.config_source.digest.git_commit {
+        Some(commit) if is_full_commit_sha(commit) => commit.clone(),
+        Some(commit) => {
+            return vec![VerificationCheck::fail(
+                VerificationCheckKind::SourceCommit,
+                "Recorded git commit is not a full lowercase commit SHA",
+                json!({ "commit": commit }),
+            )];
+        }
+        None => {
+            return vec![VerificationCheck::fail(
+                VerificationCheckKind::SourceCommit,
+                "Provenance does not record a git commit for its config source",
+                json!({ "uri": record.slsa_provenance.invocation.config_source.uri }),
+            )];
+        }
+    };
+    let source_ref = record.slsa_provenance.normalize().source_ref;
+
+    match repository.commit_exists(&commit) {
+        Ok(true) => {}
+        Ok(false) => {
+            return vec![VerificationCheck::fail(
+                VerificationCheckKind::SourceCommit,
+                "Commit does not exist in the repository",
+                json!({ "commit": commit }),
+            )];
+        }
+        Err(e) => {
+            return vec![VerificationCheck::fail(
+                VerificationCheckKind::SourceCommit,
+                format!("Failed to look up commit: {}", e),
+                json!({ "commit": commit }),
+            )];
+        }
+    }
+
+    vec![
+        VerificationCheck::pass(VerificationCheckKind::SourceCommit, "Commit exists in the repository", json!({ "commit": commit })),
+        verify_source_signature(repository, &commit, source_ref.as_deref(), &policy.allowed_signers),
+        verify_reachability(repository, &commit, &policy.protected_refs),
+    ]
+}
+
+fn verify_source_signature(
+    repository: &GitRepository,
+    commit: &str,
+    source_ref: Option<&str>,
+    signers: &[AllowedSigner],
+) -> VerificationCheck {
+    if signers.is_empty() {
+        return VerificationCheck::skip(VerificationCheckKind::SourceSignature, "No allowed signers are configured");
+    }
+
+    let commit_result = repository.verify_signature(commit, ObjectKind::Commit, signers);
+    if let Ok(identity) = &commit_result {
+        return VerificationCheck::pass(
+            VerificationCheckKind::SourceSignature,
+            "Commit is signed by an allowed signer",
+            json!({ "commit": commit, "signer": identity }),
+        );
+    }
+
+    // A signed tag pointing at the commit is accepted in place of a signed commit
+    let tag_result = match source_ref.filter(|name| name.starts_with("refs/tags/")) {
+        Some(tag) => match repository.tag_target(tag) {
+            Ok(Some(target)) if target == commit => Some(repository.verify_signature(tag, ObjectKind::Tag, signers)),
+            Ok(_) => Some(Err(format!("Tag {} does not point to {}", tag, commit))),
+            Err(e) => Some(Err(e.to_string())),
+        },
+        None => None,
+    };
+    if let Some(Ok(identity)) = &tag_result {
+        return VerificationCheck::pass(
+            VerificationCheckKind::SourceSignature,
+            "Source tag is signed by an allowed signer",
+            json!({ "commit": commit, "tag": source_ref, "signer": identity }),
+        );
+    }
+
+    warn!("Source commit {} is not signed by an allowed signer", commit);
+    VerificationCheck::fail(
+        VerificationCheckKind::SourceSignature,
+        "Neither the commit nor its source tag is signed by an allowed signer",
+        json!({
+            "commit": commit,
+            "commit_error": commit_result.err(),
+            "tag": source_ref,
+            "tag_error": tag_result.and_then(Result::err),
+        }),
+    )
+}
+
+fn verify_reachability(repository: &GitRepository, commit: &str, protected_refs: &[String]) -> VerificationCheck {
+    if protected_refs.is_empty() {
+        return VerificationCheck::skip(VerificationCheckKind::SourceReachability, "No protected refs are configured");
+    }
+    match repository.protected_refs_containing(commit, protected_refs) {
+        Ok(refs) if !refs.is_empty() => VerificationCheck::pass(
+            VerificationCheckKind::SourceReachability,
+            "Commit is reachable from a protected ref",
+            json!({ "commit": commit, "refs": refs }),
+        ),
+        Ok(_) => VerificationCheck::fail(
+            VerificationCheckKind::SourceReachability,
+            "Commit is not reachable from any protected ref",
+            json!({ "commit": commit, "protected_refs": protected_refs }),
+        ),
+        Err(e) => VerificationCheck::fail(
+            VerificationCheckKind::SourceReachability,
+            format!("Failed to list refs containing the commit: {}", e),
+            json!({ "commit": commit }),
+        ),
+    }
+}
+
+impl SourceVerification {
+    pub fn new(tenant_id: Uuid, record: &ProvenanceRecord, verified_by: Uuid, checks: Vec<VerificationCheck>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            provenance_id: record.id,
+            commit: record.slsa_provenance.invocation.config_source.digest.git_commit.clone(),
+            source_ref: record.slsa_provenance.normalize().source_ref,
+            passed: checks.iter().all(|check| check.status != CheckStatus::Fail),
+            checks,
+            verified_by,
+            verified_at: Utc::now(),
+        }
+    }
+}
+
+fn signature_format(object: &str) -> Option<SignatureFormat> {
+    if object.contains("-----BEGIN SSH SIGNATURE-----") {
+        Some(SignatureFormat::Ssh)
+    } else if object.contains("-----BEGIN PGP SIGNATURE-----") {
+        Some(SignatureFormat::Gpg)
+    } else if object.contains("-----BEGIN SIGNED MESSAGE-----") {
+        Some(SignatureFormat::X509)
+    } else {
+        None
+    }
+}
+
+/// Extracts the signer from `git verify-* --raw` output: the principal from
+/// ssh-keygen, or the primary key fingerprint from a GnuPG `VALIDSIG` line.
+fn signer_identity(format: SignatureFormat, status: &str) -> Option<String> {
+    match format {
+        SignatureFormat::Ssh => status.lines()
+            .find_map(|line| line.strip_prefix("Good \"git\" signature for "))
+            .and_then(|rest| rest.split(" with ").next())
+            .map(str::to_string),
+        SignatureFormat::Gpg | SignatureFormat::X509 => status.lines()
+            .find_map(|line| line.strip_prefix("[GNUPG:] VALIDSIG "))
+            .and_then(|rest| {
+                let fields: Vec<&str> = rest.split_whitespace().collect();
+                // Field 10 is the primary key fingerprint when present
+                fields.get(9).or_else(|| fields.first()).map(|fingerprint| fingerprint.to_string())
+            }),
+    }
+}
+
+/// Scratch key material for a single verification: an SSH allowed-signers
+/// file or an isolated GnuPG home holding only the allowed keys.
+struct Keyring {
+    format: SignatureFormat,
+    dir: PathBuf,
+}
+
+impl Keyring {
+    fn new(format: SignatureFormat, signers: &[&AllowedSigner]) -> Result<Self, SourceError> {
+        let dir = scratch_dir("keyring")?;
+        let keyring = Self { format, dir };
+        match format {
+            SignatureFormat::Ssh => {
+                let lines: Vec<String> = signers.iter()
+                    .map(|signer| format!("{} namespaces=\"git\" {}", signer.identity, signer.public_key.trim()))
+                    .collect();
+                std::fs::write(keyring.dir.join("allowed_signers"), lines.join("\n") + "\n")?;
+            }
+            SignatureFormat::Gpg => {
+                for signer in signers {
+                    keyring.import("gpg", &["--batch", "--import"], &signer.public_key)?;
+                }
+            }
+            SignatureFormat::X509 => {
+                // Certificates are pinned by fingerprint; CRL and OCSP lookups are
+                // disabled because verification runs offline.
+                std::fs::write(keyring.dir.join("gpgsm.conf"), "disable-crl-checks\ndisable-ocsp\n")?;
+                let trustlist: Vec<String> = signers.iter()
+                    .map(|signer| format!("{} S relax", signer.identity.replace(':', "").to_uppercase()))
+                    .collect();
+                std::fs::write(keyring.dir.join("trustlist.txt"), trustlist.join("\n") + "\n")?;
+                for signer in signers {
+                    keyring.import("gpgsm", &["--batch", "--import"], &signer.public_key)?;
+                }
+            }
+        }
+        Ok(keyring)
+    }
+
+    /// Imports one key; a key the tool rejects fails the whole keyring rather
+    /// than leaving it silently short of a signer.
+    fn import(&self, program: &str, args: &[&str], key: &str) -> Result<(), SourceError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .env("GNUPGHOME", &self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(key.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(SourceError::KeyImport(format!(
+                "{} exited with {}: {}", program, output.status, String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    fn git_config(&self) -> Vec<String> {
+        let format = match self.format {
+            SignatureFormat::Gpg => "openpgp",
+            SignatureFormat::Ssh => "ssh",
+            SignatureFormat::X509 => "x509",
+        };
+        let mut config = vec!["-c".to_string(), format!("gpg.format={}", format)];
+        if self.format == SignatureFormat::Ssh {
+            config.push("-c".to_string());
+            config.push(format!("gpg.ssh.allowedSignersFile={}", self.dir.join("allowed_signers").display()));
+        }
+        config
+    }
+
+    fn env(&self) -> Vec<(String, String)> {
+        vec![("GNUPGHOME".to_string(), self.dir.display().to_string())]
+    }
+}
+
+impl Drop for Keyring {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn scratch_dir(purpose: &str) -> Result<PathBuf, SourceError> {
+    let dir = std::env::temp_dir().join(format!("traceguard-{}-{}", purpose, Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // GnuPG refuses to use a home directory readable by other users
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(dir)
+}
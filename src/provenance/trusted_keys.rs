@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::VerifyingKey;
+use uuid::Uuid;
+use crate::provenance::dsse::{key_id_for, verify_envelope, DsseError, Envelope};
+
+/// A public key registered by a tenant as a trusted signer of provenance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedKey {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrustedKeyRequest {
+    /// Hex-encoded raw Ed25519 public key.
+    pub public_key: String,
+    pub description: Option<String>,
+}
+
+impl TrustedKey {
+    pub fn from_request(tenant_id: Uuid, request: TrustedKeyRequest) -> Result<Self, DsseError> {
+        let verifying_key = parse_ed25519_public_key(&request.public_key)?;
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            key_id: key_id_for(&verifying_key),
+            algorithm: "ed25519".to_string(),
+            public_key: hex::encode(verifying_key.as_bytes()),
+            description: request.description,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn verifying_key(&self) -> Result<VerifyingKey, DsseError> {
+        parse_ed25519_public_key(&self.public_key)
+    }
+}
+
+/// The first trusted key the envelope's signature verifies against.
+pub fn verifying_trusted_key<'a>(envelope: &Envelope, trusted_keys: &'a [TrustedKey]) -> Option<&'a TrustedKey> {
+    trusted_keys.iter().find(|key| {
+        key.verifying_key().is_ok_and(|verifying_key| verify_envelope(envelope, &verifying_key).is_ok())
+    })
+}
+
+pub fn parse_ed25519_public_key(encoded: &str) -> Result<VerifyingKey, DsseError> {
+    let bytes: [u8; 32] = hex::decode(encoded.trim())
+        .map_err(|e| DsseError::InvalidKey(e.to_string()))?
+        .try_into()
+        .map_err(|_| DsseError::InvalidKey("Ed25519 public key must be 32 bytes".to_string()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| DsseError::InvalidKey(e.to_string()))
+}
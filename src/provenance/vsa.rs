@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+use crate::models::provenance::ProvenanceRecord;
+use crate::models::{CheckStatus, VerificationCheckKind, VerificationReport};
+use crate::chain_of_custody::signing::DocumentSigner;
+use crate::provenance::dsse::{DsseError, Envelope, IN_TOTO_PAYLOAD_TYPE};
+use crate::provenance::intoto::{ResourceDescriptor, Statement};
+use crate::provenance::policy::BuilderTrustPolicy;
+
+pub const VSA_PREDICATE_TYPE: &str = "https://slsa.dev/verification_summary/v1";
+pub const TRACEGUARD_VERIFIER_ID: &str = "https://traceguard.dev/verifier/v1";
+pub const VSA_SIGNING_KEY_ID: &str = "vsa_signing_key";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum VsaError {
+    #[error("Unknown verification result: {0}")]
+    UnknownResult(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationSummaryPredicate {
+    pub verifier: Verifier,
+    pub time_verified: DateTime<Utc>,
+    pub resource_uri: String,
+    pub policy: ResourceDescriptor,
+    pub input_attestations: Vec<ResourceDescriptor>,
+    pub verification_result: VerificationResult,
+    pub verified_levels: Vec<String>,
+    #[serde(default)]
+    pub dependency_levels: BTreeMap<String, u32>,
+    pub slsa_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Verifier {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VerificationResult {
+    Passed,
+    Failed,
+}
+
+impl VerificationResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationResult::Passed => "PASSED",
+            VerificationResult::Failed => "FAILED",
+        }
+    }
+
+}
+
+impl FromStr for VerificationResult {
+    type Err = VsaError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "PASSED" => Ok(VerificationResult::Passed),
+            "FAILED" => Ok(VerificationResult::Failed),
+            _ => Err(VsaError::UnknownResult(value.to_string())),
+        }
+    }
+}
+
+/// A signed VSA as stored by TraceGuard, indexed by the digests of its subjects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationSummary {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub provenance_id: Uuid,
+    pub verification_report_id: Uuid,
+    pub subject_digests: Vec<String>,
+    pub verification_result: VerificationResult,
+    pub verified_level: i32,
+    pub envelope: Envelope,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Derives the SLSA build level that a verification report supports.
+///
+/// A passing report is worth L1, and a signature by a trusted key raises it
+/// to L2. L3 also needs a builder that a tenant policy names in its allowed
+/// builders and provenance that declares the build hermetic; a policy that
+/// matched without constraining the builder is not enough.
+pub fn verified_build_level(report: &VerificationReport) -> u32 {
+    if !report.passed {
+        return 0;
+    }
+    let passed = |kind: VerificationCheckKind| report.check(kind).filter(|check| check.status == CheckStatus::Pass);
+    if passed(VerificationCheckKind::Signature).is_none() {
+        return 1;
+    }
+    let attested = |kind: VerificationCheckKind, field: &str| {
+        passed(kind).and_then(|check| check.evidence.get(field)).and_then(Value::as_bool) == Some(true)
+    };
+    if attested(VerificationCheckKind::BuilderTrust, "builder_pinned") && attested(VerificationCheckKind::Materials, "hermetic") {
+        3
+    } else {
+        2
+    }
+}
+
+pub fn build_vsa_statement(
+    tenant_id: Uuid,
+    record: &ProvenanceRecord,
+    report: &VerificationReport,
+    policies: &[BuilderTrustPolicy],
+) -> Result<Statement<VerificationSummaryPredicate>, DsseError> {
+    let subject: Vec<ResourceDescriptor> = record.slsa_provenance.subject.iter()
+        .map(|subject| ResourceDescriptor::sha256(Some(subject.name.clone()), None, &subject.digest.sha256))
+        .collect();
+    let resource_uri = record.slsa_provenance.subject.first()
+        .map(|subject| subject.name.clone())
+        .unwrap_or_else(|| format!("traceguard:provenance/{}", record.id));
+
+    let level = verified_build_level(report);
+    let verified_levels = if level > 0 { vec![format!("SLSA_BUILD_LEVEL_{}", level)] } else { vec![] };
+
+    let policy_digest = hex::encode(Sha256::digest(serde_json::to_vec(policies)?));
+    let record_digest = hex::encode(Sha256::digest(serde_json::to_vec(record)?));
+
+    let predicate = VerificationSummaryPredicate {
+        verifier: Verifier { id: TRACEGUARD_VERIFIER_ID.to_string() },
+        time_verified: report.verified_at,
+        resource_uri,
+        policy: ResourceDescriptor::sha256(None, Some(format!("traceguard:policies/{}", tenant_id)), &policy_digest),
+        input_attestations: vec![ResourceDescriptor::sha256(None, Some(format!("traceguard:provenance/{}", record.id)), &record_digest)],
+        verification_result: if report.passed { VerificationResult::Passed } else { VerificationResult::Failed },
+        verified_levels,
+        dependency_levels: BTreeMap::new(),
+        slsa_version: "1.0".to_string(),
+    };
+
+    Ok(Statement::new(subject, VSA_PREDICATE_TYPE, predicate))
+}
+
+pub fn issue_vsa(
+    signer: &DocumentSigner,
+    tenant_id: Uuid,
+    record: &ProvenanceRecord,
+    report: &VerificationReport,
+    policies: &[BuilderTrustPolicy],
+) -> Result<VerificationSummary, DsseError> {
+    let statement = build_vsa_statement(tenant_id, record, report, policies)?;
+    let envelope = signer.envelope(IN_TOTO_PAYLOAD_TYPE, &serde_json::to_vec(&statement)?);
+
+    Ok(VerificationSummary {
+        id: Uuid::new_v4(),
+        tenant_id,
+        provenance_id: record.id,
+        verification_report_id: report.id,
+        subject_digests: statement.subject.iter()
+            .filter_map(|subject| subject.sha256_digest().map(str::to_string))
+            .collect(),
+        verification_result: statement.predicate.verification_result,
+        verified_level: verified_build_level(report) as i32,
+        envelope,
+        created_at: Utc::now(),
+    })
+}
@@ -1,49 +1,145 @@
 use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait SecretManager {
     async fn get_secret(&self, secret_id: &str, tenant_id: Uuid) -> Result<String, SecretError>;
     async fn set_secret(&self, secret_id: &str, secret_value: &str, tenant_id: Uuid) -> Result<(), SecretError>;
+    /// Stores the secret only if nothing is stored under `secret_id` yet,
+    /// failing with `SecretError::AlreadyExists` otherwise.
+    async fn create_secret(&self, secret_id: &str, secret_value: &str, tenant_id: Uuid) -> Result<(), SecretError>;
     async fn delete_secret(&self, secret_id: &str, tenant_id: Uuid) -> Result<(), SecretError>;
 }
 
+const VAULT_TOKEN_HEADER: &str = "X-Vault-Token";
+
+/// Tenant secrets in a Vault KV v2 engine mounted at `secret/`, one entry per
+/// `traceguard/{tenant_id}/{secret_id}` holding the secret under `value`.
+#[derive(Clone)]
 pub struct VaultSecretManager {
-    client: vault::Client,
+    base_url: String,
+    token: String,
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct KvResponse {
+    data: KvData,
+}
+
+#[derive(Deserialize)]
+struct KvData {
+    data: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct VaultErrors {
+    #[serde(default)]
+    errors: Vec<String>,
 }
 
 impl VaultSecretManager {
-    pub fn new(vault_addr: &str, token: &str) -> Result<Self, vault::Error> {
-        let client = vault::Client::new(vault_addr, token)?;
-        Ok(Self { client })
+    pub fn new(vault_addr: &str, token: &str) -> Result<Self, SecretError> {
+        Ok(Self {
+            base_url: vault_addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            http: reqwest::Client::builder().build()?,
+        })
+    }
+
+    fn url(&self, kind: &str, secret_id: &str, tenant_id: Uuid) -> String {
+        format!("{}/v1/secret/{}/traceguard/{}/{}", self.base_url, kind, tenant_id, secret_id)
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, SecretError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let errors = response.json::<VaultErrors>().await.unwrap_or_default().errors;
+        Err(match status.as_u16() {
+            404 => SecretError::SecretNotFound,
+            // KV v2 rejects a write whose `cas` does not match the current version
+            400 if errors.iter().any(|error| error.contains("check-and-set")) => SecretError::AlreadyExists,
+            status => SecretError::VaultError { status, message: errors.join("; ") },
+        })
+    }
+
+    async fn write(&self, secret_id: &str, body: Value, tenant_id: Uuid) -> Result<(), SecretError> {
+        let response = self.http.post(self.url("data", secret_id, tenant_id))
+            .header(VAULT_TOKEN_HEADER, &self.token)
+            .json(&body)
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
     }
 }
 
 #[async_trait]
 impl SecretManager for VaultSecretManager {
     async fn get_secret(&self, secret_id: &str, tenant_id: Uuid) -> Result<String, SecretError> {
-        let path = format!("secret/data/{}/{}", tenant_id, secret_id);
-        let secret = self.client.get_secret(&path).await?;
-        Ok(secret)
+        let response = self.http.get(self.url("data", secret_id, tenant_id))
+            .header(VAULT_TOKEN_HEADER, &self.token)
+            .send()
+            .await?;
+        let mut secret: KvResponse = Self::check(response).await?.json().await?;
+        secret.data.data.remove("value").ok_or(SecretError::SecretNotFound)
     }
 
     async fn set_secret(&self, secret_id: &str, secret_value: &str, tenant_id: Uuid) -> Result<(), SecretError> {
-        let path = format!("secret/data/{}/{}", tenant_id, secret_id);
-        self.client.set_secret(&path, secret_value).await?;
-        Ok(())
+        self.write(secret_id, json!({ "data": { "value": secret_value } }), tenant_id).await
+    }
+
+    async fn create_secret(&self, secret_id: &str, secret_value: &str, tenant_id: Uuid) -> Result<(), SecretError> {
+        // Check-and-set with version 0 only writes keys that have no versions yet
+        self.write(secret_id, json!({ "options": { "cas": 0 }, "data": { "value": secret_value } }), tenant_id).await
     }
 
+    /// Removes every version, so the key can be created afresh.
     async fn delete_secret(&self, secret_id: &str, tenant_id: Uuid) -> Result<(), SecretError> {
-        let path = format!("secret/data/{}/{}", tenant_id, secret_id);
-        self.client.delete_secret(&path).await?;
+        let response = self.http.delete(self.url("metadata", secret_id, tenant_id))
+            .header(VAULT_TOKEN_HEADER, &self.token)
+            .send()
+            .await?;
+        Self::check(response).await?;
         Ok(())
     }
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum SecretError {
-    #[error("Vault error: {0}")]
-    VaultError(#[from] vault::Error),
+    #[error("Vault request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Vault error {status}: {message}")]
+    VaultError { status: u16, message: String },
     #[error("Secret not found")]
     SecretNotFound,
-}
\ No newline at end of file
+    #[error("Secret already exists")]
+    AlreadyExists,
+}
+
+/// Returns the secret stored under `secret_id`, storing `generate()` first if
+/// there is none. Callers racing on first use all get the value that was
+/// stored first.
+pub async fn get_or_create_secret<M: SecretManager>(
+    secret_manager: &M,
+    secret_id: &str,
+    tenant_id: Uuid,
+    generate: impl FnOnce() -> String,
+) -> Result<String, SecretError> {
+    match secret_manager.get_secret(secret_id, tenant_id).await {
+        Err(SecretError::SecretNotFound) => {
+            let value = generate();
+            match secret_manager.create_secret(secret_id, &value, tenant_id).await {
+                Ok(()) => Ok(value),
+                Err(SecretError::AlreadyExists) => secret_manager.get_secret(secret_id, tenant_id).await,
+                Err(e) => Err(e),
+            }
+        }
+        result => result,
+    }
+}
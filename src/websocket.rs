@@ -1,8 +1,17 @@
 use axum::{
-    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
     response::IntoResponse,
 };
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+use uuid::Uuid;
+use crate::auth::AuthenticatedUser;
 
 pub async fn websocket_handler(ws: WebSocketUpgrade) -> impl IntoResponse {
     ws.on_upgrade(handle_socket)
@@ -25,4 +34,91 @@ async fn handle_socket(mut socket: WebSocket) {
             break;
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveUpdateKind {
+    Revocation,
+    SplitView,
+}
+
+/// A change pushed to the tenant's connected clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveUpdate {
+    pub tenant_id: Uuid,
+    pub kind: LiveUpdateKind,
+    pub resource_id: Uuid,
+    pub payload: serde_json::Value,
+    pub published_at: DateTime<Utc>,
+}
+
+impl LiveUpdate {
+    pub fn new(tenant_id: Uuid, kind: LiveUpdateKind, resource_id: Uuid, payload: serde_json::Value) -> Self {
+        Self { tenant_id, kind, resource_id, payload, published_at: Utc::now() }
+    }
+}
+
+pub const LIVE_UPDATE_CAPACITY: usize = 256;
+
+/// Fans updates out to every open `/api/updates` socket. Updates published
+/// while nobody is connected are dropped; a client that falls more than
+/// `LIVE_UPDATE_CAPACITY` updates behind skips the ones it missed.
+#[derive(Clone)]
+pub struct LiveUpdates {
+    sender: broadcast::Sender<LiveUpdate>,
+}
+
+impl Default for LiveUpdates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveUpdates {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(LIVE_UPDATE_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, update: LiveUpdate) {
+        // Sending only fails when there are no subscribers
+        let _ = self.sender.send(update);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveUpdate> {
+        self.sender.subscribe()
+    }
+}
+
+/// Streams the caller's tenant updates as JSON text messages.
+pub async fn live_updates_handler(
+    ws: WebSocketUpgrade,
+    State(updates): State<LiveUpdates>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> impl IntoResponse {
+    let receiver = updates.subscribe();
+    ws.on_upgrade(move |socket| forward_updates(socket, receiver, user.tenant_id))
+}
+
+async fn forward_updates(mut socket: WebSocket, mut receiver: broadcast::Receiver<LiveUpdate>, tenant_id: Uuid) {
+    loop {
+        tokio::select! {
+            update = receiver.recv() => match update {
+                Ok(update) if update.tenant_id == tenant_id => {
+                    let Ok(text) = serde_json::to_string(&update) else { continue };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => warn!("Live update client lagged; skipped {} updates", skipped),
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
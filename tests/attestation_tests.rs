@@ -0,0 +1,65 @@
+mod common;
+
+use common::{envelope, signer};
+use serde_json::{json, Value};
+use traceguard::provenance::attestations::{
+    Attestation, AttestationError, AttestationKind, TypedPredicate, COSIGN_VULN_PREDICATE_TYPE, TEST_RESULT_PREDICATE_TYPE,
+};
+use traceguard::provenance::intoto::{ResourceDescriptor, Statement};
+use uuid::Uuid;
+
+fn statement(predicate_type: &str, predicate: Value) -> Statement<Value> {
+    Statement::new(
+        vec![ResourceDescriptor::sha256(Some("api.tar".to_string()), None, &"a".repeat(64))],
+        predicate_type,
+        predicate,
+    )
+}
+
+#[test]
+fn test_test_result_is_typed_and_links_to_subject() {
+    let predicate = json!({ "result": "FAILED", "failedTests": ["auth::login"], "configuration": [] });
+    let signed = envelope(&signer(3), &statement(TEST_RESULT_PREDICATE_TYPE, predicate));
+    let attestation = Attestation::from_envelope(Uuid::new_v4(), Uuid::new_v4(), signed).unwrap();
+
+    assert_eq!(attestation.kind, AttestationKind::TestResult);
+    assert_eq!(attestation.subject_digests, vec!["a".repeat(64)]);
+    assert_eq!(attestation.passed, Some(false));
+    match attestation.typed_predicate().unwrap() {
+        TypedPredicate::TestResult(result) => assert_eq!(result.failed_tests, vec!["auth::login"]),
+        other => panic!("unexpected predicate {:?}", other),
+    }
+}
+
+#[test]
+fn test_vuln_scan_and_versioned_sbom_types() {
+    let predicate = json!({
+        "scanner": { "uri": "pkg:github/aquasecurity/trivy@0.45.0", "result": {} },
+        "metadata": { "scanStartedOn": "2023-06-01T00:00:00Z", "scanFinishedOn": "2023-06-01T00:01:00Z" }
+    });
+    let signed = envelope(&signer(3), &statement(COSIGN_VULN_PREDICATE_TYPE, predicate));
+    let attestation = Attestation::from_envelope(Uuid::new_v4(), Uuid::new_v4(), signed).unwrap();
+    assert_eq!(attestation.kind, AttestationKind::VulnerabilityScan);
+    assert_eq!(attestation.passed, None);
+    assert_eq!(attestation.kind.as_str().parse::<AttestationKind>().unwrap(), AttestationKind::VulnerabilityScan);
+    assert!(matches!("vuln".parse::<AttestationKind>(), Err(AttestationError::UnknownKind(_))));
+
+    let sbom = json!({ "specVersion": "1.5", "components": [{ "name": "serde" }, { "name": "tokio" }] });
+    let signed = envelope(&signer(3), &statement("https://cyclonedx.org/bom/v1.5", sbom));
+    let attestation = Attestation::from_envelope(Uuid::new_v4(), Uuid::new_v4(), signed).unwrap();
+    match attestation.typed_predicate().unwrap() {
+        TypedPredicate::Sbom(summary) => assert_eq!(summary.component_count, 2),
+        other => panic!("unexpected predicate {:?}", other),
+    }
+}
+
+#[test]
+fn test_malformed_well_known_predicate_is_rejected() {
+    let signed = envelope(&signer(3), &statement(TEST_RESULT_PREDICATE_TYPE, json!({})));
+    let result = Attestation::from_envelope(Uuid::new_v4(), Uuid::new_v4(), signed);
+    assert!(matches!(result, Err(AttestationError::InvalidPredicate { .. })));
+
+    let signed = envelope(&signer(3), &statement("https://slsa.dev/provenance/v1", json!({})));
+    let result = Attestation::from_envelope(Uuid::new_v4(), Uuid::new_v4(), signed);
+    assert!(matches!(result, Err(AttestationError::IsProvenance)));
+}
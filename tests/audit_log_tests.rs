@@ -0,0 +1,111 @@
+mod common;
+
+use traceguard::chain_of_custody::audit_log::{
+    client_ip, paginate_audit, to_jsonl, verify_audit_chain, AuditChannel, AuditCheckpoint, AuditEntry, AuditLogError, AuditRecord,
+};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+fn record(tenant_id: Uuid, action: &str) -> AuditRecord {
+    AuditRecord {
+        tenant_id,
+        actor: Some(Uuid::new_v4()),
+        channel: AuditChannel::Rest,
+        action: action.to_string(),
+        resource: format!("/api/provenance/{}", Uuid::new_v4()),
+        status: 200,
+        before_hash: Some("a".repeat(64)),
+        after_hash: Some("b".repeat(64)),
+        request_id: Uuid::new_v4().to_string(),
+        source_ip: Some("10.0.0.7".to_string()),
+    }
+}
+
+fn chain(length: usize) -> Vec<AuditEntry> {
+    let tenant_id = Uuid::new_v4();
+    let mut entries: Vec<AuditEntry> = Vec::new();
+    for _ in 0..length {
+        let entry = AuditEntry::append(entries.last(), record(tenant_id, "PUT /api/provenance/:id"));
+        entries.push(entry);
+    }
+    entries
+}
+
+#[test]
+fn test_audit_chain_detects_edits_and_removals() {
+    let entries = chain(4);
+    assert_eq!(entries[0].sequence, 1);
+    assert_eq!(entries[3].previous_hash.as_deref(), Some(entries[2].entry_hash.as_str()));
+    verify_audit_chain(&entries).unwrap();
+    // A page from the middle of the chain verifies on its own
+    verify_audit_chain(&entries[2..]).unwrap();
+
+    let mut edited = entries.clone();
+    edited[1].record.actor = None;
+    assert!(matches!(verify_audit_chain(&edited), Err(AuditLogError::HashMismatch(2))));
+
+    let mut removed = entries.clone();
+    removed.remove(1);
+    assert!(matches!(verify_audit_chain(&removed), Err(AuditLogError::BrokenChain(3))));
+
+    // Moving an entry to another field must change the hash too
+    let mut shifted = entries[0].clone();
+    shifted.record.before_hash = None;
+    shifted.record.after_hash = Some(format!("{}{}", "a".repeat(64), "b".repeat(64)));
+    assert_ne!(shifted.compute_hash(), entries[0].entry_hash);
+}
+
+#[test]
+fn test_audit_checkpoint_signature_covers_head() {
+    let entries = chain(3);
+    let signer = common::signer(5);
+    let checkpoint = AuditCheckpoint::sign(&signer, &entries[2]).unwrap();
+    assert_eq!(checkpoint.checkpoint.sequence, 3);
+    checkpoint.verify(&signer.verifying_key(), &entries[2]).unwrap();
+
+    assert!(matches!(checkpoint.verify(&signer.verifying_key(), &entries[1]), Err(AuditLogError::CheckpointMismatch(3))));
+    let other = common::signer(6);
+    assert!(checkpoint.verify(&other.verifying_key(), &entries[2]).is_err());
+
+    let mut forged = checkpoint.clone();
+    forged.checkpoint.head_hash = "f".repeat(64);
+    assert!(matches!(forged.verify(&signer.verifying_key(), &entries[2]), Err(AuditLogError::CheckpointPayloadMismatch)));
+}
+
+#[test]
+fn test_audit_pages_and_jsonl_export() {
+    let entries = chain(5);
+    let page = paginate_audit(entries[..3].to_vec(), 2);
+    assert_eq!(page.entries.len(), 2);
+    assert_eq!(page.next_sequence, Some(2));
+    assert_eq!(paginate_audit(entries[..2].to_vec(), 2).next_sequence, None);
+
+    let jsonl = to_jsonl(&entries).unwrap();
+    let lines: Vec<&str> = jsonl.lines().collect();
+    assert_eq!(lines.len(), 5);
+    let parsed: AuditEntry = serde_json::from_str(lines[4]).unwrap();
+    assert_eq!(parsed, entries[4]);
+    let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(value["channel"], "rest");
+    assert_eq!(value["source_ip"], "10.0.0.7");
+}
+
+#[test]
+fn test_forwarded_for_is_only_trusted_from_proxies() {
+    let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+    let client: IpAddr = "203.0.113.9".parse().unwrap();
+
+    // A direct caller cannot claim another address
+    assert_eq!(client_ip(Some(client), Some("198.51.100.1"), &[proxy]).as_deref(), Some("203.0.113.9"));
+    // Behind the proxy, the nearest untrusted hop is the client; spoofed
+    // hops the client prepended are ignored
+    assert_eq!(
+        client_ip(Some(proxy), Some("198.51.100.1, 203.0.113.9, 10.0.0.2"), &[proxy]).as_deref(),
+        Some("203.0.113.9"),
+    );
+    assert_eq!(client_ip(Some(proxy), None, &[proxy]).as_deref(), Some("10.0.0.2"));
+    assert_eq!(client_ip(None, Some("198.51.100.1"), &[proxy]), None);
+
+    assert_eq!("grpc".parse::<AuditChannel>().unwrap(), AuditChannel::Grpc);
+    assert!(matches!("soap".parse::<AuditChannel>(), Err(AuditLogError::UnknownChannel(_))));
+}
@@ -0,0 +1,83 @@
+//! Fixtures shared by the integration tests. Every test crate compiles its
+//! own copy and uses only some of them.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+use traceguard::database::DatabaseError;
+use traceguard::models::provenance::{
+    ProvenanceRecord, SLSABuilder, SLSAConfigSource, SLSADigest, SLSAInvocation, SLSAMaterial, SLSAMetadata, SLSAProvenance,
+    SLSASubject,
+};
+use traceguard::provenance::dsse::{Envelope, EnvelopeSigner, IN_TOTO_PAYLOAD_TYPE};
+use traceguard::provenance::lineage::{record_edges, LineageEdge, LineageStore};
+use traceguard::provenance::trusted_keys::{TrustedKey, TrustedKeyRequest};
+use uuid::Uuid;
+
+pub const BUILDER: &str = "https://github.com/actions/runner";
+pub const SOURCE: &str = "git+https://github.com/ourorg/api@refs/heads/main";
+
+/// A build by `BUILDER` of one artifact with digest `subject` from
+/// `materials`, with `SOURCE` pinned by a sha256 digest.
+pub fn record(subject: &str, materials: &[&str]) -> ProvenanceRecord {
+    ProvenanceRecord::from_slsa(SLSAProvenance {
+        subject: vec![SLSASubject { name: "pkg:oci/ourorg/api".to_string(), digest: SLSADigest::sha256(subject) }],
+        builder: SLSABuilder { id: BUILDER.to_string() },
+        build_type: "https://slsa.dev/container-based-build/v0.1".to_string(),
+        invocation: SLSAInvocation {
+            config_source: SLSAConfigSource { uri: SOURCE.to_string(), digest: SLSADigest::sha256("f".repeat(64)) },
+        },
+        materials: materials.iter()
+            .enumerate()
+            .map(|(index, digest)| SLSAMaterial { uri: format!("pkg:cargo/dep-{}@1.0.0", index), digest: SLSADigest::sha256(*digest) })
+            .collect(),
+        metadata: SLSAMetadata::default(),
+    })
+}
+
+/// Pins the record's source to a git commit instead of a sha256 digest.
+pub fn at_commit(mut record: ProvenanceRecord, commit: &str) -> ProvenanceRecord {
+    record.slsa_provenance.invocation.config_source.digest = SLSADigest { sha256: String::new(), git_commit: Some(commit.to_string()) };
+    record
+}
+
+pub fn built_by(mut record: ProvenanceRecord, builder_id: &str) -> ProvenanceRecord {
+    record.slsa_provenance.builder.id = builder_id.to_string();
+    record
+}
+
+pub fn signer(seed: u8) -> EnvelopeSigner {
+    EnvelopeSigner::from_signing_key(SigningKey::from_bytes(&[seed; 32]))
+}
+
+/// A tenant trusted key for `signer`'s public key.
+pub fn trusted(signer: &EnvelopeSigner) -> TrustedKey {
+    let public_key = hex::encode(signer.verifying_key().as_bytes());
+    TrustedKey::from_request(Uuid::new_v4(), TrustedKeyRequest { public_key, description: None }).unwrap()
+}
+
+/// Signs `statement` as an in-toto DSSE envelope.
+pub fn envelope<T: Serialize>(signer: &EnvelopeSigner, statement: &T) -> Envelope {
+    signer.sign_json(IN_TOTO_PAYLOAD_TYPE, statement).unwrap()
+}
+
+/// An in-memory lineage graph, built from the edges of provenance records.
+pub struct MemoryLineage(pub Vec<LineageEdge>);
+
+impl MemoryLineage {
+    pub fn from_records<'a>(records: impl IntoIterator<Item = &'a ProvenanceRecord>) -> Self {
+        MemoryLineage(records.into_iter().flat_map(record_edges).collect())
+    }
+}
+
+#[async_trait]
+impl LineageStore for MemoryLineage {
+    async fn edges_producing(&self, _tenant_id: &Uuid, digests: &[String]) -> Result<Vec<LineageEdge>, DatabaseError> {
+        Ok(self.0.iter().filter(|edge| digests.contains(&edge.output_digest)).cloned().collect())
+    }
+
+    async fn edges_consuming(&self, _tenant_id: &Uuid, digests: &[String]) -> Result<Vec<LineageEdge>, DatabaseError> {
+        Ok(self.0.iter().filter(|edge| digests.contains(&edge.input_digest)).cloned().collect())
+    }
+}
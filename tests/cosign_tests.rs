@@ -0,0 +1,108 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::json;
+use std::sync::Arc;
+use traceguard::chain_of_custody::signing::{DocumentKey, SigningAlgorithm};
+use traceguard::provenance::cosign::{
+    attestation_tag, signature_tag, CosignError, CosignService, CosignVerifier, ImportedRecordKind, SimpleSigningPayload,
+    COSIGN_SIGNATURE_ANNOTATION, DSSE_ENVELOPE_MEDIA_TYPE, SIMPLE_SIGNING_MEDIA_TYPE,
+};
+use traceguard::provenance::dsse::{pae, Envelope, EnvelopeSignature, IN_TOTO_PAYLOAD_TYPE};
+use traceguard::provenance::oci_registry::{Descriptor, HttpRegistryClient, ImageManifest, MemoryRegistry};
+use traceguard::provenance::trusted_keys::{TrustedKey, TrustedKeyRequest};
+use uuid::Uuid;
+
+const REPOSITORY: &str = "team/app";
+const DIGEST: &str = "sha256:8f434346648f6b96df89dda901c5176b10a6d83961dd3c1ac88b59b2dc327aa4";
+
+fn config_descriptor(registry: &MemoryRegistry) -> Descriptor {
+    registry.push_blob("application/vnd.oci.image.config.v1+json", b"{}")
+}
+
+fn push_signature(registry: &MemoryRegistry, key: &DocumentKey, digest: &str) {
+    let payload = serde_json::to_vec(&SimpleSigningPayload::new("localhost:5000/team/app", digest)).unwrap();
+    let mut layer = registry.push_blob(SIMPLE_SIGNING_MEDIA_TYPE, &payload);
+    layer.annotations.insert(COSIGN_SIGNATURE_ANNOTATION.to_string(), BASE64.encode(key.sign(&payload)));
+    registry.push_manifest(REPOSITORY, &signature_tag(DIGEST), ImageManifest::new(config_descriptor(registry), vec![layer]));
+}
+
+fn attestation_envelope(key: &DocumentKey, predicate_type: &str, subject_digest: &str) -> Vec<u8> {
+    let statement = json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": [{"name": "localhost:5000/team/app", "digest": {"sha256": subject_digest.trim_start_matches("sha256:")}}],
+        "predicateType": predicate_type,
+        "predicate": {"result": "PASSED"},
+    });
+    let payload = serde_json::to_vec(&statement).unwrap();
+    let envelope = Envelope {
+        payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+        payload: BASE64.encode(&payload),
+        signatures: vec![EnvelopeSignature {
+            keyid: String::new(),
+            sig: BASE64.encode(key.sign(&pae(IN_TOTO_PAYLOAD_TYPE, &payload))),
+        }],
+    };
+    serde_json::to_vec(&envelope).unwrap()
+}
+
+#[tokio::test]
+async fn verifies_signatures_and_attestations_from_registry() {
+    let key = DocumentKey::generate(SigningAlgorithm::EcdsaP256);
+    let registry = Arc::new(MemoryRegistry::new());
+    push_signature(&registry, &key, DIGEST);
+    let layers = vec![
+        registry.push_blob(DSSE_ENVELOPE_MEDIA_TYPE, &attestation_envelope(&key, "https://in-toto.io/attestation/test-result/v0.1", DIGEST)),
+        registry.push_blob(DSSE_ENVELOPE_MEDIA_TYPE, &attestation_envelope(&key, "https://slsa.dev/provenance/v1", "sha256:0000000000000000000000000000000000000000000000000000000000000000")),
+    ];
+    registry.push_manifest(REPOSITORY, &attestation_tag(DIGEST), ImageManifest::new(config_descriptor(&registry), layers));
+
+    let addr = registry.clone().spawn().await.unwrap();
+    let client = HttpRegistryClient::new(&format!("http://{}", addr), None);
+    let service = CosignService::new(Some(Arc::new(client)), vec![key.public_key()]);
+    let lookup = service.lookup(&[], REPOSITORY, DIGEST.trim_start_matches("sha256:")).await.unwrap();
+
+    assert_eq!(lookup.digest, DIGEST);
+    assert_eq!(lookup.signatures.len(), 1);
+    assert_eq!(lookup.signatures[0].verified_key_id, Some(key.public_key().key_id()));
+    assert_eq!(lookup.attestations.len(), 2);
+    assert!(lookup.attestations[0].verified());
+    assert!(lookup.attestations[0].envelope.is_some());
+    // The provenance layer is about another image, so it is not trusted for this one.
+    assert!(lookup.attestations[1].is_provenance());
+    assert!(!lookup.attestations[1].verified());
+}
+
+#[tokio::test]
+async fn rejects_untrusted_keys_and_misdirected_signatures() {
+    let signer = DocumentKey::generate(SigningAlgorithm::Ed25519);
+    let registry = MemoryRegistry::new();
+    push_signature(&registry, &signer, "sha256:1111111111111111111111111111111111111111111111111111111111111111");
+
+    let lookup = CosignVerifier::new(vec![signer.public_key()]).lookup(&registry, REPOSITORY, DIGEST).await.unwrap();
+    assert!(lookup.signatures[0].error.as_deref().unwrap().contains("Signature is for"));
+
+    push_signature(&registry, &signer, DIGEST);
+    let other = DocumentKey::generate(SigningAlgorithm::EcdsaP256);
+    let lookup = CosignVerifier::new(vec![other.public_key()]).lookup(&registry, REPOSITORY, DIGEST).await.unwrap();
+    assert_eq!(lookup.signatures[0].verified_key_id, None);
+
+    // A tenant's Ed25519 trusted key is enough on its own.
+    let public_key = hex::encode(signer.public_key().to_bytes());
+    let trusted = TrustedKey::from_request(Uuid::new_v4(), TrustedKeyRequest { public_key, description: None }).unwrap();
+    let lookup = CosignVerifier::with_trusted_keys(&[], std::slice::from_ref(&trusted)).lookup(&registry, REPOSITORY, DIGEST).await.unwrap();
+    assert_eq!(lookup.signatures[0].verified_key_id, Some(trusted.key_id));
+}
+
+#[tokio::test]
+async fn follows_cosign_tag_convention() {
+    assert_eq!(signature_tag(DIGEST), format!("sha256-{}.sig", DIGEST.trim_start_matches("sha256:")));
+    assert_eq!(attestation_tag(DIGEST), format!("sha256-{}.att", DIGEST.trim_start_matches("sha256:")));
+
+    let registry = MemoryRegistry::new();
+    let lookup = CosignVerifier::new(Vec::new()).lookup(&registry, REPOSITORY, DIGEST).await.unwrap();
+    assert!(lookup.signatures.is_empty() && lookup.attestations.is_empty());
+    assert!(matches!(CosignVerifier::new(Vec::new()).lookup(&registry, REPOSITORY, "latest").await, Err(CosignError::InvalidDigest(_))));
+    assert!(matches!(CosignService::new(None, Vec::new()).lookup(&[], REPOSITORY, DIGEST).await, Err(CosignError::Disabled)));
+
+    assert_eq!("provenance".parse::<ImportedRecordKind>().unwrap(), ImportedRecordKind::Provenance);
+    assert!(matches!("sbom".parse::<ImportedRecordKind>(), Err(CosignError::UnknownRecordKind(_))));
+}
@@ -0,0 +1,106 @@
+use serde_json::json;
+use traceguard::chain_of_custody::custody_events::{
+    evaluate_custody_policies, verify_custody_chain, AppendCustodyEventRequest, CustodyError, CustodyEvent,
+    CustodyEventType, CustodyPolicy, CustodyPolicyRequest, RequiredEvent,
+};
+use uuid::Uuid;
+
+const DIGEST: &str = "sha256:2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824";
+
+fn request(event_type: CustodyEventType, category: Option<&str>, environment: Option<&str>) -> AppendCustodyEventRequest {
+    AppendCustodyEventRequest {
+        event_type,
+        category: category.map(str::to_string),
+        environment: environment.map(str::to_string),
+        evidence: Vec::new(),
+        details: None,
+    }
+}
+
+fn build_chain(tenant_id: Uuid, requests: Vec<AppendCustodyEventRequest>) -> Result<Vec<CustodyEvent>, CustodyError> {
+    let mut events: Vec<CustodyEvent> = Vec::new();
+    for request in requests {
+        let event = CustodyEvent::append(tenant_id, DIGEST, Uuid::new_v4(), events.last(), request)?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+#[test]
+fn chain_links_events_and_detects_tampering() {
+    let mut events = build_chain(Uuid::new_v4(), vec![
+        request(CustodyEventType::Built, None, None),
+        request(CustodyEventType::Scanned, None, None),
+        request(CustodyEventType::Promoted, None, Some("staging")),
+    ])
+    .unwrap();
+    assert_eq!(events[0].artifact_digest, DIGEST.trim_start_matches("sha256:").to_lowercase());
+    assert_eq!(events[2].sequence, 3);
+    assert_eq!(events[1].previous_hash.as_deref(), Some(events[0].event_hash.as_str()));
+    assert!(verify_custody_chain(&events).is_ok());
+
+    events[1].details = Some(json!({"scanner": "swapped"}));
+    assert_eq!(verify_custody_chain(&events), Err(CustodyError::HashMismatch(2)));
+
+    events.remove(1);
+    assert_eq!(verify_custody_chain(&events), Err(CustodyError::BrokenChain(3)));
+}
+
+#[test]
+fn revoked_chain_rejects_further_events() {
+    let events = build_chain(Uuid::new_v4(), vec![
+        request(CustodyEventType::Built, None, None),
+        request(CustodyEventType::Revoked, None, None),
+    ])
+    .unwrap();
+
+    let result = CustodyEvent::append(events[0].tenant_id, DIGEST, Uuid::new_v4(), events.last(), request(CustodyEventType::Deployed, None, Some("prod")));
+    assert!(matches!(result, Err(CustodyError::Revoked(_))));
+
+    let result = CustodyEvent::append(events[0].tenant_id, DIGEST, Uuid::new_v4(), None, request(CustodyEventType::Promoted, None, None));
+    assert_eq!(result.unwrap_err(), CustodyError::MissingEnvironment("promoted"));
+}
+
+#[test]
+fn policy_requires_security_approval_before_promotion() {
+    let tenant_id = Uuid::new_v4();
+    let policy = CustodyPolicy::from_request(tenant_id, CustodyPolicyRequest {
+        name: "security-approval-before-prod".to_string(),
+        description: None,
+        gated_event: CustodyEventType::Promoted,
+        environment_pattern: Some("prod*".to_string()),
+        required_events: vec![RequiredEvent {
+            event_type: CustodyEventType::Approved,
+            category: Some("security".to_string()),
+            environment: None,
+        }],
+        enabled: true,
+    })
+    .unwrap();
+    let policies = vec![policy];
+
+    let mut events = build_chain(tenant_id, vec![
+        request(CustodyEventType::Built, None, None),
+        request(CustodyEventType::Approved, Some("qa"), None),
+    ])
+    .unwrap();
+    let violations = evaluate_custody_policies(&policies, &events, CustodyEventType::Promoted, Some("prod-eu"));
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].missing.category.as_deref(), Some("security"));
+    assert!(evaluate_custody_policies(&policies, &events, CustodyEventType::Promoted, Some("staging")).is_empty());
+
+    let approval = CustodyEvent::append(tenant_id, DIGEST, Uuid::new_v4(), events.last(), request(CustodyEventType::Approved, Some("security"), None)).unwrap();
+    events.push(approval);
+    assert!(evaluate_custody_policies(&policies, &events, CustodyEventType::Promoted, Some("prod-eu")).is_empty());
+}
+
+#[test]
+fn events_need_a_sha256_digest_and_a_known_type() {
+    for digest in ["sha256:1234", "latest", &"g".repeat(64)] {
+        let result = CustodyEvent::append(Uuid::new_v4(), digest, Uuid::new_v4(), None, request(CustodyEventType::Built, None, None));
+        assert!(matches!(result, Err(CustodyError::InvalidDigest(_))), "{} was accepted", digest);
+    }
+
+    assert_eq!("approved".parse::<CustodyEventType>(), Ok(CustodyEventType::Approved));
+    assert_eq!("signed_off".parse::<CustodyEventType>(), Err(CustodyError::UnknownEventType("signed_off".to_string())));
+}
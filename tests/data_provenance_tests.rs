@@ -0,0 +1,113 @@
+use serde_json::json;
+use traceguard::data::data_provenance::{
+    CodeVersion, DataLineage, DataProvenanceError, DatasetFile, DatasetVersion, ProvTerm, RecordTransformationRequest,
+    RegisterDatasetVersionRequest, Transformation, PROV_NAMESPACE,
+};
+use traceguard::provenance::lineage::{LineageDirection, LineageGraph};
+use uuid::Uuid;
+
+fn file(path: &str, fill: &str) -> DatasetFile {
+    DatasetFile { path: path.to_string(), sha256: fill.repeat(64), size_bytes: 1024 }
+}
+
+fn dataset(name: &str, files: Vec<DatasetFile>) -> DatasetVersion {
+    DatasetVersion::new(Uuid::nil(), Uuid::nil(), RegisterDatasetVersionRequest {
+        name: name.to_string(),
+        version: "snapshot-42".to_string(),
+        uri: None,
+        digest: None,
+        files,
+    })
+    .unwrap()
+}
+
+fn transformation(inputs: &[DatasetVersion], outputs: &[DatasetVersion]) -> Transformation {
+    Transformation::new(Uuid::nil(), Uuid::nil(), RecordTransformationRequest {
+        job_name: "daily_orders_rollup".to_string(),
+        run_id: Some("run-1".to_string()),
+        code: Some(CodeVersion { uri: "git+https://github.com/ourorg/pipelines".to_string(), commit: "c".repeat(40) }),
+        inputs: inputs.iter().map(|dataset| dataset.id).collect(),
+        outputs: outputs.iter().map(|dataset| dataset.id).collect(),
+        facets: json!({}),
+        started_at: None,
+        ended_at: None,
+    }, inputs, outputs)
+    .unwrap()
+}
+
+#[test]
+fn manifest_digest_ignores_file_order_and_checks_given_digest() {
+    let forward = dataset("orders", vec![file("part-0.parquet", "a"), file("part-1.parquet", "b")]);
+    let reverse = dataset("orders", vec![file("part-1.parquet", "b"), file("part-0.parquet", "a")]);
+    assert_eq!(forward.digest, reverse.digest);
+
+    let mismatch = DatasetVersion::new(Uuid::nil(), Uuid::nil(), RegisterDatasetVersionRequest {
+        name: "orders".to_string(),
+        version: "snapshot-42".to_string(),
+        uri: None,
+        digest: Some("f".repeat(64)),
+        files: vec![file("part-0.parquet", "a")],
+    });
+    assert!(matches!(mismatch, Err(DataProvenanceError::ManifestMismatch { .. })));
+
+    let duplicate = DatasetVersion::new(Uuid::nil(), Uuid::nil(), RegisterDatasetVersionRequest {
+        name: "orders".to_string(),
+        version: "snapshot-42".to_string(),
+        uri: None,
+        digest: None,
+        files: vec![file("part-0.parquet", "a"), file("part-0.parquet", "b")],
+    });
+    assert_eq!(duplicate.unwrap_err(), DataProvenanceError::DuplicateFile("part-0.parquet".to_string()));
+}
+
+#[test]
+fn transformation_edges_link_inputs_and_code_to_outputs() {
+    let orders = dataset("orders", vec![file("part-0.parquet", "a")]);
+    let customers = dataset("customers", vec![file("part-0.parquet", "b")]);
+    let rollup = dataset("orders_daily", vec![file("part-0.parquet", "c")]);
+    let edges = transformation(&[orders.clone(), customers.clone()], std::slice::from_ref(&rollup)).lineage_edges();
+
+    assert_eq!(edges.len(), 3);
+    assert!(edges.iter().all(|edge| edge.output_digest == rollup.digest));
+    assert!(edges.iter().any(|edge| edge.input_digest == orders.digest && edge.input_uri == "dataset:orders@snapshot-42"));
+    assert!(edges.iter().any(|edge| edge.input_digest == "c".repeat(40)));
+}
+
+#[test]
+fn lineage_exports_as_prov_o() {
+    let orders = dataset("orders", vec![file("part-0.parquet", "a")]);
+    let rollup = dataset("orders_daily", vec![file("part-0.parquet", "c")]);
+    let run = transformation(std::slice::from_ref(&orders), std::slice::from_ref(&rollup));
+    let graph = LineageGraph {
+        root: rollup.digest.clone(),
+        direction: LineageDirection::Upstream,
+        max_depth: 5,
+        nodes: vec![],
+        edges: run.lineage_edges(),
+        truncated: false,
+        cycles: vec![],
+    };
+    let lineage = DataLineage::new(rollup.clone(), &graph, vec![orders.clone(), rollup.clone()], vec![run.clone()]);
+    assert_eq!(lineage.datasets.len(), 1);
+    assert!(lineage.provenance_ids.is_empty());
+
+    let prov = lineage.to_prov();
+    let rollup_iri = format!("urn:traceguard:dataset:{}", rollup.id);
+    let orders_iri = format!("urn:traceguard:dataset:{}", orders.id);
+    assert!(prov.contains(&rollup_iri, &format!("{}wasDerivedFrom", PROV_NAMESPACE), &ProvTerm::Iri(orders_iri.clone())));
+    assert!(prov.contains(
+        &rollup_iri,
+        &format!("{}wasGeneratedBy", PROV_NAMESPACE),
+        &ProvTerm::Iri(format!("urn:traceguard:transformation:{}", run.id)),
+    ));
+
+    let turtle = prov.to_turtle();
+    assert!(turtle.starts_with("@prefix prov: <http://www.w3.org/ns/prov#> ."));
+    assert!(turtle.contains(&format!("prov:wasDerivedFrom <{}>", orders_iri)));
+    assert!(turtle.contains("rdfs:label \"daily_orders_rollup\""));
+
+    let json_ld = prov.to_json_ld();
+    let node = json_ld["@graph"].as_array().unwrap().iter().find(|node| node["@id"] == rollup_iri.as_str()).unwrap();
+    assert_eq!(node["@type"], json!(["prov:Entity"]));
+    assert_eq!(node["prov:wasDerivedFrom"], json!([{ "@id": orders_iri }]));
+}
@@ -0,0 +1,51 @@
+use jsonwebtoken::{encode, EncodingKey, Header};
+use tonic::metadata::MetadataMap;
+use tonic::Code;
+use traceguard::grpc::auth::{authenticate, GrpcCaller, GrpcClaims};
+use uuid::Uuid;
+
+const SECRET: &[u8] = b"grpc-test-secret";
+
+fn token(tenant_id: Uuid, user_id: Uuid, secret: &[u8]) -> String {
+    let claims = GrpcClaims {
+        sub: user_id,
+        tenant_id,
+        exp: (chrono::Utc::now().timestamp() + 3600) as usize,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap()
+}
+
+fn metadata(token: &str) -> MetadataMap {
+    let mut metadata = MetadataMap::new();
+    metadata.insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    metadata
+}
+
+#[test]
+fn test_caller_comes_from_the_signed_token() {
+    let (tenant_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let mut metadata = metadata(&token(tenant_id, user_id, SECRET));
+    // Tenant and user metadata cannot redirect the call to another tenant
+    metadata.insert("x-tenant-id", Uuid::new_v4().to_string().parse().unwrap());
+    metadata.insert("x-user-id", Uuid::new_v4().to_string().parse().unwrap());
+
+    assert_eq!(authenticate(&metadata, SECRET).unwrap(), GrpcCaller { user_id, tenant_id });
+}
+
+#[test]
+fn test_spoofed_tenant_metadata_is_unauthenticated() {
+    let mut metadata = MetadataMap::new();
+    metadata.insert("x-tenant-id", Uuid::new_v4().to_string().parse().unwrap());
+    metadata.insert("x-user-id", Uuid::new_v4().to_string().parse().unwrap());
+
+    assert_eq!(authenticate(&metadata, SECRET).unwrap_err().code(), Code::Unauthenticated);
+}
+
+#[test]
+fn test_token_signed_with_another_secret_is_rejected() {
+    let forged = metadata(&token(Uuid::new_v4(), Uuid::new_v4(), b"attacker-secret"));
+    assert_eq!(authenticate(&forged, SECRET).unwrap_err().code(), Code::Unauthenticated);
+
+    let garbage = metadata("not-a-jwt");
+    assert_eq!(authenticate(&garbage, SECRET).unwrap_err().code(), Code::Unauthenticated);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::path::PathBuf;
+use traceguard::provenance::generator::LocalBuild;
+use std::io::Write;
+use traceguard::provenance::ingest::{parse_upload, parse_upload_limited, IngestFormat};
+
+fn envelope_json() -> String {
+    let dir = std::env::temp_dir().join(format!("traceguard-ingest-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let build = LocalBuild {
+        builder_id: "https://traceguard.dev/local-runner".to_string(),
+        command: vec!["sh".to_string(), "-c".to_string(), "echo built > out.bin".to_string()],
+        working_dir: dir,
+        env_allowlist: vec![],
+        inputs: vec![],
+        outputs: vec![PathBuf::from("out.bin")],
+    };
+    let signer = common::signer(7);
+    serde_json::to_string(&build.run_and_sign(&signer).unwrap()).unwrap()
+}
+
+#[test]
+fn test_json_lines_report_each_item() {
+    let body = format!("{}\n\n{{\"not\": \"an envelope\"}}\n{}\n", envelope_json(), envelope_json());
+    let parsed = parse_upload(IngestFormat::JsonLines, body.as_bytes()).unwrap();
+
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(parsed[0].source, "line 1");
+    assert!(parsed[0].record.is_ok());
+    assert_eq!(parsed[1].source, "line 3");
+    assert!(parsed[1].record.is_err());
+    assert!(parsed[2].record.is_ok());
+}
+
+#[test]
+fn test_tar_archive_reads_json_and_jsonl_members() {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut append = |path: &str, contents: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents).unwrap();
+    };
+    append("attestations/one.json", envelope_json().as_bytes());
+    append("attestations/many.intoto.jsonl", format!("{}\n{}\n", envelope_json(), envelope_json()).as_bytes());
+    append("README.md", b"ignored");
+    let archive = builder.into_inner().unwrap();
+
+    let parsed = parse_upload(IngestFormat::Tar, &archive).unwrap();
+    let sources: Vec<&str> = parsed.iter().map(|item| item.source.as_str()).collect();
+    assert_eq!(sources, vec!["attestations/one.json", "attestations/many.intoto.jsonl:1", "attestations/many.intoto.jsonl:2"]);
+    assert!(parsed.iter().all(|item| item.record.is_ok()));
+}
+
+#[test]
+fn test_content_type_detection() {
+    assert_eq!(IngestFormat::from_content_type("application/x-ndjson; charset=utf-8"), Some(IngestFormat::JsonLines));
+    assert_eq!(IngestFormat::from_content_type("application/gzip"), Some(IngestFormat::TarGz));
+    assert_eq!(IngestFormat::from_content_type("text/plain"), None);
+}
+
+#[test]
+fn test_gzip_upload_is_size_limited() {
+    let mut builder = tar::Builder::new(Vec::new());
+    let padding = vec![b' '; 64 * 1024];
+    let mut header = tar::Header::new_gnu();
+    header.set_size(padding.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, "padding.json", padding.as_slice()).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(&builder.into_inner().unwrap()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert!(compressed.len() < 1024);
+
+    // Within the limit the member is read; past it the upload is refused, not truncated
+    let parsed = parse_upload_limited(IngestFormat::TarGz, &compressed, 1024 * 1024).unwrap();
+    assert_eq!(parsed.len(), 1);
+    let error = parse_upload_limited(IngestFormat::TarGz, &compressed, 16 * 1024).unwrap_err();
+    assert!(error.to_string().contains("exceeds 16384 bytes"), "{}", error);
+}
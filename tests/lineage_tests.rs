@@ -0,0 +1,55 @@
+mod common;
+
+use common::{at_commit, record, MemoryLineage};
+use traceguard::models::provenance::ProvenanceRecord;
+use traceguard::provenance::lineage::{traverse, LineageDirection};
+use uuid::Uuid;
+
+fn build(output: &str, material: &str, commit: &str) -> ProvenanceRecord {
+    at_commit(record(output, &[material]), commit)
+}
+
+#[tokio::test]
+async fn test_upstream_reaches_source_commit() {
+    let lineage = MemoryLineage::from_records(&[build("lib", "dep", "c1"), build("app", "lib", "c2")]);
+    let graph = traverse(&lineage, &Uuid::new_v4(), "app", LineageDirection::Upstream, 5).await.unwrap();
+
+    let digests: Vec<&str> = graph.nodes.iter().map(|node| node.digest.as_str()).collect();
+    assert!(digests.contains(&"lib"));
+    assert!(digests.contains(&"dep"));
+    assert!(digests.contains(&"c1"));
+    assert!(digests.contains(&"c2"));
+    assert!(!graph.truncated);
+    assert!(graph.cycles.is_empty());
+}
+
+#[tokio::test]
+async fn test_downstream_from_commit_respects_depth() {
+    let lineage = MemoryLineage::from_records(&[build("lib", "dep", "c1"), build("app", "lib", "c2")]);
+    let graph = traverse(&lineage, &Uuid::new_v4(), "c1", LineageDirection::Downstream, 1).await.unwrap();
+
+    let digests: Vec<&str> = graph.nodes.iter().map(|node| node.digest.as_str()).collect();
+    assert_eq!(digests, vec!["c1", "lib"]);
+    assert!(graph.truncated);
+}
+
+#[tokio::test]
+async fn test_cycles_are_detected_and_terminate() {
+    let lineage = MemoryLineage::from_records(&[build("a", "b", "c1"), build("b", "a", "c1")]);
+    let graph = traverse(&lineage, &Uuid::new_v4(), "a", LineageDirection::Upstream, 10).await.unwrap();
+
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.cycles.len(), 1);
+    assert_eq!(graph.cycles[0].first(), graph.cycles[0].last());
+}
+
+#[tokio::test]
+async fn test_edges_store_digests_lowercase() {
+    let lineage = MemoryLineage::from_records(&[build("sha256:APP", "DEP", "C1")]);
+    assert!(lineage.0.iter().all(|edge| edge.output_digest == "app" && ["dep", "c1"].contains(&edge.input_digest.as_str())));
+
+    let graph = traverse(&lineage, &Uuid::new_v4(), "app", LineageDirection::Upstream, 5).await.unwrap();
+    let digests: Vec<&str> = graph.nodes.iter().map(|node| node.digest.as_str()).collect();
+    assert!(digests.contains(&"dep"));
+    assert!(digests.contains(&"c1"));
+}
@@ -0,0 +1,86 @@
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use traceguard::models::model_registry::{
+    hash_weights, ChunkedHasher, DatasetVersionRef, ModelCard, ModelRegistryError, ModelVersion, PerformanceMetric,
+    RegisterModelRequest, RegisterModelVersionRequest, RegisteredModel,
+};
+use uuid::Uuid;
+
+fn model() -> RegisteredModel {
+    RegisteredModel::new(Uuid::new_v4(), Uuid::new_v4(), RegisterModelRequest { name: "fraud-detector".to_string(), description: None })
+        .unwrap()
+}
+
+fn request(datasets: Vec<DatasetVersionRef>) -> RegisterModelVersionRequest {
+    RegisterModelVersionRequest {
+        version: "1.2.0".to_string(),
+        weights_uri: Some("s3://models/fraud-detector/1.2.0/model.safetensors".to_string()),
+        card: Some(ModelCard {
+            approach: Some("supervised".to_string()),
+            task: Some("classification".to_string()),
+            architecture_family: Some("transformer".to_string()),
+            architecture: Some("bert-base".to_string()),
+            hyperparameters: [("learning_rate".to_string(), json!(0.0003)), ("epochs".to_string(), json!(4))].into_iter().collect(),
+            metrics: vec![PerformanceMetric { metric_type: "f1".to_string(), value: "0.93".to_string(), slice: None }],
+        }),
+        mlbom: None,
+        training_provenance_id: None,
+        datasets,
+    }
+}
+
+#[test]
+fn chunked_hash_is_independent_of_how_input_is_split() {
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+
+    let mut whole = ChunkedHasher::with_chunk_size(4096);
+    whole.update(&data);
+    let whole = whole.finalize();
+
+    let mut pieces = ChunkedHasher::with_chunk_size(4096);
+    for piece in data.chunks(333) {
+        pieces.update(piece);
+    }
+    let pieces = pieces.finalize();
+
+    assert_eq!(whole, pieces);
+    assert_eq!(whole.sha256, hex::encode(Sha256::digest(&data)));
+    assert_eq!(whole.size_bytes, 10_000);
+    assert_eq!(whole.chunk_digests.len(), 3);
+    assert_eq!(whole.chunk_digests[2], hex::encode(Sha256::digest(&data[8192..])));
+    assert_eq!(hash_weights(&data[..]).unwrap().sha256, whole.sha256);
+}
+
+#[test]
+fn mlbom_export_round_trips_the_model_card() {
+    let dataset = DatasetVersionRef { name: "transactions".to_string(), version: "2023-05".to_string(), sha256: "ab".repeat(32) };
+    let version = ModelVersion::new(&model(), Uuid::new_v4(), request(vec![dataset])).unwrap();
+
+    let bom = version.to_cyclonedx();
+    assert_eq!(bom["specVersion"], "1.5");
+    assert_eq!(bom["components"][1]["type"], "data");
+    assert_eq!(bom["components"][0]["modelCard"]["modelParameters"]["datasets"][0]["ref"], "dataset:transactions@2023-05");
+
+    let imported = ModelVersion::new(&model(), Uuid::new_v4(), RegisterModelVersionRequest {
+        card: None,
+        mlbom: Some(bom),
+        ..request(vec![])
+    })
+    .unwrap();
+    assert_eq!(imported.card.architecture.as_deref(), Some("bert-base"));
+    assert_eq!(imported.card.metrics, version.card.metrics);
+    assert_eq!(imported.card.hyperparameters["epochs"], json!(4));
+}
+
+#[test]
+fn dataset_versions_require_sha256_digests() {
+    let dataset = DatasetVersionRef { name: "transactions".to_string(), version: "2023-05".to_string(), sha256: "not-a-digest".to_string() };
+    assert_eq!(
+        ModelVersion::new(&model(), Uuid::new_v4(), request(vec![dataset])).unwrap_err(),
+        ModelRegistryError::InvalidDatasetDigest("transactions".to_string())
+    );
+
+    let dataset = DatasetVersionRef { name: "transactions".to_string(), version: "2023-05".to_string(), sha256: format!("sha256:{}", "AB".repeat(32)) };
+    let version = ModelVersion::new(&model(), Uuid::new_v4(), request(vec![dataset])).unwrap();
+    assert_eq!(version.datasets[0].sha256, "ab".repeat(32));
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::signer;
+use std::path::PathBuf;
+use traceguard::models::model_signing::{
+    sign_manifest, verify_directory, ManifestResource, ModelManifest, ModelSigningError, MODEL_SIGNATURE_FILE,
+};
+use uuid::Uuid;
+
+fn model_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("traceguard-model-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("tokenizer")).unwrap();
+    std::fs::write(dir.join("model.safetensors"), vec![7u8; 20_000]).unwrap();
+    std::fs::write(dir.join("config.json"), br#"{"hidden_size": 768}"#).unwrap();
+    std::fs::write(dir.join("tokenizer/vocab.txt"), b"[PAD]\n[UNK]\n").unwrap();
+    dir
+}
+
+#[test]
+fn untouched_directory_verifies() {
+    let dir = model_dir();
+    let signer = signer(1);
+    let manifest = ModelManifest::from_directory(&dir, "fraud-detector", &[]).unwrap();
+    assert_eq!(manifest.resources.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+        vec!["config.json", "model.safetensors", "tokenizer/vocab.txt"]);
+
+    let bundle = sign_manifest(&signer, &manifest).unwrap();
+    std::fs::write(dir.join(MODEL_SIGNATURE_FILE), serde_json::to_vec(&bundle).unwrap()).unwrap();
+
+    let verification = verify_directory(&bundle, &signer.verifying_key(), &dir).unwrap();
+    assert!(verification.verified);
+    assert_eq!(verification.root_digest, manifest.root_digest());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn changed_added_and_removed_files_are_reported() {
+    let dir = model_dir();
+    let signer = signer(1);
+    let bundle = sign_manifest(&signer, &ModelManifest::from_directory(&dir, "fraud-detector", &[]).unwrap()).unwrap();
+
+    std::fs::write(dir.join("model.safetensors"), vec![8u8; 20_000]).unwrap();
+    std::fs::write(dir.join("extra.bin"), b"payload").unwrap();
+    std::fs::remove_file(dir.join("tokenizer/vocab.txt")).unwrap();
+
+    let verification = verify_directory(&bundle, &signer.verifying_key(), &dir).unwrap();
+    assert!(!verification.verified);
+    assert_eq!(verification.diff.changed, vec!["model.safetensors"]);
+    assert_eq!(verification.diff.added, vec!["extra.bin"]);
+    assert_eq!(verification.diff.removed, vec!["tokenizer/vocab.txt"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn signature_from_another_key_is_rejected() {
+    let dir = model_dir();
+    let bundle = sign_manifest(&signer(1), &ModelManifest::from_directory(&dir, "fraud-detector", &[]).unwrap()).unwrap();
+
+    let result = verify_directory(&bundle, &signer(2).verifying_key(), &dir);
+    assert!(matches!(result, Err(ModelSigningError::Dsse(_))));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn manifests_need_hex_sha256_digests() {
+    let resource = |digest: &str| ManifestResource {
+        name: "model.safetensors".to_string(),
+        algorithm: "sha256".to_string(),
+        digest: digest.to_string(),
+    };
+    let manifest = ModelManifest::new("fraud-detector".to_string(), vec![], vec![resource(&"AB".repeat(32))]).unwrap();
+    assert_eq!(manifest.resources[0].digest, "ab".repeat(32));
+
+    for digest in ["ab".repeat(16), "zz".repeat(32), String::new()] {
+        let result = ModelManifest::new("fraud-detector".to_string(), vec![], vec![resource(&digest)]);
+        assert!(matches!(result, Err(ModelSigningError::InvalidDigest(_))));
+    }
+
+    let unchecked = serde_json::json!({ "model_name": "fraud-detector", "resources": [resource("not-hex")] });
+    assert!(serde_json::from_value::<ModelManifest>(unchecked).is_err());
+}
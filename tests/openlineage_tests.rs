@@ -0,0 +1,77 @@
+use serde_json::json;
+use traceguard::data::openlineage::{identity_digest, EventType, RunEvent};
+
+fn complete_event() -> RunEvent {
+    serde_json::from_value(json!({
+        "eventType": "COMPLETE",
+        "eventTime": "2023-06-13T02:15:00.000Z",
+        "run": {
+            "runId": "0176a8c2-fe01-7439-87e6-56a1a1b4029f",
+            "facets": { "nominalTime": { "nominalStartTime": "2023-06-13T00:00:00Z" } }
+        },
+        "job": {
+            "namespace": "airflow-prod",
+            "name": "orders_dag.rollup",
+            "facets": {
+                "sourceCodeLocation": { "type": "git", "url": "https://github.com/ourorg/pipelines", "version": "9f2c1e0" }
+            }
+        },
+        "inputs": [{
+            "namespace": "postgres://db:5432",
+            "name": "analytics.public.orders",
+            "facets": { "version": { "datasetVersion": 42 }, "schema": { "fields": [{ "name": "id", "type": "BIGINT" }] } },
+            "inputFacets": { "dataQualityMetrics": { "rowCount": 1200 } }
+        }],
+        "outputs": [{ "namespace": "s3://warehouse", "name": "orders_daily" }],
+        "producer": "https://github.com/OpenLineage/OpenLineage/tree/1.0.0/integration/airflow",
+        "schemaURL": "https://openlineage.io/spec/2-0-0/OpenLineage.json#/$defs/RunEvent"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn run_event_maps_job_code_and_dataset_versions() {
+    let event = complete_event();
+    assert_eq!(event.event_type(), EventType::Complete);
+    assert_eq!(event.job_name(), "airflow-prod/orders_dag.rollup");
+
+    let code = event.code_version().unwrap();
+    assert_eq!(code.uri, "https://github.com/ourorg/pipelines");
+    assert_eq!(code.commit, "9f2c1e0");
+
+    assert_eq!(event.inputs[0].qualified_name(), "postgres://db:5432/analytics.public.orders");
+    assert_eq!(event.inputs[0].version_facet().as_deref(), Some("42"));
+    assert_eq!(event.outputs[0].version_facet(), None);
+}
+
+#[test]
+fn facets_are_kept_on_the_transformation() {
+    let facets = complete_event().transformation_facets();
+    let openlineage = &facets["openlineage"];
+    assert_eq!(openlineage["run"]["nominalTime"]["nominalStartTime"], "2023-06-13T00:00:00Z");
+    assert_eq!(openlineage["job"]["sourceCodeLocation"]["version"], "9f2c1e0");
+    let input = &openlineage["inputs"]["postgres://db:5432/analytics.public.orders"];
+    assert_eq!(input["inputFacets"]["dataQualityMetrics"]["rowCount"], 1200);
+    assert_eq!(input["facets"]["schema"]["fields"][0]["name"], "id");
+}
+
+#[test]
+fn datasets_without_content_digests_use_an_identity_digest() {
+    let event = complete_event();
+    let request = event.outputs[0].register_request("0176a8c2-fe01-7439-87e6-56a1a1b4029f".to_string());
+    assert_eq!(request.name, "s3://warehouse/orders_daily");
+    assert_eq!(
+        request.digest.as_deref(),
+        Some(identity_digest("s3://warehouse/orders_daily", "0176a8c2-fe01-7439-87e6-56a1a1b4029f").as_str())
+    );
+    assert_ne!(identity_digest("s3://warehouse/orders_daily", "1"), identity_digest("s3://warehouse/orders_daily", "2"));
+
+    let missing_type: RunEvent = serde_json::from_value(json!({
+        "eventTime": "2023-06-13T02:15:00Z",
+        "run": { "runId": "0176a8c2-fe01-7439-87e6-56a1a1b4029f" },
+        "job": { "namespace": "airflow-prod", "name": "orders_dag.rollup" },
+        "producer": "test"
+    }))
+    .unwrap();
+    assert_eq!(missing_type.event_type(), EventType::Other);
+}
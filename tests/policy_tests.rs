@@ -0,0 +1,77 @@
+use traceguard::models::provenance::NormalizedProvenance;
+use traceguard::provenance::policy::{BuilderTrustPolicy, BuilderTrustPolicyRequest, PolicyField};
+use uuid::Uuid;
+
+fn oci_policy() -> BuilderTrustPolicy {
+    BuilderTrustPolicy::from_request(Uuid::new_v4(), BuilderTrustPolicyRequest {
+        name: "ourorg-images".to_string(),
+        description: None,
+        subject_pattern: "pkg:oci/ourorg/*".to_string(),
+        allowed_builder_ids: vec!["https://github.com/ourorg/builders/*".to_string()],
+        allowed_build_types: vec!["https://slsa.dev/container-based-build/v0.1".to_string()],
+        allowed_source_uris: vec!["github.com/ourorg/*".to_string()],
+        allowed_source_refs: vec!["refs/heads/main".to_string(), "refs/tags/v*".to_string()],
+        require_reproducible: false,
+        enabled: true,
+    })
+    .unwrap()
+}
+
+fn provenance(subject: &str, builder_id: &str, source_uri: &str, source_ref: Option<&str>) -> NormalizedProvenance {
+    NormalizedProvenance {
+        subject_names: vec![subject.to_string()],
+        subject_digests: vec!["a".repeat(64)],
+        builder_id: builder_id.to_string(),
+        build_type: "https://slsa.dev/container-based-build/v0.1".to_string(),
+        source_uri: source_uri.to_string(),
+        source_ref: source_ref.map(str::to_string),
+        source_digest: "b".repeat(64),
+        reproduced: false,
+    }
+}
+
+#[test]
+fn test_policy_ignores_unmatched_subjects() {
+    let policy = oci_policy();
+    let provenance = provenance("pkg:npm/left-pad", "https://example.com/builder", "github.com/other/repo", None);
+    assert!(policy.evaluate(&provenance).is_none());
+}
+
+#[test]
+fn test_policy_accepts_trusted_builder() {
+    let policy = oci_policy();
+    let provenance = provenance(
+        "pkg:oci/ourorg/api",
+        "https://github.com/ourorg/builders/container@v1",
+        "github.com/ourorg/api",
+        Some("refs/tags/v1.2.0"),
+    );
+    let evaluation = policy.evaluate(&provenance).unwrap();
+    assert!(evaluation.satisfied());
+    assert_eq!(evaluation.matched_subjects, vec!["pkg:oci/ourorg/api".to_string()]);
+}
+
+#[test]
+fn test_policy_reports_each_violation() {
+    let policy = oci_policy();
+    let provenance = provenance("pkg:oci/ourorg/api", "https://evil.example/builder", "github.com/ourorg/api", None);
+    let evaluation = policy.evaluate(&provenance).unwrap();
+    let fields: Vec<PolicyField> = evaluation.violations.iter().map(|violation| violation.field).collect();
+    assert_eq!(fields, vec![PolicyField::BuilderId, PolicyField::SourceRef]);
+}
+
+#[test]
+fn test_policy_rejects_invalid_pattern() {
+    let result = BuilderTrustPolicy::from_request(Uuid::new_v4(), BuilderTrustPolicyRequest {
+        name: "broken".to_string(),
+        description: None,
+        subject_pattern: "pkg:oci/[ourorg".to_string(),
+        allowed_builder_ids: vec![],
+        allowed_build_types: vec![],
+        allowed_source_uris: vec![],
+        allowed_source_refs: vec![],
+        require_reproducible: false,
+        enabled: true,
+    });
+    assert!(result.is_err());
+}
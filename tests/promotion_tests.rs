@@ -0,0 +1,282 @@
+mod common;
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use traceguard::chain_of_custody::custody_events::{
+    AppendCustodyEventRequest, CustodyEvent, CustodyEventType, CustodyPolicy, CustodyPolicyRequest, RequiredEvent,
+};
+use traceguard::chain_of_custody::promotion::{
+    check_manual_event, evaluate_promotion, license_ids, LicenseRule, PromotionCheckKind, PromotionDecision, PromotionError,
+    PromotionEvidence, PromotionOutcome, PromotionPolicy, PromotionPolicyRequest, PromotionRequest, PromotionRules,
+    SbomRule, VulnerabilityThresholds,
+};
+use traceguard::models::{VerificationCheck, VerificationCheckKind, VerificationReport};
+use traceguard::provenance::attestations::{Attestation, AttestationKind};
+use traceguard::provenance::dsse::Envelope;
+use traceguard::provenance::revocation::{Revocation, RevocationRequest, RevocationTarget, RevokedArtifact};
+use uuid::Uuid;
+
+const DIGEST: &str = "sha256:AB12CD34CD34CD34CD34CD34CD34CD34CD34CD34CD34CD34CD34CD34CD34CD34";
+/// `DIGEST` as stored, normalized.
+const ARTIFACT: &str = "ab12cd34cd34cd34cd34cd34cd34cd34cd34cd34cd34cd34cd34cd34cd34cd34";
+
+fn event(events: &mut Vec<CustodyEvent>, tenant_id: Uuid, event_type: CustodyEventType, category: Option<&str>, environment: Option<&str>) {
+    let request = AppendCustodyEventRequest {
+        event_type,
+        category: category.map(str::to_string),
+        environment: environment.map(str::to_string),
+        evidence: vec![],
+        details: None,
+    };
+    let next = CustodyEvent::append(tenant_id, DIGEST, Uuid::new_v4(), events.last(), request).unwrap();
+    events.push(next);
+}
+
+fn attestation(tenant_id: Uuid, kind: AttestationKind, predicate: Value) -> Attestation {
+    Attestation {
+        id: Uuid::new_v4(),
+        tenant_id,
+        predicate_type: String::new(),
+        kind,
+        subject_names: vec!["app".to_string()],
+        subject_digests: vec![ARTIFACT.to_string()],
+        predicate,
+        passed: None,
+        verified_key_id: Some("ci-key".to_string()),
+        envelope: Envelope { payload_type: String::new(), payload: String::new(), signatures: vec![] },
+        created_by: Uuid::new_v4(),
+        created_at: Utc::now(),
+    }
+}
+
+fn cyclonedx(licenses: &[&str]) -> Value {
+    let components: Vec<Value> = licenses.iter().enumerate()
+        .map(|(index, license)| json!({
+            "name": format!("lib{}", index),
+            "version": "1.0.0",
+            "purl": format!("pkg:cargo/lib{}@1.0.0", index),
+            "licenses": [{ "expression": license }],
+        }))
+        .collect();
+    json!({ "specVersion": "1.5", "components": components })
+}
+
+fn trivy(severities: &[&str]) -> Value {
+    let findings: Vec<Value> = severities.iter().map(|severity| json!({ "VulnerabilityID": "CVE-1", "Severity": severity })).collect();
+    json!({ "scanner": { "uri": "pkg:github/aquasecurity/trivy", "result": { "Results": [{ "Vulnerabilities": findings }] } } })
+}
+
+fn prod_policy(tenant_id: Uuid) -> PromotionPolicy {
+    PromotionPolicy::from_request(tenant_id, PromotionPolicyRequest {
+        name: "prod gate".to_string(),
+        description: None,
+        environment_pattern: Some("prod*".to_string()),
+        rules: PromotionRules {
+            require_provenance: true,
+            min_slsa_level: Some(2),
+            sbom: Some(SbomRule { min_components: 2, min_coverage: 1.0 }),
+            vulnerabilities: Some(VulnerabilityThresholds { critical: Some(0), high: Some(1), ..Default::default() }),
+            licenses: Some(LicenseRule { allowed: vec![], denied: vec!["GPL-3.0-only".to_string()] }),
+        },
+        enabled: true,
+    }).unwrap()
+}
+
+/// An artifact in staging with signed provenance, a clean SBOM and scan,
+/// and the security approval a custody policy demands before prod.
+fn staged(tenant_id: Uuid) -> PromotionEvidence {
+    let mut custody_events = Vec::new();
+    event(&mut custody_events, tenant_id, CustodyEventType::Built, None, None);
+    event(&mut custody_events, tenant_id, CustodyEventType::Promoted, None, Some("staging"));
+    event(&mut custody_events, tenant_id, CustodyEventType::Approved, Some("security"), None);
+    let custody_policy = CustodyPolicy::from_request(tenant_id, CustodyPolicyRequest {
+        name: "security sign-off".to_string(),
+        description: None,
+        gated_event: CustodyEventType::Promoted,
+        environment_pattern: Some("prod*".to_string()),
+        required_events: vec![RequiredEvent { event_type: CustodyEventType::Approved, category: Some("security".to_string()), environment: None }],
+        enabled: true,
+    }).unwrap();
+    let signed = VerificationReport::new(Uuid::new_v4(), vec![VerificationCheck::pass(VerificationCheckKind::Signature, "signed", Value::Null)]);
+
+    PromotionEvidence {
+        reports: vec![signed],
+        attestations: vec![
+            attestation(tenant_id, AttestationKind::VulnerabilityScan, trivy(&["HIGH", "LOW", "MEDIUM"])),
+            attestation(tenant_id, AttestationKind::CycloneDx, cyclonedx(&["MIT", "Apache-2.0 OR MIT"])),
+        ],
+        custody_events,
+        custody_policies: vec![custody_policy],
+        ..Default::default()
+    }
+}
+
+fn failed(checks: &[traceguard::chain_of_custody::promotion::PromotionCheck]) -> Vec<PromotionCheckKind> {
+    checks.iter().filter(|check| !check.passed).map(|check| check.kind).collect()
+}
+
+#[test]
+fn test_promotion_passes_with_complete_evidence() {
+    let tenant_id = Uuid::new_v4();
+    let policies = vec![prod_policy(tenant_id)];
+    let evidence = staged(tenant_id);
+
+    let checks = evaluate_promotion(&policies, &evidence, "staging", "prod");
+    assert!(failed(&checks).is_empty(), "{:?}", checks);
+    for kind in [PromotionCheckKind::Provenance, PromotionCheckKind::Sbom, PromotionCheckKind::Vulnerabilities, PromotionCheckKind::Licenses] {
+        let check = checks.iter().find(|check| check.kind == kind).unwrap();
+        assert_eq!(check.policy_id, Some(policies[0].id));
+    }
+
+    // The policy only gates prod, and the artifact is not in dev
+    let checks = evaluate_promotion(&policies, &evidence, "dev", "qa");
+    assert_eq!(failed(&checks), vec![PromotionCheckKind::SourceEnvironment]);
+    assert!(checks.iter().all(|check| check.kind != PromotionCheckKind::Sbom));
+
+    assert_eq!(license_ids("(MIT OR Apache-2.0) AND GPL-2.0-only WITH Classpath-exception-2.0"), vec!["MIT", "Apache-2.0", "GPL-2.0-only"]);
+}
+
+#[test]
+fn test_promotion_fails_on_each_missing_piece_of_evidence() {
+    let tenant_id = Uuid::new_v4();
+    let policies = vec![prod_policy(tenant_id)];
+    let mut evidence = staged(tenant_id);
+    evidence.reports.clear();
+    evidence.attestations = vec![
+        attestation(tenant_id, AttestationKind::VulnerabilityScan, trivy(&["CRITICAL", "HIGH", "high"])),
+        attestation(tenant_id, AttestationKind::CycloneDx, cyclonedx(&["MIT", "LGPL-2.1 OR GPL-3.0-only"])),
+    ];
+    evidence.custody_events.pop();
+
+    let checks = evaluate_promotion(&policies, &evidence, "staging", "prod");
+    assert_eq!(failed(&checks), vec![
+        PromotionCheckKind::CustodyEvents,
+        PromotionCheckKind::Provenance,
+        PromotionCheckKind::Vulnerabilities,
+        PromotionCheckKind::Licenses,
+    ]);
+    let vulnerabilities = checks.iter().find(|check| check.kind == PromotionCheckKind::Vulnerabilities).unwrap();
+    assert!(vulnerabilities.reason.contains("1 critical (max 0)"));
+    assert!(vulnerabilities.reason.contains("2 high (max 1)"));
+
+    // Thin SBOMs, and no scan or SBOM at all
+    evidence.attestations = vec![attestation(tenant_id, AttestationKind::Spdx, json!({ "packages": [{ "name": "lib", "versionInfo": "1" }] }))];
+    let checks = evaluate_promotion(&policies, &evidence, "staging", "prod");
+    let sbom = checks.iter().find(|check| check.kind == PromotionCheckKind::Sbom).unwrap();
+    assert!(!sbom.passed);
+    assert!(sbom.reason.contains("1 components, requires 2"));
+    evidence.attestations.clear();
+    assert_eq!(failed(&evaluate_promotion(&policies, &evidence, "staging", "prod")).len(), 5);
+
+    // A revoked chain cannot be promoted
+    let mut evidence = staged(tenant_id);
+    event(&mut evidence.custody_events, tenant_id, CustodyEventType::Revoked, None, None);
+    assert!(failed(&evaluate_promotion(&policies, &evidence, "staging", "prod")).contains(&PromotionCheckKind::CustodyChain));
+}
+
+#[test]
+fn test_promotion_decision_is_signed_with_rationale() {
+    let tenant_id = Uuid::new_v4();
+    let signer = common::signer(3);
+    let policies = vec![prod_policy(tenant_id)];
+    let mut evidence = staged(tenant_id);
+    evidence.reports.clear();
+    let checks = evaluate_promotion(&policies, &evidence, "staging", "prod");
+
+    let mut request = PromotionRequest { from: "staging".to_string(), to: "prod".to_string(), override_reason: None };
+    let rejected = PromotionDecision::sign(&signer, tenant_id, DIGEST, &request, &policies, checks.clone(), Uuid::new_v4(), None).unwrap();
+    assert_eq!(rejected.decision.outcome, PromotionOutcome::Rejected);
+    assert_eq!(rejected.decision.artifact_digest, ARTIFACT);
+    assert_eq!(rejected.decision.policy_ids, vec![policies[0].id]);
+    rejected.verify(&signer.verifying_key()).unwrap();
+
+    request.override_reason = Some("hotfix for INC-42".to_string());
+    let overridden = PromotionDecision::sign(&signer, tenant_id, DIGEST, &request, &policies, checks, Uuid::new_v4(), Some("req-1".to_string())).unwrap();
+    assert_eq!(overridden.decision.outcome, PromotionOutcome::Overridden);
+    assert!(overridden.decision.outcome.promotes());
+    assert_eq!(overridden.decision.override_reason.as_deref(), Some("hotfix for INC-42"));
+    assert_ne!(overridden.payload_hash().unwrap(), rejected.payload_hash().unwrap());
+
+    // The rationale cannot be edited after signing
+    let mut edited = overridden.clone();
+    edited.decision.checks.retain(|check| check.passed);
+    assert!(matches!(edited.verify(&signer.verifying_key()), Err(PromotionError::PayloadMismatch)));
+    let other = common::signer(4);
+    assert!(overridden.verify(&other.verifying_key()).is_err());
+
+    // Policies must check something, and requests must move between environments
+    let empty = PromotionPolicyRequest { name: "empty".to_string(), description: None, environment_pattern: None, rules: PromotionRules::default(), enabled: true };
+    assert!(matches!(PromotionPolicy::from_request(tenant_id, empty), Err(PromotionError::InvalidPolicy(_))));
+    let same = PromotionRequest { from: "prod".to_string(), to: "prod".to_string(), override_reason: None };
+    assert!(same.validate().is_err());
+}
+
+#[test]
+fn test_promotion_ignores_unsigned_evidence_and_revoked_artifacts() {
+    let tenant_id = Uuid::new_v4();
+    let policies = vec![prod_policy(tenant_id)];
+
+    // A clean scan uploaded without a verified signature is not evidence
+    let mut evidence = staged(tenant_id);
+    let mut unsigned = attestation(tenant_id, AttestationKind::VulnerabilityScan, trivy(&[]));
+    unsigned.verified_key_id = None;
+    evidence.attestations[0] = attestation(tenant_id, AttestationKind::VulnerabilityScan, trivy(&["CRITICAL"]));
+    evidence.attestations.insert(0, unsigned);
+    let checks = evaluate_promotion(&policies, &evidence, "staging", "prod");
+    assert_eq!(failed(&checks), vec![PromotionCheckKind::Vulnerabilities]);
+
+    // Nor is a signed one whose attestation has been revoked
+    let mut evidence = staged(tenant_id);
+    let revocation = Revocation::new(tenant_id, Uuid::new_v4(), RevocationRequest {
+        target_type: RevocationTarget::Attestation,
+        target: evidence.attestations[0].id.to_string(),
+        reason: "scanner misconfigured".to_string(),
+        effective_at: None,
+    }).unwrap();
+    evidence.revocations.push(revocation.clone());
+    let checks = evaluate_promotion(&policies, &evidence, "staging", "prod");
+    assert_eq!(failed(&checks), vec![PromotionCheckKind::Vulnerabilities]);
+
+    // A revoked artifact is refused even with an override, and even when no policy asks for provenance
+    let mut evidence = staged(tenant_id);
+    evidence.revoked.push(RevokedArtifact {
+        digest: ARTIFACT.to_string(),
+        revocation_id: revocation.id,
+        depth: 1,
+        provenance_id: None,
+        revoked_input: Some("cd34".to_string()),
+    });
+    let checks = evaluate_promotion(&[], &evidence, "staging", "prod");
+    assert_eq!(failed(&checks), vec![PromotionCheckKind::Revocation]);
+    let request = PromotionRequest { from: "staging".to_string(), to: "prod".to_string(), override_reason: Some("ship it".to_string()) };
+    let signer = common::signer(5);
+    let decision = PromotionDecision::sign(&signer, tenant_id, DIGEST, &request, &[], checks, Uuid::new_v4(), None).unwrap();
+    assert_eq!(decision.decision.outcome, PromotionOutcome::Rejected);
+    assert!("revoked".parse::<PromotionOutcome>().is_err());
+}
+
+#[test]
+fn test_custody_events_cannot_move_artifacts() {
+    let tenant_id = Uuid::new_v4();
+    let request = |event_type, environment: Option<&str>| AppendCustodyEventRequest {
+        event_type,
+        category: None,
+        environment: environment.map(str::to_string),
+        evidence: vec![],
+        details: None,
+    };
+
+    // Before any promotion, the first `from` is the artifact's initial environment
+    let mut events = Vec::new();
+    event(&mut events, tenant_id, CustodyEventType::Built, None, None);
+    let evidence = PromotionEvidence { custody_events: events.clone(), ..Default::default() };
+    assert!(failed(&evaluate_promotion(&[], &evidence, "dev", "staging")).is_empty());
+
+    // Promotions only go through the promotion API, and deployments stay where the artifact is
+    assert!(check_manual_event(&request(CustodyEventType::Promoted, Some("prod")), &events).is_err());
+    assert!(check_manual_event(&request(CustodyEventType::Deployed, Some("prod")), &events).is_err());
+    let events = staged(tenant_id).custody_events;
+    assert!(check_manual_event(&request(CustodyEventType::Deployed, Some("prod")), &events).is_err());
+    check_manual_event(&request(CustodyEventType::Deployed, Some("staging")), &events).unwrap();
+    check_manual_event(&request(CustodyEventType::Approved, None), &events).unwrap();
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::record;
+use traceguard::provenance::query::{paginate, ProvenanceCursor, ProvenanceQuery, QueryError};
+use uuid::Uuid;
+
+#[test]
+fn test_cursor_round_trip() {
+    let cursor = ProvenanceCursor { created_at: Utc::now(), id: Uuid::new_v4() };
+    assert_eq!(ProvenanceCursor::decode(&cursor.encode()), Ok(cursor));
+    assert_eq!(ProvenanceCursor::decode("not-a-cursor"), Err(QueryError::InvalidCursor));
+}
+
+#[test]
+fn test_prepare_normalizes_filters_and_rejects_bad_ranges() {
+    let mut query = ProvenanceQuery {
+        subject_digest: Some(format!("sha256:{}", "AB".repeat(32))),
+        source_uri: Some("git+https://github.com/ourorg/api.git".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(query.prepare(), Ok(None));
+    assert_eq!(query.subject_digest, Some("ab".repeat(32)));
+    assert_eq!(query.source_uri.as_deref(), Some("github.com/ourorg/api"));
+
+    let mut query = ProvenanceQuery { subject_digest: Some("sha256:abc".to_string()), ..Default::default() };
+    assert_eq!(query.prepare(), Err(QueryError::InvalidDigest));
+
+    let now = Utc::now();
+    let mut query = ProvenanceQuery {
+        created_after: Some(now),
+        created_before: Some(now - Duration::days(1)),
+        ..Default::default()
+    };
+    assert_eq!(query.prepare(), Err(QueryError::InvalidRange));
+}
+
+#[test]
+fn test_paginate_emits_cursor_only_when_more_rows_exist() {
+    let page = paginate((0..3).map(|_| record(&"a".repeat(64), &[])).collect(), 2);
+    assert_eq!(page.records.len(), 2);
+    let cursor = ProvenanceCursor::decode(page.next_cursor.as_deref().unwrap()).unwrap();
+    assert_eq!(cursor.id, page.records[1].id);
+
+    let page = paginate(vec![record(&"a".repeat(64), &[])], 2);
+    assert!(page.next_cursor.is_none());
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signer, SigningKey};
+use p256::pkcs8::{EncodePublicKey, LineEnding};
+use sha2::{Digest, Sha256, Sha512};
+use std::sync::Arc;
+use traceguard::chain_of_custody::embedded_rekor::EmbeddedRekor;
+use traceguard::chain_of_custody::rekor::{
+    sign_hashed_rekord, Checkpoint, HttpRekorClient, LogKey, ProposedEntry, RekorBackend, RekorError,
+};
+use traceguard::chain_of_custody::ChainOfCustody;
+use traceguard::provenance::dsse::IN_TOTO_PAYLOAD_TYPE;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn sign_and_record_returns_verifiable_entry() {
+    let rekor = Arc::new(EmbeddedRekor::generate());
+    let custody = ChainOfCustody::new(rekor.clone(), rekor.log_key());
+
+    let first = custody.sign_and_record(b"artifact v1", &[7; 32]).await.unwrap();
+    custody.sign_and_record(b"artifact v2", &[7; 32]).await.unwrap();
+    assert_eq!(rekor.tree_size(), 2);
+
+    // The first entry's proof is now against the larger tree
+    let recorded = custody.get_verified_entry(&first).await.unwrap();
+    recorded.verify_signed_entry_timestamp(&rekor.log_key()).unwrap();
+    let proof = recorded.entry.verification.unwrap().inclusion_proof.unwrap();
+    assert_eq!(proof.tree_size, 2);
+    let checkpoint = Checkpoint::verify(&proof.checkpoint.unwrap(), &rekor.log_key()).unwrap();
+    assert_eq!(hex::encode(checkpoint.root_hash), proof.root_hash);
+
+    // Entries vouched for by another log's key are refused
+    let impostor = EmbeddedRekor::generate();
+    let misconfigured = ChainOfCustody::new(rekor.clone(), impostor.log_key());
+    assert!(misconfigured.get_verified_entry(&first).await.is_err());
+    assert!(misconfigured.sign_and_record(b"artifact v3", &[7; 32]).await.is_err());
+}
+
+#[tokio::test]
+async fn http_client_records_intoto_entries_with_embedded_server() {
+    let rekor = Arc::new(EmbeddedRekor::generate());
+    let addr = rekor.clone().spawn().await.unwrap();
+    let client = HttpRekorClient::new(&format!("http://{}", addr));
+
+    let signer = common::signer(9);
+    let envelope = signer.sign(IN_TOTO_PAYLOAD_TYPE, br#"{"_type":"https://in-toto.io/Statement/v1"}"#);
+    let entry = ProposedEntry::intoto(&envelope, &signer.verifying_key());
+
+    let recorded = client.create_entry(&entry).await.unwrap();
+    recorded.verify_inclusion().unwrap();
+    assert_eq!(client.get_entry(&recorded.uuid).await.unwrap().uuid, recorded.uuid);
+    assert!(matches!(client.create_entry(&entry).await, Err(RekorError::Conflict(_))));
+    assert!(matches!(client.get_entry(&"0".repeat(64)).await, Err(RekorError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn entry_with_bad_signature_is_rejected() {
+    let rekor = EmbeddedRekor::generate();
+    let signing_key = SigningKey::from_bytes(&[5; 32]);
+    let digest = hex::encode(Sha512::digest(b"artifact"));
+
+    // Pure Ed25519 over the digest is not an Ed25519ph signature
+    let pure = signing_key.sign(&hex::decode(&digest).unwrap());
+    let entry = ProposedEntry::hashed_rekord(&digest, &pure.to_bytes(), &signing_key.verifying_key());
+    assert!(matches!(rekor.create_entry(&entry).await, Err(RekorError::Signature(_))));
+    let (_, other) = sign_hashed_rekord(&signing_key, b"something else").unwrap();
+    let entry = ProposedEntry::hashed_rekord(&digest, &other.to_bytes(), &signing_key.verifying_key());
+    assert!(matches!(rekor.create_entry(&entry).await, Err(RekorError::Signature(_))));
+    assert_eq!(rekor.tree_size(), 0);
+
+    let (sha512, signature) = sign_hashed_rekord(&signing_key, b"artifact").unwrap();
+    assert_eq!(sha512, digest);
+    let entry = ProposedEntry::hashed_rekord(&sha512, &signature.to_bytes(), &signing_key.verifying_key());
+    rekor.create_entry(&entry).await.unwrap();
+}
+
+#[tokio::test]
+async fn embedded_log_survives_restarts() {
+    let data_dir = std::env::temp_dir().join(format!("traceguard-rekor-{}", Uuid::new_v4()));
+    let rekor = Arc::new(EmbeddedRekor::open(&data_dir).unwrap());
+    let custody = ChainOfCustody::new(rekor.clone(), rekor.log_key());
+    let uuid = custody.sign_and_record(b"artifact", &[7; 32]).await.unwrap();
+    let log_key = rekor.log_key();
+    drop(custody);
+    drop(rekor);
+
+    let restarted = Arc::new(EmbeddedRekor::open(&data_dir).unwrap());
+    assert_eq!(restarted.log_key(), log_key);
+    assert_eq!(restarted.tree_size(), 1);
+    let custody = ChainOfCustody::new(restarted.clone(), log_key);
+    custody.get_verified_entry(&uuid).await.unwrap();
+    custody.sign_and_record(b"artifact v2", &[7; 32]).await.unwrap();
+    std::fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn checkpoints_verify_against_p256_log_keys() {
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&[3; 32]).unwrap();
+    let pem = signing_key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap();
+    let log_key = LogKey::from_pem(&pem).unwrap();
+    assert!(matches!(log_key, LogKey::P256(_)));
+
+    // Rekor's note format: the line is named after the host and the hint is
+    // the start of the sha256 of the DER public key
+    let checkpoint = Checkpoint { origin: "rekor.example - 42".to_string(), tree_size: 7, root_hash: [1; 32] };
+    let der = signing_key.verifying_key().to_public_key_der().unwrap();
+    let mut signature = Sha256::digest(der.as_bytes())[..4].to_vec();
+    let der_signature: p256::ecdsa::DerSignature = signing_key.sign(checkpoint.body().as_bytes());
+    signature.extend_from_slice(der_signature.as_bytes());
+    let note = format!("{}\n\u{2014} rekor.example {}\n", checkpoint.body(), BASE64.encode(signature));
+
+    assert_eq!(Checkpoint::verify(&note, &log_key).unwrap(), checkpoint);
+    let other = LogKey::from_pem(&p256::ecdsa::SigningKey::from_slice(&[4; 32]).unwrap()
+        .verifying_key().to_public_key_pem(LineEnding::LF).unwrap()).unwrap();
+    assert!(Checkpoint::verify(&note, &other).is_err());
+}
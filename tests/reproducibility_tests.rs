@@ -0,0 +1,82 @@
+mod common;
+
+use common::{built_by, envelope, signer, trusted};
+use traceguard::models::provenance::ProvenanceRecord;
+use traceguard::provenance::dsse::EnvelopeSigner;
+use traceguard::provenance::reproducibility::{compare_attestations, ReproducibilityError};
+use uuid::Uuid;
+
+fn signed(mut record: ProvenanceRecord, signer: &EnvelopeSigner) -> ProvenanceRecord {
+    record.envelope = Some(envelope(signer, &record.slsa_provenance));
+    record
+}
+
+fn record(builder_id: &str, output_digest: &str, material_digest: &str) -> ProvenanceRecord {
+    built_by(common::record(output_digest, &[material_digest]), builder_id)
+}
+
+#[test]
+fn test_independent_builders_reproduce_subject() {
+    let (github, gitlab) = (signer(1), signer(2));
+    let records = vec![
+        signed(record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64)), &github),
+        signed(record("https://gitlab.com/runner", &"a".repeat(64), &"1".repeat(64)), &gitlab),
+    ];
+    let keys = vec![trusted(&github), trusted(&gitlab)];
+    let report = compare_attestations(Uuid::new_v4(), "pkg:oci/ourorg/api", &records, &keys).unwrap();
+    assert!(report.reproduced);
+    assert_eq!(report.independent_builders.len(), 2);
+    assert_eq!(report.subject_digest, Some("a".repeat(64)));
+    assert!(report.material_diffs.is_empty());
+}
+
+#[test]
+fn test_same_builder_is_not_independent() {
+    let (github, gitlab) = (signer(1), signer(2));
+    let keys = vec![trusted(&github), trusted(&gitlab)];
+    let records = vec![
+        signed(record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64)), &github),
+        signed(record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64)), &gitlab),
+    ];
+    let report = compare_attestations(Uuid::new_v4(), "pkg:oci/ourorg/api", &records, &keys).unwrap();
+    assert!(!report.reproduced);
+
+    // One key claiming two builder IDs is still one builder
+    let records = vec![
+        signed(record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64)), &github),
+        signed(record("https://gitlab.com/runner", &"a".repeat(64), &"1".repeat(64)), &github),
+    ];
+    let report = compare_attestations(Uuid::new_v4(), "pkg:oci/ourorg/api", &records, &keys).unwrap();
+    assert!(!report.reproduced);
+
+    // Unsigned or untrusted attestations do not count at all
+    let records = vec![
+        record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64)),
+        signed(record("https://gitlab.com/runner", &"a".repeat(64), &"1".repeat(64)), &signer(9)),
+    ];
+    let report = compare_attestations(Uuid::new_v4(), "pkg:oci/ourorg/api", &records, &keys).unwrap();
+    assert!(!report.reproduced);
+    assert!(report.independent_builders.is_empty());
+}
+
+#[test]
+fn test_diverging_outputs_are_reported() {
+    let records = vec![
+        record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64)),
+        record("https://gitlab.com/runner", &"b".repeat(64), &"2".repeat(64)),
+    ];
+    let report = compare_attestations(Uuid::new_v4(), "pkg:oci/ourorg/api", &records, &[]).unwrap();
+    assert!(!report.reproduced);
+    assert_eq!(report.subject_digest, None);
+    assert_eq!(report.output_diffs.len(), 1);
+    assert_eq!(report.material_diffs[0].key, "pkg:cargo/dep-0@1.0.0");
+}
+
+#[test]
+fn test_requires_two_attestations() {
+    let records = vec![record("https://github.com/actions/runner", &"a".repeat(64), &"1".repeat(64))];
+    assert_eq!(
+        compare_attestations(Uuid::new_v4(), "pkg:oci/ourorg/api", &records, &[]).unwrap_err(),
+        ReproducibilityError::NotEnoughAttestations(1)
+    );
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::built_by;
+use traceguard::models::provenance::ProvenanceRecord;
+use traceguard::provenance::revisions::{verify_chain, ProvenanceRevision, RevisionError, RevisionKind};
+use uuid::Uuid;
+
+fn record(id: Uuid, builder_id: &str) -> ProvenanceRecord {
+    let mut record = built_by(common::record(&"a".repeat(64), &[]), builder_id);
+    record.id = id;
+    record
+}
+
+fn history() -> Vec<ProvenanceRevision> {
+    let id = Uuid::new_v4();
+    let user = Uuid::new_v4();
+    let created = ProvenanceRevision::initial(record(id, "https://github.com/actions/runner")).unwrap();
+    let updated = ProvenanceRevision::update(&created, record(id, "https://gitlab.com/runner"), user).unwrap();
+    let deleted = ProvenanceRevision::tombstone(&updated, "Superseded".to_string(), user).unwrap();
+    vec![created, updated, deleted]
+}
+
+#[test]
+fn test_revisions_form_a_hash_chain() {
+    let revisions = history();
+    assert_eq!(revisions.iter().map(|r| r.revision).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(revisions[1].previous_hash.as_deref(), Some(revisions[0].chain_hash.as_str()));
+    assert_eq!(revisions[2].kind, RevisionKind::Tombstone);
+    assert!(revisions[2].content.is_none());
+    assert_eq!(verify_chain(&revisions), Ok(()));
+    assert_eq!(revisions[2].kind.as_str().parse::<RevisionKind>(), Ok(RevisionKind::Tombstone));
+    assert_eq!("purged".parse::<RevisionKind>(), Err(RevisionError::UnknownKind("purged".to_string())));
+}
+
+#[test]
+fn test_tombstone_is_final_and_needs_reason() {
+    let revisions = history();
+    let id = revisions[0].provenance_id;
+    assert_eq!(
+        ProvenanceRevision::update(&revisions[2], record(id, "x"), Uuid::new_v4()).unwrap_err(),
+        RevisionError::Deleted(id)
+    );
+    assert_eq!(
+        ProvenanceRevision::tombstone(&revisions[1], " ".to_string(), Uuid::new_v4()).unwrap_err(),
+        RevisionError::MissingReason
+    );
+}
+
+#[test]
+fn test_tampering_breaks_the_chain() {
+    let mut revisions = history();
+    revisions[0].content.as_mut().unwrap().slsa_provenance.builder.id = "https://evil.example/runner".to_string();
+    assert_eq!(verify_chain(&revisions), Err(RevisionError::ContentMismatch(1)));
+
+    let mut revisions = history();
+    revisions.remove(1);
+    assert_eq!(verify_chain(&revisions), Err(RevisionError::BrokenChain(3)));
+}
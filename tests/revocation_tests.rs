@@ -0,0 +1,132 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{at_commit, record, MemoryLineage, BUILDER};
+use traceguard::models::provenance::{ProvenanceRecord, VerificationOptions};
+use traceguard::models::{CheckStatus, VerificationCheckKind};
+use traceguard::provenance::dsse::{Envelope, EnvelopeSignature, IN_TOTO_PAYLOAD_TYPE};
+use traceguard::provenance::revocation::{
+    direct_revocations, propagate, propagate_within, record_revocations, upstream_closure, Revocation, RevocationError,
+    RevocationRequest, RevocationTarget,
+};
+use uuid::Uuid;
+
+fn digest(c: char) -> String {
+    c.to_string().repeat(64)
+}
+
+fn build(output: &str, material: &str) -> ProvenanceRecord {
+    at_commit(record(output, &[material]), "c0ffee")
+}
+
+fn revoke(target_type: RevocationTarget, target: &str, effective_at: Option<chrono::DateTime<Utc>>) -> Revocation {
+    let request = RevocationRequest { target_type, target: target.to_string(), reason: "compromised".to_string(), effective_at };
+    Revocation::new(Uuid::new_v4(), Uuid::new_v4(), request).unwrap()
+}
+
+#[test]
+fn test_key_and_builder_revocations_cover_records_stored_after_effective_time() {
+    let leaked_at = Utc::now() - Duration::hours(1);
+    let key = revoke(RevocationTarget::Key, "leaked-key", Some(leaked_at));
+    let builder = revoke(RevocationTarget::Builder, BUILDER, Some(leaked_at));
+
+    let mut before = build(&digest('a'), &digest('0'));
+    before.created_at = leaked_at - Duration::hours(1);
+    let mut after = build(&digest('b'), &digest('0'));
+    after.envelope = Some(Envelope {
+        payload_type: IN_TOTO_PAYLOAD_TYPE.to_string(),
+        payload: String::new(),
+        signatures: vec![EnvelopeSignature { keyid: "leaked-key".to_string(), sig: String::new() }],
+    });
+
+    let revocations = vec![key.clone(), builder.clone()];
+    assert!(record_revocations(&revocations, &before, &["leaked-key".to_string()], Utc::now()).is_empty());
+    let covering = record_revocations(&revocations, &after, &["leaked-key".to_string()], Utc::now());
+    assert_eq!(covering.len(), 2);
+
+    let direct = direct_revocations(std::slice::from_ref(&key), &[before, after], &[], Utc::now());
+    assert_eq!(direct.len(), 1);
+    assert_eq!(direct[0].digest, digest('b'));
+    assert_eq!(direct[0].depth, 0);
+}
+
+#[tokio::test]
+async fn test_revocation_propagates_downstream_through_lineage() {
+    let (dep, lib, app, other) = (digest('d'), digest('1'), digest('2'), digest('9'));
+    let lib_build = build(&lib, &dep);
+    let app_build = build(&app, &lib);
+    // A build that feeds back into its own input must not loop forever
+    let cycle = build(&dep, &app);
+    let lineage = MemoryLineage::from_records([&lib_build, &app_build, &cycle, &build(&other, &digest('8'))]);
+
+    let revocation = revoke(RevocationTarget::Artifact, &format!("sha256:{}", dep.to_uppercase()), None);
+    assert_eq!(revocation.target, dep);
+    let direct = direct_revocations(std::slice::from_ref(&revocation), &[], &[], Utc::now());
+    let revoked = propagate(&lineage, &Uuid::new_v4(), direct).await.unwrap();
+
+    let lib_entry = revoked.iter().find(|artifact| artifact.digest == lib).unwrap();
+    assert_eq!(lib_entry.depth, 1);
+    assert_eq!(lib_entry.revoked_input.as_deref(), Some(dep.as_str()));
+    assert_eq!(lib_entry.provenance_id, Some(lib_build.id));
+    let app_entry = revoked.iter().find(|artifact| artifact.digest == app).unwrap();
+    assert_eq!(app_entry.depth, 2);
+    assert_eq!(revoked.len(), 3);
+    assert!(revoked.iter().all(|artifact| artifact.digest != other && artifact.revocation_id == revocation.id));
+
+    // Scheduled revocations do nothing until they take effect
+    let scheduled = revoke(RevocationTarget::Artifact, &dep, Some(Utc::now() + Duration::days(1)));
+    assert!(direct_revocations(&[scheduled], &[], &[], Utc::now()).is_empty());
+}
+
+#[tokio::test]
+async fn test_verification_fails_for_build_with_revoked_input() {
+    let (lib, app) = (digest('1'), digest('2'));
+    let lineage = MemoryLineage(Vec::new());
+    let revocation = revoke(RevocationTarget::Artifact, &lib, None);
+    let revoked_artifacts = propagate(&lineage, &Uuid::new_v4(), direct_revocations(std::slice::from_ref(&revocation), &[], &[], Utc::now()))
+        .await
+        .unwrap();
+
+    let record = build(&app, &lib);
+    let clean = record.verify_slsa_with(&VerificationOptions::default());
+    assert_eq!(clean.check(VerificationCheckKind::Revocation).unwrap().status, CheckStatus::Pass);
+
+    let options = VerificationOptions { revocations: vec![revocation], revoked_artifacts, ..Default::default() };
+    let report = record.verify_slsa_with(&options);
+    assert!(!report.passed);
+    assert_eq!(report.check(VerificationCheckKind::Revocation).unwrap().status, CheckStatus::Fail);
+
+    // Revoking the record itself fails it too
+    let options = VerificationOptions { revocations: vec![revoke(RevocationTarget::Attestation, &record.id.to_string(), None)], ..Default::default() };
+    assert!(!record.verify_slsa_with(&options).passed);
+
+    let invalid = RevocationRequest { target_type: RevocationTarget::Artifact, target: "not-a-digest".to_string(), reason: "x".to_string(), effective_at: None };
+    assert!(Revocation::new(Uuid::new_v4(), Uuid::new_v4(), invalid).is_err());
+    let no_reason = RevocationRequest { target_type: RevocationTarget::Builder, target: BUILDER.to_string(), reason: " ".to_string(), effective_at: None };
+    assert!(Revocation::new(Uuid::new_v4(), Uuid::new_v4(), no_reason).is_err());
+}
+
+#[tokio::test]
+async fn test_scoped_evaluation_matches_full_propagation() {
+    let (dep, lib, app, tool, sibling) = (digest('d'), digest('1'), digest('2'), digest('7'), digest('3'));
+    let lineage = MemoryLineage::from_records(&[build(&lib, &dep), build(&app, &lib), build(&sibling, &dep), build(&app, &tool)]);
+    let tenant_id = Uuid::new_v4();
+    let revocations = vec![revoke(RevocationTarget::Artifact, &dep, None), revoke(RevocationTarget::Artifact, &tool, None)];
+    let direct = direct_revocations(&revocations, &[], &[], Utc::now());
+
+    let full: Vec<_> = propagate(&lineage, &tenant_id, direct.clone()).await.unwrap()
+        .into_iter()
+        .filter(|artifact| artifact.digest == app)
+        .collect();
+    let upstream = upstream_closure(&lineage, &tenant_id, std::slice::from_ref(&app)).await.unwrap();
+    assert!(upstream.contains(&dep) && !upstream.contains(&sibling));
+    let scoped: Vec<_> = propagate_within(&lineage, &tenant_id, direct, Some(&upstream)).await.unwrap()
+        .into_iter()
+        .filter(|artifact| artifact.digest == app)
+        .collect();
+    assert_eq!(scoped, full);
+    assert_eq!(scoped.len(), 2);
+
+    assert_eq!("builder".parse::<RevocationTarget>().unwrap(), RevocationTarget::Builder);
+    assert!(matches!("team".parse::<RevocationTarget>(), Err(RevocationError::UnknownTarget(_))));
+}
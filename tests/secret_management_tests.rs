@@ -0,0 +1,127 @@
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use traceguard::provenance::dsse::{DsseError, EnvelopeSigner};
+use traceguard::security::secret_management::{get_or_create_secret, SecretError, SecretManager, VaultSecretManager};
+use uuid::Uuid;
+
+const TOKEN: &str = "test-token";
+
+/// The parts of a Vault KV v2 engine the secret manager uses: versioned
+/// reads and writes with check-and-set, and deleting all versions.
+#[derive(Clone, Default)]
+struct MockKv(Arc<Mutex<HashMap<String, Vec<Value>>>>);
+
+fn authorized(headers: &HeaderMap) -> Result<(), (StatusCode, Json<Value>)> {
+    match headers.get("X-Vault-Token") {
+        Some(token) if token == TOKEN => Ok(()),
+        _ => Err((StatusCode::FORBIDDEN, Json(json!({ "errors": ["permission denied"] })))),
+    }
+}
+
+async fn read(State(kv): State<MockKv>, headers: HeaderMap, Path(path): Path<String>) -> (StatusCode, Json<Value>) {
+    if let Err(denied) = authorized(&headers) {
+        return denied;
+    }
+    let store = kv.0.lock().unwrap();
+    match store.get(&path).and_then(|versions| versions.last().map(|data| (versions.len(), data))) {
+        Some((version, data)) => (StatusCode::OK, Json(json!({ "data": { "data": data, "metadata": { "version": version } } }))),
+        None => (StatusCode::NOT_FOUND, Json(json!({ "errors": [] }))),
+    }
+}
+
+async fn write(State(kv): State<MockKv>, headers: HeaderMap, Path(path): Path<String>, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    if let Err(denied) = authorized(&headers) {
+        return denied;
+    }
+    let mut store = kv.0.lock().unwrap();
+    let versions = store.entry(path).or_default();
+    if let Some(cas) = body["options"]["cas"].as_u64() {
+        if cas != versions.len() as u64 {
+            let error = "check-and-set parameter did not match the current version";
+            return (StatusCode::BAD_REQUEST, Json(json!({ "errors": [error] })));
+        }
+    }
+    versions.push(body["data"].clone());
+    (StatusCode::OK, Json(json!({ "data": { "version": versions.len() } })))
+}
+
+async fn destroy(State(kv): State<MockKv>, headers: HeaderMap, Path(path): Path<String>) -> (StatusCode, Json<Value>) {
+    if let Err(denied) = authorized(&headers) {
+        return denied;
+    }
+    kv.0.lock().unwrap().remove(&path);
+    (StatusCode::NO_CONTENT, Json(Value::Null))
+}
+
+async fn vault() -> String {
+    let app = Router::new()
+        .route("/v1/secret/data/*path", get(read).post(write))
+        .route("/v1/secret/metadata/*path", delete(destroy))
+        .with_state(MockKv::default());
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn missing_secrets_are_created_on_first_use() {
+    let vault = VaultSecretManager::new(&vault().await, TOKEN).unwrap();
+    let tenant_id = Uuid::new_v4();
+    assert!(matches!(vault.get_secret("vsa_signing_key", tenant_id).await, Err(SecretError::SecretNotFound)));
+
+    let created = get_or_create_secret(&vault, "vsa_signing_key", tenant_id, || "first".to_string()).await.unwrap();
+    let again = get_or_create_secret(&vault, "vsa_signing_key", tenant_id, || "second".to_string()).await.unwrap();
+    assert_eq!((created.as_str(), again.as_str()), ("first", "first"));
+    assert!(matches!(vault.create_secret("vsa_signing_key", "third", tenant_id).await, Err(SecretError::AlreadyExists)));
+
+    // Signing keys go through the same path, and loading never creates one
+    assert!(matches!(
+        EnvelopeSigner::load(&vault, "audit_signing_key", tenant_id).await,
+        Err(DsseError::SecretError(SecretError::SecretNotFound))
+    ));
+    let signer = EnvelopeSigner::load_or_create(&vault, "audit_signing_key", tenant_id).await.unwrap();
+    let loaded = EnvelopeSigner::load(&vault, "audit_signing_key", tenant_id).await.unwrap();
+    assert_eq!(signer.key_id(), loaded.key_id());
+}
+
+#[tokio::test]
+async fn concurrent_first_use_settles_on_one_key() {
+    let vault = VaultSecretManager::new(&vault().await, TOKEN).unwrap();
+    let tenant_id = Uuid::new_v4();
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let vault = vault.clone();
+            tokio::spawn(async move {
+                EnvelopeSigner::load_or_create(&vault, "promotion_signing_key", tenant_id).await.unwrap().key_id().to_string()
+            })
+        })
+        .collect();
+    let mut key_ids = Vec::new();
+    for task in tasks {
+        key_ids.push(task.await.unwrap());
+    }
+    assert!(key_ids.iter().all(|key_id| key_id == &key_ids[0]));
+}
+
+#[tokio::test]
+async fn deleted_secrets_can_be_recreated_and_errors_are_reported() {
+    let addr = vault().await;
+    let vault = VaultSecretManager::new(&addr, TOKEN).unwrap();
+    let tenant_id = Uuid::new_v4();
+    vault.create_secret("witness_key", "old", tenant_id).await.unwrap();
+    vault.delete_secret("witness_key", tenant_id).await.unwrap();
+    vault.create_secret("witness_key", "new", tenant_id).await.unwrap();
+    assert_eq!(vault.get_secret("witness_key", tenant_id).await.unwrap(), "new");
+
+    let unauthorized = VaultSecretManager::new(&addr, "wrong-token").unwrap();
+    assert!(matches!(
+        unauthorized.get_secret("witness_key", tenant_id).await,
+        Err(SecretError::VaultError { status: 403, .. })
+    ));
+}
@@ -0,0 +1,145 @@
+use async_trait::async_trait;
+use jsonwebtoken::{EncodingKey, Header};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use traceguard::chain_of_custody::fulcio::{
+    CertificateAuthority, FulcioError, HttpFulcioClient, LocalFulcio, SigningCertificateRequest,
+};
+use traceguard::chain_of_custody::signing::{
+    DocumentKey, DocumentSigner, SigningAlgorithm, SigningError, SigningService, SIGNABLE_PAYLOAD_TYPES,
+};
+use traceguard::provenance::dsse::IN_TOTO_PAYLOAD_TYPE;
+use traceguard::security::secret_management::{SecretError, SecretManager};
+use uuid::Uuid;
+
+const TOKEN_SECRET: &[u8] = b"test-secret";
+
+/// Holds secrets in memory. Reads yield after looking up the secret, so
+/// callers racing on first use all miss and all try to create the key.
+#[derive(Default)]
+struct MemorySecrets(Mutex<HashMap<(Uuid, String), String>>);
+
+#[async_trait]
+impl SecretManager for MemorySecrets {
+    async fn get_secret(&self, secret_id: &str, tenant_id: Uuid) -> Result<String, SecretError> {
+        let secret = self.0.lock().unwrap().get(&(tenant_id, secret_id.to_string())).cloned();
+        tokio::task::yield_now().await;
+        secret.ok_or(SecretError::SecretNotFound)
+    }
+
+    async fn set_secret(&self, secret_id: &str, secret_value: &str, tenant_id: Uuid) -> Result<(), SecretError> {
+        self.0.lock().unwrap().insert((tenant_id, secret_id.to_string()), secret_value.to_string());
+        Ok(())
+    }
+
+    async fn create_secret(&self, secret_id: &str, secret_value: &str, tenant_id: Uuid) -> Result<(), SecretError> {
+        let mut secrets = self.0.lock().unwrap();
+        if secrets.contains_key(&(tenant_id, secret_id.to_string())) {
+            return Err(SecretError::AlreadyExists);
+        }
+        secrets.insert((tenant_id, secret_id.to_string()), secret_value.to_string());
+        Ok(())
+    }
+
+    async fn delete_secret(&self, secret_id: &str, tenant_id: Uuid) -> Result<(), SecretError> {
+        self.0.lock().unwrap().remove(&(tenant_id, secret_id.to_string()));
+        Ok(())
+    }
+}
+
+fn identity_token(subject: &str, secret: &[u8]) -> String {
+    let exp = chrono::Utc::now().timestamp() + 600;
+    jsonwebtoken::encode(&Header::default(), &json!({ "sub": subject, "exp": exp }), &EncodingKey::from_secret(secret)).unwrap()
+}
+
+#[test]
+fn keyed_signatures_verify_for_both_algorithms() {
+    for algorithm in [SigningAlgorithm::Ed25519, SigningAlgorithm::EcdsaP256] {
+        let key = DocumentKey::generate(algorithm);
+        let public_key = key.public_key();
+        let signer = DocumentSigner::keyed(key);
+        let report = br#"{"report":"compliance","passed":true}"#;
+
+        let envelope = signer.sign_envelope("application/vnd.traceguard.report+json", report);
+        let verified = envelope.verify_with_key(&public_key, None).unwrap();
+        assert_eq!(verified.algorithm, algorithm);
+        assert_eq!(verified.key_id, signer.key_id());
+
+        let detached = signer.sign_detached(report);
+        assert!(detached.verify_with_key(&public_key, Some(report)).is_ok());
+        assert!(matches!(detached.verify_with_key(&public_key, Some(b"tampered")), Err(SigningError::DigestMismatch(_))));
+        assert!(matches!(detached.verify_with_key(&public_key, None), Err(SigningError::MissingContent)));
+
+        let other = DocumentKey::generate(algorithm).public_key();
+        assert!(matches!(envelope.verify_with_key(&other, None), Err(SigningError::UnexpectedKey(_))));
+    }
+}
+
+#[tokio::test]
+async fn keyless_signature_chains_to_local_ca() {
+    let ca = Arc::new(LocalFulcio::generate("traceguard", TOKEN_SECRET).unwrap());
+    let addr = ca.clone().spawn().await.unwrap();
+    let service = SigningService::new(Some(Arc::new(HttpFulcioClient::new(&format!("http://{}", addr)))), vec![ca.root_certificate()]);
+
+    let signer = service.keyless_signer(&identity_token("alice@example.com", TOKEN_SECRET)).await.unwrap();
+    let bundle = signer.sign_envelope("application/vnd.traceguard.vsa+json", br#"{"verificationResult":"PASSED"}"#);
+    let verified = service.verify(&bundle, None, None).unwrap();
+    let identity = verified.identity.unwrap();
+    assert_eq!(identity.subject, "alice@example.com");
+    assert_eq!(identity.issuer.as_deref(), Some("traceguard"));
+
+    let other_ca = LocalFulcio::generate("traceguard", TOKEN_SECRET).unwrap();
+    let result = bundle.verify_keyless(&[other_ca.root_certificate()], None);
+    assert!(matches!(result, Err(SigningError::Fulcio(FulcioError::UntrustedChain))));
+}
+
+#[tokio::test]
+async fn certificate_authority_rejects_bad_tokens_and_proofs() {
+    let ca = LocalFulcio::generate("traceguard", TOKEN_SECRET).unwrap();
+    let key = p256::ecdsa::SigningKey::random(&mut rand::rngs::OsRng);
+
+    let forged = SigningCertificateRequest::new(&identity_token("mallory", b"wrong-secret"), &key).unwrap();
+    assert!(matches!(ca.signing_certificate(&forged).await, Err(FulcioError::InvalidToken(_))));
+
+    let mut request = SigningCertificateRequest::new(&identity_token("bob", TOKEN_SECRET), &key).unwrap();
+    let other_key = p256::ecdsa::SigningKey::random(&mut rand::rngs::OsRng);
+    request.public_key_request.proof_of_possession = SigningCertificateRequest::new(&identity_token("bob", TOKEN_SECRET), &other_key)
+        .unwrap()
+        .public_key_request
+        .proof_of_possession;
+    assert!(matches!(ca.signing_certificate(&request).await, Err(FulcioError::ProofOfPossession)));
+
+    assert!(matches!(SigningService::new(None, Vec::new()).keyless_signer("token").await, Err(SigningError::KeylessDisabled)));
+}
+
+#[tokio::test]
+async fn concurrent_first_use_settles_on_one_key() {
+    let secrets = MemorySecrets::default();
+    let tenant_id = Uuid::new_v4();
+    let (first, second) = tokio::join!(
+        DocumentKey::load_or_create(&secrets, tenant_id, SigningAlgorithm::Ed25519),
+        DocumentKey::load_or_create(&secrets, tenant_id, SigningAlgorithm::Ed25519),
+    );
+    assert_eq!(first.unwrap().public_key(), second.unwrap().public_key());
+    let stored = DocumentKey::load(&secrets, tenant_id, SigningAlgorithm::Ed25519).await.unwrap();
+    assert_eq!(stored.public_key(), DocumentKey::load_or_create(&secrets, tenant_id, SigningAlgorithm::Ed25519).await.unwrap().public_key());
+
+    let unused = DocumentKey::load(&secrets, tenant_id, SigningAlgorithm::EcdsaP256).await;
+    assert!(matches!(unused, Err(SigningError::SecretError(SecretError::SecretNotFound))));
+    assert!(!SIGNABLE_PAYLOAD_TYPES.contains(&IN_TOTO_PAYLOAD_TYPE));
+}
+
+#[tokio::test]
+async fn embedded_ca_key_survives_restarts() {
+    let key_file = std::env::temp_dir().join(format!("traceguard-fulcio-{}.pem", Uuid::new_v4()));
+    let ca = Arc::new(LocalFulcio::load_or_create(&key_file, "traceguard", TOKEN_SECRET).unwrap());
+    let addr = ca.clone().spawn().await.unwrap();
+    let service = SigningService::new(Some(Arc::new(HttpFulcioClient::new(&format!("http://{}", addr)))), vec![ca.root_certificate()]);
+    let signer = service.keyless_signer(&identity_token("alice@example.com", TOKEN_SECRET)).await.unwrap();
+    let bundle = signer.sign_envelope("application/json", br#"{"passed":true}"#);
+
+    let restarted = LocalFulcio::load_or_create(&key_file, "traceguard", TOKEN_SECRET).unwrap();
+    assert!(bundle.verify_keyless(&[restarted.root_certificate()], None).is_ok());
+    std::fs::remove_file(&key_file).unwrap();
+}
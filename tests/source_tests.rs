@@ -0,0 +1,102 @@
+mod common;
+
+use common::at_commit;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use traceguard::models::provenance::ProvenanceRecord;
+use traceguard::models::verification::{CheckStatus, VerificationCheckKind};
+use traceguard::provenance::source::{
+    is_full_commit_sha, verify_source, GitRepository, SourceError, SourceTrustPolicy, SourceTrustPolicyRequest,
+};
+use uuid::Uuid;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=dev", "-c", "user.email=dev@example.com", "-c", "commit.gpgsign=false"])
+        .args(args)
+        .output()
+        .expect("git is installed");
+    assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// A repository with one commit on `main` and one on an unprotected `feature` branch.
+fn repository() -> (PathBuf, String, String) {
+    let dir = std::env::temp_dir().join(format!("traceguard-source-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    git(&dir, &["init", "--quiet", "--initial-branch=main"]);
+    git(&dir, &["commit", "--quiet", "--allow-empty", "-m", "release"]);
+    let main = git(&dir, &["rev-parse", "HEAD"]);
+    git(&dir, &["checkout", "--quiet", "-b", "feature"]);
+    git(&dir, &["commit", "--quiet", "--allow-empty", "-m", "wip"]);
+    let feature = git(&dir, &["rev-parse", "HEAD"]);
+    (dir, main, feature)
+}
+
+fn record(commit: &str) -> ProvenanceRecord {
+    at_commit(common::record(&"a".repeat(64), &[]), commit)
+}
+
+fn policy(protected_refs: &[&str]) -> SourceTrustPolicy {
+    SourceTrustPolicy::from_request(Uuid::new_v4(), SourceTrustPolicyRequest {
+        allowed_signers: vec![],
+        protected_refs: protected_refs.iter().map(|r| r.to_string()).collect(),
+    })
+}
+
+fn status(checks: &[traceguard::models::verification::VerificationCheck], kind: VerificationCheckKind) -> Option<CheckStatus> {
+    checks.iter().find(|check| check.kind == kind).map(|check| check.status)
+}
+
+#[test]
+fn missing_commit_fails_source_commit_check() {
+    let (dir, _, _) = repository();
+    let repository = GitRepository::open(&dir).unwrap();
+    let checks = verify_source(&repository, &record(&"0".repeat(40)), &policy(&["refs/heads/main"]));
+
+    assert_eq!(checks.len(), 1);
+    assert_eq!(status(&checks, VerificationCheckKind::SourceCommit), Some(CheckStatus::Fail));
+}
+
+#[test]
+fn non_hex_commits_are_never_passed_to_git() {
+    let (dir, main, _) = repository();
+    let repository = GitRepository::open(&dir).unwrap();
+    let policy = policy(&["refs/heads/main"]);
+
+    for commit in ["--output=/tmp/pwned", "HEAD", "main~1", &main[..12], &main.to_uppercase()] {
+        let checks = verify_source(&repository, &record(commit), &policy);
+        assert_eq!(checks.len(), 1, "{} was checked further", commit);
+        assert_eq!(status(&checks, VerificationCheckKind::SourceCommit), Some(CheckStatus::Fail));
+        assert!(matches!(repository.commit_exists(commit), Err(SourceError::InvalidCommit(_))));
+    }
+    assert!(is_full_commit_sha(&main));
+    assert!(is_full_commit_sha(&"e".repeat(64)));
+}
+
+#[test]
+fn reachability_is_checked_against_protected_refs() {
+    let (dir, main, feature) = repository();
+    let repository = GitRepository::open(&dir).unwrap();
+    let policy = policy(&["refs/heads/main", "refs/tags/v*"]);
+
+    let checks = verify_source(&repository, &record(&main), &policy);
+    assert_eq!(status(&checks, VerificationCheckKind::SourceCommit), Some(CheckStatus::Pass));
+    assert_eq!(status(&checks, VerificationCheckKind::SourceReachability), Some(CheckStatus::Pass));
+    assert_eq!(status(&checks, VerificationCheckKind::SourceSignature), Some(CheckStatus::Skip));
+
+    let checks = verify_source(&repository, &record(&feature), &policy);
+    assert_eq!(status(&checks, VerificationCheckKind::SourceReachability), Some(CheckStatus::Fail));
+}
+
+#[test]
+fn bundle_upload_is_verified_like_a_local_repository() {
+    let (dir, main, _) = repository();
+    let bundle = dir.join("repo.bundle");
+    git(&dir, &["bundle", "create", "--quiet", bundle.to_str().unwrap(), "--all"]);
+
+    let repository = GitRepository::from_bundle(&std::fs::read(&bundle).unwrap()).unwrap();
+    assert!(repository.commit_exists(&main).unwrap());
+    assert_eq!(repository.protected_refs_containing(&main, &["refs/heads/main".to_string()]).unwrap(), vec!["refs/heads/main"]);
+}
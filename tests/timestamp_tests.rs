@@ -0,0 +1,89 @@
+mod common;
+
+use common::record;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use traceguard::chain_of_custody::timestamp::{
+    token_from_response, verify_timestamp_token, HttpTimestampClient, LocalTimestampAuthority, TimestampError,
+    TimestampRequest, TimestampService,
+};
+use traceguard::models::provenance::VerificationOptions;
+use traceguard::models::{CheckStatus, VerificationCheckKind};
+
+fn digest_of(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+#[tokio::test]
+async fn tokens_from_tsa_verify_against_its_certificate() {
+    let tsa = Arc::new(LocalTimestampAuthority::generate().unwrap());
+    let addr = tsa.clone().spawn().await.unwrap();
+    let service = TimestampService::new(Some(Arc::new(HttpTimestampClient::new(&format!("http://{}", addr)))), vec![tsa.certificate()]);
+
+    let digest = digest_of(br#"{"bomFormat":"CycloneDX"}"#);
+    let token = service.timestamp(&format!("sha256:{}", digest)).await.unwrap();
+    let verified = service.verify(&token, &digest).unwrap();
+    assert_eq!(verified.gen_time, token.gen_time);
+    assert_eq!(verified.serial_number, token.serial_number);
+    assert!(verified.tsa.contains("TraceGuard timestamp authority"));
+
+    assert!(matches!(service.verify(&token, &digest_of(b"other")), Err(TimestampError::ImprintMismatch)));
+    let stranger = TimestampService::new(None, vec![LocalTimestampAuthority::generate().unwrap().certificate()]);
+    assert!(matches!(stranger.verify(&token, &digest), Err(TimestampError::UntrustedSigner)));
+    assert!(matches!(stranger.timestamp(&digest).await, Err(TimestampError::Disabled)));
+}
+
+#[tokio::test]
+async fn embedded_tsa_key_survives_restart() {
+    let key_file = std::env::temp_dir().join(format!("tsa-{}.pem", uuid::Uuid::new_v4()));
+    let first = LocalTimestampAuthority::load_or_create(&key_file).unwrap();
+    let digest = digest_of(b"custody event");
+    let token = TimestampService::new(Some(Arc::new(first)), Vec::new()).timestamp(&digest).await;
+    // Nothing is trusted yet, so the service refuses its own token.
+    assert!(matches!(token, Err(TimestampError::UntrustedSigner)));
+
+    let first = LocalTimestampAuthority::load_or_create(&key_file).unwrap();
+    let first_certificate = first.certificate();
+    let token = TimestampService::new(Some(Arc::new(first)), vec![first_certificate]).timestamp(&digest).await.unwrap();
+
+    let restarted = LocalTimestampAuthority::load_or_create(&key_file).unwrap();
+    let service = TimestampService::new(None, vec![restarted.certificate()]);
+    assert!(service.verify(&token, &digest).is_ok());
+    std::fs::remove_file(&key_file).unwrap();
+}
+
+#[test]
+fn rejects_malformed_requests_and_altered_tokens() {
+    let tsa = LocalTimestampAuthority::generate().unwrap();
+    assert!(matches!(token_from_response(&tsa.respond(b"not a request")), Err(TimestampError::Rejected(_))));
+
+    let digest = Sha256::digest(b"provenance").to_vec();
+    let request = TimestampRequest::new(&digest).unwrap();
+    let mut token = token_from_response(&tsa.respond(request.to_der())).unwrap();
+    assert!(verify_timestamp_token(&token, &digest, &[tsa.certificate()]).is_ok());
+
+    // Swap the imprint inside TSTInfo: the signed message digest no longer matches.
+    let position = token.windows(digest.len()).position(|window| window == digest.as_slice()).unwrap();
+    let forged = Sha256::digest(b"forged").to_vec();
+    token[position..position + forged.len()].copy_from_slice(&forged);
+    assert!(matches!(verify_timestamp_token(&token, &forged, &[tsa.certificate()]), Err(TimestampError::InvalidSignature)));
+}
+
+#[tokio::test]
+async fn verification_checks_the_revision_timestamp() {
+    let record = record(&"a".repeat(64), &[]);
+    let tsa = LocalTimestampAuthority::generate().unwrap();
+    let certificate = tsa.certificate();
+    let service = TimestampService::new(Some(Arc::new(tsa)), vec![certificate]);
+    let digest = digest_of(b"revision 1");
+    let token = service.timestamp(&digest).await.unwrap();
+
+    let status = |timestamps| {
+        let options = VerificationOptions { timestamps, ..Default::default() };
+        record.verify_slsa_with(&options).check(VerificationCheckKind::Timestamp).unwrap().status
+    };
+    assert_eq!(status(Vec::new()), CheckStatus::Skip);
+    assert_eq!(status(vec![service.verify(&token, &digest).map_err(|e| e.to_string())]), CheckStatus::Pass);
+    let altered = service.verify(&token, &digest_of(b"revision 2")).map_err(|e| e.to_string());
+    assert_eq!(status(vec![altered]), CheckStatus::Fail);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use std::collections::HashMap;
+use traceguard::chain_of_custody::merkle::{self, Hash};
+use traceguard::chain_of_custody::transparency_log::{
+    completed_nodes, tree_root, ConsistencyProof, InclusionProof, LogEntry, LogEntryBody, LogEntryKind, SignedTreeHead,
+    TransparencyLogError,
+};
+use uuid::Uuid;
+
+/// The leaf inputs used by the RFC 6962 reference implementation's tests.
+fn reference_leaves() -> Vec<Hash> {
+    let inputs: Vec<Vec<u8>> = vec![
+        vec![], vec![0x00], vec![0x10], vec![0x20, 0x21], vec![0x30, 0x31],
+        vec![0x40, 0x41, 0x42, 0x43], (0x50..=0x57).collect(), (0x60..=0x6f).collect(),
+    ];
+    inputs.iter().map(|data| merkle::leaf_hash(data)).collect()
+}
+
+#[test]
+fn inclusion_proofs_match_reference_root() {
+    let leaves = reference_leaves();
+    let root = merkle::root(&leaves);
+    assert_eq!(hex::encode(root), "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328");
+
+    for size in 1..=leaves.len() {
+        let root = merkle::root(&leaves[..size]);
+        for index in 0..size as u64 {
+            let proof = InclusionProof::new(&leaves[..size], index).unwrap();
+            proof.verify(&root).unwrap();
+        }
+    }
+
+    let mut forged = InclusionProof::new(&leaves, 3).unwrap();
+    forged.leaf_hash = hex::encode(merkle::leaf_hash(b"forged"));
+    assert!(forged.verify(&root).is_err());
+}
+
+#[test]
+fn consistency_proof_detects_rewritten_history() {
+    let leaves = reference_leaves();
+    for first in 0..=leaves.len() as u64 {
+        let proof = ConsistencyProof::new(&leaves, first).unwrap();
+        proof.verify(&merkle::root(&leaves[..first as usize]), &merkle::root(&leaves)).unwrap();
+    }
+
+    // A log that replaced entry 1 cannot prove its new tree extends the old one
+    let mut rewritten = leaves.clone();
+    rewritten[1] = merkle::leaf_hash(b"replaced");
+    let proof = ConsistencyProof::new(&rewritten, 5).unwrap();
+    assert!(proof.verify(&merkle::root(&leaves[..5]), &merkle::root(&rewritten)).is_err());
+}
+
+#[test]
+fn signed_tree_head_covers_logged_entries() {
+    let tenant_id = Uuid::new_v4();
+    let signer = common::signer(3);
+    let entries: Vec<LogEntry> = (0..3)
+        .map(|index| LogEntry::new(tenant_id, index, LogEntryBody::for_content(LogEntryKind::Sbom, Uuid::new_v4(), b"{}")).unwrap())
+        .collect();
+    assert_eq!(entries[0].leaf_hash, hex::encode(merkle::leaf_hash(entries[0].leaf_data.as_bytes())));
+
+    let leaves: Vec<Hash> = entries.iter().map(|entry| merkle::decode_hash(&entry.leaf_hash).unwrap()).collect();
+    let head = SignedTreeHead::sign(&signer, tenant_id, &leaves).unwrap();
+    head.verify(&signer.verifying_key()).unwrap();
+    InclusionProof::new(&leaves, 2).unwrap().verify(&head.root().unwrap()).unwrap();
+
+    let mut tampered = head.clone();
+    tampered.tree_head.tree_size = 2;
+    assert!(matches!(tampered.verify(&signer.verifying_key()), Err(TransparencyLogError::TreeHeadMismatch)));
+}
+
+#[test]
+fn proofs_from_stored_nodes_match_proofs_from_leaves() {
+    let leaves: Vec<Hash> = (0..13u8).map(|i| merkle::leaf_hash(&[i])).collect();
+    let mut nodes = HashMap::new();
+    for (index, leaf) in leaves.iter().enumerate() {
+        let completed = completed_nodes(index as u64, *leaf, |id| nodes.get(&id).copied()).unwrap();
+        nodes.extend(completed);
+    }
+
+    for size in 1..=leaves.len() as u64 {
+        assert_eq!(tree_root(&nodes, size).unwrap(), merkle::root(&leaves[..size as usize]));
+        for index in 0..size {
+            let proof = InclusionProof::from_nodes(&nodes, index, size).unwrap();
+            assert_eq!(proof.hashes, InclusionProof::new(&leaves[..size as usize], index).unwrap().hashes);
+        }
+        for first in 0..=size {
+            let proof = ConsistencyProof::from_nodes(&nodes, first, size).unwrap();
+            assert_eq!(proof.hashes, ConsistencyProof::new(&leaves[..size as usize], first).unwrap().hashes);
+        }
+    }
+
+    // A node the log never stored is reported rather than hashed over
+    nodes.remove(&merkle::NodeId::leaf(12));
+    assert!(InclusionProof::from_nodes(&nodes, 12, 13).is_err());
+    assert!("vex".parse::<LogEntryKind>().is_err());
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::record;
+use traceguard::models::provenance::{VerificationOptions, VerifyParams};
+use traceguard::models::{CheckStatus, VerificationCheckKind};
+
+fn freshness(created_days_ago: i64, params: &VerifyParams) -> CheckStatus {
+    let mut record = record(&"a".repeat(64), &[]);
+    record.created_at = Utc::now() - Duration::days(created_days_ago);
+    let options = VerificationOptions { max_age: params.max_age(), ..Default::default() };
+    record.verify_slsa_with(&options).check(VerificationCheckKind::Freshness).unwrap().status
+}
+
+#[test]
+fn stale_records_fail_freshness() {
+    let week = VerifyParams { max_age_secs: Some(7 * 24 * 60 * 60) };
+    assert_eq!(freshness(30, &week), CheckStatus::Fail);
+    assert_eq!(freshness(1, &week), CheckStatus::Pass);
+}
+
+#[test]
+fn freshness_is_skipped_without_a_maximum_age() {
+    assert_eq!(freshness(3650, &VerifyParams::default()), CheckStatus::Skip);
+    // Ages past what a duration can hold impose no limit
+    assert_eq!(freshness(3650, &VerifyParams { max_age_secs: Some(u64::MAX) }), CheckStatus::Skip);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::signer;
+use traceguard::models::{VerificationCheck, VerificationCheckKind, VerificationReport};
+use traceguard::provenance::dsse::{verify_envelope, IN_TOTO_PAYLOAD_TYPE};
+use traceguard::provenance::vsa::{verified_build_level, VerificationResult};
+use uuid::Uuid;
+
+#[test]
+fn test_envelope_round_trip() {
+    let signer = signer(7);
+    let envelope = signer.sign(IN_TOTO_PAYLOAD_TYPE, br#"{"hello":"world"}"#);
+    assert!(verify_envelope(&envelope, &signer.verifying_key()).is_ok());
+    assert_eq!(envelope.signatures[0].keyid, signer.key_id());
+}
+
+#[test]
+fn test_envelope_detects_tampering() {
+    let signer = signer(7);
+    let mut envelope = signer.sign(IN_TOTO_PAYLOAD_TYPE, br#"{"hello":"world"}"#);
+    envelope.payload_type = "application/json".to_string();
+    assert!(verify_envelope(&envelope, &signer.verifying_key()).is_err());
+}
+
+#[test]
+fn test_verified_build_level() {
+    let unsigned = VerificationReport::new(Uuid::new_v4(), vec![
+        VerificationCheck::skip(VerificationCheckKind::Signature, "unsigned"),
+        VerificationCheck::pass(VerificationCheckKind::BuilderTrust, "ok", serde_json::json!({})),
+    ]);
+    assert_eq!(verified_build_level(&unsigned), 1);
+
+    let signed_with_policy = VerificationReport::new(Uuid::new_v4(), vec![
+        VerificationCheck::pass(VerificationCheckKind::Signature, "signed", serde_json::json!({})),
+        VerificationCheck::pass(VerificationCheckKind::BuilderTrust, "trusted", serde_json::json!({ "policies": [], "builder_pinned": false })),
+        VerificationCheck::pass(VerificationCheckKind::Materials, "ok", serde_json::json!({ "hermetic": true })),
+    ]);
+    assert_eq!(verified_build_level(&signed_with_policy), 2);
+
+    let pinned_but_not_hermetic = VerificationReport::new(Uuid::new_v4(), vec![
+        VerificationCheck::pass(VerificationCheckKind::Signature, "signed", serde_json::json!({})),
+        VerificationCheck::pass(VerificationCheckKind::BuilderTrust, "trusted", serde_json::json!({ "builder_pinned": true })),
+        VerificationCheck::pass(VerificationCheckKind::Materials, "ok", serde_json::json!({ "hermetic": false })),
+    ]);
+    assert_eq!(verified_build_level(&pinned_but_not_hermetic), 2);
+
+    let pinned_and_hermetic = VerificationReport::new(Uuid::new_v4(), vec![
+        VerificationCheck::pass(VerificationCheckKind::Signature, "signed", serde_json::json!({})),
+        VerificationCheck::pass(VerificationCheckKind::BuilderTrust, "trusted", serde_json::json!({ "builder_pinned": true })),
+        VerificationCheck::pass(VerificationCheckKind::Materials, "ok", serde_json::json!({ "hermetic": true })),
+    ]);
+    assert_eq!(verified_build_level(&pinned_and_hermetic), 3);
+
+    let failed = VerificationReport::new(Uuid::new_v4(), vec![
+        VerificationCheck::fail(VerificationCheckKind::Materials, "bad", serde_json::json!({})),
+    ]);
+    assert_eq!(verified_build_level(&failed), 0);
+}
+
+#[test]
+fn test_verification_result_parsing() {
+    assert_eq!("PASSED".parse::<VerificationResult>().unwrap(), VerificationResult::Passed);
+    assert_eq!("FAILED".parse::<VerificationResult>().unwrap(), VerificationResult::Failed);
+    assert!("passed".parse::<VerificationResult>().is_err());
+}